@@ -1,4 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use x12_delimiters::index::DocumentIndex;
 use x12_delimiters::Delimiters;
 
 const SAMPLE_ISA_SEGMENT: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*U*00501*000000001*0*P*:~";
@@ -47,6 +49,29 @@ fn bench_are_valid(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_indexing(c: &mut Criterion) {
+    let mut data = SAMPLE_ISA_SEGMENT.to_vec();
+    for _ in 0..1000 {
+        data.extend_from_slice(b"CLP*A1*1*100*80**MC*1234567890~SVC*HC:99213*100*80**1~\n");
+    }
+    let delimiters = Delimiters::default();
+
+    let mut group = c.benchmark_group("indexing");
+    group.bench_function("segments_then_elements", |b| {
+        b.iter(|| {
+            let mut elements = 0;
+            for segment in delimiters.segments(black_box(&data)) {
+                elements += segment.as_bytes().split(|&b| b == b'*').count();
+            }
+            black_box(elements)
+        })
+    });
+    group.bench_function("document_index", |b| {
+        b.iter(|| black_box(DocumentIndex::build(black_box(&data), &delimiters)))
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_default,
@@ -54,6 +79,7 @@ criterion_group!(
     bench_from_isa_standard,
     bench_from_isa_alternative,
     bench_getters,
-    bench_are_valid
+    bench_are_valid,
+    bench_indexing
 );
 criterion_main!(benches);
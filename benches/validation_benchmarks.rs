@@ -0,0 +1,96 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use x12_delimiters::charset::CharacterSet;
+use x12_delimiters::validation::{validate, validate_isa_identifiers, validate_strict};
+use x12_delimiters::Delimiters;
+
+/// Counts heap allocations so each benchmark can first check that validating a
+/// well-formed document allocates nothing.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// A real-time 270 eligibility inquiry, the shape of a typical latency-sensitive request.
+const INQUIRY: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\
+GS*HS*SENDERID*RECEIVERID*20250403*0856*1*X*005010X279A1~\
+ST*270*0001*005010X279A1~\
+BHT*0022*13*10001234*20250403*0856~\
+HL*1**20*1~NM1*PR*2*PAYER*****PI*12345~\
+HL*2*1*21*1~NM1*1P*2*CLINIC*****XX*1234567893~\
+HL*3*2*22*0~TRN*1*93175-012547*9877281234~\
+NM1*IL*1*DOE*JANE****MI*123456789~DMG*D8*19700101~DTP*291*D8*20250403~EQ*30~\
+SE*13*0001~GE*1*1~IEA*1*000000001~";
+
+fn assert_no_allocations<T>(name: &str, f: impl Fn() -> T) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    let after = ALLOCATIONS.load(Ordering::Relaxed);
+    drop(result);
+    assert_eq!(after - before, 0, "{} allocated on a valid document", name);
+}
+
+fn bench_validate(c: &mut Criterion) {
+    assert!(validate(INQUIRY).is_valid());
+    assert_no_allocations("validate", || validate(INQUIRY));
+    c.bench_function("validate_270", |b| {
+        b.iter(|| black_box(validate(black_box(INQUIRY))))
+    });
+}
+
+fn bench_validate_strict(c: &mut Criterion) {
+    let strict = || validate_strict(INQUIRY, CharacterSet::Extended);
+    assert!(strict().is_valid());
+    assert_no_allocations("validate_strict", strict);
+    c.bench_function("validate_strict_270", |b| {
+        b.iter(|| black_box(validate_strict(black_box(INQUIRY), CharacterSet::Extended)))
+    });
+}
+
+fn bench_validate_isa_identifiers(c: &mut Criterion) {
+    assert!(validate_isa_identifiers(INQUIRY).is_valid());
+    assert_no_allocations("validate_isa_identifiers", || {
+        validate_isa_identifiers(INQUIRY)
+    });
+    c.bench_function("validate_isa_identifiers", |b| {
+        b.iter(|| black_box(validate_isa_identifiers(black_box(INQUIRY))))
+    });
+}
+
+fn bench_delimiter_report(c: &mut Criterion) {
+    let delimiters = Delimiters::from_isa(INQUIRY).unwrap();
+    assert!(delimiters.validate().is_clean());
+    assert_no_allocations("Delimiters::validate", || delimiters.validate());
+    c.bench_function("Delimiters::validate", |b| {
+        b.iter(|| black_box(black_box(delimiters).validate()))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_validate,
+    bench_validate_strict,
+    bench_validate_isa_identifiers,
+    bench_delimiter_report
+);
+criterion_main!(benches);
@@ -0,0 +1,1029 @@
+use crate::builder::DocumentBuilder;
+use crate::control::ControlField;
+use crate::envelope::{numeric_element, required_element, GsSegment};
+use crate::errors::{BuildError, DelimiterError, EnvelopeError, IsaError};
+use crate::functional_ack::{
+    ack_segments, AckFormat, GroupFindings, TransactionFindings, IMPLEMENTATION_ACK_VERSION,
+};
+use crate::index::{DocumentIndex, EnvelopeKind, IndexedEnvelope};
+use crate::isa::IsaSegment;
+use crate::segment::Segment;
+use crate::summary::InterchangeSummary;
+use crate::validation::{validate, Diagnostic, DiagnosticCode, Severity, ValidationReport};
+use crate::{DelimiterKind, Delimiters};
+
+/// Offset of ISA08, the receiver ID, in an ISA segment with fixed-width elements.
+const ISA08_OFFSET: usize = 54;
+
+/// TA104, the interchange acknowledgment code.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Ta1Code {
+    /// `A`: the interchange was accepted with no errors.
+    Accepted,
+    /// `E`: the interchange was accepted, but errors were noted.
+    AcceptedWithErrors,
+    /// `R`: the interchange was rejected.
+    Rejected,
+}
+
+impl Ta1Code {
+    /// All codes, in code order.
+    pub const ALL: [Ta1Code; 3] = [
+        Ta1Code::Accepted,
+        Ta1Code::AcceptedWithErrors,
+        Ta1Code::Rejected,
+    ];
+
+    /// Returns the code as written in TA104.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ta1Code::Accepted => "A",
+            Ta1Code::AcceptedWithErrors => "E",
+            Ta1Code::Rejected => "R",
+        }
+    }
+
+    /// Reads the code as written in TA104, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+}
+
+/// IK501 and AK901, the transaction set and functional group acknowledgment codes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum AckCode {
+    /// `A`: accepted.
+    Accepted,
+    /// `E`: accepted, but errors were noted.
+    AcceptedWithErrors,
+    /// `P`: some transaction sets in the group were accepted. Only used in AK901.
+    PartiallyAccepted,
+    /// `R`: rejected.
+    Rejected,
+}
+
+impl AckCode {
+    /// All codes, in code order.
+    pub const ALL: [AckCode; 4] = [
+        AckCode::Accepted,
+        AckCode::AcceptedWithErrors,
+        AckCode::PartiallyAccepted,
+        AckCode::Rejected,
+    ];
+
+    /// Returns the code as written in IK501 or AK901.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckCode::Accepted => "A",
+            AckCode::AcceptedWithErrors => "E",
+            AckCode::PartiallyAccepted => "P",
+            AckCode::Rejected => "R",
+        }
+    }
+
+    /// Reads the code as written in IK501, AK501 or AK901, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+}
+
+/// TA105, the interchange note code, describing the first error found in an interchange.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Ta1NoteCode {
+    /// `000`: no error.
+    NoError,
+    /// `001`: the control numbers in ISA13 and IEA02 do not match.
+    ControlNumberMismatch,
+    /// `002`: the standard in ISA11 is not supported.
+    UnsupportedStandard,
+    /// `003`: the version in ISA12 is not supported.
+    UnsupportedVersion,
+    /// `004`: the segment terminator is invalid.
+    InvalidSegmentTerminator,
+    /// `005`: ISA05 is invalid.
+    InvalidSenderQualifier,
+    /// `006`: ISA06 is invalid.
+    InvalidSenderId,
+    /// `007`: ISA07 is invalid.
+    InvalidReceiverQualifier,
+    /// `008`: ISA08 is invalid.
+    InvalidReceiverId,
+    /// `009`: ISA08 is not a known receiver.
+    UnknownReceiverId,
+    /// `010`: ISA01 is invalid.
+    InvalidAuthorizationQualifier,
+    /// `011`: ISA02 is invalid.
+    InvalidAuthorizationInformation,
+    /// `012`: ISA03 is invalid.
+    InvalidSecurityQualifier,
+    /// `013`: ISA04 is invalid.
+    InvalidSecurityInformation,
+    /// `014`: ISA09 is invalid.
+    InvalidDate,
+    /// `015`: ISA10 is invalid.
+    InvalidTime,
+    /// `016`: ISA11 is invalid.
+    InvalidStandardsIdentifier,
+    /// `017`: ISA12 is invalid.
+    InvalidVersion,
+    /// `018`: ISA13 is invalid.
+    InvalidControlNumber,
+    /// `019`: ISA14 is invalid.
+    InvalidAcknowledgmentRequested,
+    /// `020`: ISA15 is invalid.
+    InvalidUsageIndicator,
+    /// `021`: IEA01 does not match the number of functional groups.
+    InvalidGroupCount,
+    /// `022`: the envelope structure is invalid.
+    InvalidControlStructure,
+    /// `023`: the data ends before the interchange is complete.
+    PrematureEndOfFile,
+    /// `024`: the interchange content is invalid, e.g. a bad GS segment.
+    InvalidContent,
+    /// `025`: ISA13 repeats the control number of an earlier interchange.
+    DuplicateControlNumber,
+    /// `026`: the element separator is invalid.
+    InvalidElementSeparator,
+    /// `027`: the component element separator in ISA16 is invalid.
+    InvalidComponentSeparator,
+}
+
+impl Ta1NoteCode {
+    /// All codes, in code order.
+    pub const ALL: [Ta1NoteCode; 28] = [
+        Ta1NoteCode::NoError,
+        Ta1NoteCode::ControlNumberMismatch,
+        Ta1NoteCode::UnsupportedStandard,
+        Ta1NoteCode::UnsupportedVersion,
+        Ta1NoteCode::InvalidSegmentTerminator,
+        Ta1NoteCode::InvalidSenderQualifier,
+        Ta1NoteCode::InvalidSenderId,
+        Ta1NoteCode::InvalidReceiverQualifier,
+        Ta1NoteCode::InvalidReceiverId,
+        Ta1NoteCode::UnknownReceiverId,
+        Ta1NoteCode::InvalidAuthorizationQualifier,
+        Ta1NoteCode::InvalidAuthorizationInformation,
+        Ta1NoteCode::InvalidSecurityQualifier,
+        Ta1NoteCode::InvalidSecurityInformation,
+        Ta1NoteCode::InvalidDate,
+        Ta1NoteCode::InvalidTime,
+        Ta1NoteCode::InvalidStandardsIdentifier,
+        Ta1NoteCode::InvalidVersion,
+        Ta1NoteCode::InvalidControlNumber,
+        Ta1NoteCode::InvalidAcknowledgmentRequested,
+        Ta1NoteCode::InvalidUsageIndicator,
+        Ta1NoteCode::InvalidGroupCount,
+        Ta1NoteCode::InvalidControlStructure,
+        Ta1NoteCode::PrematureEndOfFile,
+        Ta1NoteCode::InvalidContent,
+        Ta1NoteCode::DuplicateControlNumber,
+        Ta1NoteCode::InvalidElementSeparator,
+        Ta1NoteCode::InvalidComponentSeparator,
+    ];
+
+    /// Returns the code as written in TA105.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ta1NoteCode::NoError => "000",
+            Ta1NoteCode::ControlNumberMismatch => "001",
+            Ta1NoteCode::UnsupportedStandard => "002",
+            Ta1NoteCode::UnsupportedVersion => "003",
+            Ta1NoteCode::InvalidSegmentTerminator => "004",
+            Ta1NoteCode::InvalidSenderQualifier => "005",
+            Ta1NoteCode::InvalidSenderId => "006",
+            Ta1NoteCode::InvalidReceiverQualifier => "007",
+            Ta1NoteCode::InvalidReceiverId => "008",
+            Ta1NoteCode::UnknownReceiverId => "009",
+            Ta1NoteCode::InvalidAuthorizationQualifier => "010",
+            Ta1NoteCode::InvalidAuthorizationInformation => "011",
+            Ta1NoteCode::InvalidSecurityQualifier => "012",
+            Ta1NoteCode::InvalidSecurityInformation => "013",
+            Ta1NoteCode::InvalidDate => "014",
+            Ta1NoteCode::InvalidTime => "015",
+            Ta1NoteCode::InvalidStandardsIdentifier => "016",
+            Ta1NoteCode::InvalidVersion => "017",
+            Ta1NoteCode::InvalidControlNumber => "018",
+            Ta1NoteCode::InvalidAcknowledgmentRequested => "019",
+            Ta1NoteCode::InvalidUsageIndicator => "020",
+            Ta1NoteCode::InvalidGroupCount => "021",
+            Ta1NoteCode::InvalidControlStructure => "022",
+            Ta1NoteCode::PrematureEndOfFile => "023",
+            Ta1NoteCode::InvalidContent => "024",
+            Ta1NoteCode::DuplicateControlNumber => "025",
+            Ta1NoteCode::InvalidElementSeparator => "026",
+            Ta1NoteCode::InvalidComponentSeparator => "027",
+        }
+    }
+
+    /// Reads the code as written in TA105, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    /// Returns the note code for a validation finding.
+    ///
+    /// Like the `From<DiagnosticCode>` conversion, but an `InvalidIdentifier` finding is
+    /// reported as [`Ta1NoteCode::InvalidReceiverId`] when its span lies in ISA08.
+    pub fn from_diagnostic(diagnostic: &Diagnostic) -> Self {
+        match diagnostic.code() {
+            DiagnosticCode::InvalidIdentifier
+                if diagnostic
+                    .span()
+                    .is_some_and(|span| span.start >= ISA08_OFFSET) =>
+            {
+                Ta1NoteCode::InvalidReceiverId
+            }
+            code => code.into(),
+        }
+    }
+
+    /// Returns the note code for a problem with ISA element `element`.
+    fn for_isa_element(element: usize) -> Self {
+        match element {
+            1 => Ta1NoteCode::InvalidAuthorizationQualifier,
+            2 => Ta1NoteCode::InvalidAuthorizationInformation,
+            3 => Ta1NoteCode::InvalidSecurityQualifier,
+            4 => Ta1NoteCode::InvalidSecurityInformation,
+            5 => Ta1NoteCode::InvalidSenderQualifier,
+            6 => Ta1NoteCode::InvalidSenderId,
+            7 => Ta1NoteCode::InvalidReceiverQualifier,
+            8 => Ta1NoteCode::InvalidReceiverId,
+            9 => Ta1NoteCode::InvalidDate,
+            10 => Ta1NoteCode::InvalidTime,
+            11 => Ta1NoteCode::InvalidStandardsIdentifier,
+            12 => Ta1NoteCode::InvalidVersion,
+            13 => Ta1NoteCode::InvalidControlNumber,
+            14 => Ta1NoteCode::InvalidAcknowledgmentRequested,
+            15 => Ta1NoteCode::InvalidUsageIndicator,
+            16 => Ta1NoteCode::InvalidComponentSeparator,
+            _ => Ta1NoteCode::InvalidControlStructure,
+        }
+    }
+}
+
+impl From<DiagnosticCode> for Ta1NoteCode {
+    fn from(code: DiagnosticCode) -> Self {
+        match code {
+            DiagnosticCode::ControlNumberMismatch => Ta1NoteCode::ControlNumberMismatch,
+            DiagnosticCode::CountMismatch => Ta1NoteCode::InvalidGroupCount,
+            DiagnosticCode::DuplicateDelimiters | DiagnosticCode::IllegalDelimiter => {
+                Ta1NoteCode::InvalidElementSeparator
+            }
+            DiagnosticCode::IsaTooShort
+            | DiagnosticCode::UnclosedEnvelope
+            | DiagnosticCode::MissingTerminator => Ta1NoteCode::PrematureEndOfFile,
+            DiagnosticCode::UnexpectedTrailer => Ta1NoteCode::InvalidControlStructure,
+            DiagnosticCode::InvalidIdentifier => Ta1NoteCode::InvalidSenderId,
+            DiagnosticCode::SegmentOutsideEnvelope => Ta1NoteCode::InvalidContent,
+        }
+    }
+}
+
+impl From<&DelimiterError> for Ta1NoteCode {
+    fn from(error: &DelimiterError) -> Self {
+        match error {
+            DelimiterError::InvalidIsaLength => Ta1NoteCode::PrematureEndOfFile,
+            DelimiterError::DisallowedDelimiter { kind, .. } => match kind {
+                DelimiterKind::SegmentTerminator => Ta1NoteCode::InvalidSegmentTerminator,
+                DelimiterKind::ElementSeparator => Ta1NoteCode::InvalidElementSeparator,
+                DelimiterKind::SubElementSeparator => Ta1NoteCode::InvalidComponentSeparator,
+            },
+            DelimiterError::DuplicateDelimiter(_)
+            | DelimiterError::InconsistentSeparators { .. } => Ta1NoteCode::InvalidElementSeparator,
+            DelimiterError::MissingIsa
+            | DelimiterError::NotIsaSegment
+            | DelimiterError::NoSegmentStructure
+            | DelimiterError::TrailingData { .. } => Ta1NoteCode::InvalidControlStructure,
+        }
+    }
+}
+
+impl From<&IsaError> for Ta1NoteCode {
+    fn from(error: &IsaError) -> Self {
+        match error {
+            IsaError::Delimiters(e) => e.into(),
+            IsaError::MissingElement { element }
+            | IsaError::FieldWidth { element, .. }
+            | IsaError::InvalidValue { element } => Ta1NoteCode::for_isa_element(*element),
+        }
+    }
+}
+
+impl From<&EnvelopeError> for Ta1NoteCode {
+    fn from(error: &EnvelopeError) -> Self {
+        match error {
+            EnvelopeError::Isa(e) => e.into(),
+            EnvelopeError::MissingSegment { expected: "IEA" } => Ta1NoteCode::PrematureEndOfFile,
+            EnvelopeError::WrongSegment {
+                expected: "ISA" | "IEA",
+            }
+            | EnvelopeError::MissingSegment { .. } => Ta1NoteCode::InvalidControlStructure,
+            EnvelopeError::MissingElement { id: "ISA", element }
+            | EnvelopeError::InvalidValue { id: "ISA", element } => {
+                Ta1NoteCode::for_isa_element(*element)
+            }
+            EnvelopeError::MissingElement {
+                id: "IEA",
+                element: 1,
+            }
+            | EnvelopeError::InvalidValue {
+                id: "IEA",
+                element: 1,
+            } => Ta1NoteCode::InvalidGroupCount,
+            EnvelopeError::MissingElement { id: "IEA", .. }
+            | EnvelopeError::InvalidValue { id: "IEA", .. } => Ta1NoteCode::InvalidControlNumber,
+            _ => Ta1NoteCode::InvalidContent,
+        }
+    }
+}
+
+/// Field values of a TA1 interchange acknowledgment.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ta1Segment {
+    control_number: String,
+    date: String,
+    time: String,
+    code: Ta1Code,
+    note: Ta1NoteCode,
+}
+
+impl Ta1Segment {
+    /// Creates a TA1 segment.
+    ///
+    /// # Arguments
+    /// * `control_number` - TA101, the ISA13 of the acknowledged interchange as written
+    /// * `date` - TA102, its ISA09 date as `YYMMDD`
+    /// * `time` - TA103, its ISA10 time as `HHMM`
+    /// * `code` - TA104, whether the interchange was accepted
+    /// * `note` - TA105, the first error found
+    pub fn new(
+        control_number: &str,
+        date: &str,
+        time: &str,
+        code: Ta1Code,
+        note: Ta1NoteCode,
+    ) -> Self {
+        Ta1Segment {
+            control_number: control_number.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            code,
+            note,
+        }
+    }
+
+    /// Reads a TA1 segment, e.g. from an acknowledgment received from a partner.
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::WrongSegment` if `segment` is not a TA1,
+    /// `EnvelopeError::MissingElement` if an element is absent, and
+    /// `EnvelopeError::InvalidValue` if TA101 is not a nine-digit control number or TA104
+    /// or TA105 is not a known code.
+    pub fn parse(segment: &Segment<'_>) -> Result<Self, EnvelopeError> {
+        if segment.id() != b"TA1" {
+            return Err(EnvelopeError::WrongSegment { expected: "TA1" });
+        }
+        numeric_element(segment, "TA1", 1, ControlField::Interchange)?;
+        let text = |element| {
+            required_element(segment, "TA1", element)
+                .map(|value| String::from_utf8_lossy(value).into_owned())
+        };
+        let code = Ta1Code::from_code(&text(4)?).ok_or(EnvelopeError::InvalidValue {
+            id: "TA1",
+            element: 4,
+        })?;
+        let note = Ta1NoteCode::from_code(&text(5)?).ok_or(EnvelopeError::InvalidValue {
+            id: "TA1",
+            element: 5,
+        })?;
+        Ok(Ta1Segment::new(&text(1)?, &text(2)?, &text(3)?, code, note))
+    }
+
+    /// Builds the TA1 for the first interchange in `data` from its validation report.
+    ///
+    /// Only findings outside the functional groups are considered. The interchange is
+    /// rejected if any of them is an error and accepted with errors if there are only
+    /// warnings; the note code describes the first finding. Returns `None` if `data` has
+    /// no interchange.
+    ///
+    /// ```
+    /// use x12_delimiters::ack::{Ta1Code, Ta1NoteCode, Ta1Segment};
+    /// use x12_delimiters::validation::validate;
+    ///
+    /// let inbound = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000042*0*P*:~IEA*1*000000042~";
+    /// let ta1 = Ta1Segment::for_interchange(inbound, &validate(inbound)).unwrap();
+    /// assert_eq!(ta1.code(), Ta1Code::Rejected);
+    /// assert_eq!(ta1.note(), Ta1NoteCode::InvalidGroupCount);
+    /// assert_eq!(ta1.control_number(), "000000042");
+    /// ```
+    pub fn for_interchange(data: &[u8], report: &ValidationReport) -> Option<Self> {
+        let delimiters = Delimiters::from_isa(data).ok()?;
+        let index = DocumentIndex::build(data, &delimiters);
+        let triage = Triage::new(&index, data.len(), report)?;
+        Some(triage.ta1(&index, data))
+    }
+
+    /// Returns TA101, the acknowledged interchange control number.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+
+    /// Returns TA102, the acknowledged interchange date.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Returns TA103, the acknowledged interchange time.
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// Returns TA104, the acknowledgment code.
+    pub fn code(&self) -> Ta1Code {
+        self.code
+    }
+
+    /// Returns TA105, the note code.
+    pub fn note(&self) -> Ta1NoteCode {
+        self.note
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = [
+            &b"TA1"[..],
+            self.control_number.as_bytes(),
+            self.date.as_bytes(),
+            self.time.as_bytes(),
+            self.code.as_str().as_bytes(),
+            self.note.as_str().as_bytes(),
+        ]
+        .join(&delimiters.element_separator());
+        out.push(delimiters.segment_terminator());
+        out
+    }
+}
+
+/// Envelope values for the response written by [`validate_and_acknowledge`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AckOptions {
+    control_number: u32,
+    date: String,
+    time: String,
+    functional_acks: bool,
+    format: Option<AckFormat>,
+}
+
+impl AckOptions {
+    /// Creates options for a response interchange.
+    ///
+    /// # Arguments
+    /// * `control_number` - ISA13 and GS06 of the response
+    /// * `date` - the response date as `CCYYMMDD`; ISA09 uses the last six digits
+    /// * `time` - the response time as `HHMM`
+    pub fn new(control_number: u32, date: &str, time: &str) -> Self {
+        AckOptions {
+            control_number,
+            date: date.to_string(),
+            time: time.to_string(),
+            functional_acks: true,
+            format: None,
+        }
+    }
+
+    /// Sets whether a 997 or 999 is written for each functional group. Defaults to true.
+    pub fn with_functional_acks(mut self, functional_acks: bool) -> Self {
+        self.functional_acks = functional_acks;
+        self
+    }
+
+    /// Writes functional group acknowledgments in `format`, whatever the inbound version.
+    /// By default the format follows the GS08 of the first inbound group; see
+    /// [`AckFormat::for_version`].
+    pub fn with_format(mut self, format: AckFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Returns the response control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Returns the response date as `CCYYMMDD`.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Returns the response time as `HHMM`.
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// Returns true if a 997 or 999 is written for each functional group.
+    pub fn functional_acks(&self) -> bool {
+        self.functional_acks
+    }
+
+    /// Returns the forced acknowledgment format, if any.
+    pub fn format(&self) -> Option<AckFormat> {
+        self.format
+    }
+}
+
+/// The result of [`validate_and_acknowledge`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Acknowledgment {
+    summary: Option<InterchangeSummary>,
+    report: ValidationReport,
+    response: Option<Vec<u8>>,
+}
+
+impl Acknowledgment {
+    /// Returns the inbound interchange summary, or `None` if the ISA could not be read.
+    pub fn summary(&self) -> Option<&InterchangeSummary> {
+        self.summary.as_ref()
+    }
+
+    /// Returns the envelope validation report for the inbound data.
+    pub fn report(&self) -> &ValidationReport {
+        &self.report
+    }
+
+    /// Returns the response interchange to send back, if one is due.
+    pub fn response(&self) -> Option<&[u8]> {
+        self.response.as_deref()
+    }
+}
+
+/// Validates inbound data and builds the acknowledgment interchange to return.
+///
+/// The response is addressed from the inbound receiver to the inbound sender and written
+/// with the inbound delimiters and version. It contains:
+/// - a TA1 for the first interchange if it has envelope errors outside its functional
+///   groups, or if ISA14 requests one; the TA105 note code describes the first such error
+/// - unless the interchange is rejected, a 999 per functional group in one `FA` group,
+///   with an AK2/IK5 pair per transaction set; or a 997 with AK2/AK5 pairs if the first
+///   group is on a version before 005010, or [`AckOptions::with_format`] asks for one
+///
+/// No response is built if the ISA cannot be read, or if no TA1 is due and there are no
+/// groups to acknowledge.
+///
+/// ```
+/// use x12_delimiters::ack::{validate_and_acknowledge, AckOptions};
+///
+/// let inbound = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000042*0*P*:~\
+/// GS*HC*SENDERID*RECEIVERID*20250403*0856*7*X*005010X222A1~ST*837*0001~BHT*0019~SE*3*0001~GE*1*7~IEA*1*000000042~";
+/// let ack = validate_and_acknowledge(inbound, &AckOptions::new(1, "20250404", "1200")).unwrap();
+/// assert!(ack.report().is_valid());
+/// assert_eq!(ack.summary().unwrap().control_number(), "000000042");
+///
+/// let response = ack.response().unwrap();
+/// assert!(response.starts_with(b"ISA*00*          *00*          *ZZ*RECEIVERID     *ZZ*SENDERID       *250404*1200*"));
+/// let tail = b"AK1*HC*7*005010X222A1~AK2*837*0001~IK5*A~AK9*A*1*1*1~SE*6*0001~GE*1*1~IEA*1*000000001~";
+/// assert!(response.ends_with(tail));
+/// ```
+///
+/// # Errors
+/// Returns `BuildError::DelimiterConflict` if a value copied into the response, such as
+/// the date or time in `options`, contains one of the inbound delimiters.
+pub fn validate_and_acknowledge(
+    data: &[u8],
+    options: &AckOptions,
+) -> Result<Acknowledgment, BuildError> {
+    let report = validate(data);
+    let summary = InterchangeSummary::from_isa(data).ok();
+    let response = match &summary {
+        Some(summary) => build_response(data, summary, &report, options)?,
+        None => None,
+    };
+    Ok(Acknowledgment {
+        summary,
+        report,
+        response,
+    })
+}
+
+/// Builds a response interchange holding only a TA1 for the first interchange in `data`.
+///
+/// Unlike [`validate_and_acknowledge`], the TA1 is built whatever ISA14 says and no 999 is
+/// added, for partners that expect interchange acknowledgments only. `report` is the
+/// envelope validation of `data`, e.g. from [`validate`]; see
+/// [`Ta1Segment::for_interchange`] for how it sets the codes. The response is addressed
+/// and written as by `validate_and_acknowledge`. Returns `None` if `data` has no readable
+/// interchange.
+///
+/// ```
+/// use x12_delimiters::ack::{acknowledge_interchange, AckOptions};
+/// use x12_delimiters::validation::validate;
+///
+/// let inbound = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000042*0*P*:~IEA*0*000000041~";
+/// let options = AckOptions::new(7, "20250404", "1200");
+/// let response = acknowledge_interchange(inbound, &validate(inbound), &options).unwrap().unwrap();
+/// assert!(response.ends_with(b"~TA1*000000042*250403*0856*R*001~IEA*0*000000007~"));
+/// ```
+///
+/// # Errors
+/// Returns `BuildError::DelimiterConflict` if a value copied into the response, such as
+/// the date or time in `options`, contains one of the inbound delimiters.
+pub fn acknowledge_interchange(
+    data: &[u8],
+    report: &ValidationReport,
+    options: &AckOptions,
+) -> Result<Option<Vec<u8>>, BuildError> {
+    let Ok(summary) = InterchangeSummary::from_isa(data) else {
+        return Ok(None);
+    };
+    let Some(ta1) = Ta1Segment::for_interchange(data, report) else {
+        return Ok(None);
+    };
+    let delimiters = summary.delimiters();
+    let mut doc = DocumentBuilder::new(delimiters);
+    push_raw(
+        &mut doc,
+        &response_isa(&summary, options).to_bytes(&delimiters),
+    )?;
+    push_raw(&mut doc, &ta1.to_bytes(&delimiters))?;
+    doc.push(Segment::new(b"IEA", delimiters))?;
+    doc.finish().map(Some)
+}
+
+/// Validation findings sorted by the envelope they belong to.
+struct Triage<'r> {
+    /// Index of the first interchange envelope.
+    interchange: usize,
+    /// Findings outside any functional group.
+    interchange_findings: Vec<&'r Diagnostic>,
+    /// Findings owned by each envelope, by envelope index.
+    findings: Vec<Vec<&'r Diagnostic>>,
+}
+
+impl<'r> Triage<'r> {
+    /// Assigns each finding to the innermost group or transaction set containing it.
+    ///
+    /// Returns `None` if the index has no interchange.
+    fn new(index: &DocumentIndex, data_len: usize, report: &'r ValidationReport) -> Option<Self> {
+        let envelopes = index.envelopes();
+        let interchange = envelopes
+            .iter()
+            .position(|e| e.kind() == EnvelopeKind::Interchange)?;
+        let ranges: Vec<(usize, usize)> = (0..envelopes.len())
+            .map(|i| byte_range(index, data_len, i))
+            .collect();
+        let mut interchange_findings = Vec::new();
+        let mut findings = vec![Vec::new(); envelopes.len()];
+        for diagnostic in report.diagnostics() {
+            let owner = diagnostic.span().and_then(|span| {
+                (0..envelopes.len())
+                    .rev()
+                    .filter(|&i| envelopes[i].kind() != EnvelopeKind::Interchange)
+                    .find(|&i| ranges[i].0 <= span.start && span.start < ranges[i].1)
+            });
+            match owner {
+                Some(i) => findings[i].push(diagnostic),
+                None => interchange_findings.push(diagnostic),
+            }
+        }
+        Some(Triage {
+            interchange,
+            interchange_findings,
+            findings,
+        })
+    }
+
+    /// Returns the TA1 for the interchange, from the findings outside its groups.
+    fn ta1(&self, index: &DocumentIndex, data: &[u8]) -> Ta1Segment {
+        let header = index.envelopes()[self.interchange].header();
+        let isa_element = |n| {
+            String::from_utf8_lossy(index.element(data, header, n).unwrap_or_default()).into_owned()
+        };
+        let code = match ack_code(&self.interchange_findings) {
+            AckCode::Accepted => Ta1Code::Accepted,
+            AckCode::AcceptedWithErrors => Ta1Code::AcceptedWithErrors,
+            _ => Ta1Code::Rejected,
+        };
+        let note = self
+            .interchange_findings
+            .first()
+            .map_or(Ta1NoteCode::NoError, |d| Ta1NoteCode::from_diagnostic(d));
+        Ta1Segment::new(
+            &isa_element(13),
+            &isa_element(9),
+            &isa_element(10),
+            code,
+            note,
+        )
+    }
+}
+
+/// Returns the ISA of a response to `summary`, addressed back to its sender.
+fn response_isa(summary: &InterchangeSummary, options: &AckOptions) -> IsaSegment {
+    let isa = IsaSegment::new(
+        summary.receiver_qualifier(),
+        summary.receiver_id(),
+        summary.sender_qualifier(),
+        summary.sender_id(),
+        options.date.get(2..).unwrap_or_default(),
+        &options.time,
+        options.control_number,
+    )
+    .with_version(summary.version())
+    .with_usage_indicator(summary.usage_indicator().bytes().next().unwrap_or(b'P'));
+    match summary.delimiters().repetition_separator() {
+        Some(repetition) => isa.with_repetition_separator(repetition),
+        None => isa.with_repetition_separator(b'U'),
+    }
+}
+
+/// Pushes a serialized segment, dropping its terminator.
+fn push_raw(doc: &mut DocumentBuilder, segment: &[u8]) -> Result<(), BuildError> {
+    let delimiters = doc.delimiters();
+    doc.push(Segment::new(&segment[..segment.len() - 1], delimiters))
+}
+
+fn build_response(
+    data: &[u8],
+    summary: &InterchangeSummary,
+    report: &ValidationReport,
+    options: &AckOptions,
+) -> Result<Option<Vec<u8>>, BuildError> {
+    let delimiters = summary.delimiters();
+    let index = DocumentIndex::build(data, &delimiters);
+    let envelopes = index.envelopes();
+    let Some(triage) = Triage::new(&index, data.len(), report) else {
+        return Ok(None);
+    };
+    let interchange = triage.interchange;
+
+    let header = envelopes[interchange].header();
+    let ta1 = triage.ta1(&index, data);
+    let ta1_due = ta1.code() != Ta1Code::Accepted || index.element(data, header, 14) == Some(b"1");
+
+    let groups: Vec<usize> = if ta1.code() == Ta1Code::Rejected || !options.functional_acks {
+        Vec::new()
+    } else {
+        (0..envelopes.len())
+            .filter(|&i| {
+                envelopes[i].kind() == EnvelopeKind::Group
+                    && envelopes[i].parent() == Some(interchange)
+            })
+            .collect()
+    };
+    if !ta1_due && groups.is_empty() {
+        return Ok(None);
+    }
+
+    let mut doc = DocumentBuilder::new(delimiters);
+    push_raw(
+        &mut doc,
+        &response_isa(summary, options).to_bytes(&delimiters),
+    )?;
+    if ta1_due {
+        push_raw(&mut doc, &ta1.to_bytes(&delimiters))?;
+    }
+    if let Some(&first) = groups.first() {
+        let gs_element = |n| {
+            let value = index.element(data, envelopes[first].header(), n);
+            String::from_utf8_lossy(value.unwrap_or_default()).into_owned()
+        };
+        let version = gs_element(8);
+        let format = options
+            .format
+            .unwrap_or_else(|| AckFormat::for_version(&version));
+        let gs = GsSegment::new(
+            "FA",
+            &gs_element(3),
+            &gs_element(2),
+            &options.date,
+            &options.time,
+            options.control_number,
+            match format {
+                AckFormat::Implementation999 => IMPLEMENTATION_ACK_VERSION,
+                AckFormat::Functional997 => &version,
+            },
+        );
+        push_raw(&mut doc, &gs.to_bytes(&delimiters))?;
+        for (n, &group) in groups.iter().enumerate() {
+            let control = format!("{:04}", n + 1);
+            let segments = group_ack(&index, data, group, format, &control, &triage.findings)?;
+            for segment in segments {
+                doc.push(Segment::new(&segment, delimiters))?;
+            }
+            doc.push(Segment::new(b"SE", delimiters))?;
+        }
+        doc.push(Segment::new(b"GE", delimiters))?;
+    }
+    doc.push(Segment::new(b"IEA", delimiters))?;
+    doc.finish().map(Some)
+}
+
+/// Returns the raw segments of a 997 or 999 for `group`, from ST through AK9.
+fn group_ack(
+    index: &DocumentIndex,
+    data: &[u8],
+    group: usize,
+    format: AckFormat,
+    control: &str,
+    findings: &[Vec<&Diagnostic>],
+) -> Result<Vec<Vec<u8>>, BuildError> {
+    let envelopes = index.envelopes();
+    let element = |envelope: &IndexedEnvelope, n| {
+        let value = index.element(data, envelope.header(), n);
+        String::from_utf8_lossy(value.unwrap_or_default()).into_owned()
+    };
+
+    let gs = &envelopes[group];
+    let mut results = GroupFindings::new(&element(gs, 1), &element(gs, 6), &element(gs, 8))
+        .with_code(ack_code(&findings[group]));
+    for (i, st) in envelopes.iter().enumerate() {
+        if st.kind() != EnvelopeKind::Transaction || st.parent() != Some(group) {
+            continue;
+        }
+        let mut transaction = TransactionFindings::new(&element(st, 1), &element(st, 2))
+            .with_code(ack_code(&findings[i]));
+        let reference = element(st, 3);
+        if !reference.is_empty() {
+            transaction = transaction.with_implementation_reference(&reference);
+        }
+        results = results.with_transaction(transaction);
+    }
+    ack_segments(&results, format, control, &index.delimiters())
+}
+
+/// Returns the bytes an envelope covers, from its header through its trailer.
+///
+/// An envelope without a trailer extends to the end of its parent, or of the data.
+fn byte_range(index: &DocumentIndex, data_len: usize, envelope: usize) -> (usize, usize) {
+    let envelopes = index.envelopes();
+    let start = index
+        .segment_span(envelopes[envelope].header())
+        .map_or(data_len, |span| span.start);
+    let mut current = envelopes[envelope];
+    let end = loop {
+        if let Some(trailer) = current.trailer() {
+            break index
+                .segment_span(trailer)
+                .map_or(data_len, |span| span.end);
+        }
+        match current.parent() {
+            Some(parent) => current = envelopes[parent],
+            None => break data_len,
+        }
+    };
+    (start, end)
+}
+
+fn ack_code(findings: &[&Diagnostic]) -> AckCode {
+    if findings.iter().any(|d| d.severity() == Severity::Error) {
+        AckCode::Rejected
+    } else if findings.is_empty() {
+        AckCode::Accepted
+    } else {
+        AckCode::AcceptedWithErrors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000042*1*P*:~";
+
+    fn options() -> AckOptions {
+        AckOptions::new(9, "20250404", "1200")
+    }
+
+    #[test]
+    fn test_rejected_interchange_gets_ta1_only() {
+        let data = format!(
+            "{}GS*HC*S*R*20250403*0856*7*X*005010~GE*0*7~IEA*2*000000042~",
+            ISA
+        );
+        let ack = validate_and_acknowledge(data.as_bytes(), &options()).unwrap();
+        assert!(!ack.report().is_valid());
+        let response = ack.response().unwrap();
+        assert!(response.ends_with(b"~TA1*000000042*250403*0856*R*021~IEA*0*000000009~"));
+    }
+
+    #[test]
+    fn test_partial_group_acceptance() {
+        let data = format!(
+            "{}GS*HC*S*R*20250403*0856*7*X*005010X222A1~\
+ST*837*0001*005010X222A1~BHT*0019~SE*3*0001~\
+ST*837*0002*005010X222A1~SE*5*0002~GE*2*7~IEA*1*000000042~",
+            ISA
+        );
+        let ack = validate_and_acknowledge(data.as_bytes(), &options()).unwrap();
+        let response = String::from_utf8(ack.response().unwrap().to_vec()).unwrap();
+        assert!(response.contains(
+            "~TA1*000000042*250403*0856*A*000~GS*FA*R*S*20250404*1200*9*X*005010X231A1~"
+        ));
+        assert!(response.contains("~AK2*837*0001*005010X222A1~IK5*A~AK2*837*0002*005010X222A1~IK5*R~AK9*P*2*2*1~SE*8*0001~"));
+
+        let quiet = options().with_functional_acks(false);
+        let ack = validate_and_acknowledge(data.as_bytes(), &quiet).unwrap();
+        assert!(!ack.response().unwrap().windows(2).any(|w| w == b"GS"));
+    }
+
+    #[test]
+    fn test_unreadable_isa_has_no_response() {
+        let ack = validate_and_acknowledge(b"ISA*00*", &options()).unwrap();
+        assert!(ack.summary().is_none());
+        assert_eq!(ack.response(), None);
+        assert_eq!(
+            ack.report().diagnostics()[0].code(),
+            DiagnosticCode::IsaTooShort
+        );
+    }
+
+    #[test]
+    fn test_ta1_only_response() {
+        let data = format!("{}IEA*0*000000042", ISA);
+        let report = validate(data.as_bytes());
+        let ta1 = Ta1Segment::for_interchange(data.as_bytes(), &report).unwrap();
+        assert_eq!(ta1.code(), Ta1Code::AcceptedWithErrors);
+        assert_eq!(ta1.note(), Ta1NoteCode::PrematureEndOfFile);
+        assert_eq!(
+            ta1.to_bytes(&Delimiters::default()),
+            b"TA1*000000042*250403*0856*E*023~"
+        );
+
+        let valid = format!(
+            "{}GS*HC*S*R*20250403*0856*7*X*005010~GE*0*7~IEA*1*000000042~",
+            ISA
+        );
+        let response =
+            acknowledge_interchange(valid.as_bytes(), &validate(valid.as_bytes()), &options())
+                .unwrap()
+                .unwrap();
+        assert!(response.ends_with(b"~TA1*000000042*250403*0856*A*000~IEA*0*000000009~"));
+        assert_eq!(
+            acknowledge_interchange(b"GS*HC~", &validate(b"GS*HC~"), &options()),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_note_codes_for_errors() {
+        let note = |e: &EnvelopeError| Ta1NoteCode::from(e);
+        assert_eq!(
+            note(&EnvelopeError::Isa(IsaError::FieldWidth {
+                element: 9,
+                expected: 6,
+                actual: 8
+            })),
+            Ta1NoteCode::InvalidDate
+        );
+        assert_eq!(
+            note(&EnvelopeError::MissingSegment { expected: "IEA" }),
+            Ta1NoteCode::PrematureEndOfFile
+        );
+        assert_eq!(
+            note(&EnvelopeError::InvalidValue {
+                id: "IEA",
+                element: 1
+            }),
+            Ta1NoteCode::InvalidGroupCount
+        );
+        assert_eq!(
+            note(&EnvelopeError::InvalidValue {
+                id: "GS",
+                element: 6
+            }),
+            Ta1NoteCode::InvalidContent
+        );
+        assert_eq!(
+            Ta1NoteCode::from(&DelimiterError::DisallowedDelimiter {
+                kind: DelimiterKind::SubElementSeparator,
+                byte: b'A'
+            })
+            .as_str(),
+            "027"
+        );
+
+        let isa = ISA.replace("RECEIVERID     ", "RECEIVER~ID    ");
+        let report = crate::validation::validate_isa_identifiers(isa.as_bytes());
+        assert_eq!(
+            Ta1NoteCode::from_diagnostic(&report.diagnostics()[0]),
+            Ta1NoteCode::InvalidReceiverId
+        );
+    }
+
+    #[test]
+    fn test_997_for_4010_groups() {
+        let data = format!(
+            "{}GS*PO*S*R*20250403*0856*7*X*004010~ST*850*0001~BEG*00~SE*3*0001~GE*1*7~IEA*1*000000042~",
+            ISA
+        );
+        let ack = validate_and_acknowledge(data.as_bytes(), &options()).unwrap();
+        let response = String::from_utf8(ack.response().unwrap().to_vec()).unwrap();
+        assert!(response.contains(
+            "~GS*FA*R*S*20250404*1200*9*X*004010~ST*997*0001~AK1*PO*7~AK2*850*0001~AK5*A~AK9*A*1*1*1~SE*6*0001~"
+        ));
+
+        let forced = options().with_format(AckFormat::Implementation999);
+        let ack = validate_and_acknowledge(data.as_bytes(), &forced).unwrap();
+        let response = String::from_utf8(ack.response().unwrap().to_vec()).unwrap();
+        assert!(response.contains("~ST*999*0001*005010X231A1~AK1*PO*7*004010~"));
+    }
+}
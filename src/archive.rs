@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::canonical::canonical_sha256;
+use crate::dedupe::{find_duplicates, DuplicateGroup};
+use crate::errors::DelimiterError;
+use crate::segment::Span;
+use crate::sequence::{analyze_control_numbers, SenderSequence};
+use crate::summary::{interchange_spans, InterchangeSummary};
+use crate::Delimiters;
+
+/// Options for [`report_archive`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ArchiveOptions {
+    digests: bool,
+}
+
+impl ArchiveOptions {
+    /// Creates options that compute a canonical digest for every interchange.
+    pub fn new() -> Self {
+        ArchiveOptions { digests: true }
+    }
+
+    /// Enables or disables canonical digests, the most expensive step.
+    ///
+    /// Without digests, duplicate groups are reported as
+    /// [`crate::dedupe::DuplicateKind::Unverified`].
+    pub fn with_digests(mut self, digests: bool) -> Self {
+        self.digests = digests;
+        self
+    }
+}
+
+impl Default for ArchiveOptions {
+    fn default() -> Self {
+        ArchiveOptions::new()
+    }
+}
+
+/// Why [`report_archive`] could not read a file.
+#[derive(Debug)]
+pub enum ArchiveFailure {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file does not start with an ISA segment.
+    Delimiters(DelimiterError),
+}
+
+/// One interchange found in an archive file.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InterchangeRecord {
+    summary: InterchangeSummary,
+    span: Span,
+    digest: Option<[u8; 32]>,
+}
+
+impl InterchangeRecord {
+    /// Returns the identifying fields from the ISA.
+    pub fn summary(&self) -> &InterchangeSummary {
+        &self.summary
+    }
+
+    /// Returns the span of the interchange within its file.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the SHA-256 of the canonical form, if digests were enabled and the
+    /// interchange could be canonicalized.
+    pub fn digest(&self) -> Option<&[u8; 32]> {
+        self.digest.as_ref()
+    }
+}
+
+/// Totals for one sender across an archive.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PartnerStats {
+    /// Files containing at least one interchange from the sender.
+    pub files: usize,
+    /// Interchanges from the sender.
+    pub interchanges: usize,
+    /// Bytes in the sender's interchanges.
+    pub bytes: u64,
+    /// Distinct delimiter sets the sender used, in order of first use.
+    pub delimiters: Vec<Delimiters>,
+}
+
+/// Combined results of [`report_archive`].
+#[derive(Debug, Default)]
+pub struct ArchiveReport {
+    files: Vec<(PathBuf, Vec<InterchangeRecord>)>,
+    failed: Vec<(PathBuf, ArchiveFailure)>,
+    partners: BTreeMap<(String, String), PartnerStats>,
+    duplicates: Vec<DuplicateGroup>,
+    sequences: Vec<SenderSequence>,
+}
+
+impl ArchiveReport {
+    /// Returns each readable file and its interchanges, in input order.
+    pub fn files(&self) -> &[(PathBuf, Vec<InterchangeRecord>)] {
+        &self.files
+    }
+
+    /// Returns the files that could not be read, in input order.
+    pub fn failed(&self) -> &[(PathBuf, ArchiveFailure)] {
+        &self.failed
+    }
+
+    /// Returns every interchange with the file it came from, in input order.
+    ///
+    /// [`DuplicateGroup::members`] are positions in this sequence.
+    pub fn interchanges(&self) -> impl Iterator<Item = (&Path, &InterchangeRecord)> {
+        self.files
+            .iter()
+            .flat_map(|(path, records)| records.iter().map(move |r| (path.as_path(), r)))
+    }
+
+    /// Returns statistics per sender, keyed by ISA05 and ISA06.
+    pub fn partners(&self) -> &BTreeMap<(String, String), PartnerStats> {
+        &self.partners
+    }
+
+    /// Returns interchanges that share a dedupe key across the archive.
+    pub fn duplicates(&self) -> &[DuplicateGroup] {
+        &self.duplicates
+    }
+
+    /// Returns the control-number sequence analysis per sender.
+    pub fn sequences(&self) -> &[SenderSequence] {
+        &self.sequences
+    }
+}
+
+/// Builds one reconciliation report over many archive files.
+///
+/// Each file is read in full and split into interchanges, each with its own delimiters.
+/// Every interchange is summarized and, unless disabled, given a SHA-256 digest of its
+/// canonical form (see [`crate::canonical::write_canonical`]). The results are then
+/// aggregated into per-sender statistics, duplicate groups, and control-number sequence
+/// analysis. With the `rayon` feature, files are processed in parallel; the report is the
+/// same either way.
+///
+/// Unreadable files and files without an ISA are listed in [`ArchiveReport::failed`] and
+/// do not stop the run.
+pub fn report_archive<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    options: &ArchiveOptions,
+) -> ArchiveReport {
+    let scan = |path: &P| scan_file(path.as_ref(), options);
+    #[cfg(feature = "rayon")]
+    let outcomes: Vec<Result<Vec<InterchangeRecord>, ArchiveFailure>> = {
+        use rayon::prelude::*;
+        paths.par_iter().map(scan).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let outcomes: Vec<Result<Vec<InterchangeRecord>, ArchiveFailure>> =
+        paths.iter().map(scan).collect();
+
+    let mut report = ArchiveReport::default();
+    for (path, outcome) in paths.iter().zip(outcomes) {
+        let path = path.as_ref().to_path_buf();
+        match outcome {
+            Ok(records) => report.files.push((path, records)),
+            Err(failure) => report.failed.push((path, failure)),
+        }
+    }
+
+    for (_, records) in &report.files {
+        let mut seen_in_file: Vec<(&str, &str)> = Vec::new();
+        for record in records {
+            let summary = &record.summary;
+            let key = (summary.sender_qualifier(), summary.sender_id());
+            let stats = report
+                .partners
+                .entry((key.0.to_string(), key.1.to_string()))
+                .or_default();
+            if !seen_in_file.contains(&key) {
+                seen_in_file.push(key);
+                stats.files += 1;
+            }
+            stats.interchanges += 1;
+            stats.bytes += record.span.len() as u64;
+            if !stats.delimiters.contains(&summary.delimiters()) {
+                stats.delimiters.push(summary.delimiters());
+            }
+        }
+    }
+    report.duplicates = find_duplicates(
+        report
+            .interchanges()
+            .map(|(_, record)| (&record.summary, record.digest)),
+    );
+    report.sequences = analyze_control_numbers(report.interchanges().map(|(_, r)| &r.summary));
+    report
+}
+
+fn scan_file(
+    path: &Path,
+    options: &ArchiveOptions,
+) -> Result<Vec<InterchangeRecord>, ArchiveFailure> {
+    let data = fs::read(path).map_err(ArchiveFailure::Io)?;
+    let spans = interchange_spans(&data);
+    if spans.is_empty() {
+        let error = Delimiters::from_isa(&data)
+            .err()
+            .unwrap_or(DelimiterError::MissingIsa);
+        return Err(ArchiveFailure::Delimiters(error));
+    }
+
+    let mut records = Vec::with_capacity(spans.len());
+    for (span, _) in spans {
+        let bytes = &data[span.start..span.end];
+        let summary = InterchangeSummary::from_isa(bytes).map_err(ArchiveFailure::Delimiters)?;
+        let digest = if options.digests {
+            canonical_sha256(bytes).ok()
+        } else {
+            None
+        };
+        records.push(InterchangeRecord {
+            summary,
+            span,
+            digest,
+        });
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dedupe::DuplicateKind;
+    use crate::test_support::interchange;
+
+    #[test]
+    fn test_report_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.x12");
+        let b = dir.path().join("b.x12");
+        let bad = dir.path().join("bad.txt");
+        let missing = dir.path().join("missing.x12");
+        let batch = format!("{}\n{}\n", interchange(1, ""), interchange(2, ""));
+        fs::write(&a, batch).unwrap();
+        // Control number 2 again with the same content but other line endings, then 4.
+        let resent = format!("{}\r\n", interchange(2, ""));
+        fs::write(&b, format!("{}{}\n", resent, interchange(4, ""))).unwrap();
+        fs::write(&bad, "hello").unwrap();
+
+        let report = report_archive(&[&a, &b, &bad, &missing], &ArchiveOptions::new());
+        assert_eq!(report.files().len(), 2);
+        assert_eq!(report.interchanges().count(), 4);
+        let failed: Vec<_> = report.failed().iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(failed, [bad, missing]);
+        assert!(matches!(
+            report.failed()[0].1,
+            ArchiveFailure::Delimiters(DelimiterError::InvalidIsaLength)
+        ));
+
+        let stats = &report.partners()[&("ZZ".to_string(), "SENDERID".to_string())];
+        assert_eq!((stats.files, stats.interchanges), (2, 4));
+        assert_eq!(
+            stats.delimiters,
+            [Delimiters::default().with_repetition_separator(Some(b'^'))]
+        );
+
+        assert_eq!(report.duplicates().len(), 1);
+        assert_eq!(report.duplicates()[0].kind(), DuplicateKind::Exact);
+        assert_eq!(report.duplicates()[0].members(), &[1, 2]);
+        assert_eq!(report.sequences()[0].gaps(), &[3..=3]);
+
+        let without = report_archive(&[&a, &b], &ArchiveOptions::new().with_digests(false));
+        assert_eq!(without.duplicates()[0].kind(), DuplicateKind::Unverified);
+        assert!(without.interchanges().all(|(_, r)| r.digest().is_none()));
+    }
+}
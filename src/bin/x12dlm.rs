@@ -0,0 +1,558 @@
+//! `x12dlm`, a command-line front end for inspecting X12 delimiters.
+//!
+//! Built with the `cli` feature: `cargo install x12-delimiters --features cli`.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use serde_json::{json, Value};
+use x12_delimiters::conflict::ConflictAction;
+use x12_delimiters::envelope::{GeSegment, IeaSegment};
+use x12_delimiters::interchange::{interchanges, Interchange};
+use x12_delimiters::segment::Span;
+use x12_delimiters::split::{split_interchanges, split_to_dir};
+use x12_delimiters::summary::InterchangeSummary;
+use x12_delimiters::transcode::redelimit_stream;
+use x12_delimiters::{Delimiters, TerminatorSuffix};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// The length of an ISA segment, terminator included.
+const ISA_LENGTH: usize = 106;
+
+fn main() -> ExitCode {
+    let matches = command().get_matches();
+    let result = match matches.subcommand() {
+        Some(("detect", args)) => detect(args),
+        Some(("convert", args)) => convert(args),
+        Some(("split", args)) => split(args),
+        _ => unreachable!("clap requires a subcommand"),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("x12dlm: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn command() -> Command {
+    Command::new("x12dlm")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Inspect and rewrite the delimiters of X12 EDI files")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("detect")
+                .about("Print the delimiters, version, parties, and control number of each interchange")
+                .arg(input_arg())
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .action(ArgAction::SetTrue)
+                        .help("Print a JSON array instead of text"),
+                ),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Rewrite a file with other delimiters, one segment at a time")
+                .long_about(
+                    "Rewrite a file with other delimiters, one segment at a time.\n\n\
+                     The source delimiters are read from the first ISA and used for the \
+                     whole input. Delimiters that are not given keep their source value. \
+                     A delimiter is a single ASCII character or a hex byte such as 0x1D.",
+                )
+                .arg(input_arg())
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("File to write; standard output if omitted or -"),
+                )
+                .arg(delimiter_arg("segment", "Segment terminator"))
+                .arg(delimiter_arg("element", "Element separator"))
+                .arg(delimiter_arg("component", "Sub-element (component) separator"))
+                .arg(delimiter_arg("repetition", "Repetition separator"))
+                .arg(
+                    Arg::new("line-ending")
+                        .long("line-ending")
+                        .value_name("STYLE")
+                        .value_parser(["none", "lf", "crlf", "cr"])
+                        .help("Line ending after each segment terminator"),
+                )
+                .arg(
+                    Arg::new("on-conflict")
+                        .long("on-conflict")
+                        .value_name("ACTION")
+                        .value_parser(["error", "strip", "space"])
+                        .default_value("error")
+                        .help("What to do with data bytes that equal a target delimiter"),
+                ),
+        )
+        .subcommand(
+            Command::new("split")
+                .about("Write each interchange or transaction set of a batch to its own file")
+                .long_about(
+                    "Write each interchange or transaction set of a batch to its own file.\n\n\
+                     Interchanges are copied byte for byte and named \
+                     SENDER_RECEIVER_ISA13.x12. With --by transaction, each transaction set \
+                     is wrapped in its own ISA and GS, with new GE and IEA trailers, and \
+                     named SENDER_RECEIVER_ISA13_GS06_ST02.x12. Names already used get -2, \
+                     -3, and so on. The paths written are printed one per line.",
+                )
+                .arg(input_arg())
+                .arg(
+                    Arg::new("dir")
+                        .short('d')
+                        .long("dir")
+                        .value_name("DIR")
+                        .default_value(".")
+                        .help("Directory to write to, created if missing"),
+                )
+                .arg(
+                    Arg::new("by")
+                        .long("by")
+                        .value_name("UNIT")
+                        .value_parser(["interchange", "transaction"])
+                        .default_value("interchange")
+                        .help("Write one file per interchange or per transaction set"),
+                ),
+        )
+}
+
+fn delimiter_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_name("CHAR")
+        .value_parser(parse_byte)
+        .help(help)
+}
+
+/// Parses a delimiter given as one ASCII character or as `0xNN`.
+fn parse_byte(value: &str) -> Result<u8, String> {
+    match value.as_bytes() {
+        [byte] if byte.is_ascii() => Ok(*byte),
+        _ => value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            .ok_or_else(|| "expected one ASCII character or a hex byte like 0x1D".to_string()),
+    }
+}
+
+fn input_arg() -> Arg {
+    Arg::new("input")
+        .value_name("FILE")
+        .help("File to read; standard input if omitted or -")
+}
+
+/// Reads the file named by the `input` argument, or standard input.
+fn read_input(args: &ArgMatches) -> CliResult<Vec<u8>> {
+    match args.get_one::<String>("input").map(String::as_str) {
+        None | Some("-") => {
+            let mut data = Vec::new();
+            io::stdin().lock().read_to_end(&mut data)?;
+            Ok(data)
+        }
+        Some(path) => fs::read(path).map_err(|e| format!("{}: {}", path, e).into()),
+    }
+}
+
+fn detect(args: &ArgMatches) -> CliResult<()> {
+    let data = read_input(args)?;
+    let mut found = Vec::new();
+    for (span, _, bytes) in interchanges(&data).spanned() {
+        found.push((span, InterchangeSummary::from_isa(bytes)?));
+    }
+    if found.is_empty() {
+        // Reports why the start of the input is not an ISA segment.
+        Delimiters::from_isa(&data)?;
+    }
+    let out = if args.get_flag("json") {
+        let values: Vec<_> = found
+            .iter()
+            .map(|(span, s)| summary_json(*span, s))
+            .collect();
+        serde_json::to_string_pretty(&values)? + "\n"
+    } else {
+        found
+            .iter()
+            .enumerate()
+            .map(|(i, (span, summary))| summary_text(i + 1, *span, summary))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    io::stdout().lock().write_all(out.as_bytes())?;
+    Ok(())
+}
+
+/// The target delimiters and conflict handling for `convert`.
+#[derive(Debug, Default)]
+struct ConvertOptions {
+    segment: Option<u8>,
+    element: Option<u8>,
+    component: Option<u8>,
+    repetition: Option<u8>,
+    line_ending: Option<TerminatorSuffix>,
+    on_conflict: ConflictAction,
+}
+
+impl ConvertOptions {
+    fn from_args(args: &ArgMatches) -> Self {
+        let byte = |name| args.get_one::<u8>(name).copied();
+        ConvertOptions {
+            segment: byte("segment"),
+            element: byte("element"),
+            component: byte("component"),
+            repetition: byte("repetition"),
+            line_ending: args
+                .get_one::<String>("line-ending")
+                .map(|style| match style.as_str() {
+                    "lf" => TerminatorSuffix::Lf,
+                    "crlf" => TerminatorSuffix::CrLf,
+                    "cr" => TerminatorSuffix::Cr,
+                    _ => TerminatorSuffix::None,
+                }),
+            on_conflict: match args.get_one::<String>("on-conflict").map(String::as_str) {
+                Some("strip") => ConflictAction::Strip,
+                Some("space") => ConflictAction::EscapeToSpace,
+                _ => ConflictAction::Error,
+            },
+        }
+    }
+
+    /// Returns `source` with the delimiters these options give replaced.
+    fn target(&self, source: &Delimiters) -> Delimiters {
+        Delimiters::new(
+            self.segment.unwrap_or(source.segment_terminator()),
+            self.element.unwrap_or(source.element_separator()),
+            self.component.unwrap_or(source.sub_element_separator()),
+        )
+        .with_repetition_separator(self.repetition.or(source.repetition_separator()))
+        .with_suffix(self.line_ending.unwrap_or(source.suffix()))
+    }
+}
+
+fn convert(args: &ArgMatches) -> CliResult<()> {
+    let input: Box<dyn Read> = match args.get_one::<String>("input").map(String::as_str) {
+        None | Some("-") => Box::new(io::stdin().lock()),
+        Some(path) => Box::new(fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?),
+    };
+    let output: Box<dyn Write> = match args.get_one::<String>("output").map(String::as_str) {
+        None | Some("-") => Box::new(io::stdout().lock()),
+        Some(path) => Box::new(fs::File::create(path).map_err(|e| format!("{}: {}", path, e))?),
+    };
+    convert_stream(
+        input,
+        BufWriter::new(output),
+        &ConvertOptions::from_args(args),
+    )?;
+    Ok(())
+}
+
+/// Rewrites `input` into `output`, reading the source delimiters from the leading ISA.
+/// Returns the number of segments written.
+fn convert_stream<R: Read, W: Write>(
+    mut input: R,
+    output: W,
+    options: &ConvertOptions,
+) -> CliResult<usize> {
+    // Two bytes past the ISA are enough to tell the line ending.
+    let mut head = Vec::with_capacity(ISA_LENGTH + 2);
+    input
+        .by_ref()
+        .take(ISA_LENGTH as u64 + 2)
+        .read_to_end(&mut head)?;
+    let source = Delimiters::from_isa(&head)?;
+    let target = options.target(&source);
+    if !target.are_valid() {
+        return Err("the target delimiters must be distinct".into());
+    }
+    Ok(redelimit_stream(
+        head.chain(input),
+        output,
+        &source,
+        &target,
+        &options.on_conflict,
+    )?)
+}
+
+fn split(args: &ArgMatches) -> CliResult<()> {
+    let data = read_input(args)?;
+    let dir = Path::new(args.get_one::<String>("dir").map_or(".", String::as_str));
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let paths = match args.get_one::<String>("by").map(String::as_str) {
+        Some("transaction") => split_transactions_to_dir(&data, dir)?,
+        _ => split_to_dir(&data, dir)?,
+    };
+    let mut out = io::stdout().lock();
+    for path in paths {
+        writeln!(out, "{}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Writes each transaction set of `data` to `dir` as an interchange of its own, and
+/// returns the paths written.
+fn split_transactions_to_dir(data: &[u8], dir: &Path) -> CliResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for part in split_interchanges(data)? {
+        let interchange = Interchange::parse(part.as_bytes())?;
+        let delimiters = part.delimiters();
+        let stem = part.file_name().trim_end_matches(".x12").to_string();
+        let bytes = interchange.as_bytes();
+        let first_segment = |from: usize| {
+            let raw = delimiters
+                .segments(&bytes[from..])
+                .raw()
+                .next()
+                .unwrap_or_default();
+            [
+                raw,
+                &[delimiters.segment_terminator()],
+                delimiters.suffix().as_bytes(),
+            ]
+            .concat()
+        };
+        let isa = first_segment(0);
+        for group in interchange.groups() {
+            let gs = first_segment(group.span().start);
+            let ge = GeSegment::new(1, group.gs().control_number()).to_bytes(&delimiters);
+            let iea = IeaSegment::new(1, interchange.isa().control_number()).to_bytes(&delimiters);
+            for transaction in group.transactions() {
+                let mut out = [&isa[..], &gs, transaction.as_bytes()].concat();
+                for trailer in [&ge, &iea] {
+                    out.extend_from_slice(delimiters.suffix().as_bytes());
+                    out.extend_from_slice(trailer);
+                }
+                out.extend_from_slice(delimiters.suffix().as_bytes());
+                let name = format!(
+                    "{}_{}_{:04}",
+                    stem,
+                    group.gs().control_number(),
+                    transaction.st().control_number()
+                );
+                files.push((name, out));
+            }
+        }
+    }
+
+    let mut used = HashSet::new();
+    let mut paths = Vec::new();
+    for (stem, out) in files {
+        let mut name = format!("{}.x12", stem);
+        let mut n = 1;
+        while !used.insert(name.clone()) {
+            n += 1;
+            name = format!("{}-{}.x12", stem, n);
+        }
+        let path = dir.join(name);
+        fs::write(&path, out).map_err(|e| format!("{}: {}", path.display(), e))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn summary_text(number: usize, span: Span, summary: &InterchangeSummary) -> String {
+    let delimiters = summary.delimiters();
+    let rows = [
+        (
+            "segment terminator",
+            show_byte(delimiters.segment_terminator()),
+        ),
+        (
+            "element separator",
+            show_byte(delimiters.element_separator()),
+        ),
+        (
+            "sub-element separator",
+            show_byte(delimiters.sub_element_separator()),
+        ),
+        (
+            "repetition separator",
+            delimiters
+                .repetition_separator()
+                .map_or_else(|| "none".to_string(), show_byte),
+        ),
+        ("line ending", suffix_name(delimiters.suffix()).to_string()),
+        ("version", summary.version().to_string()),
+        (
+            "sender",
+            format!("{} {}", summary.sender_qualifier(), summary.sender_id()),
+        ),
+        (
+            "receiver",
+            format!("{} {}", summary.receiver_qualifier(), summary.receiver_id()),
+        ),
+        ("control number", summary.control_number().to_string()),
+    ];
+    let mut out = format!(
+        "interchange {} (bytes {}..{})\n",
+        number, span.start, span.end
+    );
+    for (name, value) in rows {
+        out.push_str(&format!("  {:<23}{}\n", name, value));
+    }
+    out
+}
+
+fn summary_json(span: Span, summary: &InterchangeSummary) -> Value {
+    let delimiters = summary.delimiters();
+    let text = |b: u8| (b as char).to_string();
+    json!({
+        "span": { "start": span.start, "end": span.end },
+        "delimiters": {
+            "segment_terminator": text(delimiters.segment_terminator()),
+            "element_separator": text(delimiters.element_separator()),
+            "sub_element_separator": text(delimiters.sub_element_separator()),
+            "repetition_separator": delimiters.repetition_separator().map(text),
+            "line_ending": suffix_name(delimiters.suffix()),
+        },
+        "version": summary.version(),
+        "sender": { "qualifier": summary.sender_qualifier(), "id": summary.sender_id() },
+        "receiver": { "qualifier": summary.receiver_qualifier(), "id": summary.receiver_id() },
+        "control_number": summary.control_number(),
+        "date": summary.date(),
+        "time": summary.time(),
+        "usage_indicator": summary.usage_indicator(),
+    })
+}
+
+/// Shows a delimiter as itself if printable, escaped otherwise, with its hex value.
+fn show_byte(byte: u8) -> String {
+    format!("{} (0x{:02X})", byte.escape_ascii(), byte)
+}
+
+fn suffix_name(suffix: TerminatorSuffix) -> &'static str {
+    match suffix {
+        TerminatorSuffix::None => "none",
+        TerminatorSuffix::Lf => "lf",
+        TerminatorSuffix::CrLf => "crlf",
+        TerminatorSuffix::Cr => "cr",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\n";
+
+    #[test]
+    fn test_command_is_well_formed() {
+        command().debug_assert();
+        let matches = command().get_matches_from(["x12dlm", "detect", "--json", "in.x12"]);
+        let (name, args) = matches.subcommand().unwrap();
+        assert_eq!(name, "detect");
+        assert!(args.get_flag("json"));
+
+        let matches = command().get_matches_from([
+            "x12dlm",
+            "convert",
+            "--segment",
+            "0x27",
+            "--element",
+            "|",
+            "--on-conflict",
+            "space",
+        ]);
+        let options = ConvertOptions::from_args(matches.subcommand().unwrap().1);
+        assert_eq!(
+            (options.segment, options.element),
+            (Some(b'\''), Some(b'|'))
+        );
+        assert_eq!(options.on_conflict, ConflictAction::EscapeToSpace);
+        assert!(command()
+            .try_get_matches_from(["x12dlm", "convert", "--element", "**"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_split_transactions() {
+        let data = [
+            ISA,
+            b"GS*HC*S*R*20250403*0856*17*X*005010X222A1~\r\n\
+ST*837*0001~\r\nBHT*0019~\r\nSE*3*0001~\r\nST*837*0002~\r\nSE*2*0002~\r\n\
+GE*2*17~\r\nIEA*1*000000001~\r\n",
+        ]
+        .concat();
+        let dir = tempfile::tempdir().unwrap();
+        let paths = split_transactions_to_dir(&data, dir.path()).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "SENDERID_RECEIVERID_000000001_17_0001.x12",
+                "SENDERID_RECEIVERID_000000001_17_0002.x12"
+            ]
+        );
+        let second = fs::read(&paths[1]).unwrap();
+        let expected = [
+            ISA,
+            b"GS*HC*S*R*20250403*0856*17*X*005010X222A1~\r\n\
+ST*837*0002~\r\nSE*2*0002~\r\nGE*1*17~\r\nIEA*1*000000001~\r\n",
+        ]
+        .concat();
+        assert_eq!(
+            String::from_utf8(second).unwrap(),
+            String::from_utf8(expected).unwrap()
+        );
+        assert!(
+            x12_delimiters::counts::validate_counts(&fs::read(&paths[0]).unwrap())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_convert_stream() {
+        let data = [ISA, b"NTE*ADD*A|B~\r\nIEA*0*000000001~\r\n"].concat();
+        let options = ConvertOptions {
+            element: Some(b'|'),
+            component: Some(b'>'),
+            line_ending: Some(TerminatorSuffix::None),
+            on_conflict: ConflictAction::Strip,
+            ..ConvertOptions::default()
+        };
+        let mut out = Vec::new();
+        assert_eq!(convert_stream(&data[..], &mut out, &options).unwrap(), 3);
+        let isa: Vec<u8> = ISA[..ISA.len() - 4]
+            .iter()
+            .map(|&b| if b == b'*' { b'|' } else { b })
+            .collect();
+        assert_eq!(out, [&isa[..], b">~NTE|ADD|AB~IEA|0|000000001~"].concat());
+
+        let options = ConvertOptions {
+            element: Some(b'~'),
+            ..ConvertOptions::default()
+        };
+        assert!(convert_stream(&data[..], &mut Vec::new(), &options).is_err());
+        assert!(convert_stream(&b"GS*HC~"[..], &mut Vec::new(), &options).is_err());
+    }
+
+    #[test]
+    fn test_summary_formats() {
+        let summary = InterchangeSummary::from_isa(ISA).unwrap();
+        let text = summary_text(1, Span::new(0, 106), &summary);
+        assert!(text.starts_with("interchange 1 (bytes 0..106)\n"));
+        assert!(text.contains("  segment terminator     ~ (0x7E)\n"));
+        assert!(text.contains("  line ending            crlf\n"));
+        assert!(text.contains("  sender                 ZZ SENDERID\n"));
+
+        let value = summary_json(Span::new(0, 106), &summary);
+        assert_eq!(value["delimiters"]["repetition_separator"], "^");
+        assert_eq!(value["receiver"]["id"], "RECEIVERID");
+        assert_eq!(value["control_number"], "000000001");
+        assert_eq!(show_byte(0x1D), "\\x1d (0x1D)");
+    }
+}
@@ -0,0 +1,915 @@
+use crate::conflict::{is_delimiter, resolve_into, Conflict, ConflictAction, ConflictPolicy};
+use crate::envelope::GsSegment;
+use crate::errors::BuildError;
+use crate::isa::IsaSegment;
+use crate::outbound::OutboundPolicy;
+use crate::segment::{element_at, AsSegment, OwnedSegment, Segment};
+use crate::{Delimiters, InterchangeVersion};
+
+/// Accumulates segments into an X12 document, filling in envelope trailer counts.
+///
+/// Segments may come from any source delimiters; they are written with the builder's
+/// delimiters. The builder tracks envelope state as segments arrive and rewrites the count
+/// element of each trailer:
+/// - `SE01` becomes the number of segments from `ST` to `SE` inclusive
+/// - `GE01` becomes the number of transaction sets in the group
+/// - `IEA01` becomes the number of functional groups in the interchange
+///
+/// A trailer pushed without a control number (`SE02`, `GE02`, `IEA02`) gets the one from
+/// its matching header (`ST02`, `GS06`, `ISA13`).
+///
+/// Data bytes that collide with the output delimiters are handled by the builder's
+/// [`ConflictPolicy`], which rejects them by default. Once a segment is rejected the
+/// builder keeps the error and [`DocumentBuilder::finish`] returns it.
+///
+/// ```
+/// use x12_delimiters::Delimiters;
+/// use x12_delimiters::builder::DocumentBuilder;
+///
+/// let rows = b"ST*837*0001~CLM*A1*100~CLM*A2*250~SE~";
+/// let mut builder = DocumentBuilder::new(Delimiters::default());
+/// builder.extend(Delimiters::default().segments(rows));
+/// assert_eq!(builder.finish().unwrap(), b"ST*837*0001~CLM*A1*100~CLM*A2*250~SE*4*0001~");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentBuilder<P = ConflictAction> {
+    delimiters: Delimiters,
+    policy: P,
+    out: Vec<u8>,
+    error: Option<BuildError>,
+    segment_count: usize,
+    transaction_count: usize,
+    group_count: usize,
+    st_control: Vec<u8>,
+    gs_control: Vec<u8>,
+    isa_control: Vec<u8>,
+    limits: Limits,
+    line_length: usize,
+}
+
+/// Length limits from an [`OutboundPolicy`].
+#[derive(Debug, Clone, Copy, Default)]
+struct Limits {
+    segment: Option<usize>,
+    line: Option<usize>,
+}
+
+impl DocumentBuilder {
+    /// Creates an empty builder that writes with `delimiters` and rejects conflicts.
+    pub fn new(delimiters: Delimiters) -> Self {
+        DocumentBuilder {
+            delimiters,
+            policy: ConflictAction::Error,
+            out: Vec::new(),
+            error: None,
+            segment_count: 0,
+            transaction_count: 0,
+            group_count: 0,
+            st_control: Vec::new(),
+            gs_control: Vec::new(),
+            isa_control: Vec::new(),
+            limits: Limits::default(),
+            line_length: 0,
+        }
+    }
+
+    /// Creates an empty builder that applies every part of `policy`: delimiters, suffix,
+    /// conflict handling, and length limits.
+    pub fn with_outbound_policy<P: ConflictPolicy>(
+        policy: OutboundPolicy<P>,
+    ) -> DocumentBuilder<P> {
+        let (delimiters, conflict_policy, segment, line) = policy.into_parts();
+        let mut builder = DocumentBuilder::new(delimiters).with_conflict_policy(conflict_policy);
+        builder.limits = Limits { segment, line };
+        builder
+    }
+
+    /// Starts a fluent builder for a complete interchange with the given ISA header.
+    ///
+    /// The fluent builder writes the ISA, GS, ST, SE, GE, and IEA segments itself, pads the
+    /// ISA fields, numbers transaction sets, and fills every trailer count.
+    ///
+    /// ```
+    /// use x12_delimiters::builder::DocumentBuilder;
+    /// use x12_delimiters::envelope::GsSegment;
+    /// use x12_delimiters::isa::IsaSegment;
+    ///
+    /// let isa = IsaSegment::new("ZZ", "SENDER", "ZZ", "RECEIVER", "250403", "0856", 7);
+    /// let gs = GsSegment::new("HC", "SENDER", "RECEIVER", "20250403", "0856", 7, "005010X222A1");
+    /// let bytes = DocumentBuilder::interchange(isa)
+    ///     .group(gs)
+    ///     .transaction("837", |t| {
+    ///         t.segment("BHT", &["0019", "00", "244579", "20250403", "0856", "CH"]);
+    ///         t.composite_segment("SV1", &[&["HC", "99213"], &["100"]]);
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(bytes.ends_with(b"SV1*HC:99213*100~SE*4*0001~GE*1*7~IEA*1*000000007~"));
+    /// ```
+    pub fn interchange(isa: IsaSegment) -> InterchangeBuilder {
+        InterchangeBuilder {
+            isa,
+            delimiters: Delimiters::default(),
+            policy: ConflictAction::Error,
+            limits: Limits::default(),
+            groups: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl<P: ConflictPolicy> DocumentBuilder<P> {
+    /// Replaces the policy for data bytes that collide with the output delimiters.
+    pub fn with_conflict_policy<Q: ConflictPolicy>(self, policy: Q) -> DocumentBuilder<Q> {
+        DocumentBuilder {
+            delimiters: self.delimiters,
+            policy,
+            out: self.out,
+            error: self.error,
+            segment_count: self.segment_count,
+            transaction_count: self.transaction_count,
+            group_count: self.group_count,
+            st_control: self.st_control,
+            gs_control: self.gs_control,
+            isa_control: self.isa_control,
+            limits: self.limits,
+            line_length: self.line_length,
+        }
+    }
+
+    /// Returns the delimiters used for output.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Appends one segment, updating envelope counts.
+    ///
+    /// # Errors
+    /// Returns `BuildError::DelimiterConflict` if the policy rejects a data byte,
+    /// `BuildError::SegmentTooLong` or `BuildError::LineTooLong` if the segment would exceed
+    /// a limit of the outbound policy, or the error from an earlier rejected segment.
+    pub fn push<S: AsSegment>(&mut self, segment: S) -> Result<(), BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let result = self.push_segment(segment.as_segment());
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+        result
+    }
+
+    /// Returns the number of segments in the current transaction set so far, including `ST`.
+    pub fn segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Returns the number of transaction sets closed in the current functional group.
+    pub fn transaction_count(&self) -> usize {
+        self.transaction_count
+    }
+
+    /// Returns the number of functional groups closed in the current interchange.
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// Consumes the builder and returns the serialized document.
+    ///
+    /// # Errors
+    /// Returns the error of the first rejected segment, if any.
+    pub fn finish(self) -> Result<Vec<u8>, BuildError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.out),
+        }
+    }
+
+    fn push_segment(&mut self, segment: Segment<'_>) -> Result<(), BuildError> {
+        let start = self.out.len();
+        let result = self
+            .write_segment(segment)
+            .and_then(|()| self.check_limits(start));
+        if result.is_err() {
+            self.out.truncate(start);
+        }
+        result
+    }
+
+    /// Checks the segment written from `start` against the length limits.
+    fn check_limits(&mut self, start: usize) -> Result<(), BuildError> {
+        let written = &self.out[start..];
+        if let Some(max) = self.limits.segment {
+            let length = written.len() - self.delimiters.suffix().as_bytes().len();
+            if length > max {
+                return Err(BuildError::SegmentTooLong { length, max });
+            }
+        }
+        let mut line_length = self.line_length;
+        let mut lines = written.split(|&b| b == b'\n' || b == b'\r').peekable();
+        while let Some(line) = lines.next() {
+            let length = line_length + line.len();
+            if let Some(max) = self.limits.line.filter(|&max| length > max) {
+                return Err(BuildError::LineTooLong { length, max });
+            }
+            line_length = if lines.peek().is_some() { 0 } else { length };
+        }
+        self.line_length = line_length;
+        Ok(())
+    }
+
+    fn write_segment(&mut self, segment: Segment<'_>) -> Result<(), BuildError> {
+        match segment.id() {
+            b"ISA" => {
+                self.group_count = 0;
+                self.isa_control = control_number(&segment, 13);
+            }
+            b"GS" => {
+                self.transaction_count = 0;
+                self.gs_control = control_number(&segment, 6);
+            }
+            b"ST" => {
+                self.segment_count = 0;
+                self.st_control = control_number(&segment, 2);
+            }
+            _ => {}
+        }
+        self.segment_count += 1;
+
+        match segment.id() {
+            b"SE" => {
+                let count = self.segment_count;
+                let control = std::mem::take(&mut self.st_control);
+                self.write_trailer(&segment, count, &control)?;
+                self.transaction_count += 1;
+            }
+            b"GE" => {
+                let count = self.transaction_count;
+                let control = std::mem::take(&mut self.gs_control);
+                self.write_trailer(&segment, count, &control)?;
+                self.group_count += 1;
+            }
+            b"IEA" => {
+                let count = self.group_count;
+                let control = std::mem::take(&mut self.isa_control);
+                self.write_trailer(&segment, count, &control)?;
+            }
+            _ => segment.write_with_policy(&mut self.out, &self.delimiters, &self.policy)?,
+        }
+        Ok(())
+    }
+
+    fn write_trailer(
+        &mut self,
+        segment: &Segment<'_>,
+        count: usize,
+        header_control: &[u8],
+    ) -> Result<(), BuildError> {
+        let source = segment.delimiters();
+        let mut elements: Vec<&[u8]> = segment
+            .as_bytes()
+            .split(|&b| b == source.element_separator())
+            .collect();
+        let count = count.to_string();
+        elements.resize(elements.len().max(3), b"");
+        elements[1] = count.as_bytes();
+        if elements[2].is_empty() {
+            elements[2] = header_control;
+        }
+        let raw = elements.join(&source.element_separator());
+        Segment::new(&raw, source).write_with_policy(&mut self.out, &self.delimiters, &self.policy)
+    }
+}
+
+/// Fluent builder for one interchange, created by [`DocumentBuilder::interchange`].
+#[derive(Debug, Clone)]
+pub struct InterchangeBuilder<P = ConflictAction> {
+    isa: IsaSegment,
+    delimiters: Delimiters,
+    policy: P,
+    limits: Limits,
+    groups: Vec<GroupDraft>,
+    error: Option<BuildError>,
+}
+
+#[derive(Debug, Clone)]
+struct GroupDraft {
+    gs: GsSegment,
+    transactions: Vec<(String, TransactionBuilder)>,
+}
+
+impl<P: ConflictPolicy> InterchangeBuilder<P> {
+    /// Sets the delimiters to write with. Defaults to [`Delimiters::default`].
+    ///
+    /// A repetition separator in `delimiters` is written as ISA11. Without one, the ISA11
+    /// of the header is used, and from version 00402 on body values are checked against it.
+    pub fn delimiters(mut self, delimiters: Delimiters) -> Self {
+        self.delimiters = delimiters;
+        self
+    }
+
+    /// Sets the policy for body values that contain an output delimiter.
+    ///
+    /// ISA and GS values are fixed by the standard and always reject conflicts.
+    pub fn conflict_policy<Q: ConflictPolicy>(self, policy: Q) -> InterchangeBuilder<Q> {
+        InterchangeBuilder {
+            isa: self.isa,
+            delimiters: self.delimiters,
+            policy,
+            limits: self.limits,
+            groups: self.groups,
+            error: self.error,
+        }
+    }
+
+    /// Applies every part of `policy` at once: delimiters, suffix, conflict handling, and
+    /// length limits. Replaces earlier calls to [`Self::delimiters`] and
+    /// [`Self::conflict_policy`].
+    pub fn outbound_policy<Q: ConflictPolicy>(
+        self,
+        policy: OutboundPolicy<Q>,
+    ) -> InterchangeBuilder<Q> {
+        let (delimiters, conflict_policy, segment, line) = policy.into_parts();
+        InterchangeBuilder {
+            isa: self.isa,
+            delimiters,
+            policy: conflict_policy,
+            limits: Limits { segment, line },
+            groups: self.groups,
+            error: self.error,
+        }
+    }
+
+    /// Opens a functional group; following transactions are added to it.
+    pub fn group(mut self, gs: GsSegment) -> Self {
+        self.groups.push(GroupDraft {
+            gs,
+            transactions: Vec::new(),
+        });
+        self
+    }
+
+    /// Adds a transaction set of type `id` (ST01) to the current group.
+    ///
+    /// The closure adds the body segments; `ST` and `SE` are written by the builder. ST02
+    /// is numbered sequentially within the interchange from `0001`, and ST03 repeats GS08
+    /// when GS08 names an implementation guide (longer than six characters).
+    pub fn transaction<F>(mut self, id: &str, build: F) -> Self
+    where
+        F: FnOnce(&mut TransactionBuilder),
+    {
+        let mut transaction = TransactionBuilder::default();
+        build(&mut transaction);
+        match self.groups.last_mut() {
+            Some(group) => group.transactions.push((id.to_string(), transaction)),
+            None => {
+                self.error
+                    .get_or_insert(BuildError::TransactionOutsideGroup);
+            }
+        }
+        self
+    }
+
+    /// Serializes the interchange.
+    ///
+    /// # Errors
+    /// Returns `BuildError::TransactionOutsideGroup` if a transaction was added before any
+    /// group was opened, `BuildError::DelimiterConflict` if a value contains an output
+    /// delimiter and the policy rejects it, `BuildError::Isa` if an ISA field does not fit
+    /// its fixed width, and `BuildError::SegmentTooLong` or `BuildError::LineTooLong` if
+    /// output exceeds a limit of the outbound policy.
+    pub fn build(self) -> Result<Vec<u8>, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let delimiters = declared_delimiters(&self.isa, self.delimiters);
+        let mut doc = DocumentBuilder::new(delimiters).with_conflict_policy(&self.policy);
+        doc.limits = self.limits;
+        let mut isa_fields = self.isa.fields(&delimiters);
+        // ISA11 declares the repetition separator rather than colliding with it.
+        isa_fields[10].clear();
+        check_header(&isa_fields, &delimiters)?;
+        let isa = self.isa.try_to_bytes(&delimiters)?;
+        doc.push(Segment::new(&isa[..isa.len() - 1], delimiters))?;
+
+        let mut st_control = 0u32;
+        for group in &self.groups {
+            check_header(&group.gs.fields(), &delimiters)?;
+            let gs = group.gs.to_bytes(&delimiters);
+            doc.push(Segment::new(&gs[..gs.len() - 1], delimiters))?;
+            for (id, transaction) in &group.transactions {
+                st_control += 1;
+                let control = format!("{:04}", st_control);
+                let mut st = vec![id.as_str(), control.as_str()];
+                if group.gs.version().len() > 6 {
+                    st.push(group.gs.version());
+                }
+                let st = raw_segment("ST", st.iter().map(|e| [*e]), &delimiters, &self.policy)?;
+                doc.push(Segment::new(&st, delimiters))?;
+                for segment in &transaction.segments {
+                    match segment {
+                        BodySegment::Fields(id, elements) => {
+                            let raw = raw_segment(id, elements, &delimiters, &self.policy)?;
+                            doc.push(Segment::new(&raw, delimiters))?;
+                        }
+                        BodySegment::Parsed(segment) => doc.push(segment)?,
+                    }
+                }
+                doc.push(Segment::new(b"SE", delimiters))?;
+            }
+            doc.push(Segment::new(b"GE", delimiters))?;
+        }
+        doc.push(Segment::new(b"IEA", delimiters))?;
+        doc.finish()
+    }
+}
+
+/// Collects the body segments of one transaction set in the fluent builder.
+///
+/// Body segments are built from strings with [`Self::segment`] and
+/// [`Self::composite_segment`], or taken from existing segments with [`Self::push`] and
+/// [`Extend`]. Envelope segments (`ST`, `SE`, and outer ones) are written by
+/// [`InterchangeBuilder`] and must not be added here.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionBuilder {
+    segments: Vec<BodySegment>,
+}
+
+#[derive(Debug, Clone)]
+enum BodySegment {
+    Fields(String, Vec<Vec<String>>),
+    Parsed(OwnedSegment),
+}
+
+impl TransactionBuilder {
+    /// Adds a segment whose elements are all simple (no sub-elements).
+    pub fn segment(&mut self, id: &str, elements: &[&str]) -> &mut Self {
+        let elements = elements.iter().map(|e| vec![e.to_string()]).collect();
+        self.segments
+            .push(BodySegment::Fields(id.to_string(), elements));
+        self
+    }
+
+    /// Adds an existing segment, such as one read from another document.
+    ///
+    /// The segment is rewritten with the interchange's delimiters when built, so it may
+    /// come from any source delimiters.
+    ///
+    /// ```
+    /// use x12_delimiters::builder::DocumentBuilder;
+    /// use x12_delimiters::envelope::GsSegment;
+    /// use x12_delimiters::isa::IsaSegment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let body = b"BHT|0019|00|244579'CLM|A1|100|||11>B>1'";
+    /// let source = Delimiters::new(b'\'', b'|', b'>');
+    /// let isa = IsaSegment::new("ZZ", "SENDER", "ZZ", "RECEIVER", "250403", "0856", 7);
+    /// let gs = GsSegment::new("HC", "SENDER", "RECEIVER", "20250403", "0856", 7, "005010X222A1");
+    /// let bytes = DocumentBuilder::interchange(isa)
+    ///     .group(gs)
+    ///     .transaction("837", |t| t.extend(source.segments(body)))
+    ///     .build()
+    ///     .unwrap();
+    /// let text = String::from_utf8(bytes).unwrap();
+    /// assert!(text.contains("~CLM*A1*100***11:B:1~SE*4*0001~"));
+    /// ```
+    pub fn push<S: AsSegment>(&mut self, segment: S) -> &mut Self {
+        let segment = segment.as_segment().to_owned_segment();
+        self.segments.push(BodySegment::Parsed(segment));
+        self
+    }
+
+    /// Adds a segment whose elements may be composites; each inner slice lists the
+    /// sub-elements of one element.
+    pub fn composite_segment(&mut self, id: &str, elements: &[&[&str]]) -> &mut Self {
+        let elements = elements
+            .iter()
+            .map(|e| e.iter().map(|c| c.to_string()).collect())
+            .collect();
+        self.segments
+            .push(BodySegment::Fields(id.to_string(), elements));
+        self
+    }
+
+    /// Returns the number of body segments added so far.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns true if no body segments have been added.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl<S: AsSegment> Extend<S> for TransactionBuilder {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for segment in iter {
+            self.push(segment);
+        }
+    }
+}
+
+/// Pushes every segment, recording the first rejected one for [`DocumentBuilder::finish`].
+impl<P: ConflictPolicy, S: AsSegment> Extend<S> for DocumentBuilder<P> {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, iter: I) {
+        for segment in iter {
+            if self.push(segment).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+pub(crate) fn raw_segment<E, C, P>(
+    id: &str,
+    elements: E,
+    delimiters: &Delimiters,
+    policy: &P,
+) -> Result<Vec<u8>, BuildError>
+where
+    E: IntoIterator,
+    E::Item: IntoIterator<Item = C>,
+    C: AsRef<str>,
+    P: ConflictPolicy + ?Sized,
+{
+    let mut raw = id.as_bytes().to_vec();
+    for (index, element) in elements.into_iter().enumerate() {
+        raw.push(delimiters.element_separator());
+        for (i, component) in element.into_iter().enumerate() {
+            if i > 0 {
+                raw.push(delimiters.sub_element_separator());
+            }
+            for (offset, &b) in component.as_ref().as_bytes().iter().enumerate() {
+                if is_delimiter(delimiters, b) {
+                    let conflict = Conflict::new(id.as_bytes(), index + 1, offset, b);
+                    resolve_into(&mut raw, policy, conflict, delimiters)?;
+                } else {
+                    raw.push(b);
+                }
+            }
+        }
+    }
+    Ok(raw)
+}
+
+fn check_header(fields: &[Vec<u8>], delimiters: &Delimiters) -> Result<(), BuildError> {
+    for (index, field) in fields.iter().enumerate() {
+        if let Some(offset) = field.iter().position(|&b| is_delimiter(delimiters, b)) {
+            return Err(BuildError::DelimiterConflict {
+                byte: field[offset],
+                element: index + 1,
+                offset,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns `delimiters` with the repetition separator that `isa` declares in ISA11, read
+/// the same way as [`Delimiters::from_isa`], unless they already set one.
+fn declared_delimiters(isa: &IsaSegment, delimiters: Delimiters) -> Delimiters {
+    if delimiters.repetition_separator().is_some() {
+        return delimiters;
+    }
+    let isa11 = isa.repetition_separator();
+    let version = InterchangeVersion::from_isa12(isa.version().as_bytes());
+    let declared = !isa11.is_ascii_alphanumeric() && version != Some(InterchangeVersion::Version4010);
+    delimiters.with_repetition_separator(Some(isa11).filter(|_| declared))
+}
+
+fn control_number(segment: &Segment<'_>, n: usize) -> Vec<u8> {
+    element_at(segment.as_bytes(), &segment.delimiters(), n)
+        .unwrap_or_default()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::IsaError;
+    use crate::TerminatorSuffix;
+
+    #[test]
+    fn test_trailer_counts_are_filled() {
+        let source = Delimiters::default();
+        let input = b"GS*HC*S*R*20250403*0856*17*X*005010X222A1~\
+ST*837*0001~BHT*0019~SE*99*0001~\
+ST*837*0002~BHT*0019~CLM*1~SE~\
+GE*0*17~";
+        let mut builder = DocumentBuilder::new(source);
+        builder.extend(source.segments(input));
+        assert_eq!(builder.group_count(), 1);
+        assert_eq!(
+            builder.finish().unwrap(),
+            b"GS*HC*S*R*20250403*0856*17*X*005010X222A1~\
+ST*837*0001~BHT*0019~SE*3*0001~\
+ST*837*0002~BHT*0019~CLM*1~SE*4*0002~\
+GE*2*17~"
+        );
+    }
+
+    #[test]
+    fn test_interchange_trailer_and_missing_control_number() {
+        let source = Delimiters::default();
+        let isa = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*U*00501*000000001*0*P*:";
+        let mut builder = DocumentBuilder::new(Delimiters::new(b'\n', b'|', b'^'));
+        builder.push(Segment::new(isa, source)).unwrap();
+        builder
+            .push(OwnedSegment::new(
+                b"GS*HC*S*R*20250403*0856*5*X*005010".to_vec(),
+                source,
+            ))
+            .unwrap();
+        builder
+            .push(OwnedSegment::new(b"GE".to_vec(), source))
+            .unwrap();
+        builder
+            .push(OwnedSegment::new(b"IEA".to_vec(), source))
+            .unwrap();
+
+        let out = builder.finish().unwrap();
+        assert!(out.starts_with(b"ISA|00|"));
+        assert!(
+            out.ends_with(b"|P|^\nGS|HC|S|R|20250403|0856|5|X|005010\nGE|0|5\nIEA|1|000000001\n")
+        );
+    }
+
+    #[test]
+    fn test_fluent_interchange() {
+        let isa = IsaSegment::new("ZZ", "SENDERID", "ZZ", "RECEIVERID", "250403", "0856", 1);
+        let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 1, "005010X222A1");
+        let gs2 = GsSegment::new("HC", "S", "R", "20250403", "0856", 2, "005010");
+        let bytes = DocumentBuilder::interchange(isa)
+            .group(gs)
+            .transaction("837", |t| {
+                t.segment("BHT", &["0019", "00"])
+                    .segment("CLM", &["A1", "100"]);
+            })
+            .transaction("837", |t| {
+                t.segment("BHT", &["0019", "00"]);
+            })
+            .group(gs2)
+            .transaction("835", |_| {})
+            .build()
+            .unwrap();
+
+        let expected_tail: &[u8] = b"*P*:~\
+GS*HC*S*R*20250403*0856*1*X*005010X222A1~\
+ST*837*0001*005010X222A1~BHT*0019*00~CLM*A1*100~SE*4*0001~\
+ST*837*0002*005010X222A1~BHT*0019*00~SE*3*0002~\
+GE*2*1~\
+GS*HC*S*R*20250403*0856*2*X*005010~\
+ST*835*0003~SE*2*0003~\
+GE*1*2~\
+IEA*2*000000001~";
+        assert!(bytes.ends_with(expected_tail));
+        assert_eq!(
+            Delimiters::from_isa(&bytes).unwrap(),
+            Delimiters::default().with_repetition_separator(Some(b'^'))
+        );
+    }
+
+    #[test]
+    fn test_fluent_uses_chosen_delimiters() {
+        let delimiters = Delimiters::new(b'~', b'|', b'>').with_suffix(TerminatorSuffix::Lf);
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 9);
+        let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 9, "005010");
+        let bytes = DocumentBuilder::interchange(isa)
+            .delimiters(delimiters)
+            .group(gs)
+            .transaction("837", |t| {
+                t.composite_segment("SV1", &[&["HC", "99213"], &["100"]]);
+            })
+            .build()
+            .unwrap();
+        assert_eq!(
+            Delimiters::from_isa(&bytes).unwrap(),
+            delimiters.with_repetition_separator(Some(b'^'))
+        );
+        assert!(bytes.ends_with(b"SV1|HC>99213|100~\nSE|3|0001~\nGE|1|9~\nIEA|1|000000009~\n"));
+    }
+
+    #[test]
+    fn test_fluent_wraps_parsed_segments() {
+        let source = Delimiters::new(b'\'', b'|', b'>');
+        let body = b"NM1|IL|1|DOE'\nHI|ABK>J449'\n";
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 3);
+        let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 3, "005010");
+        let bytes = DocumentBuilder::interchange(isa)
+            .conflict_policy(ConflictAction::ReplaceWith(b'/'))
+            .group(gs)
+            .transaction("837", |t| {
+                t.segment("BHT", &["0019"]);
+                t.extend(source.segments(body));
+                t.push(Segment::new(b"NTE|ADD|A*B", source));
+                assert_eq!(t.len(), 4);
+            })
+            .build()
+            .unwrap();
+        assert!(bytes.ends_with(
+            b"ST*837*0001~BHT*0019~NM1*IL*1*DOE~HI*ABK:J449~NTE*ADD*A/B~SE*6*0001~GE*1*3~IEA*1*000000003~"
+        ));
+    }
+
+    #[test]
+    fn test_fluent_transaction_without_group() {
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 1);
+        let result = DocumentBuilder::interchange(isa)
+            .transaction("837", |_| {})
+            .build();
+        assert_eq!(result, Err(BuildError::TransactionOutsideGroup));
+    }
+
+    #[test]
+    fn test_fluent_rejects_isa_width_errors() {
+        let isa = IsaSegment::new("ZZ", "A-SENDER-ID-OVER-15", "ZZ", "R", "250403", "0856", 1);
+        assert_eq!(
+            DocumentBuilder::interchange(isa).build(),
+            Err(BuildError::Isa(IsaError::FieldWidth {
+                element: 6,
+                expected: 15,
+                actual: 19
+            }))
+        );
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 1_000_000_000);
+        assert!(matches!(
+            DocumentBuilder::interchange(isa).build(),
+            Err(BuildError::Isa(IsaError::FieldWidth { element: 13, .. }))
+        ));
+    }
+
+    #[test]
+    fn test_fluent_checks_isa11_repetition_separator() {
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 1);
+        let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 1, "005010");
+        let build = |isa: IsaSegment, delimiters: Delimiters| {
+            DocumentBuilder::interchange(isa)
+                .delimiters(delimiters)
+                .group(gs.clone())
+                .transaction("837", |t| {
+                    t.segment("NTE", &["ADD", "A^B"]);
+                })
+                .build()
+        };
+        assert_eq!(
+            build(isa.clone(), Delimiters::default()),
+            Err(BuildError::DelimiterConflict {
+                byte: b'^',
+                element: 2,
+                offset: 1
+            })
+        );
+
+        let delimiters = Delimiters::default().with_repetition_separator(Some(b'{'));
+        let bytes = build(isa.clone(), delimiters).unwrap();
+        assert_eq!(Delimiters::from_isa(&bytes).unwrap(), delimiters);
+        assert!(bytes.windows(12).any(|w| w == b"NTE*ADD*A^B~"));
+
+        let legacy = isa.with_version("00401").with_repetition_separator(b'U');
+        let bytes = build(legacy, Delimiters::default()).unwrap();
+        assert_eq!(bytes[82], b'U');
+    }
+
+    #[test]
+    fn test_builder_policy_and_sticky_error() {
+        let source = Delimiters::default();
+        let target = Delimiters::new(b'~', b'|', b':');
+        let input = b"ST*837*0001~NTE*ADD*A|B~SE~";
+
+        let mut builder = DocumentBuilder::new(target);
+        builder.extend(source.segments(input));
+        assert!(builder.push(Segment::new(b"SE", source)).is_err());
+        assert_eq!(
+            builder.finish(),
+            Err(BuildError::DelimiterConflict {
+                byte: b'|',
+                element: 2,
+                offset: 9
+            })
+        );
+
+        let mut builder =
+            DocumentBuilder::new(target).with_conflict_policy(ConflictAction::ReplaceWith(b'/'));
+        builder.extend(source.segments(input));
+        assert_eq!(
+            builder.finish().unwrap(),
+            b"ST|837|0001~NTE|ADD|A/B~SE|3|0001~"
+        );
+    }
+
+    #[test]
+    fn test_fluent_conflict_policy() {
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 1);
+        let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 1, "005010");
+        let build = |policy| {
+            DocumentBuilder::interchange(isa.clone())
+                .conflict_policy(policy)
+                .group(gs.clone())
+                .transaction("837", |t| {
+                    t.segment("NM1", &["IL", "1", "SMITH*JONES"]);
+                })
+                .build()
+        };
+
+        assert_eq!(
+            build(ConflictAction::Error),
+            Err(BuildError::DelimiterConflict {
+                byte: b'*',
+                element: 3,
+                offset: 5
+            })
+        );
+        let bytes = build(ConflictAction::EscapeToSpace).unwrap();
+        assert!(bytes.windows(18).any(|w| w == b"NM1*IL*1*SMITH JON"));
+
+        let bad_isa = IsaSegment::new("ZZ", "S*1", "ZZ", "R", "250403", "0856", 1);
+        let result = DocumentBuilder::interchange(bad_isa)
+            .conflict_policy(ConflictAction::Strip)
+            .build();
+        assert_eq!(
+            result,
+            Err(BuildError::DelimiterConflict {
+                byte: b'*',
+                element: 6,
+                offset: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_outbound_line_limits() {
+        let source = Delimiters::default();
+        let input = b"ST*837*0001~BHT*0019~SE~";
+
+        let policy = OutboundPolicy::new(source).with_max_line_length(30);
+        let mut builder = DocumentBuilder::with_outbound_policy(policy);
+        builder.extend(source.segments(input));
+        assert_eq!(
+            builder.finish(),
+            Err(BuildError::LineTooLong {
+                length: 31,
+                max: 30
+            })
+        );
+
+        let policy = policy
+            .with_suffix(TerminatorSuffix::CrLf)
+            .with_max_line_length(12);
+        let mut builder = DocumentBuilder::with_outbound_policy(policy);
+        builder.extend(source.segments(input));
+        assert_eq!(
+            builder.finish().unwrap(),
+            b"ST*837*0001~\r\nBHT*0019~\r\nSE*3*0001~\r\n"
+        );
+
+        let mut builder = DocumentBuilder::with_outbound_policy(policy.with_max_segment_length(10));
+        builder.push(Segment::new(b"BHT*0019", source)).unwrap();
+        let error = BuildError::SegmentTooLong {
+            length: 12,
+            max: 10,
+        };
+        assert_eq!(
+            builder.push(Segment::new(b"ST*837*0001", source)),
+            Err(error)
+        );
+        assert_eq!(builder.finish(), Err(error));
+    }
+
+    #[test]
+    fn test_fluent_outbound_policy() {
+        let isa = IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 1);
+        let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 1, "005010");
+        let policy = OutboundPolicy::new(Delimiters::new(b'~', b'|', b'>'))
+            .with_suffix(TerminatorSuffix::Lf)
+            .with_conflict_policy(ConflictAction::Strip);
+        let build = |policy: OutboundPolicy| {
+            DocumentBuilder::interchange(isa.clone())
+                .outbound_policy(policy)
+                .group(gs.clone())
+                .transaction("837", |t| {
+                    t.segment("NTE", &["ADD", "A|B"]);
+                })
+                .build()
+        };
+        let bytes = build(policy).unwrap();
+        assert!(bytes.ends_with(b"NTE|ADD|AB~\nSE|3|0001~\nGE|1|1~\nIEA|1|000000001~\n"));
+        assert_eq!(
+            build(policy.with_max_segment_length(100)),
+            Err(BuildError::SegmentTooLong {
+                length: 106,
+                max: 100
+            })
+        );
+    }
+
+    #[test]
+    fn test_segment_count_tracks_open_transaction() {
+        let source = Delimiters::default();
+        let mut builder = DocumentBuilder::new(source);
+        builder.extend(source.segments(b"ST*270*0001~BHT*0022~HL*1**20*1~"));
+        assert_eq!(builder.segment_count(), 3);
+        assert_eq!(builder.transaction_count(), 0);
+    }
+}
@@ -0,0 +1,267 @@
+use std::io::Write;
+
+use sha2::{Digest, Sha256};
+
+use crate::errors::{CanonicalError, DelimiterError};
+use crate::interchange::interchanges;
+use crate::pretty::minify;
+use crate::segment::Segment;
+use crate::Delimiters;
+
+/// The canonical form of a document and its SHA-256 digest, returned by [`canonicalize`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Canonical {
+    bytes: Vec<u8>,
+    digest: [u8; 32],
+}
+
+impl Canonical {
+    /// Returns the canonical bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the canonical bytes, consuming `self`.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// Returns the SHA-256 digest of the canonical bytes.
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// Returns the digest as 64 lowercase hex digits.
+    pub fn hex_digest(&self) -> String {
+        self.digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Returns the canonical form of every interchange in `data` and its SHA-256 digest, so
+/// two files can be compared for semantic equality by digest alone.
+///
+/// Each interchange is read with the delimiters from its own ISA and written like
+/// [`write_canonical`] writes it. Whitespace between interchanges and segments,
+/// including the indentation written by [`crate::pretty::format_pretty`], is dropped.
+/// For a single interchange, the digest equals the SHA-256 of the
+/// [`write_canonical`] stream.
+///
+/// ```
+/// use x12_delimiters::canonical::canonicalize;
+///
+/// let unix = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nGS*HC*S*R~\n";
+/// let legacy = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}\r\n  GS|HC|S|R||}\r\n";
+///
+/// let a = canonicalize(unix).unwrap();
+/// let b = canonicalize(legacy).unwrap();
+/// assert_eq!(a.digest(), b.digest());
+/// assert!(a.as_bytes().ends_with(b"~GS*HC*S*R~"));
+/// assert_eq!(a.hex_digest().len(), 64);
+/// ```
+///
+/// # Errors
+/// Returns `CanonicalError::Delimiters` if `data` does not start with an ISA segment or
+/// has data other than whitespace after its last interchange, and
+/// `CanonicalError::Build` if a data byte equals one of the standard delimiters.
+pub fn canonicalize(data: &[u8]) -> Result<Canonical, CanonicalError> {
+    let mut bytes = Vec::with_capacity(data.len());
+    let mut found = interchanges(data);
+    for (delimiters, interchange) in found.by_ref() {
+        for segment in delimiters.segments(&minify(interchange, &delimiters)) {
+            write_canonical_segment(&mut bytes, &segment)?;
+        }
+    }
+    if bytes.is_empty() {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa.into());
+    }
+    if !found.remainder().iter().all(u8::is_ascii_whitespace) {
+        return Err(DelimiterError::NotIsaSegment.into());
+    }
+    Ok(Canonical {
+        digest: Sha256::digest(&bytes).into(),
+        bytes,
+    })
+}
+
+/// Streams the canonical form of `data` into `sink`.
+///
+/// The canonical form is the document rewritten with the standard delimiters (`~`, `*`,
+/// `:`), no line endings between segments, and no trailing empty elements. Two files that
+/// differ only in delimiters, line endings, or trailing element separators produce the
+/// same bytes, so hashing the stream gives a content hash suitable for deduplication.
+///
+/// Segments are written one at a time, so memory use is bounded by the largest segment.
+/// To hash the stream directly, use `canonical_digest` with the `digest` feature.
+///
+/// ```
+/// use x12_delimiters::canonical::write_canonical;
+///
+/// let unix = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nGS*HC*S*R~\n";
+/// let legacy = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}\r\nGS|HC|S|R||}\r\n";
+///
+/// let (mut a, mut b) = (Vec::new(), Vec::new());
+/// write_canonical(unix, &mut a).unwrap();
+/// write_canonical(legacy, &mut b).unwrap();
+/// assert_eq!(a, b);
+/// ```
+///
+/// # Errors
+/// Returns `CanonicalError::Delimiters` if `data` does not start with an ISA segment,
+/// `CanonicalError::Build` if a data byte equals one of the standard delimiters, and
+/// `CanonicalError::Io` if writing to `sink` fails.
+pub fn write_canonical<W: Write + ?Sized>(data: &[u8], sink: &mut W) -> Result<(), CanonicalError> {
+    for_each_canonical_segment(data, |segment| Ok(sink.write_all(segment)?))
+}
+
+/// Feeds the canonical form of `data` (see [`write_canonical`]) into `hasher`, producing
+/// an integrity hash that is stable across delimiter and line-ending variations.
+///
+/// Any hasher implementing [`digest::Update`] can be used, e.g. `sha2::Sha256` or
+/// `blake3::Hasher` with its `traits-preview` feature.
+///
+/// # Errors
+/// Returns `CanonicalError::Delimiters` if `data` does not start with an ISA segment and
+/// `CanonicalError::Build` if a data byte equals one of the standard delimiters. The
+/// hasher may already have been fed part of the document.
+#[cfg(feature = "digest")]
+pub fn canonical_digest<D: digest::Update + ?Sized>(
+    data: &[u8],
+    hasher: &mut D,
+) -> Result<(), CanonicalError> {
+    for_each_canonical_segment(data, |segment| {
+        hasher.update(segment);
+        Ok(())
+    })
+}
+
+/// Returns the SHA-256 digest of the [`write_canonical`] stream of `data`.
+pub(crate) fn canonical_sha256(data: &[u8]) -> Result<[u8; 32], CanonicalError> {
+    let mut hasher = Sha256::new();
+    for_each_canonical_segment(data, |segment| {
+        hasher.update(segment);
+        Ok(())
+    })?;
+    Ok(hasher.finalize().into())
+}
+
+/// Calls `f` with each segment of `data` in canonical form, terminator included.
+fn for_each_canonical_segment<F>(data: &[u8], mut f: F) -> Result<(), CanonicalError>
+where
+    F: FnMut(&[u8]) -> Result<(), CanonicalError>,
+{
+    let delimiters = Delimiters::from_isa(data)?;
+    let mut buf = Vec::new();
+    for segment in delimiters.segments(data) {
+        buf.clear();
+        write_canonical_segment(&mut buf, &segment)?;
+        f(&buf)?;
+    }
+    Ok(())
+}
+
+/// Appends `segment` in canonical form, including the standard terminator.
+pub(crate) fn write_canonical_segment(
+    out: &mut Vec<u8>,
+    segment: &Segment<'_>,
+) -> Result<(), CanonicalError> {
+    let canonical = Delimiters::default();
+    let start = out.len();
+    segment.write_with(out, &canonical)?;
+    out.pop();
+    while out.len() > start && out.last() == Some(&canonical.element_separator()) {
+        out.pop();
+    }
+    out.push(canonical.segment_terminator());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::BuildError;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    fn canonical(data: &[u8]) -> Result<Vec<u8>, CanonicalError> {
+        let mut out = Vec::new();
+        write_canonical(data, &mut out).map(|()| out)
+    }
+
+    #[test]
+    fn test_canonical_form() {
+        let data = format!("{}\r\nNM1*IL*1**~\r\nSV1*HC:99213*100~\r\n", ISA);
+        assert_eq!(
+            canonical(data.as_bytes()).unwrap(),
+            format!("{}NM1*IL*1~SV1*HC:99213*100~", ISA).into_bytes()
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_matches_digest_stream() {
+        let data = format!("{}\r\nNM1*IL*1**~\r\nSV1*HC:99213*100~\r\n", ISA);
+        let canonical_form = canonicalize(data.as_bytes()).unwrap();
+        assert_eq!(
+            canonical_form.digest(),
+            &canonical_sha256(data.as_bytes()).unwrap()
+        );
+        assert_eq!(
+            canonical_form.digest()[..],
+            Sha256::digest(canonical(data.as_bytes()).unwrap())[..]
+        );
+        assert_eq!(
+            canonical_form.as_bytes(),
+            canonical(data.as_bytes()).unwrap()
+        );
+
+        let first = format!("{}\nIEA*0*000000001~", data);
+        let legacy = ISA.replace('*', "|").replace(":~", ">'") + "\nNM1|IL|1'IEA|0|000000001'";
+        let batch = format!("{}\n{}\n", first, legacy);
+        assert_eq!(
+            canonicalize(batch.as_bytes()).unwrap().into_bytes(),
+            format!(
+                "{}NM1*IL*1~SV1*HC:99213*100~IEA*0*000000001~{}NM1*IL*1~IEA*0*000000001~",
+                ISA, ISA
+            )
+            .into_bytes()
+        );
+        assert!(matches!(
+            canonicalize(format!("{}GS", batch).as_bytes()),
+            Err(CanonicalError::Delimiters(DelimiterError::NotIsaSegment))
+        ));
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_canonical_digest_feeds_update() {
+        struct Collect(Vec<u8>);
+
+        impl digest::Update for Collect {
+            fn update(&mut self, data: &[u8]) {
+                self.0.extend_from_slice(data);
+            }
+        }
+
+        let data = format!("{}\r\nNM1*IL*1**~\r\nSV1*HC:99213*100~\r\n", ISA);
+        let mut hasher = Collect(Vec::new());
+        canonical_digest(data.as_bytes(), &mut hasher).unwrap();
+        assert_eq!(hasher.0, canonical(data.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_canonical_errors() {
+        assert!(matches!(
+            canonical(b"ISA*00*"),
+            Err(CanonicalError::Delimiters(_))
+        ));
+
+        let data = ISA.replace('*', "|").replace(":~", ">!") + "NTE|A*B!";
+        assert!(matches!(
+            canonical(data.as_bytes()),
+            Err(CanonicalError::Build(BuildError::DelimiterConflict {
+                byte: b'*',
+                ..
+            }))
+        ));
+    }
+}
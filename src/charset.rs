@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// Special characters of the X12 basic character set, besides letters, digits and space.
+const BASIC_SPECIALS: &[u8] = b"!\"&'()*+,-./:;?=";
+
+/// Special characters added by the X12 extended character set, besides lowercase letters.
+const EXTENDED_SPECIALS: &[u8] = b"%@[]_{}\\|<>~^#$`";
+
+/// An X12 character set.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum CharacterSet {
+    /// Uppercase letters, digits, space and `! " & ' ( ) * + , - . / : ; ? =`.
+    Basic,
+    /// The basic set plus lowercase letters and `% @ [ ] _ { } \ | < > ~ ^ # $ ``.
+    Extended,
+}
+
+impl CharacterSet {
+    /// Returns true if `byte` belongs to the character set.
+    pub fn contains(&self, byte: u8) -> bool {
+        let basic = byte.is_ascii_uppercase()
+            || byte.is_ascii_digit()
+            || byte == b' '
+            || BASIC_SPECIALS.contains(&byte);
+        match self {
+            CharacterSet::Basic => basic,
+            CharacterSet::Extended => {
+                basic || byte.is_ascii_lowercase() || EXTENDED_SPECIALS.contains(&byte)
+            }
+        }
+    }
+}
+
+/// A rule a delimiter can violate in strict mode; see [`check_delimiter`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum CharsetRule {
+    /// Letters and digits make up element data and cannot separate it.
+    Alphanumeric,
+    /// Space pads fixed-width fields and cannot separate them.
+    Space,
+    /// The byte is not in the character set agreed with the trading partner.
+    OutsideCharacterSet(CharacterSet),
+}
+
+impl fmt::Display for CharsetRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CharsetRule::Alphanumeric => write!(f, "delimiters must not be letters or digits"),
+            CharsetRule::Space => write!(f, "delimiters must not be space"),
+            CharsetRule::OutsideCharacterSet(CharacterSet::Basic) => {
+                write!(
+                    f,
+                    "delimiters must be special characters of the basic character set"
+                )
+            }
+            CharsetRule::OutsideCharacterSet(CharacterSet::Extended) => write!(
+                f,
+                "delimiters must be special characters of the basic or extended character set"
+            ),
+        }
+    }
+}
+
+/// Checks that `byte` is a legal delimiter for data in `set`.
+///
+/// A legal delimiter is a special character of the set: a member that is not a letter, a
+/// digit or space, since those appear in element data.
+///
+/// ```
+/// use x12_delimiters::charset::{check_delimiter, CharacterSet, CharsetRule};
+///
+/// assert_eq!(check_delimiter(b'*', CharacterSet::Basic), Ok(()));
+/// assert_eq!(
+///     check_delimiter(b'~', CharacterSet::Basic),
+///     Err(CharsetRule::OutsideCharacterSet(CharacterSet::Basic))
+/// );
+/// assert_eq!(check_delimiter(b'~', CharacterSet::Extended), Ok(()));
+/// ```
+pub fn check_delimiter(byte: u8, set: CharacterSet) -> Result<(), CharsetRule> {
+    if byte.is_ascii_alphanumeric() {
+        Err(CharsetRule::Alphanumeric)
+    } else if byte == b' ' {
+        Err(CharsetRule::Space)
+    } else if !set.contains(byte) {
+        Err(CharsetRule::OutsideCharacterSet(set))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_character_sets() {
+        assert!(CharacterSet::Basic.contains(b'A'));
+        assert!(!CharacterSet::Basic.contains(b'a'));
+        assert!(CharacterSet::Extended.contains(b'a'));
+        assert!(CharacterSet::Extended.contains(b'|'));
+        assert!(!CharacterSet::Extended.contains(0x1D));
+        assert!(!CharacterSet::Extended.contains(0x85));
+        for byte in 0..=0xFF {
+            if CharacterSet::Basic.contains(byte) {
+                assert!(CharacterSet::Extended.contains(byte));
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_delimiter_rules() {
+        assert_eq!(
+            check_delimiter(b'x', CharacterSet::Extended),
+            Err(CharsetRule::Alphanumeric)
+        );
+        assert_eq!(
+            check_delimiter(b' ', CharacterSet::Basic),
+            Err(CharsetRule::Space)
+        );
+        assert_eq!(
+            check_delimiter(b'\n', CharacterSet::Extended),
+            Err(CharsetRule::OutsideCharacterSet(CharacterSet::Extended))
+        );
+        assert_eq!(check_delimiter(b':', CharacterSet::Basic), Ok(()));
+    }
+}
@@ -0,0 +1,208 @@
+use std::borrow::Cow;
+
+/// Converts between a single-byte code page and Latin-1 (ISO 8859-1).
+///
+/// Detection and transcoding work on ASCII delimiters and segment IDs, so data in other
+/// code pages is decoded to Latin-1 first and, if needed, encoded back afterwards.
+/// Implementations must be bijective: `encode_byte(decode_byte(b)) == b` for every byte.
+/// [`CodePage`] implements this for any 256-entry table.
+pub trait ByteTranscoder {
+    /// Returns the name of the code page, such as `IBM037`.
+    fn name(&self) -> &str;
+
+    /// Returns the Latin-1 byte for `byte` in this code page.
+    fn decode_byte(&self, byte: u8) -> u8;
+
+    /// Returns the byte in this code page for the Latin-1 `byte`.
+    fn encode_byte(&self, byte: u8) -> u8;
+
+    /// Decodes `data` to Latin-1, borrowing it if no byte changes.
+    fn decode<'a>(&self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        map_bytes(data, |b| self.decode_byte(b))
+    }
+
+    /// Encodes Latin-1 `data` in this code page, borrowing it if no byte changes.
+    fn encode<'a>(&self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        map_bytes(data, |b| self.encode_byte(b))
+    }
+}
+
+/// A single-byte code page defined by its mapping to Latin-1.
+///
+/// ```
+/// use x12_delimiters::codepage::{ByteTranscoder, CP037};
+///
+/// let ebcdic = CP037.encode(b"ISA*00~");
+/// assert_eq!(&ebcdic[..4], &[0xC9, 0xE2, 0xC1, 0x5C]);
+/// assert_eq!(CP037.decode(&ebcdic), &b"ISA*00~"[..]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CodePage {
+    name: &'static str,
+    to_latin1: [u8; 256],
+    from_latin1: [u8; 256],
+}
+
+/// EBCDIC code page 37 (US/Canada).
+pub const CP037: CodePage = CodePage::from_table("IBM037", &CP037_TO_LATIN1);
+
+/// EBCDIC code page 500 (International).
+pub const CP500: CodePage = CodePage::from_table("IBM500", &CP500_TO_LATIN1);
+
+/// ISO 8859-1, which maps every byte to itself.
+pub const LATIN1: CodePage = CodePage::from_table("ISO-8859-1", &identity());
+
+impl CodePage {
+    /// Creates a code page from `to_latin1`, which maps each byte of the code page to
+    /// its Latin-1 byte.
+    ///
+    /// Returns `None` if two bytes map to the same Latin-1 byte.
+    pub fn new(name: &'static str, to_latin1: [u8; 256]) -> Option<Self> {
+        let mut seen = [false; 256];
+        for &b in &to_latin1 {
+            if std::mem::replace(&mut seen[b as usize], true) {
+                return None;
+            }
+        }
+        Some(CodePage::from_table(name, &to_latin1))
+    }
+
+    const fn from_table(name: &'static str, to_latin1: &[u8; 256]) -> Self {
+        let mut from_latin1 = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            from_latin1[to_latin1[i] as usize] = i as u8;
+            i += 1;
+        }
+        CodePage {
+            name,
+            to_latin1: *to_latin1,
+            from_latin1,
+        }
+    }
+}
+
+impl ByteTranscoder for CodePage {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn decode_byte(&self, byte: u8) -> u8 {
+        self.to_latin1[byte as usize]
+    }
+
+    fn encode_byte(&self, byte: u8) -> u8 {
+        self.from_latin1[byte as usize]
+    }
+}
+
+/// Returns the first of `candidates` in which `data` starts with an ISA segment ID
+/// followed by an element separator.
+///
+/// ```
+/// use x12_delimiters::codepage::{guess, ByteTranscoder, CP037, LATIN1};
+///
+/// let candidates: [&dyn ByteTranscoder; 2] = [&LATIN1, &CP037];
+/// assert_eq!(guess(&[0xC9, 0xE2, 0xC1, 0x5C], &candidates).unwrap().name(), "IBM037");
+/// assert!(guess(b"GS*HC", &candidates).is_none());
+/// ```
+pub fn guess<'t>(
+    data: &[u8],
+    candidates: &[&'t dyn ByteTranscoder],
+) -> Option<&'t dyn ByteTranscoder> {
+    let head = data.get(..4)?;
+    candidates.iter().copied().find(|transcoder| {
+        let decoded = transcoder.decode(head);
+        decoded.starts_with(b"ISA") && !decoded[3].is_ascii_alphanumeric()
+    })
+}
+
+fn map_bytes(data: &[u8], map: impl Fn(u8) -> u8) -> Cow<'_, [u8]> {
+    match data.iter().position(|&b| map(b) != b) {
+        None => Cow::Borrowed(data),
+        Some(first) => {
+            let mut out = data[..first].to_vec();
+            out.extend(data[first..].iter().map(|&b| map(b)));
+            Cow::Owned(out)
+        }
+    }
+}
+
+const fn identity() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+const CP037_TO_LATIN1: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9C, 0x09, 0x86, 0x7F, 0x97, 0x8D, 0x8E, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+    0x10, 0x11, 0x12, 0x13, 0x9D, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8F, 0x1C, 0x1D, 0x1E, 0x1F,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0A, 0x17, 0x1B, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9A, 0x9B, 0x14, 0x15, 0x9E, 0x1A,
+    0x20, 0xA0, 0xE2, 0xE4, 0xE0, 0xE1, 0xE3, 0xE5, 0xE7, 0xF1, 0xA2, 0x2E, 0x3C, 0x28, 0x2B, 0x7C,
+    0x26, 0xE9, 0xEA, 0xEB, 0xE8, 0xED, 0xEE, 0xEF, 0xEC, 0xDF, 0x21, 0x24, 0x2A, 0x29, 0x3B, 0xAC,
+    0x2D, 0x2F, 0xC2, 0xC4, 0xC0, 0xC1, 0xC3, 0xC5, 0xC7, 0xD1, 0xA6, 0x2C, 0x25, 0x5F, 0x3E, 0x3F,
+    0xF8, 0xC9, 0xCA, 0xCB, 0xC8, 0xCD, 0xCE, 0xCF, 0xCC, 0x60, 0x3A, 0x23, 0x40, 0x27, 0x3D, 0x22,
+    0xD8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xAB, 0xBB, 0xF0, 0xFD, 0xFE, 0xB1,
+    0xB0, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F, 0x70, 0x71, 0x72, 0xAA, 0xBA, 0xE6, 0xB8, 0xC6, 0xA4,
+    0xB5, 0x7E, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0xA1, 0xBF, 0xD0, 0xDD, 0xDE, 0xAE,
+    0x5E, 0xA3, 0xA5, 0xB7, 0xA9, 0xA7, 0xB6, 0xBC, 0xBD, 0xBE, 0x5B, 0x5D, 0xAF, 0xA8, 0xB4, 0xD7,
+    0x7B, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xAD, 0xF4, 0xF6, 0xF2, 0xF3, 0xF5,
+    0x7D, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F, 0x50, 0x51, 0x52, 0xB9, 0xFB, 0xFC, 0xF9, 0xFA, 0xFF,
+    0x5C, 0xF7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0xB2, 0xD4, 0xD6, 0xD2, 0xD3, 0xD5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xB3, 0xDB, 0xDC, 0xD9, 0xDA, 0x9F,
+];
+
+const CP500_TO_LATIN1: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9C, 0x09, 0x86, 0x7F, 0x97, 0x8D, 0x8E, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+    0x10, 0x11, 0x12, 0x13, 0x9D, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8F, 0x1C, 0x1D, 0x1E, 0x1F,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0A, 0x17, 0x1B, 0x88, 0x89, 0x8A, 0x8B, 0x8C, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9A, 0x9B, 0x14, 0x15, 0x9E, 0x1A,
+    0x20, 0xA0, 0xE2, 0xE4, 0xE0, 0xE1, 0xE3, 0xE5, 0xE7, 0xF1, 0x5B, 0x2E, 0x3C, 0x28, 0x2B, 0x21,
+    0x26, 0xE9, 0xEA, 0xEB, 0xE8, 0xED, 0xEE, 0xEF, 0xEC, 0xDF, 0x5D, 0x24, 0x2A, 0x29, 0x3B, 0x5E,
+    0x2D, 0x2F, 0xC2, 0xC4, 0xC0, 0xC1, 0xC3, 0xC5, 0xC7, 0xD1, 0xA6, 0x2C, 0x25, 0x5F, 0x3E, 0x3F,
+    0xF8, 0xC9, 0xCA, 0xCB, 0xC8, 0xCD, 0xCE, 0xCF, 0xCC, 0x60, 0x3A, 0x23, 0x40, 0x27, 0x3D, 0x22,
+    0xD8, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xAB, 0xBB, 0xF0, 0xFD, 0xFE, 0xB1,
+    0xB0, 0x6A, 0x6B, 0x6C, 0x6D, 0x6E, 0x6F, 0x70, 0x71, 0x72, 0xAA, 0xBA, 0xE6, 0xB8, 0xC6, 0xA4,
+    0xB5, 0x7E, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0xA1, 0xBF, 0xD0, 0xDD, 0xDE, 0xAE,
+    0xA2, 0xA3, 0xA5, 0xB7, 0xA9, 0xA7, 0xB6, 0xBC, 0xBD, 0xBE, 0xAC, 0x7C, 0xAF, 0xA8, 0xB4, 0xD7,
+    0x7B, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xAD, 0xF4, 0xF6, 0xF2, 0xF3, 0xF5,
+    0x7D, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E, 0x4F, 0x50, 0x51, 0x52, 0xB9, 0xFB, 0xFC, 0xF9, 0xFA, 0xFF,
+    0x5C, 0xF7, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0xB2, 0xD4, 0xD6, 0xD2, 0xD3, 0xD5,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xB3, 0xDB, 0xDC, 0xD9, 0xDA, 0x9F,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtins_round_trip() {
+        for page in [&CP037, &CP500, &LATIN1] {
+            let all: Vec<u8> = (0..=255).collect();
+            assert_eq!(page.encode(&page.decode(&all)), &all[..], "{}", page.name());
+        }
+        assert!(matches!(LATIN1.decode(b"ISA*"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_code_pages_differ_on_punctuation() {
+        assert_eq!(CP037.decode_byte(0x4F), b'|');
+        assert_eq!(CP500.decode_byte(0x4F), b'!');
+        assert_eq!(CP037.encode(b"ISA*~:"), CP500.encode(b"ISA*~:"));
+        assert_eq!(CP037.decode_byte(0x25), b'\n');
+    }
+
+    #[test]
+    fn test_new_rejects_duplicates() {
+        let mut table = identity();
+        let custom = CodePage::new("custom", table).unwrap();
+        assert_eq!(custom.encode(b"ISA*"), &b"ISA*"[..]);
+        table[0] = 1;
+        assert_eq!(CodePage::new("custom", table), None);
+    }
+}
@@ -0,0 +1,184 @@
+use std::io::Write;
+
+use crate::errors::{ConcatError, EnvelopeError};
+use crate::interchange::{Document, Interchange};
+use crate::suffix::normalize_suffixes;
+use crate::TerminatorSuffix;
+
+/// Options for [`concat_interchanges`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ConcatOptions {
+    suffix: TerminatorSuffix,
+    separator: bool,
+}
+
+impl ConcatOptions {
+    /// Creates options that write every segment terminator with no line ending and put
+    /// nothing between interchanges.
+    pub fn new() -> Self {
+        ConcatOptions {
+            suffix: TerminatorSuffix::None,
+            separator: false,
+        }
+    }
+
+    /// Sets the line ending written after every segment terminator.
+    pub fn with_suffix(mut self, suffix: TerminatorSuffix) -> Self {
+        self.suffix = suffix;
+        self
+    }
+
+    /// Writes a newline between consecutive interchanges, in addition to the suffix.
+    pub fn with_separator(mut self, separator: bool) -> Self {
+        self.separator = separator;
+        self
+    }
+}
+
+impl Default for ConcatOptions {
+    fn default() -> Self {
+        ConcatOptions::new()
+    }
+}
+
+/// Writes the interchanges of every output in `outputs` to `writer` as one stream.
+///
+/// Each output may hold any number of interchanges, each with its own delimiters, which
+/// are kept. Every interchange must be closed: it and each of its groups and transaction
+/// sets needs its trailer. All outputs are checked before anything is written, so a bad
+/// output leaves `writer` untouched. Line endings after segment terminators are rewritten
+/// to the configured suffix, and whitespace between interchanges is dropped unless
+/// [`ConcatOptions::with_separator`] is set.
+///
+/// Returns the number of interchanges written.
+///
+/// ```
+/// use x12_delimiters::concat::{concat_interchanges, ConcatOptions};
+/// use x12_delimiters::TerminatorSuffix;
+///
+/// let first = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\nIEA*0*000000001~\r\n";
+/// let second = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}IEA|0|000000002}";
+///
+/// let mut batch = Vec::new();
+/// let options = ConcatOptions::new().with_suffix(TerminatorSuffix::Lf);
+/// assert_eq!(concat_interchanges([&first[..], &second[..]], &mut batch, &options).unwrap(), 2);
+/// let batch = String::from_utf8(batch).unwrap();
+/// assert!(batch.contains("*:~\nIEA*0*000000001~\nISA|00|"));
+/// assert!(batch.ends_with("}\n"));
+/// ```
+///
+/// # Errors
+/// Returns `ConcatError::Envelope` for the first output that does not parse (see
+/// [`Document::parse`]) or holds an envelope without its trailer, reported as
+/// `EnvelopeError::MissingSegment`, and `ConcatError::Io` if writing fails.
+pub fn concat_interchanges<I, T, W>(
+    outputs: I,
+    mut writer: W,
+    options: &ConcatOptions,
+) -> Result<usize, ConcatError>
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+    W: Write,
+{
+    let outputs: Vec<T> = outputs.into_iter().collect();
+    let mut interchanges = Vec::new();
+    for (output, data) in outputs.iter().enumerate() {
+        let error = |error| ConcatError::Envelope { output, error };
+        let document = Document::parse(data.as_ref()).map_err(error)?;
+        for interchange in document.iter() {
+            check_closed(interchange).map_err(error)?;
+        }
+        interchanges.extend(
+            document
+                .interchanges()
+                .iter()
+                .map(|interchange| (interchange.as_bytes(), interchange.delimiters())),
+        );
+    }
+
+    for (i, (bytes, delimiters)) in interchanges.iter().enumerate() {
+        if i > 0 && options.separator {
+            writer.write_all(b"\n")?;
+        }
+        writer.write_all(&normalize_suffixes(bytes, delimiters, options.suffix))?;
+    }
+    Ok(interchanges.len())
+}
+
+/// Checks that the interchange and every envelope inside it has its trailer.
+fn check_closed(interchange: &Interchange<'_>) -> Result<(), EnvelopeError> {
+    for group in interchange.groups() {
+        if group.transactions().iter().any(|t| t.se().is_none()) {
+            return Err(EnvelopeError::MissingSegment { expected: "SE" });
+        }
+        if group.ge().is_none() {
+            return Err(EnvelopeError::MissingSegment { expected: "GE" });
+        }
+    }
+    if interchange.iea().is_none() {
+        return Err(EnvelopeError::MissingSegment { expected: "IEA" });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{interchange, isa};
+
+    const GROUP: &str = "GS*HC*S*R*20250403*0856*1*X*005010~ST*837*0001~SE*2*0001~GE*1*1~";
+
+    #[test]
+    fn test_concat_with_separator_and_suffix() {
+        let first = format!("{}\r\n\r\n{}", interchange(1, GROUP), interchange(2, GROUP));
+        let second = interchange(3, GROUP).replace('~', "~\n");
+        let options = ConcatOptions::new()
+            .with_suffix(TerminatorSuffix::CrLf)
+            .with_separator(true);
+        let mut out = Vec::new();
+        assert_eq!(
+            concat_interchanges([first, second], &mut out, &options).unwrap(),
+            3
+        );
+        let expected: Vec<String> = (1..=3)
+            .map(|n| interchange(n, GROUP).replace('~', "~\r\n"))
+            .collect();
+        assert_eq!(String::from_utf8(out).unwrap(), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_unclosed_envelopes_write_nothing() {
+        let closed = interchange(1, GROUP);
+        let cases = [
+            (isa(2) + GROUP, "IEA"),
+            (
+                interchange(
+                    2,
+                    "GS*HC*S*R*20250403*0856*1*X*005010~ST*837*0001~SE*2*0001~",
+                ),
+                "GE",
+            ),
+            (
+                interchange(2, "GS*HC*S*R*20250403*0856*1*X*005010~ST*837*0001~GE*1*1~"),
+                "SE",
+            ),
+        ];
+        for (bad, expected) in cases {
+            let mut out = Vec::new();
+            let result = concat_interchanges([&closed, &bad], &mut out, &ConcatOptions::new());
+            assert!(matches!(
+                result,
+                Err(ConcatError::Envelope {
+                    output: 1,
+                    error: EnvelopeError::MissingSegment { expected: e },
+                }) if e == expected
+            ));
+            assert!(out.is_empty());
+        }
+        assert_eq!(
+            concat_interchanges(Vec::<&[u8]>::new(), Vec::new(), &ConcatOptions::new()).unwrap(),
+            0
+        );
+    }
+}
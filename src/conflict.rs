@@ -0,0 +1,551 @@
+use std::fmt;
+
+use crate::errors::{BuildError, DelimiterError};
+use crate::summary::interchange_spans;
+use crate::{DelimiterKind, Delimiters};
+
+/// A data byte that collides with one of the delimiters being written.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Conflict<'a> {
+    segment_id: &'a [u8],
+    element: usize,
+    offset: usize,
+    byte: u8,
+}
+
+impl<'a> Conflict<'a> {
+    /// Creates a conflict record.
+    ///
+    /// # Arguments
+    /// * `segment_id` - ID of the segment being written
+    /// * `element` - Element index, where 0 is the segment ID
+    /// * `offset` - Byte offset of the conflicting byte within the source segment or value
+    /// * `byte` - The conflicting data byte
+    pub fn new(segment_id: &'a [u8], element: usize, offset: usize, byte: u8) -> Self {
+        Conflict {
+            segment_id,
+            element,
+            offset,
+            byte,
+        }
+    }
+
+    /// Returns the ID of the segment being written.
+    pub fn segment_id(&self) -> &'a [u8] {
+        self.segment_id
+    }
+
+    /// Returns the element index, where 0 is the segment ID.
+    pub fn element(&self) -> usize {
+        self.element
+    }
+
+    /// Returns the byte offset of the conflicting byte.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the conflicting data byte.
+    pub fn byte(&self) -> u8 {
+        self.byte
+    }
+}
+
+/// What a writer does with a data byte that equals an active delimiter.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ConflictAction {
+    /// Fail the write with `BuildError::DelimiterConflict`.
+    #[default]
+    Error,
+    /// Write the given byte instead.
+    ReplaceWith(u8),
+    /// Drop the byte.
+    Strip,
+    /// Write a space instead.
+    EscapeToSpace,
+}
+
+/// Decides how writers handle data values that contain an active delimiter.
+///
+/// Every writer and builder in the crate consults a policy for each conflicting byte.
+/// [`ConflictAction`] is itself a policy that always answers with the same action;
+/// implement the trait to vary the answer, e.g. per segment or per partner.
+///
+/// ```
+/// use x12_delimiters::conflict::{Conflict, ConflictAction, ConflictPolicy};
+///
+/// /// Strips conflicts from free-form notes but rejects them everywhere else.
+/// struct NotesOnly;
+///
+/// impl ConflictPolicy for NotesOnly {
+///     fn resolve(&self, conflict: &Conflict<'_>) -> ConflictAction {
+///         match conflict.segment_id() {
+///             b"NTE" => ConflictAction::Strip,
+///             _ => ConflictAction::Error,
+///         }
+///     }
+/// }
+/// ```
+pub trait ConflictPolicy {
+    /// Returns the action to take for `conflict`.
+    fn resolve(&self, conflict: &Conflict<'_>) -> ConflictAction;
+}
+
+impl ConflictPolicy for ConflictAction {
+    fn resolve(&self, _conflict: &Conflict<'_>) -> ConflictAction {
+        *self
+    }
+}
+
+impl<P: ConflictPolicy + ?Sized> ConflictPolicy for &P {
+    fn resolve(&self, conflict: &Conflict<'_>) -> ConflictAction {
+        (**self).resolve(conflict)
+    }
+}
+
+impl<P: ConflictPolicy + ?Sized> ConflictPolicy for Box<P> {
+    fn resolve(&self, conflict: &Conflict<'_>) -> ConflictAction {
+        (**self).resolve(conflict)
+    }
+}
+
+/// A byte in a value that equals a delimiter, found by [`Delimiters::check_value`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ValueConflict {
+    offset: usize,
+    byte: u8,
+    delimiter: Option<DelimiterKind>,
+}
+
+impl ValueConflict {
+    /// Returns the offset of the byte within the value.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the conflicting byte.
+    pub fn byte(&self) -> u8 {
+        self.byte
+    }
+
+    /// Returns the delimiter the byte equals, or `None` if it is the repetition separator.
+    pub fn delimiter(&self) -> Option<DelimiterKind> {
+        self.delimiter
+    }
+}
+
+impl fmt::Display for ValueConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .delimiter
+            .map_or("repetition separator", |kind| kind.as_str());
+        write!(
+            f,
+            "byte {:?} at offset {} is the {}",
+            char::from(self.byte),
+            self.offset,
+            name
+        )
+    }
+}
+
+/// A content byte in a document that equals a delimiter of a candidate set, found by
+/// [`Delimiters::find_collisions`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Collision<'a> {
+    offset: usize,
+    segment_id: &'a [u8],
+    element: usize,
+    byte: u8,
+    delimiter: Option<DelimiterKind>,
+}
+
+impl<'a> Collision<'a> {
+    /// Returns the offset of the byte in the scanned data.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the ID of the segment holding the byte.
+    pub fn segment_id(&self) -> &'a [u8] {
+        self.segment_id
+    }
+
+    /// Returns the element index, where 0 is the segment ID.
+    pub fn element(&self) -> usize {
+        self.element
+    }
+
+    /// Returns the colliding byte.
+    pub fn byte(&self) -> u8 {
+        self.byte
+    }
+
+    /// Returns the delimiter the byte equals, or `None` if it is the repetition separator.
+    pub fn delimiter(&self) -> Option<DelimiterKind> {
+        self.delimiter
+    }
+}
+
+/// Every byte of a value that cannot be written verbatim.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ConflictReport {
+    conflicts: Vec<ValueConflict>,
+}
+
+impl ConflictReport {
+    /// Returns the conflicts in offset order. The list is never empty.
+    pub fn conflicts(&self) -> &[ValueConflict] {
+        &self.conflicts
+    }
+
+    /// Returns the offsets of the conflicting bytes.
+    pub fn offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.conflicts.iter().map(ValueConflict::offset)
+    }
+}
+
+impl fmt::Display for ConflictReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value contains {} delimiter byte(s)",
+            self.conflicts.len()
+        )?;
+        for (i, conflict) in self.conflicts.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { "; " })?;
+            write!(f, "{}", conflict)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConflictReport {}
+
+impl Delimiters {
+    /// Checks whether `value` can be written as an element verbatim.
+    ///
+    /// Every byte equal to one of the delimiters, or to the repetition separator if one
+    /// is declared, is reported with its offset, so mapping code can reject or transform
+    /// the value before serialization. Unlike a [`ConflictPolicy`], this does not change
+    /// anything.
+    ///
+    /// ```
+    /// use x12_delimiters::{DelimiterKind, Delimiters};
+    ///
+    /// let delimiters = Delimiters::default();
+    /// assert!(delimiters.check_value(b"ACME CORP").is_ok());
+    ///
+    /// let report = delimiters.check_value(b"A*B:C").unwrap_err();
+    /// assert_eq!(report.offsets().collect::<Vec<_>>(), [1, 3]);
+    /// assert_eq!(
+    ///     report.conflicts()[1].delimiter(),
+    ///     Some(DelimiterKind::SubElementSeparator)
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `ConflictReport` listing every conflicting byte.
+    pub fn check_value(&self, value: &[u8]) -> Result<(), ConflictReport> {
+        let conflicts: Vec<ValueConflict> = value
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, &byte)| {
+                let delimiter = DelimiterKind::ALL
+                    .into_iter()
+                    .find(|&kind| self.get(kind) == byte);
+                let repeated = self.repetition_separator() == Some(byte);
+                (delimiter.is_some() || repeated).then_some(ValueConflict {
+                    offset,
+                    byte,
+                    delimiter,
+                })
+            })
+            .collect();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(ConflictReport { conflicts })
+        }
+    }
+
+    /// Reports every content byte of `data` that equals one of these delimiters, or the
+    /// repetition separator if one is declared.
+    ///
+    /// Each interchange is read with the delimiters declared in its own ISA, and data
+    /// after the last interchange with that interchange's delimiters. Separators of the
+    /// source and the delimiter declarations in ISA11 and ISA16 are structure, not content,
+    /// so only bytes that would have to be escaped or replaced when writing `data` with
+    /// these delimiters are reported. An empty result means the delimiters are safe.
+    ///
+    /// ```
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~NM1*IL*1*SMITH|JONES~IEA*0*000000001~";
+    /// let pipes = Delimiters::new(b'~', b'|', b'>');
+    /// let collisions = pipes.find_collisions(data).unwrap();
+    /// assert_eq!(collisions.len(), 1);
+    /// assert_eq!((collisions[0].offset(), collisions[0].element()), (120, 3));
+    ///
+    /// let candidates = [pipes, Delimiters::new(b'\'', b'+', b'>')];
+    /// let safe = candidates.iter().find(|d| d.find_collisions(data).unwrap().is_empty());
+    /// assert_eq!(safe, Some(&candidates[1]));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `DelimiterError` if `data` does not start with an ISA segment; use
+    /// [`Delimiters::find_collisions_with`] for fragments without one.
+    pub fn find_collisions<'a>(
+        &self,
+        data: &'a [u8],
+    ) -> Result<Vec<Collision<'a>>, DelimiterError> {
+        let spans = interchange_spans(data);
+        let Some(&(_, last)) = spans.last() else {
+            Delimiters::from_isa(data)?;
+            return Err(DelimiterError::MissingIsa);
+        };
+        let mut collisions = Vec::new();
+        let mut end = 0;
+        for &(span, source) in &spans {
+            collect_collisions(&mut collisions, data, span.start..span.end, &source, self);
+            end = span.end;
+        }
+        collect_collisions(&mut collisions, data, end..data.len(), &last, self);
+        Ok(collisions)
+    }
+
+    /// Like [`Delimiters::find_collisions`], reading all of `data` with `source` instead
+    /// of the delimiters declared in an ISA.
+    pub fn find_collisions_with<'a>(
+        &self,
+        data: &'a [u8],
+        source: &Delimiters,
+    ) -> Vec<Collision<'a>> {
+        let mut collisions = Vec::new();
+        collect_collisions(&mut collisions, data, 0..data.len(), source, self);
+        collisions
+    }
+}
+
+/// Appends the collisions with `candidate` in `data[range]`, read with `source`.
+fn collect_collisions<'a>(
+    out: &mut Vec<Collision<'a>>,
+    data: &'a [u8],
+    range: std::ops::Range<usize>,
+    source: &Delimiters,
+    candidate: &Delimiters,
+) {
+    let base = range.start;
+    for (span, segment) in source.segments(&data[range]).spanned() {
+        let id = segment.id();
+        let is_isa = id == b"ISA";
+        let repetition = source.repetition_separator().filter(|_| !is_isa);
+        let mut element = 0;
+        for (offset, &byte) in segment.as_bytes().iter().enumerate() {
+            if byte == source.element_separator() {
+                element += 1;
+                continue;
+            }
+            if byte == source.sub_element_separator()
+                || Some(byte) == repetition
+                || (is_isa && matches!(element, 11 | 16))
+            {
+                continue;
+            }
+            let delimiter = DelimiterKind::ALL
+                .into_iter()
+                .find(|&kind| candidate.get(kind) == byte);
+            if delimiter.is_some() || candidate.repetition_separator() == Some(byte) {
+                out.push(Collision {
+                    offset: base + span.start + offset,
+                    segment_id: id,
+                    element,
+                    byte,
+                    delimiter,
+                });
+            }
+        }
+    }
+}
+
+/// Returns true if `byte` is one of the delimiters written by `delimiters`, including the
+/// repetition separator if there is one.
+pub(crate) fn is_delimiter(delimiters: &Delimiters, byte: u8) -> bool {
+    byte == delimiters.segment_terminator()
+        || byte == delimiters.element_separator()
+        || byte == delimiters.sub_element_separator()
+        || delimiters.repetition_separator() == Some(byte)
+}
+
+/// Applies `policy` to a conflicting byte, appending the replacement (if any) to `out`.
+pub(crate) fn resolve_into<P: ConflictPolicy + ?Sized>(
+    out: &mut Vec<u8>,
+    policy: &P,
+    conflict: Conflict<'_>,
+    target: &Delimiters,
+) -> Result<(), BuildError> {
+    let error = BuildError::DelimiterConflict {
+        byte: conflict.byte(),
+        element: conflict.element(),
+        offset: conflict.offset(),
+    };
+    match policy.resolve(&conflict) {
+        ConflictAction::Error => Err(error),
+        ConflictAction::ReplaceWith(byte) if is_delimiter(target, byte) => Err(error),
+        ConflictAction::ReplaceWith(byte) => {
+            out.push(byte);
+            Ok(())
+        }
+        ConflictAction::Strip => Ok(()),
+        ConflictAction::EscapeToSpace => {
+            out.push(b' ');
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_is_constant_policy() {
+        let conflict = Conflict::new(b"NM1", 3, 12, b'*');
+        assert_eq!(
+            ConflictAction::Strip.resolve(&conflict),
+            ConflictAction::Strip
+        );
+        assert_eq!(ConflictAction::default(), ConflictAction::Error);
+    }
+
+    #[test]
+    fn test_resolve_into() {
+        let target = Delimiters::default();
+        let conflict = Conflict::new(b"NM1", 3, 12, b'*');
+        let mut out = Vec::new();
+
+        resolve_into(
+            &mut out,
+            &ConflictAction::ReplaceWith(b'-'),
+            conflict,
+            &target,
+        )
+        .unwrap();
+        resolve_into(&mut out, &ConflictAction::Strip, conflict, &target).unwrap();
+        resolve_into(&mut out, &ConflictAction::EscapeToSpace, conflict, &target).unwrap();
+        assert_eq!(out, b"- ");
+
+        let expected = Err(BuildError::DelimiterConflict {
+            byte: b'*',
+            element: 3,
+            offset: 12,
+        });
+        assert_eq!(
+            resolve_into(&mut out, &ConflictAction::Error, conflict, &target),
+            expected
+        );
+        assert_eq!(
+            resolve_into(
+                &mut out,
+                &ConflictAction::ReplaceWith(b'~'),
+                conflict,
+                &target
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_check_value() {
+        let delimiters = Delimiters::new(b'~', b'|', b'>').with_repetition_separator(Some(b'^'));
+        assert_eq!(delimiters.check_value(b""), Ok(()));
+        assert_eq!(delimiters.check_value(b"A*B:C"), Ok(()));
+
+        let report = delimiters.check_value(b"~x^").unwrap_err();
+        assert_eq!(
+            report.conflicts(),
+            &[
+                ValueConflict {
+                    offset: 0,
+                    byte: b'~',
+                    delimiter: Some(DelimiterKind::SegmentTerminator)
+                },
+                ValueConflict {
+                    offset: 2,
+                    byte: b'^',
+                    delimiter: None
+                },
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "value contains 2 delimiter byte(s): byte '~' at offset 0 is the segment terminator; \
+byte '^' at offset 2 is the repetition separator"
+        );
+    }
+
+    #[test]
+    fn test_find_collisions_skips_structure() {
+        let data = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}\r\n\
+HI|ABK>J449^ABF>E119}\r\nNTE|ADD|50*2:1~}\r\nIEA|0|000000001}\r\nREF|X^Y}";
+        let candidate = Delimiters::default().with_repetition_separator(Some(b'^'));
+        let found: Vec<_> = candidate
+            .find_collisions(data)
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    &data[c.offset()..=c.offset()],
+                    c.segment_id(),
+                    c.element(),
+                    c.delimiter(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    &b"*"[..],
+                    &b"NTE"[..],
+                    2,
+                    Some(DelimiterKind::ElementSeparator)
+                ),
+                (b":", b"NTE", 2, Some(DelimiterKind::SubElementSeparator)),
+                (b"~", b"NTE", 2, Some(DelimiterKind::SegmentTerminator)),
+            ]
+        );
+
+        let fragment = b"NTE|ADD|A^B}";
+        let source = Delimiters::new(b'}', b'|', b'>');
+        let collisions = candidate.find_collisions_with(fragment, &source);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(
+            (collisions[0].offset(), collisions[0].delimiter()),
+            (9, None)
+        );
+        assert_eq!(
+            candidate.find_collisions(fragment),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        struct NotesOnly;
+        impl ConflictPolicy for NotesOnly {
+            fn resolve(&self, conflict: &Conflict<'_>) -> ConflictAction {
+                match conflict.segment_id() {
+                    b"NTE" => ConflictAction::Strip,
+                    _ => ConflictAction::Error,
+                }
+            }
+        }
+
+        let boxed: Box<dyn ConflictPolicy> = Box::new(NotesOnly);
+        let note = Conflict::new(b"NTE", 2, 8, b'~');
+        let name = Conflict::new(b"NM1", 3, 8, b'~');
+        assert_eq!(boxed.resolve(&note), ConflictAction::Strip);
+        assert_eq!(boxed.resolve(&name), ConflictAction::Error);
+    }
+}
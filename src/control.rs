@@ -0,0 +1,170 @@
+use std::cmp::Ordering;
+
+use crate::errors::ControlNumberError;
+
+/// An envelope control number element and its width rules.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ControlField {
+    /// ISA13 and IEA02: exactly nine digits.
+    Interchange,
+    /// GS06 and GE02: one to nine digits.
+    Group,
+    /// ST02 and SE02: four to nine characters, conventionally zero-padded digits.
+    Transaction,
+}
+
+impl ControlField {
+    /// Returns the header element name: `ISA13`, `GS06`, or `ST02`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ControlField::Interchange => "ISA13",
+            ControlField::Group => "GS06",
+            ControlField::Transaction => "ST02",
+        }
+    }
+
+    /// Returns the minimum number of characters.
+    pub fn min_width(self) -> usize {
+        match self {
+            ControlField::Interchange => 9,
+            ControlField::Group => 1,
+            ControlField::Transaction => 4,
+        }
+    }
+
+    /// Returns the maximum number of characters.
+    pub fn max_width(self) -> usize {
+        9
+    }
+
+    /// Formats `number`, zero-padded to the field's minimum width.
+    ///
+    /// ```
+    /// use x12_delimiters::control::ControlField;
+    ///
+    /// assert_eq!(ControlField::Interchange.format(42).unwrap(), "000000042");
+    /// assert_eq!(ControlField::Group.format(42).unwrap(), "42");
+    /// assert_eq!(ControlField::Transaction.format(42).unwrap(), "0042");
+    /// assert!(ControlField::Interchange.format(1_000_000_000).is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `ControlNumberError::WrongWidth` if `number` has more than nine digits.
+    pub fn format(self, number: u32) -> Result<String, ControlNumberError> {
+        let formatted = format!("{:0width$}", number, width = self.min_width());
+        self.check_width(formatted.len())?;
+        Ok(formatted)
+    }
+
+    /// Parses a control number as written, checking its width.
+    ///
+    /// ```
+    /// use x12_delimiters::control::ControlField;
+    ///
+    /// assert_eq!(ControlField::Interchange.parse(b"000000042"), Ok(42));
+    /// assert!(ControlField::Interchange.parse(b"42").is_err());
+    /// assert_eq!(ControlField::Group.parse(b"42"), Ok(42));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `ControlNumberError::NotNumeric` if `value` is empty or has a non-digit,
+    /// and `ControlNumberError::WrongWidth` if it is too short or too long for the field.
+    pub fn parse(self, value: &[u8]) -> Result<u32, ControlNumberError> {
+        let number = parse_digits(value)?;
+        self.check_width(value.len())?;
+        Ok(number)
+    }
+
+    fn check_width(self, actual: usize) -> Result<(), ControlNumberError> {
+        let (min, max) = (self.min_width(), self.max_width());
+        if (min..=max).contains(&actual) {
+            Ok(())
+        } else {
+            Err(ControlNumberError::WrongWidth { min, max, actual })
+        }
+    }
+}
+
+/// Compares two control numbers by numeric value, ignoring leading zeros.
+///
+/// Use this to match numbers across envelopes that write them at different widths, such
+/// as an ISA13 of `000000042` and a GS06 of `42`. Values that are not numeric compare
+/// as written, after every numeric value.
+///
+/// ```
+/// use std::cmp::Ordering;
+/// use x12_delimiters::control::compare_control_numbers;
+///
+/// assert_eq!(compare_control_numbers(b"000000042", b"42"), Ordering::Equal);
+/// assert_eq!(compare_control_numbers(b"0099", b"100"), Ordering::Less);
+/// ```
+pub fn compare_control_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    match (parse_digits(a), parse_digits(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+fn parse_digits(value: &[u8]) -> Result<u32, ControlNumberError> {
+    if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+        return Err(ControlNumberError::NotNumeric);
+    }
+    let significant = &value[value.iter().take_while(|&&b| b == b'0').count()..];
+    if significant.len() > 9 {
+        return Err(ControlNumberError::WrongWidth {
+            min: 1,
+            max: 9,
+            actual: significant.len(),
+        });
+    }
+    Ok(significant
+        .iter()
+        .fold(0, |n, &b| n * 10 + u32::from(b - b'0')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_and_parse_round_trip() {
+        for field in [
+            ControlField::Interchange,
+            ControlField::Group,
+            ControlField::Transaction,
+        ] {
+            for number in [0, 1, 9999, 999_999_999] {
+                let formatted = field.format(number).unwrap();
+                assert_eq!(field.parse(formatted.as_bytes()), Ok(number));
+            }
+        }
+        assert_eq!(
+            ControlField::Transaction.format(u32::MAX),
+            Err(ControlNumberError::WrongWidth {
+                min: 4,
+                max: 9,
+                actual: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_values() {
+        let field = ControlField::Transaction;
+        assert_eq!(field.parse(b""), Err(ControlNumberError::NotNumeric));
+        assert_eq!(field.parse(b"00 1"), Err(ControlNumberError::NotNumeric));
+        assert_eq!(field.parse(b"-001"), Err(ControlNumberError::NotNumeric));
+        assert!(field.parse(b"001").is_err());
+        assert!(field.parse(b"0000000001").is_err());
+        assert!(ControlField::Group.parse(b"99999999999").is_err());
+    }
+
+    #[test]
+    fn test_compare_non_numeric() {
+        assert_eq!(compare_control_numbers(b"1", b"A"), Ordering::Less);
+        assert_eq!(compare_control_numbers(b"B", b"A"), Ordering::Greater);
+        assert_eq!(compare_control_numbers(b"0", b"000"), Ordering::Equal);
+    }
+}
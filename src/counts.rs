@@ -0,0 +1,304 @@
+use crate::errors::DelimiterError;
+use crate::interchange::interchanges;
+use crate::segment::Span;
+use crate::Delimiters;
+
+/// A trailer whose first element counts what its envelope encloses.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum CountedTrailer {
+    /// SE01, the number of segments from the ST through the SE.
+    Se,
+    /// GE01, the number of transaction sets in the functional group.
+    Ge,
+    /// IEA01, the number of functional groups in the interchange.
+    Iea,
+}
+
+impl CountedTrailer {
+    /// All counted trailers, innermost first.
+    pub const ALL: [CountedTrailer; 3] =
+        [CountedTrailer::Se, CountedTrailer::Ge, CountedTrailer::Iea];
+
+    /// Returns the segment ID.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CountedTrailer::Se => "SE",
+            CountedTrailer::Ge => "GE",
+            CountedTrailer::Iea => "IEA",
+        }
+    }
+}
+
+/// A trailer count that disagrees with the contents of its envelope, found by
+/// [`validate_counts`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CountMismatch {
+    trailer: CountedTrailer,
+    span: Span,
+    declared: String,
+    actual: usize,
+    /// The element separator to write before the count when the trailer has no elements.
+    missing: Option<u8>,
+}
+
+impl CountMismatch {
+    /// Returns the trailer holding the count.
+    pub fn trailer(&self) -> CountedTrailer {
+        self.trailer
+    }
+
+    /// Returns the position of the count element's value in the input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the count as written, which may be padded, empty, or not a number. It is
+    /// empty, with an empty span just before the terminator, when the trailer has no
+    /// count element at all.
+    pub fn declared(&self) -> &str {
+        &self.declared
+    }
+
+    /// Returns the count the trailer should declare.
+    pub fn actual(&self) -> usize {
+        self.actual
+    }
+
+    /// Returns the value that replaces [`Self::declared`], padded to the same width the
+    /// same way: with leading zeros, leading spaces, or trailing spaces. A count wider
+    /// than the original is written in full.
+    pub fn replacement(&self) -> String {
+        let declared = self.declared.as_bytes();
+        let width = declared.len();
+        match (declared.first(), declared.last()) {
+            (Some(b' '), _) => format!("{:>width$}", self.actual, width = width),
+            (_, Some(b' ')) => format!("{:<width$}", self.actual, width = width),
+            (Some(b'0'), _) if width > 1 => format!("{:0width$}", self.actual, width = width),
+            _ => self.actual.to_string(),
+        }
+    }
+}
+
+/// Checks SE01, GE01, and IEA01 in every interchange of `data` against what their
+/// envelopes actually enclose.
+///
+/// SE01 is compared with the number of segments from the ST through the SE, GE01 with
+/// the number of ST segments in the group, and IEA01 with the number of GS segments in
+/// the interchange. A count that is missing or not a number is always a mismatch.
+/// Envelopes without a trailer are not checked.
+///
+/// ```
+/// use x12_delimiters::counts::{validate_counts, CountedTrailer};
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC*S*R*20250403*0856*17*X*005010X222A1~ST*837*0001~BHT*0019~SE*2*0001~GE*1*17~IEA*1*000000001~";
+/// let mismatches = validate_counts(data).unwrap();
+/// assert_eq!(mismatches.len(), 1);
+/// assert_eq!(mismatches[0].trailer(), CountedTrailer::Se);
+/// assert_eq!((mismatches[0].declared(), mismatches[0].actual()), ("2", 3));
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::InvalidIsaLength` or `DelimiterError::MissingIsa` if `data`
+/// does not start with an ISA segment.
+pub fn validate_counts(data: &[u8]) -> Result<Vec<CountMismatch>, DelimiterError> {
+    let mut mismatches = Vec::new();
+    let mut found = false;
+    for (span, delimiters, bytes) in interchanges(data).spanned() {
+        found = true;
+        check_interchange(bytes, span.start, &delimiters, &mut mismatches);
+    }
+    if !found {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa);
+    }
+    Ok(mismatches)
+}
+
+/// Rewrites every count [`validate_counts`] finds wrong, in place, and returns the
+/// mismatches it repaired.
+///
+/// Only the count values change: delimiters, line endings, and every other byte are
+/// kept, and each new count is padded like the old one (see
+/// [`CountMismatch::replacement`]). Spans in the returned mismatches refer to `data`
+/// before the repair.
+///
+/// ```
+/// use x12_delimiters::counts::repair_counts;
+///
+/// let mut data = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>'\n\
+/// GS|HC|S|R|20250403|0856|17|X|005010X222A1'\nST|837|0001'\nSE|09|0001'\nGE|2|17'\nIEA|1|000000001'\n".to_vec();
+/// let repaired = repair_counts(&mut data).unwrap();
+/// assert_eq!(repaired.len(), 2);
+/// assert!(data.ends_with(b"ST|837|0001'\nSE|02|0001'\nGE|1|17'\nIEA|1|000000001'\n"));
+/// ```
+///
+/// # Errors
+/// Same as [`validate_counts`]; `data` is unchanged on error.
+pub fn repair_counts(data: &mut Vec<u8>) -> Result<Vec<CountMismatch>, DelimiterError> {
+    let mismatches = validate_counts(data)?;
+    // Later spans first, so earlier ones stay valid when a count changes width.
+    for mismatch in mismatches.iter().rev() {
+        let span = mismatch.span;
+        let mut replacement = mismatch.replacement().into_bytes();
+        if let Some(separator) = mismatch.missing {
+            replacement.insert(0, separator);
+        }
+        data.splice(span.start..span.end, replacement);
+    }
+    Ok(mismatches)
+}
+
+/// Checks the trailers of one interchange, `bytes`, which starts at `offset` in the input.
+fn check_interchange(
+    bytes: &[u8],
+    offset: usize,
+    delimiters: &Delimiters,
+    mismatches: &mut Vec<CountMismatch>,
+) {
+    let mut segments: Option<usize> = None;
+    let mut transactions: Option<usize> = None;
+    let mut groups = 0;
+    for (span, segment) in delimiters.segments(bytes).spanned() {
+        let check = |trailer, actual| {
+            let raw = &bytes[span.start..span.end];
+            let (start, end, missing) = match count_element(raw, delimiters) {
+                Some((start, end)) => (start, end, None),
+                None => {
+                    let end = raw
+                        .iter()
+                        .position(|&b| b == delimiters.segment_terminator())
+                        .unwrap_or(raw.len());
+                    (end, end, Some(delimiters.element_separator()))
+                }
+            };
+            let declared = &raw[start..end];
+            let matches = std::str::from_utf8(declared)
+                .ok()
+                .and_then(|text| text.trim().parse::<usize>().ok())
+                == Some(actual);
+            (!matches).then(|| CountMismatch {
+                trailer,
+                span: Span::new(offset + span.start + start, offset + span.start + end),
+                declared: String::from_utf8_lossy(declared).into_owned(),
+                actual,
+                missing,
+            })
+        };
+        let mismatch = match segment.id() {
+            b"GS" => {
+                groups += 1;
+                transactions = Some(0);
+                None
+            }
+            b"ST" => {
+                segments = Some(1);
+                if let Some(count) = &mut transactions {
+                    *count += 1;
+                }
+                None
+            }
+            b"SE" => check(CountedTrailer::Se, segments.take().unwrap_or(0) + 1),
+            b"GE" => check(CountedTrailer::Ge, transactions.take().unwrap_or(0)),
+            b"IEA" => check(CountedTrailer::Iea, groups),
+            _ => {
+                if let Some(count) = &mut segments {
+                    *count += 1;
+                }
+                None
+            }
+        };
+        mismatches.extend(mismatch);
+    }
+}
+
+/// Returns the range of the first element's value in `raw`, a segment with its
+/// terminator, or None if the segment has no elements.
+fn count_element(raw: &[u8], delimiters: &Delimiters) -> Option<(usize, usize)> {
+    let separator = delimiters.element_separator();
+    let start = raw.iter().position(|&b| b == separator)? + 1;
+    let end = raw[start..]
+        .iter()
+        .position(|&b| b == separator || b == delimiters.segment_terminator())
+        .map_or(raw.len(), |i| start + i);
+    Some((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    #[test]
+    fn test_valid_counts() {
+        let data = format!(
+            "{}GS*HC~ST*837*0001~BHT*0019~SE*3*0001~ST*837*0002~SE*2*0002~GE*2*1~GS*HC~GE*0*2~IEA*2*000000001~",
+            ISA
+        );
+        assert_eq!(validate_counts(data.as_bytes()).unwrap(), []);
+        assert_eq!(
+            validate_counts(b"GS*HC~"),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_repair_preserves_padding_and_grows() {
+        let body = "BHT~".repeat(9);
+        let data = format!(
+            "{}GS*HC~ST*837*0001~{}SE*0007*0001~GE* 5*1~IEA*X *000000001~\r\n",
+            ISA, body
+        );
+        let mut repaired = data.clone().into_bytes();
+        let mismatches = repair_counts(&mut repaired).unwrap();
+        let trailers: Vec<_> = mismatches.iter().map(|m| m.trailer()).collect();
+        assert_eq!(trailers, CountedTrailer::ALL);
+        let span = mismatches[0].span();
+        assert_eq!(&data[span.start..span.end], "0007");
+        assert_eq!(mismatches[2].declared(), "X ");
+        let expected = data
+            .replace("SE*0007", "SE*0011")
+            .replace("GE* 5", "GE* 1")
+            .replace("IEA*X ", "IEA*1 ");
+        assert_eq!(String::from_utf8(repaired).unwrap(), expected);
+
+        let mismatch = CountMismatch {
+            trailer: CountedTrailer::Se,
+            span: Span::new(0, 2),
+            declared: "09".to_string(),
+            actual: 123,
+            missing: None,
+        };
+        assert_eq!(mismatch.replacement(), "123");
+    }
+
+    #[test]
+    fn test_missing_counts_are_mismatches() {
+        let data = format!("{}GS*HC~ST*837*0001~BHT~SE~GE~IEA~", ISA);
+        let mut repaired = data.clone().into_bytes();
+        let mismatches = repair_counts(&mut repaired).unwrap();
+        let trailers: Vec<_> = mismatches.iter().map(|m| m.trailer()).collect();
+        assert_eq!(trailers, CountedTrailer::ALL);
+        for mismatch in &mismatches {
+            assert_eq!(mismatch.declared(), "");
+            assert!(mismatch.span().is_empty());
+            assert_eq!(data.as_bytes()[mismatch.span().start], b'~');
+        }
+        let actual: Vec<_> = mismatches.iter().map(|m| m.actual()).collect();
+        assert_eq!(actual, [3, 1, 1]);
+        let expected = format!("{}GS*HC~ST*837*0001~BHT~SE*3~GE*1~IEA*1~", ISA);
+        assert_eq!(String::from_utf8(repaired).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_each_interchange_uses_its_own_delimiters() {
+        let first = format!("{}GS*HC~GE*1*1~IEA*1*000000001~", ISA);
+        let second = first.replace('*', "|").replace("IEA|1", "IEA|3");
+        let mut data = format!("{}\n{}", first, second).into_bytes();
+        let mismatches = repair_counts(&mut data).unwrap();
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(mismatches[2].actual(), 1);
+        assert!(data.ends_with(b"GE|0|1~IEA|1|000000001~"));
+        assert!(data.starts_with(first.replace("GE*1", "GE*0").as_bytes()));
+    }
+}
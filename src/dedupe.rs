@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use crate::summary::{DedupeKey, InterchangeSummary};
+
+/// How closely the members of a [`DuplicateGroup`] match.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DuplicateKind {
+    /// Same key and the same canonical digest: the same interchange received again.
+    Exact,
+    /// Same key but different digests: the interchange was resent with changed content.
+    Near,
+    /// Same key, but at least one member has no digest to compare.
+    Unverified,
+}
+
+/// Interchanges that share a [`DedupeKey`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DuplicateGroup {
+    key: DedupeKey,
+    kind: DuplicateKind,
+    members: Vec<usize>,
+}
+
+impl DuplicateGroup {
+    /// Returns the shared key.
+    pub fn key(&self) -> &DedupeKey {
+        &self.key
+    }
+
+    /// Returns how closely the members match.
+    pub fn kind(&self) -> DuplicateKind {
+        self.kind
+    }
+
+    /// Returns the input positions of the members, in input order.
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+}
+
+/// Groups interchanges that share a dedupe key (sender, receiver, and ISA13).
+///
+/// Each item is a summary and, optionally, a digest of the interchange's canonical form
+/// (see [`crate::canonical::write_canonical`]). Digests distinguish a file received
+/// twice from a resubmission that reuses the control number. Only keys seen more than
+/// once are returned, sorted by key.
+///
+/// ```
+/// use x12_delimiters::dedupe::{find_duplicates, DuplicateKind};
+/// use x12_delimiters::summary::InterchangeSummary;
+///
+/// let isa = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+/// let summary = InterchangeSummary::from_isa(isa).unwrap();
+///
+/// let groups = find_duplicates([(&summary, Some(b"d1")), (&summary, Some(b"d1"))]);
+/// assert_eq!(groups[0].kind(), DuplicateKind::Exact);
+/// assert_eq!(groups[0].members(), &[0, 1]);
+/// ```
+pub fn find_duplicates<'a, I, D>(items: I) -> Vec<DuplicateGroup>
+where
+    I: IntoIterator<Item = (&'a InterchangeSummary, Option<D>)>,
+    D: AsRef<[u8]>,
+{
+    let mut by_key: BTreeMap<DedupeKey, Vec<(usize, Option<D>)>> = BTreeMap::new();
+    for (i, (summary, digest)) in items.into_iter().enumerate() {
+        by_key
+            .entry(summary.dedupe_key())
+            .or_default()
+            .push((i, digest));
+    }
+
+    by_key
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(key, members)| {
+            let digests: Option<Vec<&[u8]>> = members
+                .iter()
+                .map(|(_, digest)| digest.as_ref().map(AsRef::as_ref))
+                .collect();
+            let kind = match digests {
+                None => DuplicateKind::Unverified,
+                Some(digests) if digests.windows(2).all(|pair| pair[0] == pair[1]) => {
+                    DuplicateKind::Exact
+                }
+                Some(_) => DuplicateKind::Near,
+            };
+            DuplicateGroup {
+                key,
+                kind,
+                members: members.iter().map(|(i, _)| *i).collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(control: &str) -> InterchangeSummary {
+        let data = format!(
+            "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*{}*0*P*:~",
+            control
+        );
+        InterchangeSummary::from_isa(data.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_duplicate_kinds() {
+        let (one, two, three, four) = (
+            summary("000000001"),
+            summary("000000002"),
+            summary("000000003"),
+            summary("000000004"),
+        );
+        let items: Vec<(&InterchangeSummary, Option<&str>)> = vec![
+            (&one, Some("a")),
+            (&two, Some("b")),
+            (&one, Some("a")),
+            (&three, Some("c")),
+            (&two, Some("x")),
+            (&four, None),
+            (&three, Some("c")),
+            (&four, Some("d")),
+        ];
+        let groups = find_duplicates(items);
+
+        let found: Vec<(&str, DuplicateKind, &[usize])> = groups
+            .iter()
+            .map(|g| (g.key().control_number(), g.kind(), g.members()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("000000001", DuplicateKind::Exact, &[0, 2][..]),
+                ("000000002", DuplicateKind::Near, &[1, 4][..]),
+                ("000000003", DuplicateKind::Exact, &[3, 6][..]),
+                ("000000004", DuplicateKind::Unverified, &[5, 7][..]),
+            ]
+        );
+        assert_eq!(groups[0].key().sender(), ("ZZ", "SENDERID"));
+    }
+
+    #[test]
+    fn test_unique_interchanges() {
+        let (one, two) = (summary("000000001"), summary("000000002"));
+        assert!(find_duplicates([(&one, None::<&[u8]>), (&two, None)]).is_empty());
+    }
+}
@@ -0,0 +1,983 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+use crate::codepage::ByteTranscoder;
+use crate::errors::DelimiterError;
+use crate::segment::Span;
+use crate::summary::{interchange_spans, interchange_spans_until};
+use crate::{Delimiters, TerminatorSuffix};
+
+const ISA_LENGTH: usize = 106;
+const ISA_ELEMENT_COUNT: usize = 16;
+/// Length of an ISA line with fixed-width elements and no separators: the tag plus the
+/// widths of ISA01 through ISA16.
+pub(crate) const FIXED_WIDTH_ISA_LENGTH: usize = 89;
+
+/// How the delimiters in a [`Detection`] were found.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Confidence {
+    /// Inferred from an incomplete ISA; check before relying on it.
+    Low,
+    /// Read from the fixed positions of a complete ISA segment.
+    High,
+}
+
+/// Delimiters found by [`detect`], possibly incomplete.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Detection {
+    element_separator: u8,
+    sub_element_separator: Option<u8>,
+    segment_terminator: Option<u8>,
+    repetition_separator: Option<u8>,
+    suffix: TerminatorSuffix,
+    confidence: Confidence,
+}
+
+impl Detection {
+    fn complete(delimiters: Delimiters) -> Self {
+        Detection {
+            element_separator: delimiters.element_separator(),
+            sub_element_separator: Some(delimiters.sub_element_separator()),
+            segment_terminator: Some(delimiters.segment_terminator()),
+            repetition_separator: delimiters.repetition_separator(),
+            suffix: delimiters.suffix(),
+            confidence: Confidence::High,
+        }
+    }
+
+    /// Returns the element separator, which is always known.
+    pub fn element_separator(&self) -> u8 {
+        self.element_separator
+    }
+
+    /// Returns the sub-element separator, if it could be determined.
+    pub fn sub_element_separator(&self) -> Option<u8> {
+        self.sub_element_separator
+    }
+
+    /// Returns the segment terminator, if it could be determined.
+    pub fn segment_terminator(&self) -> Option<u8> {
+        self.segment_terminator
+    }
+
+    /// Returns the repetition separator, if the ISA was complete and declared one.
+    pub fn repetition_separator(&self) -> Option<u8> {
+        self.repetition_separator
+    }
+
+    /// Returns the line ending seen after the terminator, if any.
+    pub fn suffix(&self) -> TerminatorSuffix {
+        self.suffix
+    }
+
+    /// Returns how the delimiters were found.
+    pub fn confidence(&self) -> Confidence {
+        self.confidence
+    }
+
+    /// Returns the full delimiter set, if all three delimiters are known.
+    pub fn delimiters(&self) -> Option<Delimiters> {
+        Some(
+            Delimiters::new(
+                self.segment_terminator?,
+                self.element_separator,
+                self.sub_element_separator?,
+            )
+            .with_repetition_separator(self.repetition_separator)
+            .with_suffix(self.suffix),
+        )
+    }
+}
+
+/// Detects delimiters, falling back to inference when the ISA is truncated.
+///
+/// A complete ISA is read like [`Delimiters::from_isa`] and reported with
+/// [`Confidence::High`]. A buffer shorter than 106 bytes that starts with `ISA` is
+/// reported with [`Confidence::Low`]: the element separator is byte 3, and if all sixteen
+/// ISA elements are present the sub-element separator and terminator follow the last
+/// one. Otherwise the terminator is taken from a `GS` segment start, if one follows.
+///
+/// ```
+/// use x12_delimiters::detect::{detect, Confidence};
+///
+/// // ISA06 and ISA08 are not padded to 15 characters, so the segment is short.
+/// let data = b"ISA*00*          *00*          *ZZ*SENDER*ZZ*RECEIVER*250403*0856*^*00501*000000001*0*P*:~GS*HC";
+/// let detection = detect(data).unwrap();
+/// assert_eq!(detection.confidence(), Confidence::Low);
+/// assert_eq!(detection.segment_terminator(), Some(b'~'));
+/// assert_eq!(detection.sub_element_separator(), Some(b':'));
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::InvalidIsaLength` if `data` is shorter than an ISA segment
+/// and does not start with `ISA` followed by an element separator.
+pub fn detect(data: &[u8]) -> Result<Detection, DelimiterError> {
+    if data.len() >= ISA_LENGTH {
+        return Delimiters::from_isa(data).map(Detection::complete);
+    }
+    if !data.starts_with(b"ISA") || data.len() < 4 {
+        return Err(DelimiterError::InvalidIsaLength);
+    }
+
+    let element_separator = data[3];
+    let mut detection = Detection {
+        element_separator,
+        sub_element_separator: None,
+        segment_terminator: None,
+        repetition_separator: None,
+        suffix: TerminatorSuffix::None,
+        confidence: Confidence::Low,
+    };
+
+    let last_separator = data
+        .iter()
+        .enumerate()
+        .skip(3)
+        .filter(|(_, &b)| b == element_separator)
+        .nth(ISA_ELEMENT_COUNT - 1)
+        .map(|(i, _)| i);
+    if let Some(last) = last_separator {
+        detection.sub_element_separator = data.get(last + 1).copied();
+        detection.segment_terminator = data.get(last + 2).copied().filter(|&b| is_plausible(b));
+        detection.suffix = TerminatorSuffix::detect(data.get(last + 3..).unwrap_or_default());
+    } else {
+        detection.segment_terminator = (4..data.len()).find_map(|i| {
+            let terminator = data[i];
+            let rest = &data[i + 1..];
+            let start = rest
+                .iter()
+                .take_while(|&&b| b == b'\r' || b == b'\n')
+                .count();
+            let plausible = is_plausible(terminator)
+                && terminator != element_separator
+                && rest[start..].starts_with(b"GS")
+                && rest.get(start + 2) == Some(&element_separator);
+            plausible.then_some(terminator)
+        });
+    }
+    Ok(detection)
+}
+
+/// Like [`detect`], for data in the code page handled by `transcoder`.
+///
+/// Only the bytes needed to find the delimiters are decoded. The returned delimiters are
+/// Latin-1 bytes; encode them with `transcoder` to search the raw data.
+///
+/// ```
+/// use x12_delimiters::codepage::{ByteTranscoder, CP500};
+/// use x12_delimiters::detect::detect_encoded;
+///
+/// let data = CP500.encode(b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:!");
+/// let detection = detect_encoded(&data, &CP500).unwrap();
+/// assert_eq!(detection.segment_terminator(), Some(b'!'));
+/// ```
+///
+/// # Errors
+/// Same as [`detect`].
+pub fn detect_encoded<T: ByteTranscoder + ?Sized>(
+    data: &[u8],
+    transcoder: &T,
+) -> Result<Detection, DelimiterError> {
+    let head = &data[..data.len().min(ISA_LENGTH + 2)];
+    let detection = detect(&transcoder.decode(head))?;
+    if detection.confidence == Confidence::High {
+        return Ok(detection);
+    }
+    // A truncated ISA may need the following GS to find the terminator.
+    detect(&transcoder.decode(data))
+}
+
+/// Output of [`unwrap`]: the data with wrapping line breaks removed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Unwrapped<'a> {
+    data: Cow<'a, [u8]>,
+    delimiters: Delimiters,
+    removed: Vec<usize>,
+    isa_line_breaks: usize,
+}
+
+impl<'a> Unwrapped<'a> {
+    /// Returns the unwrapped bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the delimiters read from the unwrapped ISA segment.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the offsets in the input of every CR or LF byte that was removed.
+    pub fn removed(&self) -> &[usize] {
+        &self.removed
+    }
+
+    /// Returns how many of the removed bytes were inside the ISA segment.
+    pub fn isa_line_breaks(&self) -> usize {
+        self.isa_line_breaks
+    }
+
+    /// Returns true if any line breaks were removed.
+    pub fn is_changed(&self) -> bool {
+        !self.removed.is_empty()
+    }
+
+    /// Returns the output, borrowed from the input when nothing was removed.
+    pub fn into_cow(self) -> Cow<'a, [u8]> {
+        self.data
+    }
+}
+
+/// Removes line breaks that senders insert to wrap data at a fixed column.
+///
+/// CR and LF bytes are skipped until 105 bytes of ISA content have been read, so the
+/// delimiters come from the logical ISA positions even when the segment itself was
+/// wrapped. In the rest of the document, a CR or LF is kept only when it directly follows
+/// a segment terminator (the usual line ending) or is itself the terminator; all others
+/// are removed. Every removed byte is reported by its offset in the input.
+///
+/// ```
+/// use x12_delimiters::detect::unwrap;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250\r\n403*0856*^*00501*000000001*0*P*:~GS*HC\r\n*S*R~";
+/// let unwrapped = unwrap(data).unwrap();
+/// assert_eq!(unwrapped.delimiters().segment_terminator(), b'~');
+/// assert_eq!(unwrapped.isa_line_breaks(), 2);
+/// assert_eq!(unwrapped.removed().len(), 4);
+/// assert!(unwrapped.as_bytes().ends_with(b"~GS*HC*S*R~"));
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::InvalidIsaLength` if the data holds fewer than 106 bytes
+/// besides line breaks.
+pub fn unwrap(data: &[u8]) -> Result<Unwrapped<'_>, DelimiterError> {
+    let mut removed = Vec::new();
+    let mut isa = Vec::with_capacity(ISA_LENGTH);
+    let mut pos = 0;
+    while isa.len() < ISA_LENGTH {
+        let byte = *data.get(pos).ok_or(DelimiterError::InvalidIsaLength)?;
+        // The terminator at the last ISA position may itself be a line break.
+        if is_line_break(byte) && isa.len() < ISA_LENGTH - 1 {
+            removed.push(pos);
+        } else {
+            isa.push(byte);
+        }
+        pos += 1;
+    }
+    let delimiters =
+        Delimiters::from_isa(&isa)?.with_suffix(TerminatorSuffix::detect(&data[pos..]));
+    let isa_line_breaks = removed.len();
+
+    let terminator = delimiters.segment_terminator();
+    let mut after_terminator = true;
+    for (offset, &byte) in data.iter().enumerate().skip(pos) {
+        if byte == terminator {
+            after_terminator = true;
+        } else if is_line_break(byte) {
+            if !after_terminator {
+                removed.push(offset);
+            }
+        } else {
+            after_terminator = false;
+        }
+    }
+
+    let data = if removed.is_empty() {
+        Cow::Borrowed(data)
+    } else {
+        let mut out = Vec::with_capacity(data.len() - removed.len());
+        let mut start = 0;
+        for &offset in &removed {
+            out.extend_from_slice(&data[start..offset]);
+            start = offset + 1;
+        }
+        out.extend_from_slice(&data[start..]);
+        Cow::Owned(out)
+    };
+    Ok(Unwrapped {
+        data,
+        delimiters,
+        removed,
+        isa_line_breaks,
+    })
+}
+
+/// How far into the data [`find_isa`] looks by default: enough for a byte-order mark,
+/// blank lines, or a block of mail headers.
+pub const DEFAULT_ISA_WINDOW: usize = 4096;
+
+/// Output of [`find_isa`]: the first ISA segment found after any leading junk.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Located<'a> {
+    data: &'a [u8],
+    offset: usize,
+    delimiters: Delimiters,
+}
+
+impl<'a> Located<'a> {
+    /// Returns the offset of the ISA tag in the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the bytes before the ISA, such as a byte-order mark or mail headers.
+    pub fn skipped(&self) -> &'a [u8] {
+        &self.data[..self.offset]
+    }
+
+    /// Returns the input from the ISA tag on.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+
+    /// Returns the delimiters read from the ISA segment.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+}
+
+/// Finds the first ISA segment that starts within `window` bytes of the start of `data`
+/// and reads its delimiters.
+///
+/// Real-world files often start with a UTF-8 byte-order mark, blank lines, or the headers
+/// of the mail they arrived in. Each `ISA` tag in the window is checked with
+/// [`Delimiters::from_isa_strict`], so the letters `ISA` in a header line are passed
+/// over. The returned [`Located::as_bytes`] can be handed to any function that expects
+/// data to start with the ISA.
+///
+/// ```
+/// use x12_delimiters::detect::{find_isa, DEFAULT_ISA_WINDOW};
+///
+/// let data = b"\xEF\xBB\xBFSubject: ISA batch\r\n\r\nISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+/// let located = find_isa(data, DEFAULT_ISA_WINDOW).unwrap();
+/// assert_eq!(located.offset(), 25);
+/// assert!(located.as_bytes().starts_with(b"ISA*00*"));
+/// assert_eq!(located.delimiters().segment_terminator(), b'~');
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::NotIsaSegment` if no `ISA` tag starts within the window, or
+/// the error of [`Delimiters::from_isa_strict`] for the first tag if none is a valid ISA.
+pub fn find_isa(data: &[u8], window: usize) -> Result<Located<'_>, DelimiterError> {
+    let end = data.len().min(window.saturating_add(3));
+    let mut first_error = None;
+    for offset in data[..end]
+        .windows(3)
+        .enumerate()
+        .filter(|(_, tag)| *tag == b"ISA")
+        .map(|(offset, _)| offset)
+    {
+        match Delimiters::from_isa_strict(&data[offset..]) {
+            Ok(delimiters) => {
+                return Ok(Located {
+                    data,
+                    offset,
+                    delimiters,
+                })
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or(DelimiterError::NotIsaSegment))
+}
+
+/// An interchange whose delimiters differ from those of the interchange before it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DelimiterOverride {
+    interchange: usize,
+    span: Span,
+    previous: Delimiters,
+    delimiters: Delimiters,
+}
+
+impl DelimiterOverride {
+    /// Returns the index of the interchange in the file, counting from 0.
+    pub fn interchange(&self) -> usize {
+        self.interchange
+    }
+
+    /// Returns the span of the interchange, ISA through IEA.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the delimiters of the preceding interchange.
+    pub fn previous(&self) -> Delimiters {
+        self.previous
+    }
+
+    /// Returns the delimiters this interchange declares.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+}
+
+/// Reports each interchange in a concatenated file that switches to a different delimiter
+/// set than the interchange before it.
+///
+/// Every interchange is read with its own ISA, so a file whose envelopes all share one
+/// set yields nothing. Line-ending differences alone are not reported.
+///
+/// ```
+/// use x12_delimiters::detect::delimiter_overrides;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~\
+/// ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}IEA|0|000000002}";
+/// let overrides = delimiter_overrides(data);
+/// assert_eq!(overrides.len(), 1);
+/// assert_eq!(overrides[0].interchange(), 1);
+/// assert_eq!(overrides[0].previous().element_separator(), b'*');
+/// assert_eq!(overrides[0].delimiters().element_separator(), b'|');
+/// ```
+pub fn delimiter_overrides(data: &[u8]) -> Vec<DelimiterOverride> {
+    overrides(&interchange_spans(data))
+}
+
+/// When a time-boxed scan such as [`delimiter_overrides_within`] must return.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub struct Deadline(Instant);
+
+impl From<Instant> for Deadline {
+    fn from(instant: Instant) -> Self {
+        Deadline(instant)
+    }
+}
+
+impl From<Duration> for Deadline {
+    /// A deadline `budget` from now.
+    fn from(budget: Duration) -> Self {
+        Deadline(Instant::now() + budget)
+    }
+}
+
+/// A result that may have been cut short by a [`Deadline`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TimeBoxed<T> {
+    value: T,
+    partial: bool,
+}
+
+impl<T> TimeBoxed<T> {
+    /// Returns the result found before the deadline.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns true if the deadline passed before the scan finished, so the result only
+    /// covers the start of the data.
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    /// Returns the result, discarding whether it is partial.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// Like [`delimiter_overrides`], returning what was found so far when `deadline` passes.
+///
+/// [`detect`] reads a fixed number of bytes and needs no deadline, but finding every
+/// interchange takes time proportional to the input. On a request path with a latency
+/// budget, pass a [`Duration`] or [`Instant`]; if it runs out, the overrides among the
+/// interchanges scanned so far are returned and flagged as partial. The clock is read
+/// between interchanges and every 1024 segments.
+///
+/// ```
+/// use std::time::Duration;
+/// use x12_delimiters::detect::delimiter_overrides_within;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~";
+/// let overrides = delimiter_overrides_within(data, Duration::from_millis(200));
+/// assert!(!overrides.is_partial());
+/// assert!(overrides.value().is_empty());
+/// ```
+pub fn delimiter_overrides_within<D: Into<Deadline>>(
+    data: &[u8],
+    deadline: D,
+) -> TimeBoxed<Vec<DelimiterOverride>> {
+    let (spans, partial) = interchange_spans_until(data, Some(deadline.into().0));
+    TimeBoxed {
+        value: overrides(&spans),
+        partial,
+    }
+}
+
+fn overrides(spans: &[(Span, Delimiters)]) -> Vec<DelimiterOverride> {
+    spans
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| !pair[0].1.eq_ignoring_suffix(&pair[1].1))
+        .map(|(i, pair)| DelimiterOverride {
+            interchange: i + 1,
+            span: pair[1].0,
+            previous: pair[0].1,
+            delimiters: pair[1].1,
+        })
+        .collect()
+}
+
+/// The overall layout of a document, as recognized by [`detect_variant`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum FormatVariant {
+    /// X12 with delimiters declared by the ISA.
+    Delimited,
+    /// X12-like lines that end with a line break and hold fixed-width elements with no
+    /// separators, as emitted by some legacy systems. Convert it with
+    /// [`crate::fixed::convert_fixed_width`].
+    FixedWidth,
+    /// Neither layout; the data does not start with `ISA`.
+    Unknown,
+}
+
+/// Recognizes whether `data` is delimited X12 or the fixed-width hybrid.
+///
+/// The byte after `ISA` decides: delimited X12 has its element separator there, while
+/// the hybrid continues directly with ISA01. The hybrid is only reported when the first
+/// line is exactly as long as a separator-less ISA (89 bytes).
+///
+/// ```
+/// use x12_delimiters::detect::{detect_variant, FormatVariant};
+///
+/// let delimited = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+/// assert_eq!(detect_variant(delimited), FormatVariant::Delimited);
+///
+/// let fixed = b"ISA00          00          ZZSENDERID       ZZRECEIVERID     2504030856^005010000000010P:\nGSHC\n";
+/// assert_eq!(detect_variant(fixed), FormatVariant::FixedWidth);
+/// ```
+pub fn detect_variant(data: &[u8]) -> FormatVariant {
+    if !data.starts_with(b"ISA") {
+        return FormatVariant::Unknown;
+    }
+    match data.get(3) {
+        Some(&b) if is_plausible(b) => FormatVariant::Delimited,
+        Some(_) => {
+            let line = data.split(|&b| b == b'\n').next().unwrap_or_default();
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if line.len() == FIXED_WIDTH_ISA_LENGTH {
+                FormatVariant::FixedWidth
+            } else {
+                FormatVariant::Unknown
+            }
+        }
+        None => FormatVariant::Unknown,
+    }
+}
+
+/// Punctuation common in element data, never taken for a sub-element separator.
+const DATA_PUNCTUATION: &[u8] = b".-/,&()#@%$+='\"?;_";
+/// Sub-element separators to assume, in order, when the data has no composites.
+const SUB_ELEMENT_FALLBACKS: [u8; 4] = [b':', b'>', b'<', b'\\'];
+
+/// Delimiters inferred by [`sniff`], with how well they explain the data.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Sniffed {
+    delimiters: Delimiters,
+    confidence: f64,
+}
+
+impl Sniffed {
+    /// Returns the inferred delimiters.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns a score from 0 to 1: 1 for delimiters read from a complete ISA, otherwise
+    /// the share of segments whose ID looks like an X12 segment ID, reduced when the
+    /// sub-element separator had to be assumed or there is only one segment.
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
+
+/// Infers delimiters from data that may lack an ISA, such as a lone `ST`..`SE` fragment.
+///
+/// Data that starts with a complete ISA is read like [`Delimiters::from_isa`]. Otherwise
+/// the element separator is the byte after the leading 2–3 character segment ID, and the
+/// terminator is the byte that most often precedes another segment ID and element
+/// separator, possibly across a line break. The sub-element separator is the most
+/// frequent remaining punctuation byte, excluding punctuation common in names and
+/// amounts; with none, `:` is assumed, or the first of `>`, `<`, `\` absent from the
+/// data. No repetition separator is inferred.
+///
+/// ```
+/// use x12_delimiters::detect::sniff;
+///
+/// let fragment = b"ST|837|0001'\nSV1|HC>99213|100'\nNM1|IL|1|DOE'\nSE|4|0001'\n";
+/// let sniffed = sniff(fragment).unwrap();
+/// let delimiters = sniffed.delimiters();
+/// assert_eq!(delimiters.element_separator(), b'|');
+/// assert_eq!(delimiters.segment_terminator(), b'\'');
+/// assert_eq!(delimiters.sub_element_separator(), b'>');
+/// assert_eq!(sniffed.confidence(), 1.0);
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::NoSegmentStructure` if the data does not start with a segment
+/// ID followed by a separator, or no terminator can be found.
+pub fn sniff(data: &[u8]) -> Result<Sniffed, DelimiterError> {
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    let data = &data[start..];
+    if data.starts_with(b"ISA") && data.len() >= ISA_LENGTH {
+        if let Ok(delimiters) = Delimiters::from_isa(data) {
+            return Ok(Sniffed {
+                delimiters,
+                confidence: 1.0,
+            });
+        }
+    }
+
+    let element = match segment_id_len(data).map(|len| data[len]) {
+        Some(b) if is_plausible(b) && !is_line_break(b) => b,
+        _ => return Err(DelimiterError::NoSegmentStructure),
+    };
+
+    // Count each byte that directly precedes a segment start, skipping line breaks after
+    // it unless it is a line break itself.
+    let mut counts = [0usize; 256];
+    for (i, &b) in data.iter().enumerate() {
+        if b == element || !is_plausible(b) {
+            continue;
+        }
+        let mut next = i + 1;
+        if !is_line_break(b) {
+            next += data[next..]
+                .iter()
+                .take_while(|&&b| is_line_break(b))
+                .count();
+        }
+        if starts_segment(&data[next..], element) {
+            counts[usize::from(b)] += 1;
+        }
+    }
+    let terminator = (0..=u8::MAX)
+        .filter(|&b| counts[usize::from(b)] > 0)
+        .max_by_key(|&b| (counts[usize::from(b)], !is_line_break(b)))
+        .or_else(|| {
+            // A single segment: its last byte before any line break.
+            let end = data.iter().rposition(|&b| !is_line_break(b))?;
+            Some(data[end]).filter(|&b| b != element && is_plausible(b))
+        })
+        .ok_or(DelimiterError::NoSegmentStructure)?;
+
+    let mut frequency = [0usize; 256];
+    for &b in data {
+        if is_plausible(b)
+            && !is_line_break(b)
+            && b != element
+            && b != terminator
+            && !DATA_PUNCTUATION.contains(&b)
+        {
+            frequency[usize::from(b)] += 1;
+        }
+    }
+    let observed = (0..=u8::MAX)
+        .filter(|&b| frequency[usize::from(b)] > 0)
+        .max_by_key(|&b| (frequency[usize::from(b)], b == b':'));
+    let sub_element = observed
+        .or_else(|| {
+            SUB_ELEMENT_FALLBACKS
+                .into_iter()
+                .find(|b| *b != element && *b != terminator && !data.contains(b))
+        })
+        .ok_or(DelimiterError::NoSegmentStructure)?;
+
+    let suffix = data
+        .iter()
+        .position(|&b| b == terminator)
+        .filter(|_| !is_line_break(terminator))
+        .map_or(TerminatorSuffix::None, |i| {
+            TerminatorSuffix::detect(&data[i + 1..])
+        });
+    let delimiters = Delimiters::new(terminator, element, sub_element).with_suffix(suffix);
+
+    let mut total = 0;
+    let mut valid = 0;
+    for segment in delimiters.segments(data) {
+        total += 1;
+        let raw = segment.as_bytes();
+        if segment_id_len(raw).is_some() || (2..=3).contains(&raw.len()) && is_segment_id(raw) {
+            valid += 1;
+        }
+    }
+    let mut confidence = valid as f64 / total.max(1) as f64;
+    if observed.is_none() {
+        confidence *= 0.75;
+    }
+    if total < 2 {
+        confidence *= 0.5;
+    }
+    Ok(Sniffed {
+        delimiters,
+        confidence,
+    })
+}
+
+/// Returns the length of the segment ID at the start of `data` if it is followed by a
+/// plausible separator byte.
+fn segment_id_len(data: &[u8]) -> Option<usize> {
+    (2..=3).find(|&len| {
+        data.len() > len
+            && is_segment_id(&data[..len])
+            && is_plausible(data[len])
+            && !is_line_break(data[len])
+    })
+}
+
+fn starts_segment(data: &[u8], element: u8) -> bool {
+    segment_id_len(data).is_some_and(|len| data[len] == element)
+}
+
+/// Returns true for 2–3 uppercase letters or digits, starting with a letter.
+fn is_segment_id(id: &[u8]) -> bool {
+    id.first().is_some_and(u8::is_ascii_uppercase)
+        && id
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+}
+
+/// Returns false for bytes that cannot reasonably be a terminator.
+fn is_plausible(byte: u8) -> bool {
+    !byte.is_ascii_alphanumeric() && byte != b' '
+}
+
+fn is_line_break(byte: u8) -> bool {
+    byte == b'\r' || byte == b'\n'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codepage::CP037;
+
+    const ISA: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    #[test]
+    fn test_complete_isa_is_high_confidence() {
+        let detection = detect(ISA).unwrap();
+        assert_eq!(detection.confidence(), Confidence::High);
+        assert_eq!(
+            detection.delimiters(),
+            Some(Delimiters::default().with_repetition_separator(Some(b'^')))
+        );
+    }
+
+    #[test]
+    fn test_truncated_isa_fallbacks() {
+        let partial = detect(&ISA[..60]).unwrap();
+        assert_eq!(partial.confidence(), Confidence::Low);
+        assert_eq!(partial.element_separator(), b'*');
+        assert_eq!(partial.segment_terminator(), None);
+        assert_eq!(partial.delimiters(), None);
+
+        let with_gs = detect(b"ISA|00|  |ZZ|S}\r\nGS|HC|").unwrap();
+        assert_eq!(with_gs.segment_terminator(), Some(b'}'));
+        assert_eq!(with_gs.sub_element_separator(), None);
+
+        let short = b"ISA^00^^00^^ZZ^S^ZZ^R^250403^0856^U^00401^1^0^P^>~\r\nGS";
+        let detection = detect(short).unwrap();
+        assert_eq!(
+            detection.delimiters(),
+            Some(Delimiters::new(b'~', b'^', b'>').with_suffix(TerminatorSuffix::CrLf))
+        );
+    }
+
+    #[test]
+    fn test_detect_encoded() {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(b"\r\nGS*HC~");
+        let ebcdic = CP037.encode(&data);
+        let detection = detect_encoded(&ebcdic, &CP037).unwrap();
+        assert_eq!(
+            detection.delimiters(),
+            Some(
+                Delimiters::default()
+                    .with_repetition_separator(Some(b'^'))
+                    .with_suffix(TerminatorSuffix::CrLf)
+            )
+        );
+
+        let truncated = CP037.encode(b"ISA|00|  |ZZ|S}\nGS|HC|");
+        let detection = detect_encoded(&truncated, &CP037).unwrap();
+        assert_eq!(detection.segment_terminator(), Some(b'}'));
+    }
+
+    #[test]
+    fn test_unwrap_wrapped_isa_and_body() {
+        let mut data = Vec::new();
+        for line in ISA.chunks(80) {
+            data.extend_from_slice(line);
+            data.extend_from_slice(b"\r\n");
+        }
+        data.extend_from_slice(b"GS*HC*SENDER*RECEI\nVER~\r\nGE*1*1~\r\n");
+        let unwrapped = unwrap(&data).unwrap();
+        assert_eq!(
+            unwrapped.delimiters(),
+            Delimiters::default()
+                .with_repetition_separator(Some(b'^'))
+                .with_suffix(TerminatorSuffix::CrLf)
+        );
+        assert_eq!(unwrapped.isa_line_breaks(), 2);
+        assert_eq!(unwrapped.removed(), &[80, 81, 128]);
+        let mut expected = ISA.to_vec();
+        expected.extend_from_slice(b"\r\nGS*HC*SENDER*RECEIVER~\r\nGE*1*1~\r\n");
+        assert_eq!(unwrapped.as_bytes(), &expected[..]);
+
+        let clean = unwrap(&expected).unwrap();
+        assert!(!clean.is_changed());
+        assert!(matches!(clean.into_cow(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_unwrap_keeps_newline_terminator() {
+        let mut isa = ISA.to_vec();
+        isa[105] = b'\n';
+        isa.extend_from_slice(b"GS*HC\nGE*1*1\n");
+        let unwrapped = unwrap(&isa).unwrap();
+        assert_eq!(unwrapped.delimiters().segment_terminator(), b'\n');
+        assert!(!unwrapped.is_changed());
+        assert_eq!(unwrap(b"ISA*00\r\n"), Err(DelimiterError::InvalidIsaLength));
+    }
+
+    #[test]
+    fn test_delimiter_overrides() {
+        let alt = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}";
+        let mut data = Vec::new();
+        for isa in [ISA, ISA, &alt[..], &alt[..], ISA] {
+            data.extend_from_slice(isa);
+            data.extend_from_slice(if isa == ISA {
+                b"IEA*0*1~\n"
+            } else {
+                b"IEA|0|2}\n"
+            });
+        }
+        let overrides = delimiter_overrides(&data);
+        let found: Vec<_> = overrides
+            .iter()
+            .map(|o| (o.interchange(), o.delimiters().segment_terminator()))
+            .collect();
+        assert_eq!(found, [(2, b'}'), (4, b'~')]);
+        let span = overrides[1].span();
+        assert!(data[span.start..span.end].starts_with(ISA));
+        assert!(delimiter_overrides(ISA).is_empty());
+    }
+
+    #[test]
+    fn test_detect_variant() {
+        let fixed = b"ISA00          00          ZZSENDERID       ZZRECEIVERID     2504030856^005010000000010P:\r\n";
+        assert_eq!(detect_variant(fixed), FormatVariant::FixedWidth);
+        assert_eq!(detect_variant(&fixed[..60]), FormatVariant::Unknown);
+        assert_eq!(detect_variant(ISA), FormatVariant::Delimited);
+        assert_eq!(detect_variant(b"ISA"), FormatVariant::Unknown);
+        assert_eq!(detect_variant(b"GS*HC"), FormatVariant::Unknown);
+    }
+
+    #[test]
+    fn test_delimiter_overrides_within() {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(&b"NM1*IL~".repeat(5000));
+        data.extend_from_slice(b"IEA*0*000000001~");
+        data.extend(ISA.iter().map(|&b| match b {
+            b'*' => b'|',
+            b'~' => b'}',
+            b => b,
+        }));
+
+        let expired = delimiter_overrides_within(&data, Instant::now());
+        assert!(expired.is_partial());
+        assert!(expired.value().is_empty());
+
+        let finished = delimiter_overrides_within(&data, Duration::from_secs(60));
+        assert!(!finished.is_partial());
+        assert_eq!(finished.into_inner(), delimiter_overrides(&data));
+    }
+
+    #[test]
+    fn test_not_an_isa() {
+        assert_eq!(detect(b"GS*HC*S*R"), Err(DelimiterError::InvalidIsaLength));
+        assert_eq!(detect(b"ISA"), Err(DelimiterError::InvalidIsaLength));
+    }
+
+    #[test]
+    fn test_sniff_reads_complete_isa() {
+        let sniffed = sniff(ISA).unwrap();
+        assert_eq!(sniffed.delimiters(), Delimiters::from_isa(ISA).unwrap());
+        assert_eq!(sniffed.confidence(), 1.0);
+    }
+
+    #[test]
+    fn test_sniff_fragment_without_composites() {
+        let sniffed = sniff(b"\r\nST*837*0001~\r\nBHT*0019*00*1~\r\nSE*3*0001~\r\n").unwrap();
+        let delimiters = sniffed.delimiters();
+        assert_eq!(delimiters.element_separator(), b'*');
+        assert_eq!(delimiters.segment_terminator(), b'~');
+        assert_eq!(delimiters.sub_element_separator(), b':');
+        assert_eq!(delimiters.suffix(), TerminatorSuffix::CrLf);
+        assert_eq!(sniffed.confidence(), 0.75);
+    }
+
+    #[test]
+    fn test_sniff_newline_terminator() {
+        let sniffed = sniff(b"ST^837^0001\nHI^ABK}J449^ABF}E119\nSE^3^0001\n").unwrap();
+        let delimiters = sniffed.delimiters();
+        assert_eq!(delimiters.element_separator(), b'^');
+        assert_eq!(delimiters.segment_terminator(), b'\n');
+        assert_eq!(delimiters.sub_element_separator(), b'}');
+        assert_eq!(sniffed.confidence(), 1.0);
+    }
+
+    #[test]
+    fn test_sniff_single_segment_is_low_confidence() {
+        let sniffed = sniff(b"NM1*IL*1*DOE*JOHN~").unwrap();
+        assert_eq!(sniffed.delimiters().segment_terminator(), b'~');
+        assert!(sniffed.confidence() < 0.5);
+    }
+
+    #[test]
+    fn test_sniff_rejects_unstructured_data() {
+        assert_eq!(sniff(b""), Err(DelimiterError::NoSegmentStructure));
+        assert_eq!(
+            sniff(b"hello world"),
+            Err(DelimiterError::NoSegmentStructure)
+        );
+        assert_eq!(sniff(b"ST*837"), Err(DelimiterError::NoSegmentStructure));
+    }
+
+    #[test]
+    fn test_find_isa_skips_leading_junk() {
+        let mut data = b"\xEF\xBB\xBF\r\n\r\n".to_vec();
+        data.extend_from_slice(ISA);
+        let located = find_isa(&data, DEFAULT_ISA_WINDOW).unwrap();
+        assert_eq!(located.offset(), 7);
+        assert_eq!(located.skipped(), b"\xEF\xBB\xBF\r\n\r\n");
+        assert_eq!(located.as_bytes(), ISA);
+        assert_eq!(located.delimiters(), Delimiters::from_isa(ISA).unwrap());
+
+        let located = find_isa(ISA, 0).unwrap();
+        assert_eq!((located.offset(), located.skipped()), (0, &b""[..]));
+    }
+
+    #[test]
+    fn test_find_isa_window_and_errors() {
+        let mut data = b"X-Header: ISA*not*an*interchange\r\n".to_vec();
+        let offset = data.len();
+        data.extend_from_slice(ISA);
+        assert_eq!(find_isa(&data, offset).unwrap().offset(), offset);
+        assert!(matches!(
+            find_isa(&data, offset - 1),
+            Err(DelimiterError::InconsistentSeparators { .. })
+        ));
+        assert_eq!(
+            find_isa(b"no interchange here", 100),
+            Err(DelimiterError::NotIsaSegment)
+        );
+        assert_eq!(find_isa(b"", 100), Err(DelimiterError::NotIsaSegment));
+    }
+}
@@ -0,0 +1,435 @@
+use crate::errors::DelimiterError;
+use crate::interchange::interchanges;
+use crate::segment::Segment;
+use crate::Delimiters;
+
+/// Above this many cells, the segments between the common prefix and suffix are not
+/// aligned element by element but compared as one block; see [`diff`].
+const MAX_ALIGNMENT_CELLS: usize = 1 << 24;
+
+/// A difference in one element of a changed segment.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ElementChange {
+    position: usize,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl ElementChange {
+    /// Returns the element position, starting at 1 for the first element after the ID.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the old value, written with the standard delimiters, or None if the
+    /// element was absent or empty.
+    pub fn before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// Returns the new value, written with the standard delimiters, or None if the
+    /// element is absent or empty.
+    pub fn after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+}
+
+/// One difference between two documents, as reported by [`diff`].
+///
+/// Indexes count segments from 0 in each document, across all of its interchanges.
+/// Segments are written with the standard delimiters and without trailing empty
+/// elements, whatever the documents use.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SegmentChange {
+    /// A segment only in the new document.
+    Added { new_index: usize, segment: String },
+    /// A segment only in the old document.
+    Removed { old_index: usize, segment: String },
+    /// A segment in both documents, with the same ID but different elements.
+    Changed {
+        old_index: usize,
+        new_index: usize,
+        segment_id: String,
+        elements: Vec<ElementChange>,
+    },
+}
+
+/// The differences between two documents, returned by [`diff`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Diff {
+    changes: Vec<SegmentChange>,
+}
+
+impl Diff {
+    /// Returns the changes in document order.
+    pub fn changes(&self) -> &[SegmentChange] {
+        &self.changes
+    }
+
+    /// Returns whether the documents have the same content.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Compares two files segment by segment, reading each interchange with the delimiters
+/// from its own ISA.
+///
+/// Cosmetic differences are ignored: the choice of delimiters (including ISA11 when it
+/// declares the repetition separator, and ISA16), line endings between segments, and
+/// trailing empty elements and components. Segments are aligned by a longest common
+/// subsequence, and within each run of differing segments, a removed and an added
+/// segment with the same ID are reported as one changed segment with its changed
+/// elements. Very long runs of differences (over 16M old × new segment pairs) are
+/// compared as one block, pairing segments by ID in order.
+///
+/// ```
+/// use x12_delimiters::diff::{diff, SegmentChange};
+///
+/// let old = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nST*837*0001~NM1*IL*1*DOE~SE*3*0001~";
+/// let new = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>'ST|837|0001'NM1|IL|1|ROE|'REF|EA|1'SE|4|0001'";
+/// let changes = diff(old, new).unwrap();
+/// let changes = changes.changes();
+/// assert_eq!(changes.len(), 3);
+/// match &changes[0] {
+///     SegmentChange::Changed { segment_id, elements, .. } => {
+///         assert_eq!(segment_id, "NM1");
+///         assert_eq!((elements[0].before(), elements[0].after()), (Some("DOE"), Some("ROE")));
+///     }
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// assert!(matches!(&changes[1], SegmentChange::Added { new_index: 3, segment } if segment == "REF*EA*1~"));
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::InvalidIsaLength` or `DelimiterError::MissingIsa` if either
+/// file does not start with an ISA segment.
+pub fn diff(old: &[u8], new: &[u8]) -> Result<Diff, DelimiterError> {
+    Ok(align(&document_segments(old)?, &document_segments(new)?))
+}
+
+/// Compares two fragments without ISA segments, such as single transaction sets, each
+/// read with its own delimiters. See [`diff`].
+///
+/// ```
+/// use x12_delimiters::diff::diff_segments;
+/// use x12_delimiters::Delimiters;
+///
+/// let old = b"ST*837*0001~\r\nSE*2*0001~\r\n";
+/// let new = b"ST|837|0001||'SE|2|0001'";
+/// let delimiters = Delimiters::new(b'\'', b'|', b'>');
+/// assert!(diff_segments(old, &Delimiters::default(), new, &delimiters).is_empty());
+/// ```
+pub fn diff_segments(
+    old: &[u8],
+    old_delimiters: &Delimiters,
+    new: &[u8],
+    new_delimiters: &Delimiters,
+) -> Diff {
+    let read = |data, delimiters: &Delimiters| {
+        delimiters
+            .segments(data)
+            .map(|segment| Comparable::new(&segment))
+            .collect::<Vec<_>>()
+    };
+    align(&read(old, old_delimiters), &read(new, new_delimiters))
+}
+
+/// A segment reduced to what [`diff`] compares.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Comparable {
+    id: Vec<u8>,
+    /// Repetitions of components of each element, without trailing empty ones.
+    elements: Vec<Vec<Vec<Vec<u8>>>>,
+}
+
+impl Comparable {
+    fn new(segment: &Segment<'_>) -> Self {
+        let delimiters = segment.delimiters();
+        let is_isa = segment.id() == b"ISA";
+        let mut elements: Vec<Vec<Vec<Vec<u8>>>> = (1..segment.elements().count())
+            .map(|n| {
+                let raw = segment.element(n).unwrap_or_default();
+                let cosmetic = is_isa
+                    && (n == 16
+                        || (n == 11
+                            && delimiters.repetition_separator().map(|b| vec![b])
+                                == Some(raw.to_vec())));
+                if cosmetic {
+                    Vec::new()
+                } else if is_isa {
+                    vec![vec![raw.to_vec()]]
+                } else {
+                    segment
+                        .repetitions(n)
+                        .into_iter()
+                        .flatten()
+                        .map(|repetition| {
+                            let mut components: Vec<Vec<u8>> =
+                                repetition.sub_elements().map(<[u8]>::to_vec).collect();
+                            while components.last().is_some_and(Vec::is_empty) {
+                                components.pop();
+                            }
+                            components
+                        })
+                        .collect()
+                }
+            })
+            .map(|mut repetitions: Vec<Vec<Vec<u8>>>| {
+                if repetitions.iter().all(Vec::is_empty) {
+                    repetitions.clear();
+                }
+                repetitions
+            })
+            .collect();
+        while elements.last().is_some_and(Vec::is_empty) {
+            elements.pop();
+        }
+        Comparable {
+            id: segment.id().to_vec(),
+            elements,
+        }
+    }
+
+    fn element_text(&self, position: usize) -> Option<String> {
+        let repetitions = self.elements.get(position - 1).filter(|e| !e.is_empty())?;
+        let defaults = Delimiters::default();
+        let text = repetitions
+            .iter()
+            .map(|components| components.join(&defaults.sub_element_separator()))
+            .collect::<Vec<_>>()
+            .join(&b'^');
+        Some(String::from_utf8_lossy(&text).into_owned())
+    }
+
+    fn text(&self) -> String {
+        let defaults = Delimiters::default();
+        let mut out = String::from_utf8_lossy(&self.id).into_owned();
+        for position in 1..=self.elements.len() {
+            out.push(defaults.element_separator() as char);
+            out.push_str(&self.element_text(position).unwrap_or_default());
+        }
+        out.push(defaults.segment_terminator() as char);
+        out
+    }
+}
+
+fn document_segments(data: &[u8]) -> Result<Vec<Comparable>, DelimiterError> {
+    let mut segments = Vec::new();
+    let mut found = false;
+    for (delimiters, interchange) in interchanges(data) {
+        found = true;
+        segments.extend(
+            delimiters
+                .segments(interchange)
+                .map(|segment| Comparable::new(&segment)),
+        );
+    }
+    if !found {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa);
+    }
+    Ok(segments)
+}
+
+/// Aligns `old` and `new` and reports the differences.
+fn align(old: &[Comparable], new: &[Comparable]) -> Diff {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut diff = Diff::default();
+    let mut hunk = Hunk {
+        old,
+        new,
+        removed: Vec::new(),
+        added: Vec::new(),
+    };
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_ALIGNMENT_CELLS {
+        hunk.removed.extend(prefix..old.len() - suffix);
+        hunk.added.extend(prefix..new.len() - suffix);
+    } else {
+        // lengths[i][j] is the longest common subsequence of old_middle[i..] and
+        // new_middle[j..].
+        let width = new_middle.len() + 1;
+        let mut lengths = vec![0u32; (old_middle.len() + 1) * width];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                    lengths[(i + 1) * width + j + 1] + 1
+                } else {
+                    lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() || j < new_middle.len() {
+            if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+                hunk.flush(&mut diff);
+                i += 1;
+                j += 1;
+            } else if j == new_middle.len()
+                || (i < old_middle.len()
+                    && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+            {
+                hunk.removed.push(prefix + i);
+                i += 1;
+            } else {
+                hunk.added.push(prefix + j);
+                j += 1;
+            }
+        }
+    }
+    hunk.flush(&mut diff);
+    diff
+}
+
+/// A run of removed and added segments between two matching ones.
+struct Hunk<'a> {
+    old: &'a [Comparable],
+    new: &'a [Comparable],
+    removed: Vec<usize>,
+    added: Vec<usize>,
+}
+
+impl Hunk<'_> {
+    /// Reports the run, pairing removed and added segments with the same ID in order.
+    fn flush(&mut self, diff: &mut Diff) {
+        let (mut r, mut a) = (0, 0);
+        while r < self.removed.len() || a < self.added.len() {
+            let old = self.removed.get(r).map(|&i| (i, &self.old[i]));
+            let new = self.added.get(a).map(|&i| (i, &self.new[i]));
+            let change = match (old, new) {
+                (Some((old_index, old)), Some((new_index, new))) if old.id == new.id => {
+                    r += 1;
+                    a += 1;
+                    let count = old.elements.len().max(new.elements.len());
+                    SegmentChange::Changed {
+                        old_index,
+                        new_index,
+                        segment_id: String::from_utf8_lossy(&old.id).into_owned(),
+                        elements: (1..=count)
+                            .filter(|&n| old.elements.get(n - 1) != new.elements.get(n - 1))
+                            .map(|n| ElementChange {
+                                position: n,
+                                before: old.element_text(n),
+                                after: new.element_text(n),
+                            })
+                            .collect(),
+                    }
+                }
+                // Keep the removed segment for a later added one with the same ID.
+                (Some((_, old)), Some((new_index, new)))
+                    if self.added[a..].iter().any(|&i| self.new[i].id == old.id) =>
+                {
+                    a += 1;
+                    SegmentChange::Added {
+                        new_index,
+                        segment: new.text(),
+                    }
+                }
+                (Some((old_index, old)), _) => {
+                    r += 1;
+                    SegmentChange::Removed {
+                        old_index,
+                        segment: old.text(),
+                    }
+                }
+                (None, Some((new_index, new))) => {
+                    a += 1;
+                    SegmentChange::Added {
+                        new_index,
+                        segment: new.text(),
+                    }
+                }
+                (None, None) => unreachable!(),
+            };
+            diff.changes.push(change);
+        }
+        self.removed.clear();
+        self.added.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    fn changes(old: &str, new: &str) -> Vec<SegmentChange> {
+        let old = format!("{}{}", ISA, old);
+        let new = format!("{}{}", ISA, new);
+        diff(old.as_bytes(), new.as_bytes()).unwrap().changes
+    }
+
+    #[test]
+    fn test_cosmetic_differences_ignored() {
+        let old = format!("{}\r\nHI*ABK:J449:^ABF:E119~\r\nNM1*IL*1**~", ISA);
+        let new = ISA
+            .replace('*', "|")
+            .replace("|^|", "|}|")
+            .replace(":~", ">'")
+            + "HI|ABK>J449}ABF>E119'NM1|IL|1'";
+        assert!(diff(old.as_bytes(), new.as_bytes()).unwrap().is_empty());
+        assert_eq!(
+            diff(b"GS*HC~", b"GS*HC~"),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_added_removed_and_changed() {
+        let found = changes(
+            "ST*837*0001~NM1*IL*1*DOE~DMG*D8*19800101~SE*4*0001~",
+            "ST*837*0001~REF*EA*1~NM1*IL*1*DOE*JOHN~SE*4*0001~",
+        );
+        assert_eq!(
+            found,
+            [
+                SegmentChange::Added {
+                    new_index: 2,
+                    segment: "REF*EA*1~".to_string()
+                },
+                SegmentChange::Changed {
+                    old_index: 2,
+                    new_index: 3,
+                    segment_id: "NM1".to_string(),
+                    elements: vec![ElementChange {
+                        position: 4,
+                        before: None,
+                        after: Some("JOHN".to_string())
+                    }]
+                },
+                SegmentChange::Removed {
+                    old_index: 3,
+                    segment: "DMG*D8*19800101~".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repetition_changes() {
+        let delimiters = Delimiters::default().with_repetition_separator(Some(b'^'));
+        let diff = diff_segments(
+            b"HI*ABK:J449^ABF:E119~",
+            &delimiters,
+            b"HI*ABK:J449~",
+            &delimiters,
+        );
+        match &diff.changes()[0] {
+            SegmentChange::Changed { elements, .. } => {
+                assert_eq!(elements[0].before(), Some("ABK:J449^ABF:E119"));
+                assert_eq!(elements[0].after(), Some("ABK:J449"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
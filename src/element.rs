@@ -0,0 +1,355 @@
+use std::fmt;
+
+use crate::errors::ElementValueError;
+
+/// A borrowed element value with typed conversions.
+///
+/// Conversions follow the X12 data element types: `as_u64` for unsigned `N0` values,
+/// `as_decimal` for `R` values with an explicit decimal point, `as_implied_decimal` for
+/// `Nn` values with `n` implied decimal places, and `as_date` for `DT` values. Every
+/// conversion rejects an empty element with `ElementValueError::Empty`, so callers can
+/// tell an absent value from a malformed one.
+///
+/// ```
+/// use x12_delimiters::element::{DateFormat, ElementRef};
+/// use x12_delimiters::segment::Segment;
+/// use x12_delimiters::Delimiters;
+///
+/// let clm = Segment::new(b"CLM*A37YH556*1250.5***11:B:1", Delimiters::default());
+/// let amount = clm.value(2).unwrap().as_decimal().unwrap();
+/// assert_eq!(amount.to_string(), "1250.5");
+///
+/// assert_eq!(ElementRef::new(b"012550").as_implied_decimal(2).unwrap().to_string(), "125.50");
+/// let date = ElementRef::new(b"20240229").as_date(DateFormat::Ccyymmdd).unwrap();
+/// assert_eq!((date.year(), date.month(), date.day()), (2024, 2, 29));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ElementRef<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> ElementRef<'a> {
+    /// Wraps the bytes of one element.
+    pub fn new(raw: &'a [u8]) -> Self {
+        ElementRef { raw }
+    }
+
+    /// Returns the element bytes.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns true if the element has no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Returns the element as text. An empty element is the empty string.
+    ///
+    /// # Errors
+    /// Returns `ElementValueError::NotText` if the bytes are not valid UTF-8.
+    pub fn as_str(&self) -> Result<&'a str, ElementValueError> {
+        std::str::from_utf8(self.raw).map_err(|_| ElementValueError::NotText)
+    }
+
+    /// Parses an unsigned integer.
+    ///
+    /// # Errors
+    /// Returns `ElementValueError::Empty` for an empty element,
+    /// `ElementValueError::Invalid` if any byte is not an ASCII digit, and
+    /// `ElementValueError::OutOfRange` if the value does not fit in a `u64`.
+    pub fn as_u64(&self) -> Result<u64, ElementValueError> {
+        parse_digits(self.non_empty()?)
+    }
+
+    /// Parses a decimal number with an optional leading minus sign and an optional
+    /// explicit decimal point, e.g. `-12.5` or `.75`.
+    ///
+    /// # Errors
+    /// Returns `ElementValueError::Empty` for an empty element,
+    /// `ElementValueError::Invalid` if the value is not a decimal number, and
+    /// `ElementValueError::OutOfRange` if its digits do not fit in an `i64`.
+    pub fn as_decimal(&self) -> Result<Decimal, ElementValueError> {
+        let (negative, digits) = split_sign(self.non_empty()?);
+        let (whole, fraction) = match digits.iter().position(|&b| b == b'.') {
+            Some(i) => (&digits[..i], &digits[i + 1..]),
+            None => (digits, &b""[..]),
+        };
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(ElementValueError::Invalid);
+        }
+        let mut units: i64 = 0;
+        for &b in whole.iter().chain(fraction) {
+            if !b.is_ascii_digit() {
+                return Err(ElementValueError::Invalid);
+            }
+            units = units
+                .checked_mul(10)
+                .and_then(|units| units.checked_add(i64::from(b - b'0')))
+                .ok_or(ElementValueError::OutOfRange)?;
+        }
+        let scale = u32::try_from(fraction.len()).map_err(|_| ElementValueError::OutOfRange)?;
+        Ok(Decimal::new(if negative { -units } else { units }, scale))
+    }
+
+    /// Parses a number with `places` implied decimal places and an optional leading minus
+    /// sign, as used by the `Nn` element types: `12550` read as `N2` is `125.50`.
+    ///
+    /// # Errors
+    /// Returns `ElementValueError::Empty` for an empty element,
+    /// `ElementValueError::Invalid` if the value is not a signed integer, and
+    /// `ElementValueError::OutOfRange` if it does not fit in an `i64`.
+    pub fn as_implied_decimal(&self, places: u32) -> Result<Decimal, ElementValueError> {
+        let (negative, digits) = split_sign(self.non_empty()?);
+        let units =
+            i64::try_from(parse_digits(digits)?).map_err(|_| ElementValueError::OutOfRange)?;
+        Ok(Decimal::new(if negative { -units } else { units }, places))
+    }
+
+    /// Parses a calendar date in `format`.
+    ///
+    /// # Errors
+    /// Returns `ElementValueError::Empty` for an empty element,
+    /// `ElementValueError::Invalid` if the value has the wrong length or a non-digit, and
+    /// `ElementValueError::OutOfRange` if the month or day does not exist.
+    pub fn as_date(&self, format: DateFormat) -> Result<Date, ElementValueError> {
+        let raw = self.non_empty()?;
+        let (year, rest) = match format {
+            DateFormat::Ccyymmdd if raw.len() == 8 => (parse_digits(&raw[..4])?, &raw[4..]),
+            DateFormat::Yymmdd if raw.len() == 6 => (2000 + parse_digits(&raw[..2])?, &raw[2..]),
+            _ => return Err(ElementValueError::Invalid),
+        };
+        let month = parse_digits(&rest[..2])?;
+        let day = parse_digits(&rest[2..])?;
+        // Four digits at most, so the casts cannot truncate.
+        Date::new(year as u16, month as u8, day as u8).ok_or(ElementValueError::OutOfRange)
+    }
+
+    fn non_empty(&self) -> Result<&'a [u8], ElementValueError> {
+        if self.raw.is_empty() {
+            Err(ElementValueError::Empty)
+        } else {
+            Ok(self.raw)
+        }
+    }
+}
+
+impl<'a> From<&'a [u8]> for ElementRef<'a> {
+    fn from(raw: &'a [u8]) -> Self {
+        ElementRef::new(raw)
+    }
+}
+
+/// An exact decimal number: `units` scaled down by `10^scale`.
+///
+/// Trailing zeros are kept, so `1.50` and `1.5` differ in `units` and `scale` and are not
+/// `==`; compare [`Decimal::to_f64`] or rescale to compare values.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Decimal {
+    units: i64,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Creates the number `units / 10^scale`.
+    pub fn new(units: i64, scale: u32) -> Self {
+        Decimal { units, scale }
+    }
+
+    /// Returns the value as an integer count of the smallest unit, e.g. cents for a
+    /// scale of 2.
+    pub fn units(&self) -> i64 {
+        self.units
+    }
+
+    /// Returns the number of decimal places.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        self.units as f64 / 10f64.powi(self.scale as i32)
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.units.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let sign = if self.units < 0 { "-" } else { "" };
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
+
+/// Layout of a date element, for [`ElementRef::as_date`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DateFormat {
+    /// Eight digits, e.g. `20250403`, as in GS04 and `D8` date qualifiers.
+    Ccyymmdd,
+    /// Six digits, e.g. `250403`, as in ISA09. The year is taken to be in 2000–2099.
+    Yymmdd,
+}
+
+/// A calendar date read from an element.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// Creates a date, or returns None if the month or day does not exist.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let leap = matches!((year % 4, year % 100, year % 400), (0, 1.., _) | (_, _, 0));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days)
+            .contains(&day)
+            .then_some(Date { year, month, day })
+    }
+
+    /// Returns the four-digit year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month, 1 through 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, starting at 1.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    /// Formats the date as `CCYY-MM-DD`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Splits a leading minus sign from `raw`.
+fn split_sign(raw: &[u8]) -> (bool, &[u8]) {
+    match raw.strip_prefix(b"-") {
+        Some(rest) => (true, rest),
+        None => (false, raw),
+    }
+}
+
+/// Parses ASCII digits; an empty slice is invalid.
+fn parse_digits(digits: &[u8]) -> Result<u64, ElementValueError> {
+    if digits.is_empty() {
+        return Err(ElementValueError::Invalid);
+    }
+    digits.iter().try_fold(0u64, |value, &b| {
+        if !b.is_ascii_digit() {
+            return Err(ElementValueError::Invalid);
+        }
+        value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add(u64::from(b - b'0')))
+            .ok_or(ElementValueError::OutOfRange)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(raw: &str) -> ElementRef<'_> {
+        ElementRef::new(raw.as_bytes())
+    }
+
+    #[test]
+    fn test_as_str_and_u64() {
+        assert_eq!(element("DOE").as_str(), Ok("DOE"));
+        assert_eq!(
+            ElementRef::new(b"\xFF").as_str(),
+            Err(ElementValueError::NotText)
+        );
+        assert_eq!(element("").as_str(), Ok(""));
+
+        assert_eq!(element("00042").as_u64(), Ok(42));
+        assert_eq!(element("").as_u64(), Err(ElementValueError::Empty));
+        assert_eq!(element("-1").as_u64(), Err(ElementValueError::Invalid));
+        assert_eq!(element("1.0").as_u64(), Err(ElementValueError::Invalid));
+        assert_eq!(
+            element("18446744073709551616").as_u64(),
+            Err(ElementValueError::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_decimals() {
+        let decimal = |raw| element(raw).as_decimal().map(|d| (d.units(), d.scale()));
+        assert_eq!(decimal("1250.50"), Ok((125050, 2)));
+        assert_eq!(decimal("-.75"), Ok((-75, 2)));
+        assert_eq!(decimal("12."), Ok((12, 0)));
+        assert_eq!(decimal("300"), Ok((300, 0)));
+        assert_eq!(decimal("."), Err(ElementValueError::Invalid));
+        assert_eq!(decimal("-"), Err(ElementValueError::Invalid));
+        assert_eq!(decimal("1.2.3"), Err(ElementValueError::Invalid));
+        assert_eq!(decimal("+1"), Err(ElementValueError::Invalid));
+        assert_eq!(
+            decimal("9223372036854775808"),
+            Err(ElementValueError::OutOfRange)
+        );
+
+        let implied = element("-5").as_implied_decimal(2).unwrap();
+        assert_eq!(implied.to_string(), "-0.05");
+        assert_eq!(implied.to_f64(), -0.05);
+        assert_eq!(
+            element("1.5").as_implied_decimal(2),
+            Err(ElementValueError::Invalid)
+        );
+        assert_eq!(Decimal::new(7, 0).to_string(), "7");
+        assert_eq!(Decimal::new(-12345, 3).to_string(), "-12.345");
+    }
+
+    #[test]
+    fn test_dates() {
+        let date = element("250403").as_date(DateFormat::Yymmdd).unwrap();
+        assert_eq!(date.to_string(), "2025-04-03");
+        assert_eq!(element("20250403").as_date(DateFormat::Ccyymmdd), Ok(date));
+        assert_eq!(
+            element("20230229").as_date(DateFormat::Ccyymmdd),
+            Err(ElementValueError::OutOfRange)
+        );
+        assert!(element("20000229").as_date(DateFormat::Ccyymmdd).is_ok());
+        assert_eq!(
+            element("19000229").as_date(DateFormat::Ccyymmdd),
+            Err(ElementValueError::OutOfRange)
+        );
+        assert_eq!(
+            element("20251301").as_date(DateFormat::Ccyymmdd),
+            Err(ElementValueError::OutOfRange)
+        );
+        assert_eq!(
+            element("250403").as_date(DateFormat::Ccyymmdd),
+            Err(ElementValueError::Invalid)
+        );
+        assert_eq!(
+            element("2025-4-3").as_date(DateFormat::Ccyymmdd),
+            Err(ElementValueError::Invalid)
+        );
+        assert_eq!(
+            element("").as_date(DateFormat::Yymmdd),
+            Err(ElementValueError::Empty)
+        );
+    }
+}
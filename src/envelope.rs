@@ -0,0 +1,726 @@
+use crate::control::ControlField;
+use crate::errors::EnvelopeError;
+use crate::isa::IsaSegment;
+use crate::segment::{element_at, Segment};
+use crate::Delimiters;
+
+/// Field values of a GS functional group header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GsSegment {
+    functional_id: String,
+    application_sender: String,
+    application_receiver: String,
+    date: String,
+    time: String,
+    control_number: u32,
+    responsible_agency: String,
+    version: String,
+}
+
+impl GsSegment {
+    /// Creates a GS header with responsible agency code `X`.
+    ///
+    /// # Arguments
+    /// * `functional_id` - GS01, e.g. `HC` for claims
+    /// * `application_sender` / `application_receiver` - GS02 and GS03
+    /// * `date` - GS04 as `CCYYMMDD`
+    /// * `time` - GS05 as `HHMM`
+    /// * `control_number` - GS06, the group control number
+    /// * `version` - GS08, e.g. `005010X222A1`
+    pub fn new(
+        functional_id: &str,
+        application_sender: &str,
+        application_receiver: &str,
+        date: &str,
+        time: &str,
+        control_number: u32,
+        version: &str,
+    ) -> Self {
+        GsSegment {
+            functional_id: functional_id.to_string(),
+            application_sender: application_sender.to_string(),
+            application_receiver: application_receiver.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            control_number,
+            responsible_agency: "X".to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    /// Reads a GS segment.
+    ///
+    /// ```
+    /// use x12_delimiters::envelope::GsSegment;
+    /// use x12_delimiters::segment::Segment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let raw = b"GS|HC|SENDER|RECEIVER|20250403|0856|17|X|005010X222A1";
+    /// let gs = GsSegment::parse(&Segment::new(raw, Delimiters::new(b'}', b'|', b'>'))).unwrap();
+    /// assert_eq!(gs.functional_id(), "HC");
+    /// assert_eq!(gs.application_sender(), "SENDER");
+    /// assert_eq!(gs.control_number(), 17);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::WrongSegment` if `segment` is not a GS,
+    /// `EnvelopeError::MissingElement` if any of GS01 through GS08 is absent or empty, and
+    /// `EnvelopeError::InvalidValue` if GS06 is not a control number.
+    pub fn parse(segment: &Segment<'_>) -> Result<Self, EnvelopeError> {
+        if segment.id() != b"GS" {
+            return Err(EnvelopeError::WrongSegment { expected: "GS" });
+        }
+        let text = |element| {
+            required_element(segment, "GS", element)
+                .map(|value| String::from_utf8_lossy(value).into_owned())
+        };
+        Ok(GsSegment {
+            functional_id: text(1)?,
+            application_sender: text(2)?,
+            application_receiver: text(3)?,
+            date: text(4)?,
+            time: text(5)?,
+            control_number: numeric_element(segment, "GS", 6, ControlField::Group)?,
+            responsible_agency: text(7)?,
+            version: text(8)?,
+        })
+    }
+
+    /// Returns GS01, the functional identifier code.
+    pub fn functional_id(&self) -> &str {
+        &self.functional_id
+    }
+
+    /// Returns GS02, the application sender's code.
+    pub fn application_sender(&self) -> &str {
+        &self.application_sender
+    }
+
+    /// Returns GS03, the application receiver's code.
+    pub fn application_receiver(&self) -> &str {
+        &self.application_receiver
+    }
+
+    /// Returns GS04, the group date as `CCYYMMDD`.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Returns GS05, the group time as `HHMM` or longer.
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// Returns GS07, the responsible agency code.
+    pub fn responsible_agency(&self) -> &str {
+        &self.responsible_agency
+    }
+
+    /// Returns GS06, the group control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Returns GS08, the version / release / industry identifier code.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = b"GS".to_vec();
+        for field in self.fields() {
+            out.push(delimiters.element_separator());
+            out.extend_from_slice(&field);
+        }
+        out.push(delimiters.segment_terminator());
+        out
+    }
+
+    /// Returns GS01 through GS08 as written.
+    pub(crate) fn fields(&self) -> [Vec<u8>; 8] {
+        [
+            self.functional_id.clone().into_bytes(),
+            self.application_sender.clone().into_bytes(),
+            self.application_receiver.clone().into_bytes(),
+            self.date.clone().into_bytes(),
+            self.time.clone().into_bytes(),
+            self.control_number.to_string().into_bytes(),
+            self.responsible_agency.clone().into_bytes(),
+            self.version.clone().into_bytes(),
+        ]
+    }
+}
+
+/// Field values of a GE functional group trailer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GeSegment {
+    transaction_count: u32,
+    control_number: u32,
+}
+
+impl GeSegment {
+    /// Creates a GE trailer.
+    ///
+    /// # Arguments
+    /// * `transaction_count` - GE01, the number of transaction sets in the group
+    /// * `control_number` - GE02, which must equal GS06
+    pub fn new(transaction_count: u32, control_number: u32) -> Self {
+        GeSegment {
+            transaction_count,
+            control_number,
+        }
+    }
+
+    /// Reads a GE segment.
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::WrongSegment` if `segment` is not a GE,
+    /// `EnvelopeError::MissingElement` if GE01 or GE02 is absent, and
+    /// `EnvelopeError::InvalidValue` if either is not a number.
+    pub fn parse(segment: &Segment<'_>) -> Result<Self, EnvelopeError> {
+        if segment.id() != b"GE" {
+            return Err(EnvelopeError::WrongSegment { expected: "GE" });
+        }
+        let transaction_count = numeric_element(segment, "GE", 1, ControlField::Group)?;
+        let control_number = numeric_element(segment, "GE", 2, ControlField::Group)?;
+        Ok(GeSegment::new(transaction_count, control_number))
+    }
+
+    /// Returns GE01, the declared number of transaction sets.
+    pub fn transaction_count(&self) -> u32 {
+        self.transaction_count
+    }
+
+    /// Returns GE02, the group control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Compares the trailer with its GS header and the number of transaction sets
+    /// actually found.
+    ///
+    /// ```
+    /// use x12_delimiters::envelope::{EnvelopeMismatch, GeSegment, GsSegment};
+    ///
+    /// let gs = GsSegment::new("HC", "S", "R", "20250403", "0856", 17, "005010X222A1");
+    /// assert!(GeSegment::new(2, 17).reconcile(&gs, 2).is_empty());
+    /// assert_eq!(
+    ///     GeSegment::new(2, 18).reconcile(&gs, 1),
+    ///     [
+    ///         EnvelopeMismatch::ControlNumber { header: 17, trailer: 18 },
+    ///         EnvelopeMismatch::Count { declared: 2, actual: 1 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn reconcile(&self, gs: &GsSegment, transaction_count: usize) -> Vec<EnvelopeMismatch> {
+        mismatches(
+            (gs.control_number(), self.control_number),
+            (self.transaction_count, transaction_count),
+        )
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = b"GE".to_vec();
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(self.transaction_count.to_string().as_bytes());
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(self.control_number.to_string().as_bytes());
+        out.push(delimiters.segment_terminator());
+        out
+    }
+}
+
+/// Field values of an ST transaction set header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StSegment {
+    transaction_set_id: String,
+    control_number: u32,
+    implementation_reference: Option<String>,
+}
+
+impl StSegment {
+    /// Creates an ST header without an implementation convention reference.
+    ///
+    /// # Arguments
+    /// * `transaction_set_id` - ST01, e.g. `837`
+    /// * `control_number` - ST02, written zero-padded to four digits
+    pub fn new(transaction_set_id: &str, control_number: u32) -> Self {
+        StSegment {
+            transaction_set_id: transaction_set_id.to_string(),
+            control_number,
+            implementation_reference: None,
+        }
+    }
+
+    /// Sets ST03, e.g. `005010X222A1`.
+    pub fn with_implementation_reference(mut self, reference: &str) -> Self {
+        self.implementation_reference = Some(reference.to_string());
+        self
+    }
+
+    /// Reads an ST segment.
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::WrongSegment` if `segment` is not an ST,
+    /// `EnvelopeError::MissingElement` if ST01 or ST02 is absent, and
+    /// `EnvelopeError::InvalidValue` if ST02 is not a four- to nine-digit control number.
+    pub fn parse(segment: &Segment<'_>) -> Result<Self, EnvelopeError> {
+        if segment.id() != b"ST" {
+            return Err(EnvelopeError::WrongSegment { expected: "ST" });
+        }
+        let transaction_set_id = required_element(segment, "ST", 1)?;
+        let control_number = numeric_element(segment, "ST", 2, ControlField::Transaction)?;
+        let implementation_reference = required_element(segment, "ST", 3).ok();
+        Ok(StSegment {
+            transaction_set_id: String::from_utf8_lossy(transaction_set_id).into_owned(),
+            control_number,
+            implementation_reference: implementation_reference
+                .map(|value| String::from_utf8_lossy(value).into_owned()),
+        })
+    }
+
+    /// Returns ST01, the transaction set identifier code.
+    pub fn transaction_set_id(&self) -> &str {
+        &self.transaction_set_id
+    }
+
+    /// Returns ST02, the transaction set control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Returns ST03, the implementation convention reference, if present.
+    pub fn implementation_reference(&self) -> Option<&str> {
+        self.implementation_reference.as_deref()
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = b"ST".to_vec();
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(self.transaction_set_id.as_bytes());
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(format!("{:04}", self.control_number).as_bytes());
+        if let Some(reference) = &self.implementation_reference {
+            out.push(delimiters.element_separator());
+            out.extend_from_slice(reference.as_bytes());
+        }
+        out.push(delimiters.segment_terminator());
+        out
+    }
+}
+
+/// Field values of an SE transaction set trailer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct SeSegment {
+    segment_count: u32,
+    control_number: u32,
+}
+
+impl SeSegment {
+    /// Creates an SE trailer.
+    ///
+    /// # Arguments
+    /// * `segment_count` - SE01, the number of segments from ST through SE inclusive
+    /// * `control_number` - SE02, which must equal ST02
+    pub fn new(segment_count: u32, control_number: u32) -> Self {
+        SeSegment {
+            segment_count,
+            control_number,
+        }
+    }
+
+    /// Reads an SE segment.
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::WrongSegment` if `segment` is not an SE,
+    /// `EnvelopeError::MissingElement` if SE01 or SE02 is absent, and
+    /// `EnvelopeError::InvalidValue` if SE01 is not a number or SE02 is not a four- to
+    /// nine-digit control number.
+    pub fn parse(segment: &Segment<'_>) -> Result<Self, EnvelopeError> {
+        if segment.id() != b"SE" {
+            return Err(EnvelopeError::WrongSegment { expected: "SE" });
+        }
+        let segment_count = numeric_element(segment, "SE", 1, ControlField::Group)?;
+        let control_number = numeric_element(segment, "SE", 2, ControlField::Transaction)?;
+        Ok(SeSegment::new(segment_count, control_number))
+    }
+
+    /// Returns SE01, the declared number of segments.
+    pub fn segment_count(&self) -> u32 {
+        self.segment_count
+    }
+
+    /// Returns SE02, the transaction set control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Compares the trailer with its ST header and the number of segments actually
+    /// found, ST and SE included.
+    pub fn reconcile(&self, st: &StSegment, segment_count: usize) -> Vec<EnvelopeMismatch> {
+        mismatches(
+            (st.control_number(), self.control_number),
+            (self.segment_count, segment_count),
+        )
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = b"SE".to_vec();
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(self.segment_count.to_string().as_bytes());
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(format!("{:04}", self.control_number).as_bytes());
+        out.push(delimiters.segment_terminator());
+        out
+    }
+}
+
+/// Checks the transaction set at the start of `data`: SE02 against ST02, and SE01 against
+/// the number of segments from the ST through the first SE.
+///
+/// ```
+/// use x12_delimiters::envelope::{reconcile_transaction, EnvelopeMismatch};
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ST*837*0001~BHT*0019~CLM*A~SE*3*0001~";
+/// assert_eq!(
+///     reconcile_transaction(data, &Delimiters::default()).unwrap(),
+///     [EnvelopeMismatch::Count { declared: 3, actual: 4 }]
+/// );
+/// ```
+///
+/// # Errors
+/// Returns the errors of [`StSegment::parse`] if `data` does not start with a valid ST,
+/// `EnvelopeError::MissingSegment` if there is no SE, and the errors of
+/// [`SeSegment::parse`] for a malformed SE.
+pub fn reconcile_transaction(
+    data: &[u8],
+    delimiters: &Delimiters,
+) -> Result<Vec<EnvelopeMismatch>, EnvelopeError> {
+    let mut segments = delimiters.segments(data);
+    let st = segments
+        .next()
+        .ok_or(EnvelopeError::MissingSegment { expected: "ST" })?;
+    let st = StSegment::parse(&st)?;
+    for (count, segment) in segments.enumerate() {
+        if segment.id() == b"SE" {
+            return Ok(SeSegment::parse(&segment)?.reconcile(&st, count + 2));
+        }
+    }
+    Err(EnvelopeError::MissingSegment { expected: "SE" })
+}
+
+/// Field values of an IEA interchange control trailer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IeaSegment {
+    group_count: u32,
+    control_number: u32,
+}
+
+impl IeaSegment {
+    /// Creates an IEA trailer.
+    ///
+    /// # Arguments
+    /// * `group_count` - IEA01, the number of functional groups in the interchange
+    /// * `control_number` - IEA02, which must equal ISA13
+    pub fn new(group_count: u32, control_number: u32) -> Self {
+        IeaSegment {
+            group_count,
+            control_number,
+        }
+    }
+
+    /// Reads an IEA segment.
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::WrongSegment` if `segment` is not an IEA,
+    /// `EnvelopeError::MissingElement` if IEA01 or IEA02 is absent, and
+    /// `EnvelopeError::InvalidValue` if IEA01 is not a number or IEA02 is not a nine-digit
+    /// control number.
+    pub fn parse(segment: &Segment<'_>) -> Result<Self, EnvelopeError> {
+        if segment.id() != b"IEA" {
+            return Err(EnvelopeError::WrongSegment { expected: "IEA" });
+        }
+        let group_count = numeric_element(segment, "IEA", 1, ControlField::Group)?;
+        let control_number = numeric_element(segment, "IEA", 2, ControlField::Interchange)?;
+        Ok(IeaSegment::new(group_count, control_number))
+    }
+
+    /// Returns IEA01, the declared number of functional groups.
+    pub fn group_count(&self) -> u32 {
+        self.group_count
+    }
+
+    /// Returns IEA02, the interchange control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Compares the trailer with its ISA header and the number of groups actually found.
+    pub fn reconcile(&self, isa: &IsaSegment, group_count: usize) -> Vec<EnvelopeMismatch> {
+        mismatches(
+            (isa.control_number(), self.control_number),
+            (self.group_count, group_count),
+        )
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = b"IEA".to_vec();
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(self.group_count.to_string().as_bytes());
+        out.push(delimiters.element_separator());
+        out.extend_from_slice(format!("{:09}", self.control_number).as_bytes());
+        out.push(delimiters.segment_terminator());
+        out
+    }
+}
+
+/// A disagreement between an envelope trailer and its header or contents.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EnvelopeMismatch {
+    /// The trailer control number differs from the header's.
+    ControlNumber { header: u32, trailer: u32 },
+    /// The count declared in the trailer differs from what the envelope encloses.
+    Count { declared: u32, actual: usize },
+}
+
+/// Checks the IEA of the interchange at the start of `data` against its ISA: IEA02
+/// against ISA13, and IEA01 against the number of GS segments before the IEA.
+///
+/// ```
+/// use x12_delimiters::envelope::{reconcile_interchange, EnvelopeMismatch};
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC~GE*0*1~IEA*2*000000002~";
+/// assert_eq!(
+///     reconcile_interchange(data).unwrap(),
+///     [
+///         EnvelopeMismatch::ControlNumber { header: 1, trailer: 2 },
+///         EnvelopeMismatch::Count { declared: 2, actual: 1 },
+///     ]
+/// );
+/// ```
+///
+/// # Errors
+/// Returns `EnvelopeError::Isa` if the ISA cannot be parsed,
+/// `EnvelopeError::MissingSegment` if there is no IEA, and the errors of
+/// [`IeaSegment::parse`] for a malformed IEA.
+pub fn reconcile_interchange(data: &[u8]) -> Result<Vec<EnvelopeMismatch>, EnvelopeError> {
+    let (isa, delimiters) = IsaSegment::parse(data)?;
+    let mut groups = 0;
+    for segment in delimiters.segments(data) {
+        match segment.id() {
+            b"GS" => groups += 1,
+            b"IEA" => return Ok(IeaSegment::parse(&segment)?.reconcile(&isa, groups)),
+            _ => {}
+        }
+    }
+    Err(EnvelopeError::MissingSegment { expected: "IEA" })
+}
+
+/// Compares `(header, trailer)` control numbers and `(declared, actual)` counts.
+fn mismatches(control: (u32, u32), count: (u32, usize)) -> Vec<EnvelopeMismatch> {
+    let mut mismatches = Vec::new();
+    if control.0 != control.1 {
+        mismatches.push(EnvelopeMismatch::ControlNumber {
+            header: control.0,
+            trailer: control.1,
+        });
+    }
+    if count.0 as usize != count.1 {
+        mismatches.push(EnvelopeMismatch::Count {
+            declared: count.0,
+            actual: count.1,
+        });
+    }
+    mismatches
+}
+
+/// Returns a non-empty element of `segment`.
+pub(crate) fn required_element<'a>(
+    segment: &Segment<'a>,
+    id: &'static str,
+    element: usize,
+) -> Result<&'a [u8], EnvelopeError> {
+    element_at(segment.as_bytes(), &segment.delimiters(), element)
+        .filter(|value| !value.is_empty())
+        .ok_or(EnvelopeError::MissingElement { id, element })
+}
+
+/// Reads a numeric element, checking its width like a `field` control number.
+pub(crate) fn numeric_element(
+    segment: &Segment<'_>,
+    id: &'static str,
+    element: usize,
+    field: ControlField,
+) -> Result<u32, EnvelopeError> {
+    field
+        .parse(required_element(segment, id, element)?)
+        .map_err(|_| EnvelopeError::InvalidValue { id, element })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::IsaError;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000042*0*P*:~";
+
+    #[test]
+    fn test_iea_parse_and_to_bytes() {
+        let delimiters = Delimiters::default();
+        let iea = IeaSegment::parse(&Segment::new(b"IEA*3*000000042", delimiters)).unwrap();
+        assert_eq!((iea.group_count(), iea.control_number()), (3, 42));
+        assert_eq!(iea.to_bytes(&delimiters), b"IEA*3*000000042~");
+        assert_eq!(
+            iea.to_bytes(&Delimiters::new(0x85, 0x1D, b':')),
+            b"IEA\x1D3\x1D000000042\x85"
+        );
+
+        let parse = |raw: &'static [u8]| IeaSegment::parse(&Segment::new(raw, delimiters));
+        assert_eq!(
+            parse(b"GE*3*42"),
+            Err(EnvelopeError::WrongSegment { expected: "IEA" })
+        );
+        assert_eq!(
+            parse(b"IEA*3"),
+            Err(EnvelopeError::MissingElement {
+                id: "IEA",
+                element: 2
+            })
+        );
+        assert_eq!(
+            parse(b"IEA*X*000000042"),
+            Err(EnvelopeError::InvalidValue {
+                id: "IEA",
+                element: 1
+            })
+        );
+        assert_eq!(
+            parse(b"IEA*1*42"),
+            Err(EnvelopeError::InvalidValue {
+                id: "IEA",
+                element: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_gs_ge_parse_roundtrip() {
+        let delimiters = Delimiters::new(b'}', b'|', b'>');
+        let gs = GsSegment::new("HS", "S", "R", "20250403", "085612", 9, "005010X279A1");
+        let bytes = gs.to_bytes(&delimiters);
+        let raw = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            GsSegment::parse(&Segment::new(raw, delimiters)),
+            Ok(gs.clone())
+        );
+        assert_eq!(gs.time(), "085612");
+        assert_eq!(gs.responsible_agency(), "X");
+
+        let ge = GeSegment::parse(&Segment::new(b"GE|3|9", delimiters)).unwrap();
+        assert_eq!((ge.transaction_count(), ge.control_number()), (3, 9));
+        assert_eq!(ge.to_bytes(&delimiters), b"GE|3|9}");
+        assert!(ge.reconcile(&gs, 3).is_empty());
+
+        let parse = |raw: &'static [u8]| GsSegment::parse(&Segment::new(raw, delimiters));
+        assert_eq!(
+            parse(b"GS|HS|S|R|20250403|0856|9|X"),
+            Err(EnvelopeError::MissingElement {
+                id: "GS",
+                element: 8
+            })
+        );
+        assert_eq!(
+            parse(b"GS|HS|S|R|20250403|0856|A9|X|005010"),
+            Err(EnvelopeError::InvalidValue {
+                id: "GS",
+                element: 6
+            })
+        );
+        assert_eq!(
+            GeSegment::parse(&Segment::new(b"IEA|1|9", delimiters)),
+            Err(EnvelopeError::WrongSegment { expected: "GE" })
+        );
+    }
+
+    #[test]
+    fn test_st_se_parse_and_reconcile() {
+        let delimiters = Delimiters::default();
+        let st = StSegment::new("837", 1).with_implementation_reference("005010X222A1");
+        assert_eq!(st.to_bytes(&delimiters), b"ST*837*0001*005010X222A1~");
+        let parsed = StSegment::parse(&Segment::new(b"ST*837*0001*005010X222A1", delimiters));
+        assert_eq!(parsed, Ok(st.clone()));
+        assert_eq!(
+            StSegment::parse(&Segment::new(b"ST*270*0007", delimiters))
+                .unwrap()
+                .implementation_reference(),
+            None
+        );
+        assert_eq!(
+            StSegment::parse(&Segment::new(b"ST*837*1", delimiters)),
+            Err(EnvelopeError::InvalidValue {
+                id: "ST",
+                element: 2
+            })
+        );
+
+        let se = SeSegment::parse(&Segment::new(b"SE*4*0002", delimiters)).unwrap();
+        assert_eq!(se.to_bytes(&delimiters), b"SE*4*0002~");
+        assert_eq!(
+            se.reconcile(&st, 4),
+            [EnvelopeMismatch::ControlNumber {
+                header: 1,
+                trailer: 2
+            }]
+        );
+
+        let balanced = b"ST*837*0001~\r\nBHT*0019~\r\nSE*3*0001~\r\nGE*1*1~";
+        assert_eq!(reconcile_transaction(balanced, &delimiters), Ok(Vec::new()));
+        assert_eq!(
+            reconcile_transaction(b"ST*837*0001~BHT*0019~", &delimiters),
+            Err(EnvelopeError::MissingSegment { expected: "SE" })
+        );
+        assert_eq!(
+            reconcile_transaction(b"GS*HC~", &delimiters),
+            Err(EnvelopeError::WrongSegment { expected: "ST" })
+        );
+    }
+
+    #[test]
+    fn test_reconcile_interchange() {
+        let balanced = format!("{}GS*HC~GE*0*1~GS*HC~GE*0*2~IEA*2*000000042~", ISA);
+        assert_eq!(reconcile_interchange(balanced.as_bytes()), Ok(Vec::new()));
+
+        let unclosed = format!("{}GS*HC~GE*0*1~", ISA);
+        assert_eq!(
+            reconcile_interchange(unclosed.as_bytes()),
+            Err(EnvelopeError::MissingSegment { expected: "IEA" })
+        );
+        assert!(matches!(
+            reconcile_interchange(b"IEA*0*000000001~"),
+            Err(EnvelopeError::Isa(IsaError::Delimiters(_)))
+        ));
+    }
+
+    #[test]
+    fn test_gs_to_bytes() {
+        let gs = GsSegment::new(
+            "HC",
+            "SENDER",
+            "RECEIVER",
+            "20250403",
+            "0856",
+            17,
+            "005010X222A1",
+        );
+        assert_eq!(
+            gs.to_bytes(&Delimiters::default()),
+            b"GS*HC*SENDER*RECEIVER*20250403*0856*17*X*005010X222A1~"
+        );
+    }
+}
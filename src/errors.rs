@@ -1,8 +1,18 @@
 use std::fmt;
+use std::io;
+
+use crate::DelimiterKind;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum DelimiterError {
     InvalidIsaLength,
+    MissingIsa,
+    DuplicateDelimiter(u8),
+    DisallowedDelimiter { kind: DelimiterKind, byte: u8 },
+    NotIsaSegment,
+    InconsistentSeparators { index: usize, byte: u8 },
+    NoSegmentStructure,
+    TrailingData { offset: usize },
 }
 
 impl fmt::Display for DelimiterError {
@@ -11,8 +21,518 @@ impl fmt::Display for DelimiterError {
             DelimiterError::InvalidIsaLength => {
                 write!(f, "ISA segment must be at least 106 bytes long to extract delimiters")
             }
+            DelimiterError::MissingIsa => write!(f, "data does not start with an ISA segment"),
+            DelimiterError::DuplicateDelimiter(byte) => {
+                write!(f, "byte 0x{:02X} is used for more than one delimiter", byte)
+            }
+            DelimiterError::DisallowedDelimiter { kind, byte } => {
+                write!(f, "byte 0x{:02X} is not allowed as the {}", byte, kind)
+            }
+            DelimiterError::NotIsaSegment => write!(f, "segment does not start with the ISA tag"),
+            DelimiterError::InconsistentSeparators { index, byte } => write!(
+                f,
+                "expected the element separator at ISA byte {} but found 0x{:02X}",
+                index, byte
+            ),
+            DelimiterError::NoSegmentStructure => {
+                write!(f, "data has no segment structure to infer delimiters from")
+            }
+            DelimiterError::TrailingData { offset } => {
+                write!(f, "unexpected data after the last interchange at offset {}", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DelimiterError {}
+
+/// Errors returned when serializing segments or documents.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BuildError {
+    TransactionOutsideGroup,
+    DelimiterConflict {
+        byte: u8,
+        element: usize,
+        offset: usize,
+    },
+    SegmentTooLong {
+        length: usize,
+        max: usize,
+    },
+    LineTooLong {
+        length: usize,
+        max: usize,
+    },
+    Isa(IsaError),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::TransactionOutsideGroup => {
+                write!(f, "transaction set added before any functional group was opened")
+            }
+            BuildError::DelimiterConflict {
+                byte,
+                element,
+                offset,
+            } => write!(
+                f,
+                "data byte 0x{:02X} in element {} at offset {} collides with a delimiter",
+                byte, element, offset
+            ),
+            BuildError::SegmentTooLong { length, max } => write!(
+                f,
+                "segment of {} bytes exceeds the limit of {}",
+                length, max
+            ),
+            BuildError::LineTooLong { length, max } => {
+                write!(f, "line of {} bytes exceeds the limit of {}", length, max)
+            }
+            BuildError::Isa(e) => write!(f, "invalid ISA segment: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::Isa(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<IsaError> for BuildError {
+    fn from(e: IsaError) -> Self {
+        BuildError::Isa(e)
+    }
+}
+
+/// Errors returned when parsing a delimiter set from names.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NameError {
+    UnknownName(String),
+    UnknownKey(String),
+    DuplicateKey(String),
+    WrongCount(usize),
+    Malformed,
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::UnknownName(name) => write!(f, "unknown delimiter name {:?}", name),
+            NameError::UnknownKey(key) => write!(f, "unknown delimiter key {:?}", key),
+            NameError::DuplicateKey(key) => write!(f, "delimiter key {:?} given twice", key),
+            NameError::WrongCount(count) => write!(
+                f,
+                "expected three delimiter names and an optional suffix, found {}",
+                count
+            ),
+            NameError::Malformed => write!(f, "malformed delimiter object"),
+        }
+    }
+}
+
+impl std::error::Error for NameError {}
+
+/// Errors returned when streaming segments from a reader.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Delimiters(DelimiterError),
+    SegmentTooLong { offset: u64, max: usize },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "failed to read X12 data: {}", e),
+            ReadError::Delimiters(e) => write!(f, "failed to read delimiters: {}", e),
+            ReadError::SegmentTooLong { offset, max } => write!(
+                f,
+                "segment at byte {} is longer than {} bytes without a terminator",
+                offset, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Delimiters(e) => Some(e),
+            ReadError::SegmentTooLong { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(e: io::Error) -> Self {
+        ReadError::Io(e)
+    }
+}
+
+impl From<DelimiterError> for ReadError {
+    fn from(e: DelimiterError) -> Self {
+        ReadError::Delimiters(e)
+    }
+}
+
+/// Errors returned when producing the canonical form of a document.
+#[derive(Debug)]
+pub enum CanonicalError {
+    Io(io::Error),
+    Delimiters(DelimiterError),
+    Build(BuildError),
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CanonicalError::Io(e) => write!(f, "failed to write canonical form: {}", e),
+            CanonicalError::Delimiters(e) => write!(f, "failed to read delimiters: {}", e),
+            CanonicalError::Build(e) => write!(f, "failed to canonicalize segment: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CanonicalError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CanonicalError::Io(e) => Some(e),
+            CanonicalError::Delimiters(e) => Some(e),
+            CanonicalError::Build(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for CanonicalError {
+    fn from(e: io::Error) -> Self {
+        CanonicalError::Io(e)
+    }
+}
+
+impl From<DelimiterError> for CanonicalError {
+    fn from(e: DelimiterError) -> Self {
+        CanonicalError::Delimiters(e)
+    }
+}
+
+impl From<BuildError> for CanonicalError {
+    fn from(e: BuildError) -> Self {
+        CanonicalError::Build(e)
+    }
+}
+
+/// Errors returned when updating a [`crate::index::DocumentIndex`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IndexError {
+    OffsetMismatch { expected: usize, actual: usize },
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::OffsetMismatch { expected, actual } => write!(
+                f,
+                "index resumes at offset {} but new bytes start at offset {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+/// Errors returned when rewriting a document with different delimiters.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TranscodeError {
+    Delimiters(DelimiterError),
+    Build(BuildError),
+}
+
+impl fmt::Display for TranscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranscodeError::Delimiters(e) => write!(f, "failed to read delimiters: {}", e),
+            TranscodeError::Build(e) => write!(f, "failed to rewrite segment: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TranscodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TranscodeError::Delimiters(e) => Some(e),
+            TranscodeError::Build(e) => Some(e),
+        }
+    }
+}
+
+impl From<DelimiterError> for TranscodeError {
+    fn from(e: DelimiterError) -> Self {
+        TranscodeError::Delimiters(e)
+    }
+}
+
+impl From<BuildError> for TranscodeError {
+    fn from(e: BuildError) -> Self {
+        TranscodeError::Build(e)
+    }
+}
+
+/// Errors returned when formatting or parsing an envelope control number.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ControlNumberError {
+    NotNumeric,
+    WrongWidth {
+        min: usize,
+        max: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for ControlNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlNumberError::NotNumeric => write!(f, "control number is not numeric"),
+            ControlNumberError::WrongWidth { min, max, actual } if min == max => write!(
+                f,
+                "control number has {} digits but must have {}",
+                actual, max
+            ),
+            ControlNumberError::WrongWidth { min, max, actual } => write!(
+                f,
+                "control number has {} digits but must have {} to {}",
+                actual, min, max
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ControlNumberError {}
+
+/// Errors returned when parsing or writing an ISA segment field by field.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum IsaError {
+    Delimiters(DelimiterError),
+    MissingElement {
+        element: usize,
+    },
+    FieldWidth {
+        element: usize,
+        expected: usize,
+        actual: usize,
+    },
+    InvalidValue {
+        element: usize,
+    },
+}
+
+impl fmt::Display for IsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsaError::Delimiters(e) => write!(f, "failed to read delimiters: {}", e),
+            IsaError::MissingElement { element } => write!(f, "ISA{:02} is missing", element),
+            IsaError::FieldWidth {
+                element,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "ISA{:02} must be {} bytes wide but is {}",
+                element, expected, actual
+            ),
+            IsaError::InvalidValue { element } => {
+                write!(f, "ISA{:02} has an invalid value", element)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IsaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IsaError::Delimiters(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DelimiterError> for IsaError {
+    fn from(e: DelimiterError) -> Self {
+        IsaError::Delimiters(e)
+    }
+}
+
+/// Errors returned when reading envelope header and trailer segments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EnvelopeError {
+    Isa(IsaError),
+    WrongSegment { expected: &'static str },
+    MissingSegment { expected: &'static str },
+    MissingElement { id: &'static str, element: usize },
+    InvalidValue { id: &'static str, element: usize },
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Isa(e) => write!(f, "invalid ISA segment: {}", e),
+            EnvelopeError::WrongSegment { expected } => {
+                write!(f, "expected the {} segment", expected)
+            }
+            EnvelopeError::MissingSegment { expected } => {
+                write!(f, "no {} segment found", expected)
+            }
+            EnvelopeError::MissingElement { id, element } => {
+                write!(f, "{}{:02} is missing", id, element)
+            }
+            EnvelopeError::InvalidValue { id, element } => {
+                write!(f, "{}{:02} has an invalid value", id, element)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EnvelopeError::Isa(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<IsaError> for EnvelopeError {
+    fn from(e: IsaError) -> Self {
+        EnvelopeError::Isa(e)
+    }
+}
+
+/// Errors returned when converting fixed-width lines to delimited X12.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FixedWidthError {
+    UnknownSegment { line: usize },
+    LineTooLong { line: usize },
+    DelimiterConflict { line: usize, byte: u8 },
+}
+
+impl fmt::Display for FixedWidthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedWidthError::UnknownSegment { line } => {
+                write!(
+                    f,
+                    "line {} starts with no segment ID in the width table",
+                    line
+                )
+            }
+            FixedWidthError::LineTooLong { line } => {
+                write!(f, "line {} is longer than its field widths", line)
+            }
+            FixedWidthError::DelimiterConflict { line, byte } => write!(
+                f,
+                "line {} contains byte 0x{:02X}, which is a target delimiter",
+                line, byte
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FixedWidthError {}
+
+/// Errors returned when concatenating interchanges into one stream.
+#[derive(Debug)]
+pub enum ConcatError {
+    Io(io::Error),
+    Envelope { output: usize, error: EnvelopeError },
+}
+
+impl fmt::Display for ConcatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcatError::Io(e) => write!(f, "failed to write interchanges: {}", e),
+            ConcatError::Envelope { output, error } => {
+                write!(f, "output {} has an invalid envelope: {}", output, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConcatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConcatError::Io(e) => Some(e),
+            ConcatError::Envelope { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for ConcatError {
+    fn from(e: io::Error) -> Self {
+        ConcatError::Io(e)
+    }
+}
+
+/// Errors returned when writing segments with [`crate::writer::SegmentWriter`].
+#[derive(Debug)]
+pub enum WriteError {
+    Io(io::Error),
+    Build(BuildError),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Io(e) => write!(f, "failed to write segment: {}", e),
+            WriteError::Build(e) => write!(f, "failed to serialize segment: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteError::Io(e) => Some(e),
+            WriteError::Build(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for WriteError {
+    fn from(e: io::Error) -> Self {
+        WriteError::Io(e)
+    }
+}
+
+impl From<BuildError> for WriteError {
+    fn from(e: BuildError) -> Self {
+        WriteError::Build(e)
+    }
+}
+
+/// Errors returned when converting an element value with [`crate::element::ElementRef`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ElementValueError {
+    Empty,
+    NotText,
+    Invalid,
+    OutOfRange,
+}
+
+impl fmt::Display for ElementValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementValueError::Empty => write!(f, "element is empty"),
+            ElementValueError::NotText => write!(f, "element is not valid UTF-8"),
+            ElementValueError::Invalid => write!(f, "element does not have the expected format"),
+            ElementValueError::OutOfRange => write!(f, "element value is out of range"),
         }
     }
 }
 
-impl std::error::Error for DelimiterError {}
\ No newline at end of file
+impl std::error::Error for ElementValueError {}
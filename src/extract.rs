@@ -0,0 +1,268 @@
+use crate::errors::DelimiterError;
+use crate::segment::{element_at, Segment, SegmentIter, Span};
+use crate::summary::interchange_spans;
+use crate::Delimiters;
+
+/// One occurrence of a field requested from [`extract`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ExtractedValue<'a> {
+    field: usize,
+    segment: Span,
+    value: Option<&'a [u8]>,
+}
+
+impl<'a> ExtractedValue<'a> {
+    /// Returns the index of the requested field this value belongs to.
+    pub fn field(&self) -> usize {
+        self.field
+    }
+
+    /// Returns the span of the segment the value was read from, terminator included.
+    pub fn segment(&self) -> Span {
+        self.segment
+    }
+
+    /// Returns the element value, or None if the segment has fewer elements.
+    pub fn value(&self) -> Option<&'a [u8]> {
+        self.value
+    }
+}
+
+/// Reads selected element values without building a document model.
+///
+/// Each field is a segment ID and an element position, e.g. `("GS", 6)` for GS06. Every
+/// segment with a requested ID yields one value per matching field, in document order.
+/// Each interchange is read with the delimiters from its own ISA.
+///
+/// ```
+/// use x12_delimiters::extract::extract;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC*S*R*20250403*0856*17*X*005010~ST*837*0001~SE*2*0001~GE*1*17~IEA*1*000000001~";
+/// let values = extract(data, &[("ISA", 13), ("GS", 6), ("ST", 1)]).unwrap();
+/// let values: Vec<_> = values.iter().map(|v| (v.field(), v.value().unwrap())).collect();
+/// assert_eq!(values, [(0, &b"000000001"[..]), (1, b"17"), (2, b"837")]);
+/// ```
+///
+/// # Errors
+/// Returns a `DelimiterError` if `data` does not start with an ISA segment.
+pub fn extract<'a>(
+    data: &'a [u8],
+    fields: &[(&str, usize)],
+) -> Result<Vec<ExtractedValue<'a>>, DelimiterError> {
+    let spans = interchange_spans(data);
+    if spans.is_empty() {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa);
+    }
+
+    let mut values = Vec::new();
+    for (interchange, delimiters) in spans {
+        let bytes = &data[interchange.start..interchange.end];
+        for (span, segment) in delimiters.segments(bytes).spanned() {
+            let id = segment.id();
+            for (field, &(wanted, position)) in fields.iter().enumerate() {
+                if id == wanted.as_bytes() {
+                    values.push(ExtractedValue {
+                        field,
+                        segment: Span::new(
+                            interchange.start + span.start,
+                            interchange.start + span.end,
+                        ),
+                        value: element_at(segment.as_bytes(), &delimiters, position),
+                    });
+                }
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// One transaction set and the envelope headers around it, borrowed from the input.
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionSlice<'a> {
+    bytes: &'a [u8],
+    span: Span,
+    isa: Segment<'a>,
+    gs: Option<Segment<'a>>,
+    delimiters: Delimiters,
+}
+
+impl<'a> TransactionSlice<'a> {
+    /// Returns the transaction from its ST through its SE terminator.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the position of the transaction in the input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the ISA of the enclosing interchange.
+    pub fn isa(&self) -> Segment<'a> {
+        self.isa
+    }
+
+    /// Returns the GS of the enclosing functional group, or None if the transaction is
+    /// not inside one.
+    pub fn gs(&self) -> Option<Segment<'a>> {
+        self.gs
+    }
+
+    /// Returns the delimiters declared by the enclosing ISA.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the segments of the transaction, ST through SE.
+    pub fn segments(&self) -> SegmentIter<'a> {
+        self.delimiters.segments(self.bytes)
+    }
+}
+
+/// Returns the transaction set at zero-based position `st_index` among all ST segments in
+/// `data`, with the ISA and GS that enclose it.
+///
+/// Each interchange is read with the delimiters from its own ISA. A transaction without
+/// an SE ends before the next ST, GS, GE, or IEA, or at the end of its interchange. Nothing
+/// is copied: the slice and headers borrow `data`.
+///
+/// ```
+/// use x12_delimiters::extract::extract_transaction;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC*S*R*20250403*0856*17*X*005010~ST*837*0001~SE*2*0001~ST*837*0002~CLM*B~SE*3*0002~GE*2*17~IEA*1*000000001~";
+/// let transaction = extract_transaction(data, 1).unwrap().unwrap();
+/// assert_eq!(transaction.as_bytes(), b"ST*837*0002~CLM*B~SE*3*0002~");
+/// assert_eq!(transaction.gs().unwrap().id(), b"GS");
+/// assert_eq!(transaction.segments().count(), 3);
+/// assert!(extract_transaction(data, 2).unwrap().is_none());
+/// ```
+///
+/// # Errors
+/// Returns a `DelimiterError` if `data` does not start with an ISA segment.
+pub fn extract_transaction(
+    data: &[u8],
+    st_index: usize,
+) -> Result<Option<TransactionSlice<'_>>, DelimiterError> {
+    let spans = interchange_spans(data);
+    if spans.is_empty() {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa);
+    }
+
+    let mut seen = 0;
+    for (interchange, delimiters) in spans {
+        let bytes = &data[interchange.start..interchange.end];
+        let mut segments = delimiters.segments(bytes).spanned();
+        // Interchange spans always start with their ISA.
+        let Some((_, isa)) = segments.next() else {
+            continue;
+        };
+        let mut gs = None;
+        while let Some((span, segment)) = segments.next() {
+            match segment.id() {
+                b"GS" => gs = Some(segment),
+                b"GE" => gs = None,
+                b"ST" if seen == st_index => {
+                    let end = segments
+                        .find_map(|(span, segment)| match segment.id() {
+                            b"SE" => Some(span.end),
+                            b"ST" | b"GS" | b"GE" | b"IEA" => Some(span.start),
+                            _ => None,
+                        })
+                        .unwrap_or(bytes.len());
+                    return Ok(Some(TransactionSlice {
+                        bytes: &bytes[span.start..end],
+                        span: Span::new(interchange.start + span.start, interchange.start + end),
+                        isa,
+                        gs,
+                        delimiters,
+                    }));
+                }
+                b"ST" => seen += 1,
+                _ => {}
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+    const ISA_ALT: &[u8] = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}";
+
+    #[test]
+    fn test_extract_across_interchanges() {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(b"ST*837*0001~ST*835*0002~IEA*0*000000001~\n");
+        let second = data.len();
+        data.extend_from_slice(ISA_ALT);
+        data.extend_from_slice(b"ST|999}IEA|0|000000002}");
+
+        let values = extract(&data, &[("ST", 1), ("ST", 2), ("IEA", 2)]).unwrap();
+        let found: Vec<_> = values.iter().map(|v| (v.field(), v.value())).collect();
+        assert_eq!(
+            found,
+            [
+                (0, Some(&b"837"[..])),
+                (1, Some(&b"0001"[..])),
+                (0, Some(&b"835"[..])),
+                (1, Some(&b"0002"[..])),
+                (2, Some(&b"000000001"[..])),
+                (0, Some(&b"999"[..])),
+                (1, None),
+                (2, Some(&b"000000002"[..])),
+            ]
+        );
+        let span = values[5].segment();
+        assert_eq!(&data[span.start..span.end], b"ST|999}");
+        assert!(span.start > second);
+    }
+
+    #[test]
+    fn test_extract_transaction_with_envelope() {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(b"GS*HC*S*R~ST*837*0001~SE*2*0001~GE*1*1~IEA*1*000000001~\n");
+        data.extend_from_slice(ISA_ALT);
+        data.extend_from_slice(b"ST|270|0001}BHT|0022}GS|HS}ST|271}IEA|1|000000002}");
+
+        let first = extract_transaction(&data, 0).unwrap().unwrap();
+        assert_eq!(first.as_bytes(), b"ST*837*0001~SE*2*0001~");
+        assert_eq!(first.isa().as_bytes(), &ISA[..ISA.len() - 1]);
+        assert_eq!(first.gs().unwrap().as_bytes(), b"GS*HC*S*R");
+
+        // Unterminated transactions end at the next envelope segment or the IEA.
+        let second = extract_transaction(&data, 1).unwrap().unwrap();
+        assert_eq!(second.as_bytes(), b"ST|270|0001}BHT|0022}");
+        assert!(second.gs().is_none());
+        assert_eq!(second.delimiters().element_separator(), b'|');
+        let span = second.span();
+        assert_eq!(&data[span.start..span.end], second.as_bytes());
+
+        let third = extract_transaction(&data, 2).unwrap().unwrap();
+        assert_eq!(third.as_bytes(), b"ST|271}");
+        assert_eq!(third.gs().unwrap().as_bytes(), b"GS|HS");
+        assert!(extract_transaction(&data, 3).unwrap().is_none());
+        assert_eq!(
+            extract_transaction(b"ST*837~", 0).map(|t| t.is_some()),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_extract_requires_isa() {
+        assert_eq!(
+            extract(b"ST*837~", &[("ST", 1)]),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+        let mut data = b"GS".to_vec();
+        data.extend_from_slice(ISA);
+        assert_eq!(
+            extract(&data, &[("ST", 1)]),
+            Err(DelimiterError::MissingIsa)
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::io::{Read, Write};
+
+use crate::canonical::canonical_sha256;
+use crate::errors::{DelimiterError, ReadError};
+use crate::reader::{fill, READ_CHUNK};
+use crate::segment::element_at;
+use crate::summary::InterchangeSummary;
+use crate::Delimiters;
+
+const ISA_LENGTH: usize = 106;
+
+/// Writes one NDJSON summary line per interchange read from `reader`.
+///
+/// `reader` holds any number of concatenated interchanges, each with its own delimiters,
+/// optionally separated by whitespace. Only the interchange being summarized is buffered,
+/// so memory use is bounded by the largest interchange plus the read chunk size. Each line
+/// is a JSON object:
+///
+/// ```json
+/// {
+///   "offset": 0,
+///   "length": 178,
+///   "delimiters": { "segment_terminator": 126, "element_separator": 42, "sub_element_separator": 58, "repetition_separator": 94, "suffix": "none" },
+///   "sender": { "qualifier": "ZZ", "id": "SENDERID" },
+///   "receiver": { "qualifier": "ZZ", "id": "RECEIVERID" },
+///   "control_number": "000000001",
+///   "version": "00501",
+///   "usage_indicator": "P",
+///   "functional_groups": ["HC"],
+///   "transaction_sets": ["837"],
+///   "group_count": 1,
+///   "transaction_count": 1,
+///   "segment_count": 7,
+///   "digest": "6c5f…"
+/// }
+/// ```
+///
+/// `offset` and `length` locate the interchange in the stream. `functional_groups` and
+/// `transaction_sets` list the distinct GS01 and ST01 values in order of first use.
+/// `digest` is the hex SHA-256 of the canonical form (see
+/// [`crate::canonical::write_canonical`]), or `null` if the interchange cannot be
+/// canonicalized. An interchange without an IEA runs to the end of the stream.
+///
+/// Returns the number of lines written. Lines already written stay written if a later
+/// interchange fails.
+///
+/// ```
+/// use x12_delimiters::feed::write_ndjson_summaries;
+///
+/// let data: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~\n\
+/// ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}IEA|0|000000002}";
+/// let mut out = Vec::new();
+/// assert_eq!(write_ndjson_summaries(data, &mut out).unwrap(), 2);
+/// assert_eq!(out.iter().filter(|&&b| b == b'\n').count(), 2);
+/// ```
+///
+/// # Errors
+/// Returns `ReadError::Io` if reading or writing fails, and `ReadError::Delimiters` if
+/// non-whitespace data that does not start with a complete ISA segment is found where an
+/// interchange should begin.
+pub fn write_ndjson_summaries<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+) -> Result<usize, ReadError> {
+    let mut buf = Vec::with_capacity(READ_CHUNK);
+    let mut eof = false;
+    let mut offset = 0u64;
+    let mut scanned = 0;
+    let mut written = 0;
+    loop {
+        let start = buf
+            .iter()
+            .position(|b: &u8| !b.is_ascii_whitespace())
+            .unwrap_or(buf.len());
+        if start == buf.len() && eof {
+            break;
+        }
+        match interchange_end(&buf[start..], &mut scanned, eof)? {
+            Some(end) => {
+                let interchange = &buf[start..start + end];
+                let line = summary_line(interchange, offset + start as u64)?;
+                writer.write_all(line.as_bytes())?;
+                writer.write_all(b"\n")?;
+                written += 1;
+                buf.drain(..start + end);
+                offset += (start + end) as u64;
+                scanned = 0;
+            }
+            None => eof = fill(&mut reader, &mut buf, READ_CHUNK)? == 0,
+        }
+    }
+    Ok(written)
+}
+
+/// Returns the length of the interchange at the start of `data`, through its IEA, or
+/// `None` if more data is needed to tell.
+///
+/// `scanned` is the length of complete segments already searched for the IEA, so each
+/// call only scans newly read bytes.
+fn interchange_end(
+    data: &[u8],
+    scanned: &mut usize,
+    eof: bool,
+) -> Result<Option<usize>, DelimiterError> {
+    if data.len() <= ISA_LENGTH && !eof {
+        return Ok(None);
+    }
+    if !data.starts_with(b"ISA") {
+        return Err(DelimiterError::MissingIsa);
+    }
+    let delimiters = Delimiters::from_isa(data)?;
+    let terminator = delimiters.segment_terminator();
+    let base = *scanned;
+    for (span, segment) in delimiters.segments(&data[base..]).spanned() {
+        let end = base + span.end;
+        if data[end - 1] != terminator {
+            // An incomplete trailing segment; read on unless the stream has ended.
+            break;
+        }
+        if segment.id() == b"IEA" {
+            return Ok(Some(end));
+        }
+        *scanned = end;
+    }
+    Ok(eof.then_some(data.len()))
+}
+
+fn summary_line(interchange: &[u8], offset: u64) -> Result<String, DelimiterError> {
+    #[derive(serde::Serialize)]
+    struct Party<'a> {
+        qualifier: &'a str,
+        id: &'a str,
+    }
+
+    #[derive(serde::Serialize)]
+    struct SummaryLine<'a> {
+        offset: u64,
+        length: usize,
+        delimiters: Delimiters,
+        sender: Party<'a>,
+        receiver: Party<'a>,
+        control_number: &'a str,
+        version: &'a str,
+        usage_indicator: &'a str,
+        functional_groups: Vec<String>,
+        transaction_sets: Vec<String>,
+        group_count: usize,
+        transaction_count: usize,
+        segment_count: usize,
+        digest: Option<String>,
+    }
+
+    let summary = InterchangeSummary::from_isa(interchange)?;
+    let delimiters = summary.delimiters();
+    let mut line = SummaryLine {
+        offset,
+        length: interchange.len(),
+        delimiters,
+        sender: Party {
+            qualifier: summary.sender_qualifier(),
+            id: summary.sender_id(),
+        },
+        receiver: Party {
+            qualifier: summary.receiver_qualifier(),
+            id: summary.receiver_id(),
+        },
+        control_number: summary.control_number(),
+        version: summary.version(),
+        usage_indicator: summary.usage_indicator(),
+        functional_groups: Vec::new(),
+        transaction_sets: Vec::new(),
+        group_count: 0,
+        transaction_count: 0,
+        segment_count: 0,
+        digest: None,
+    };
+    for segment in delimiters.segments(interchange) {
+        line.segment_count += 1;
+        let (count, kinds) = match segment.id() {
+            b"GS" => (&mut line.group_count, &mut line.functional_groups),
+            b"ST" => (&mut line.transaction_count, &mut line.transaction_sets),
+            _ => continue,
+        };
+        *count += 1;
+        let kind = element_at(segment.as_bytes(), &delimiters, 1).unwrap_or_default();
+        let kind = String::from_utf8_lossy(kind);
+        if !kinds.iter().any(|k| *k == kind) {
+            kinds.push(kind.into_owned());
+        }
+    }
+    if let Ok(digest) = canonical_sha256(interchange) {
+        line.digest = Some(digest.iter().map(|b| format!("{:02x}", b)).collect());
+    }
+    Ok(serde_json::to_string(&line).expect("summary lines always serialize"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{interchange, isa};
+    use serde_json::Value;
+
+    /// Reads one byte per call, so every interchange spans many buffer refills.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.0.len().min(out.len()).min(1);
+            out[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    fn lines(out: &[u8]) -> Vec<Value> {
+        std::str::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_write_ndjson_summaries() {
+        let first = interchange(
+            1,
+            "GS*HC*S*R*20250403*0856*1*X*005010X222A1~ST*837*0001~SE*2*0001~ST*837*0002~SE*2*0002~GE*2*1~",
+        );
+        let second = interchange(2, "GS*HS*S*R~ST*270*0001~SE*2*0001~GE*1*1~")
+            .replace('*', "|")
+            .replace('~', "}\r\n");
+        let data = format!("{}\n\n{}", first, second);
+
+        let mut out = Vec::new();
+        assert_eq!(
+            write_ndjson_summaries(Trickle(data.as_bytes()), &mut out).unwrap(),
+            2
+        );
+        let lines = lines(&out);
+        assert_eq!(lines.len(), 2);
+
+        assert_eq!(lines[0]["offset"], 0);
+        assert_eq!(lines[0]["length"], first.len());
+        assert_eq!(lines[0]["sender"]["id"], "SENDERID");
+        assert_eq!(lines[0]["control_number"], "000000001");
+        assert_eq!(lines[0]["functional_groups"], serde_json::json!(["HC"]));
+        assert_eq!(lines[0]["transaction_sets"], serde_json::json!(["837"]));
+        assert_eq!(lines[0]["transaction_count"], 2);
+        assert_eq!(lines[0]["segment_count"], 8);
+        assert_eq!(lines[0]["digest"].as_str().unwrap().len(), 64);
+
+        assert_eq!(lines[1]["offset"], first.len() + 2);
+        assert_eq!(lines[1]["delimiters"]["element_separator"], b'|');
+        assert_eq!(lines[1]["transaction_sets"], serde_json::json!(["270"]));
+        assert_eq!(lines[1]["group_count"], 1);
+    }
+
+    #[test]
+    fn test_write_ndjson_summaries_unterminated_and_garbage() {
+        let open = isa(3) + "GS*HC~ST*837*0001~";
+        let mut out = Vec::new();
+        assert_eq!(
+            write_ndjson_summaries(open.as_bytes(), &mut out).unwrap(),
+            1
+        );
+        assert_eq!(lines(&out)[0]["segment_count"], 3);
+
+        let data = interchange(4, "") + "\nnot an interchange";
+        let mut out = Vec::new();
+        assert!(matches!(
+            write_ndjson_summaries(data.as_bytes(), &mut out),
+            Err(ReadError::Delimiters(DelimiterError::MissingIsa))
+        ));
+        assert_eq!(lines(&out).len(), 1);
+
+        let mut out = Vec::new();
+        assert_eq!(write_ndjson_summaries(&b" \r\n"[..], &mut out).unwrap(), 0);
+        assert!(out.is_empty());
+    }
+}
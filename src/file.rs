@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::Path;
+
+use crate::errors::ReadError;
+use crate::index::DocumentIndex;
+use crate::segment::SegmentIter;
+use crate::Delimiters;
+
+/// A whole X12 file held in one buffer, with the delimiters of its first ISA.
+///
+/// The file is read with a single allocation sized from its metadata and never copied
+/// again: [`FileDocument::segments`] borrows from the buffer, as
+/// [`Delimiters::segments`] does. The bytes are resident, so memory use equals the file
+/// size; use `MappedDocument` with the `memmap2` feature to leave paging to the operating
+/// system, or [`crate::reader::SegmentReader`] to stream.
+///
+/// ```
+/// use x12_delimiters::file::FileDocument;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~".to_vec();
+/// let document = FileDocument::from_bytes(data).unwrap();
+/// assert_eq!(document.delimiters().segment_terminator(), b'~');
+/// assert_eq!(document.segments().count(), 2);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FileDocument {
+    data: Vec<u8>,
+    delimiters: Delimiters,
+}
+
+impl FileDocument {
+    /// Reads the file at `path` and extracts delimiters from its ISA segment.
+    ///
+    /// # Errors
+    /// Returns `ReadError::Io` if the file cannot be read, or `ReadError::Delimiters` if
+    /// it does not start with a valid ISA segment.
+    ///
+    /// With the `io_uring` feature on Linux, the file is read through
+    /// [`crate::uring::UringReader`], falling back to a buffered read if the ring cannot
+    /// be set up.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReadError> {
+        #[cfg(all(feature = "io_uring", target_os = "linux"))]
+        let data = crate::uring::read_file(path.as_ref())?;
+        #[cfg(not(all(feature = "io_uring", target_os = "linux")))]
+        let data = fs::read(path)?;
+        Self::from_bytes(data)
+    }
+
+    /// Takes ownership of `data` and extracts delimiters from its ISA segment.
+    ///
+    /// # Errors
+    /// Returns `ReadError::Delimiters` if `data` does not start with a valid ISA segment.
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, ReadError> {
+        let delimiters = Delimiters::from_isa(&data)?;
+        Ok(FileDocument { data, delimiters })
+    }
+
+    /// Returns the delimiters read from the ISA segment.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the file contents.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the file size in bytes.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the file is empty. Never true for a successfully opened file.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the segments of the file, borrowed from its buffer.
+    pub fn segments(&self) -> SegmentIter<'_> {
+        self.delimiters.segments(&self.data)
+    }
+
+    /// Indexes the segments and envelopes of the file; see [`DocumentIndex::build`].
+    ///
+    /// ```no_run
+    /// use x12_delimiters::file::FileDocument;
+    ///
+    /// let document = FileDocument::open("/archive/batch-0001.x12").unwrap();
+    /// let index = document.index();
+    /// println!("{} segments", index.len());
+    /// ```
+    pub fn index(&self) -> DocumentIndex {
+        DocumentIndex::build(&self.data, &self.delimiters)
+    }
+
+    /// Returns the file contents, giving up the document.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl AsRef<[u8]> for FileDocument {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// A whole X12 file mapped into memory, with the delimiters of its first ISA.
+///
+/// The same API as [`FileDocument`], backed by a read-only `memmap2` mapping instead of
+/// a heap buffer: the operating system pages the file in as [`MappedDocument::segments`]
+/// walks it and can evict pages again, so multi-gigabyte batches are scanned without
+/// being copied into the process first.
+///
+/// ```no_run
+/// use x12_delimiters::file::MappedDocument;
+///
+/// let document = MappedDocument::open("/archive/batch-0001.x12").unwrap();
+/// let claims = document.segments().filter(|s| s.id() == b"CLM").count();
+/// println!("{} claims in {} bytes", claims, document.len());
+/// ```
+#[cfg(feature = "memmap2")]
+#[derive(Debug)]
+pub struct MappedDocument {
+    map: memmap2::Mmap,
+    delimiters: Delimiters,
+}
+
+#[cfg(feature = "memmap2")]
+impl MappedDocument {
+    /// Maps the file at `path` and extracts delimiters from its ISA segment.
+    ///
+    /// The file must not be truncated or modified while the document is alive; another
+    /// process doing so can change the bytes returned or, if the file shrinks, terminate
+    /// this process with `SIGBUS`. Use [`FileDocument::open`] for files that may change.
+    ///
+    /// # Errors
+    /// Returns `ReadError::Io` if the file cannot be opened or mapped, or
+    /// `ReadError::Delimiters` if it does not start with a valid ISA segment.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReadError> {
+        let file = fs::File::open(path)?;
+        // SAFETY: the mapping is read-only and only exposed as `&[u8]`; the caller is told
+        // not to modify the file while it is mapped.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        let delimiters = Delimiters::from_isa(&map)?;
+        Ok(MappedDocument { map, delimiters })
+    }
+
+    /// Returns the delimiters read from the ISA segment.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the mapped file contents.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Returns the file size in bytes.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the file is empty. Never true for a successfully opened file.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the segments of the file, borrowed from the mapping.
+    pub fn segments(&self) -> SegmentIter<'_> {
+        self.delimiters.segments(&self.map)
+    }
+}
+
+#[cfg(feature = "memmap2")]
+impl AsRef<[u8]> for MappedDocument {
+    fn as_ref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::DelimiterError;
+    use crate::TerminatorSuffix;
+    use std::io::Write;
+
+    const DOC: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\n\
+GS*HC*S*R*20250403*0856*1*X*005010X222A1~\n\
+GE*0*1~\n\
+IEA*1*000000001~\n";
+
+    #[test]
+    fn test_open_reads_file_and_delimiters() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(DOC).unwrap();
+        let document = FileDocument::open(file.path()).unwrap();
+        assert_eq!(document.as_bytes(), DOC);
+        assert_eq!(document.len(), DOC.len());
+        assert_eq!(
+            document.delimiters(),
+            Delimiters::default()
+                .with_repetition_separator(Some(b'^'))
+                .with_suffix(TerminatorSuffix::Lf)
+        );
+        let ids: Vec<_> = document.segments().map(|s| s.id()).collect();
+        assert_eq!(ids, [&b"ISA"[..], b"GS", b"GE", b"IEA"]);
+        assert_eq!(
+            document.index(),
+            DocumentIndex::build(DOC, &document.delimiters())
+        );
+        assert_eq!(document.into_bytes(), DOC);
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn test_mapped_document_matches_file_document() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(DOC).unwrap();
+        let mapped = MappedDocument::open(file.path()).unwrap();
+        let read = FileDocument::open(file.path()).unwrap();
+        assert_eq!(mapped.as_bytes(), read.as_bytes());
+        assert_eq!(mapped.delimiters(), read.delimiters());
+        assert!(mapped.segments().eq(read.segments()));
+
+        let empty = tempfile::NamedTempFile::new().unwrap();
+        assert!(matches!(
+            MappedDocument::open(empty.path()),
+            Err(ReadError::Delimiters(DelimiterError::InvalidIsaLength))
+        ));
+        assert!(matches!(
+            MappedDocument::open(file.path().with_extension("missing")),
+            Err(ReadError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            FileDocument::open(dir.path().join("missing.x12")),
+            Err(ReadError::Io(_))
+        ));
+        assert!(matches!(
+            FileDocument::from_bytes(b"GS*HC~".to_vec()),
+            Err(ReadError::Delimiters(DelimiterError::InvalidIsaLength))
+        ));
+    }
+}
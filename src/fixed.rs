@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use crate::errors::FixedWidthError;
+use crate::isa::ELEMENT_WIDTHS;
+use crate::Delimiters;
+
+const ISA16: usize = 16;
+const ISA11: usize = 11;
+
+/// Element widths per segment ID for [`convert_fixed_width`].
+///
+/// The ISA layout is built in; add every other segment the sender emits.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldWidths {
+    segments: BTreeMap<Vec<u8>, Vec<usize>>,
+}
+
+impl FieldWidths {
+    /// Creates a table that knows only the ISA.
+    pub fn new() -> Self {
+        let mut segments = BTreeMap::new();
+        segments.insert(b"ISA".to_vec(), ELEMENT_WIDTHS.to_vec());
+        FieldWidths { segments }
+    }
+
+    /// Sets the widths of the elements of `id`, starting with element 1.
+    pub fn with_segment(mut self, id: &str, widths: &[usize]) -> Self {
+        self.segments
+            .insert(id.as_bytes().to_vec(), widths.to_vec());
+        self
+    }
+
+    /// Returns the element widths of `id`, if known.
+    pub fn widths(&self, id: &[u8]) -> Option<&[usize]> {
+        self.segments.get(id).map(Vec::as_slice)
+    }
+
+    /// Returns the segment ID at the start of `line`, preferring the longest known ID.
+    fn id_of<'a>(&self, line: &'a [u8]) -> Option<&'a [u8]> {
+        (2..=3)
+            .rev()
+            .filter_map(|len| line.get(..len))
+            .find(|id| self.segments.contains_key(*id))
+    }
+}
+
+impl Default for FieldWidths {
+    fn default() -> Self {
+        FieldWidths::new()
+    }
+}
+
+/// Converts the fixed-width hybrid layout (see
+/// [`crate::detect::FormatVariant::FixedWidth`]) to delimited X12.
+///
+/// Each non-empty line is one segment: its ID, found in `widths`, followed by elements
+/// of the listed widths. A line may stop early; the missing elements are omitted.
+/// Trailing spaces are trimmed from every element except in the ISA, which keeps its
+/// fixed widths. ISA16 is set to the target sub-element separator and, if `target` has a
+/// repetition separator, ISA11 to it. Segments are written with the target terminator and
+/// suffix.
+///
+/// ```
+/// use x12_delimiters::detect::{detect_variant, FormatVariant};
+/// use x12_delimiters::fixed::{convert_fixed_width, FieldWidths};
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ISA00          00          ZZSENDERID       ZZRECEIVERID     2504030856^005010000000010P:\n\
+/// NM1IL1DOE       JOHN    \n";
+/// assert_eq!(detect_variant(data), FormatVariant::FixedWidth);
+///
+/// let widths = FieldWidths::new().with_segment("NM1", &[2, 1, 10, 8]);
+/// let converted = convert_fixed_width(data, &widths, &Delimiters::default()).unwrap();
+/// assert!(converted.ends_with(b"*0*P*:~NM1*IL*1*DOE*JOHN~"));
+/// assert_eq!(Delimiters::from_isa(&converted).unwrap().element_separator(), b'*');
+/// ```
+///
+/// # Errors
+/// Returns `FixedWidthError::UnknownSegment` for a line whose ID is not in `widths`,
+/// `FixedWidthError::LineTooLong` for a line with bytes past its last element, and
+/// `FixedWidthError::DelimiterConflict` if an element contains a target delimiter. Lines
+/// are numbered from 1.
+pub fn convert_fixed_width(
+    data: &[u8],
+    widths: &FieldWidths,
+    target: &Delimiters,
+) -> Result<Vec<u8>, FixedWidthError> {
+    let is_delimiter = |b: u8| {
+        b == target.segment_terminator()
+            || b == target.element_separator()
+            || b == target.sub_element_separator()
+    };
+    let mut out = Vec::with_capacity(data.len() + data.len() / 8);
+    for (index, line) in data.split(|&b| b == b'\n').enumerate() {
+        let line_number = index + 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let id = widths
+            .id_of(line)
+            .ok_or(FixedWidthError::UnknownSegment { line: line_number })?;
+        let is_isa = id == b"ISA";
+
+        let mut elements: Vec<&[u8]> = Vec::new();
+        let mut position = id.len();
+        for &width in widths.widths(id).unwrap_or_default() {
+            if position >= line.len() {
+                break;
+            }
+            let end = (position + width).min(line.len());
+            elements.push(&line[position..end]);
+            position = end;
+        }
+        if position < line.len() {
+            return Err(FixedWidthError::LineTooLong { line: line_number });
+        }
+        if !is_isa {
+            for element in &mut elements {
+                let len = element
+                    .iter()
+                    .rposition(|&b| b != b' ')
+                    .map_or(0, |i| i + 1);
+                *element = &element[..len];
+            }
+            while elements.last().is_some_and(|e| e.is_empty()) {
+                elements.pop();
+            }
+        }
+
+        out.extend_from_slice(id);
+        for (i, element) in elements.iter().enumerate() {
+            out.push(target.element_separator());
+            let n = i + 1;
+            let replacement = match (is_isa, n) {
+                (true, ISA16) => Some(target.sub_element_separator()),
+                (true, ISA11) => target.repetition_separator(),
+                _ => None,
+            };
+            if let Some(byte) = replacement {
+                out.push(byte);
+                continue;
+            }
+            if let Some(&byte) = element.iter().find(|&&b| is_delimiter(b)) {
+                return Err(FixedWidthError::DelimiterConflict {
+                    line: line_number,
+                    byte,
+                });
+            }
+            out.extend_from_slice(element);
+        }
+        out.push(target.segment_terminator());
+        out.extend_from_slice(target.suffix().as_bytes());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminatorSuffix;
+
+    const FIXED_ISA: &str =
+        "ISA00          00          ZZSENDERID       ZZRECEIVERID     2504030856^005010000000010P:";
+
+    fn widths() -> FieldWidths {
+        FieldWidths::new()
+            .with_segment("GS", &[2, 15, 15, 8, 4, 9, 1, 12])
+            .with_segment("ST", &[3, 9])
+            .with_segment("SE", &[6, 9])
+    }
+
+    #[test]
+    fn test_convert_fixed_width() {
+        let data = format!(
+            "{}\r\nGSHCSENDER         RECEIVER       202504030856000000001X005010X222A1\r\nST837000000001\r\n\r\nSE2     000000001\r\n",
+            FIXED_ISA
+        );
+        let target = Delimiters::new(b'}', b'|', b'>')
+            .with_repetition_separator(Some(b'^'))
+            .with_suffix(TerminatorSuffix::Lf);
+        let converted = convert_fixed_width(data.as_bytes(), &widths(), &target).unwrap();
+        assert_eq!(
+            String::from_utf8(converted.clone()).unwrap(),
+            "ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}\n\
+             GS|HC|SENDER|RECEIVER|20250403|0856|000000001|X|005010X222A1}\n\
+             ST|837|000000001}\n\
+             SE|2|000000001}\n"
+        );
+        assert_eq!(Delimiters::from_isa(&converted).unwrap(), target);
+    }
+
+    #[test]
+    fn test_convert_fixed_width_errors() {
+        let target = Delimiters::default();
+        let convert = |body: &str| {
+            let data = format!("{}\n{}\n", FIXED_ISA, body);
+            convert_fixed_width(data.as_bytes(), &widths(), &target)
+        };
+        assert_eq!(
+            convert("NM1IL1DOE"),
+            Err(FixedWidthError::UnknownSegment { line: 2 })
+        );
+        assert_eq!(
+            convert("ST837000000001EXTRA"),
+            Err(FixedWidthError::LineTooLong { line: 2 })
+        );
+        assert_eq!(
+            convert("ST83*"),
+            Err(FixedWidthError::DelimiterConflict {
+                line: 2,
+                byte: b'*'
+            })
+        );
+        assert_eq!(convert("ST837").unwrap().len(), 106 + b"ST*837~".len());
+    }
+}
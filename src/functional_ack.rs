@@ -0,0 +1,1054 @@
+use crate::ack::AckCode;
+use crate::builder::raw_segment;
+use crate::conflict::ConflictAction;
+use crate::errors::BuildError;
+use crate::Delimiters;
+
+/// ST03 of a 999, the implementation guide the acknowledgment follows.
+pub(crate) const IMPLEMENTATION_ACK_VERSION: &str = "005010X231A1";
+/// First GS08 version, `005010`, acknowledged with a 999 by default.
+const FIRST_IMPLEMENTATION_ACK_VERSION: u32 = 5010;
+
+/// Which acknowledgment transaction set to write for a functional group.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum AckFormat {
+    /// The 997 functional acknowledgment, with AK3/AK4/AK5, for partners on 004010 and
+    /// earlier.
+    Functional997,
+    /// The 999 implementation acknowledgment, with IK3/IK4/IK5, used from 005010.
+    #[default]
+    Implementation999,
+}
+
+impl AckFormat {
+    /// Picks the format for a group with GS08 `version`, e.g. `004010X098A1`.
+    ///
+    /// Versions before 005010 get a 997 and all others a 999, including versions that do
+    /// not start with six digits.
+    ///
+    /// ```
+    /// use x12_delimiters::functional_ack::AckFormat;
+    ///
+    /// assert_eq!(AckFormat::for_version("004010X098A1"), AckFormat::Functional997);
+    /// assert_eq!(AckFormat::for_version("005010X222A1"), AckFormat::Implementation999);
+    /// ```
+    pub fn for_version(version: &str) -> Self {
+        match version.get(..6).and_then(|v| v.parse::<u32>().ok()) {
+            Some(v) if v < FIRST_IMPLEMENTATION_ACK_VERSION => AckFormat::Functional997,
+            _ => AckFormat::Implementation999,
+        }
+    }
+
+    /// Returns ST01 of the acknowledgment, `997` or `999`.
+    pub fn transaction_set_id(&self) -> &'static str {
+        match self {
+            AckFormat::Functional997 => "997",
+            AckFormat::Implementation999 => "999",
+        }
+    }
+}
+
+/// IK304, why a segment is in error.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum SegmentErrorCode {
+    /// `1`: the segment ID is not recognized.
+    UnrecognizedSegment,
+    /// `2`: the segment is not expected here.
+    UnexpectedSegment,
+    /// `3`: a required segment is missing.
+    RequiredSegmentMissing,
+    /// `4`: a loop occurs more often than allowed.
+    LoopOverMaximum,
+    /// `5`: the segment occurs more often than allowed.
+    SegmentOverMaximum,
+    /// `6`: the segment is not defined in the transaction set.
+    NotInTransactionSet,
+    /// `7`: the segment is out of sequence.
+    OutOfSequence,
+    /// `8`: the segment has data element errors, reported in IK4.
+    ElementErrors,
+    /// `I4`: a segment the implementation marks "not used" is present.
+    ImplementationNotUsed,
+    /// `I6`: a segment the implementation requires in this situation is missing.
+    ImplementationDependentMissing,
+    /// `I7`: a loop occurs less often than the implementation requires.
+    ImplementationLoopUnderMinimum,
+    /// `I8`: the segment occurs less often than the implementation requires.
+    ImplementationSegmentUnderMinimum,
+    /// `I9`: a segment the implementation excludes in this situation is present.
+    ImplementationDependentNotUsed,
+}
+
+impl SegmentErrorCode {
+    /// All codes, in code order.
+    pub const ALL: [SegmentErrorCode; 13] = [
+        SegmentErrorCode::UnrecognizedSegment,
+        SegmentErrorCode::UnexpectedSegment,
+        SegmentErrorCode::RequiredSegmentMissing,
+        SegmentErrorCode::LoopOverMaximum,
+        SegmentErrorCode::SegmentOverMaximum,
+        SegmentErrorCode::NotInTransactionSet,
+        SegmentErrorCode::OutOfSequence,
+        SegmentErrorCode::ElementErrors,
+        SegmentErrorCode::ImplementationNotUsed,
+        SegmentErrorCode::ImplementationDependentMissing,
+        SegmentErrorCode::ImplementationLoopUnderMinimum,
+        SegmentErrorCode::ImplementationSegmentUnderMinimum,
+        SegmentErrorCode::ImplementationDependentNotUsed,
+    ];
+
+    /// Returns the code as written in IK304.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SegmentErrorCode::UnrecognizedSegment => "1",
+            SegmentErrorCode::UnexpectedSegment => "2",
+            SegmentErrorCode::RequiredSegmentMissing => "3",
+            SegmentErrorCode::LoopOverMaximum => "4",
+            SegmentErrorCode::SegmentOverMaximum => "5",
+            SegmentErrorCode::NotInTransactionSet => "6",
+            SegmentErrorCode::OutOfSequence => "7",
+            SegmentErrorCode::ElementErrors => "8",
+            SegmentErrorCode::ImplementationNotUsed => "I4",
+            SegmentErrorCode::ImplementationDependentMissing => "I6",
+            SegmentErrorCode::ImplementationLoopUnderMinimum => "I7",
+            SegmentErrorCode::ImplementationSegmentUnderMinimum => "I8",
+            SegmentErrorCode::ImplementationDependentNotUsed => "I9",
+        }
+    }
+
+    /// Reads the code as written in IK304 or AK304, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    /// Returns the code as written in AK304 of a 997, which has no implementation codes:
+    /// each is replaced by the closest syntax code.
+    pub fn as_997_str(&self) -> &'static str {
+        match self {
+            SegmentErrorCode::ImplementationNotUsed
+            | SegmentErrorCode::ImplementationDependentNotUsed => "2",
+            SegmentErrorCode::ImplementationDependentMissing
+            | SegmentErrorCode::ImplementationLoopUnderMinimum
+            | SegmentErrorCode::ImplementationSegmentUnderMinimum => "3",
+            code => code.as_str(),
+        }
+    }
+}
+
+/// IK403, why a data element is in error.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ElementErrorCode {
+    /// `1`: a required element is missing.
+    RequiredMissing,
+    /// `2`: a conditionally required element is missing.
+    ConditionalRequiredMissing,
+    /// `3`: the segment has too many elements.
+    TooManyElements,
+    /// `4`: the value is too short.
+    TooShort,
+    /// `5`: the value is too long.
+    TooLong,
+    /// `6`: the value has an invalid character.
+    InvalidCharacter,
+    /// `7`: the value is not a valid code.
+    InvalidCodeValue,
+    /// `8`: the value is not a valid date.
+    InvalidDate,
+    /// `9`: the value is not a valid time.
+    InvalidTime,
+    /// `10`: the element violates an exclusion condition.
+    ExclusionViolated,
+    /// `12`: the element repeats too often.
+    TooManyRepetitions,
+    /// `13`: the composite has too many components.
+    TooManyComponents,
+    /// `I6`: the code value is not used by the implementation.
+    ImplementationCodeNotUsed,
+    /// `I9`: an element the implementation requires in this situation is missing.
+    ImplementationDependentMissing,
+    /// `I10`: an element the implementation marks "not used" is present.
+    ImplementationNotUsed,
+    /// `I11`: the element repeats less often than the implementation requires.
+    ImplementationTooFewRepetitions,
+    /// `I12`: the value does not match the pattern the implementation requires.
+    ImplementationPatternMismatch,
+    /// `I13`: an element the implementation excludes in this situation is present.
+    ImplementationDependentNotUsed,
+}
+
+impl ElementErrorCode {
+    /// All codes, in code order.
+    pub const ALL: [ElementErrorCode; 18] = [
+        ElementErrorCode::RequiredMissing,
+        ElementErrorCode::ConditionalRequiredMissing,
+        ElementErrorCode::TooManyElements,
+        ElementErrorCode::TooShort,
+        ElementErrorCode::TooLong,
+        ElementErrorCode::InvalidCharacter,
+        ElementErrorCode::InvalidCodeValue,
+        ElementErrorCode::InvalidDate,
+        ElementErrorCode::InvalidTime,
+        ElementErrorCode::ExclusionViolated,
+        ElementErrorCode::TooManyRepetitions,
+        ElementErrorCode::TooManyComponents,
+        ElementErrorCode::ImplementationCodeNotUsed,
+        ElementErrorCode::ImplementationDependentMissing,
+        ElementErrorCode::ImplementationNotUsed,
+        ElementErrorCode::ImplementationTooFewRepetitions,
+        ElementErrorCode::ImplementationPatternMismatch,
+        ElementErrorCode::ImplementationDependentNotUsed,
+    ];
+
+    /// Returns the code as written in IK403.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ElementErrorCode::RequiredMissing => "1",
+            ElementErrorCode::ConditionalRequiredMissing => "2",
+            ElementErrorCode::TooManyElements => "3",
+            ElementErrorCode::TooShort => "4",
+            ElementErrorCode::TooLong => "5",
+            ElementErrorCode::InvalidCharacter => "6",
+            ElementErrorCode::InvalidCodeValue => "7",
+            ElementErrorCode::InvalidDate => "8",
+            ElementErrorCode::InvalidTime => "9",
+            ElementErrorCode::ExclusionViolated => "10",
+            ElementErrorCode::TooManyRepetitions => "12",
+            ElementErrorCode::TooManyComponents => "13",
+            ElementErrorCode::ImplementationCodeNotUsed => "I6",
+            ElementErrorCode::ImplementationDependentMissing => "I9",
+            ElementErrorCode::ImplementationNotUsed => "I10",
+            ElementErrorCode::ImplementationTooFewRepetitions => "I11",
+            ElementErrorCode::ImplementationPatternMismatch => "I12",
+            ElementErrorCode::ImplementationDependentNotUsed => "I13",
+        }
+    }
+
+    /// Reads the code as written in IK403 or AK403, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    /// Returns the code as written in AK403 of a 997, which has no implementation codes:
+    /// each is replaced by the closest syntax code.
+    pub fn as_997_str(&self) -> &'static str {
+        match self {
+            ElementErrorCode::ImplementationCodeNotUsed => "7",
+            ElementErrorCode::ImplementationDependentMissing
+            | ElementErrorCode::ImplementationTooFewRepetitions => "2",
+            ElementErrorCode::ImplementationNotUsed
+            | ElementErrorCode::ImplementationDependentNotUsed => "10",
+            ElementErrorCode::ImplementationPatternMismatch => "6",
+            code => code.as_str(),
+        }
+    }
+}
+
+/// IK502 through IK506, why a transaction set is in error.
+///
+/// The security-related codes are not modeled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum TransactionErrorCode {
+    /// `1`: the transaction set is not supported.
+    NotSupported,
+    /// `2`: the SE trailer is missing.
+    TrailerMissing,
+    /// `3`: ST02 and SE02 do not match.
+    ControlNumberMismatch,
+    /// `4`: SE01 does not match the number of segments.
+    SegmentCountMismatch,
+    /// `5`: one or more segments are in error.
+    SegmentsInError,
+    /// `6`: ST01 is missing or invalid.
+    InvalidIdentifier,
+    /// `7`: ST02 is missing or invalid.
+    InvalidControlNumber,
+    /// `18`: the transaction set is not inside a functional group.
+    NotInGroup,
+    /// `19`: ST03 is invalid.
+    InvalidImplementationReference,
+    /// `23`: ST02 repeats within the functional group.
+    DuplicateControlNumber,
+    /// `I5`: one or more segments break the implementation guide.
+    ImplementationSegmentsInError,
+    /// `I6`: the implementation convention is not supported.
+    ImplementationNotSupported,
+}
+
+impl TransactionErrorCode {
+    /// All codes, in code order.
+    pub const ALL: [TransactionErrorCode; 12] = [
+        TransactionErrorCode::NotSupported,
+        TransactionErrorCode::TrailerMissing,
+        TransactionErrorCode::ControlNumberMismatch,
+        TransactionErrorCode::SegmentCountMismatch,
+        TransactionErrorCode::SegmentsInError,
+        TransactionErrorCode::InvalidIdentifier,
+        TransactionErrorCode::InvalidControlNumber,
+        TransactionErrorCode::NotInGroup,
+        TransactionErrorCode::InvalidImplementationReference,
+        TransactionErrorCode::DuplicateControlNumber,
+        TransactionErrorCode::ImplementationSegmentsInError,
+        TransactionErrorCode::ImplementationNotSupported,
+    ];
+
+    /// Returns the code as written in IK502 through IK506.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionErrorCode::NotSupported => "1",
+            TransactionErrorCode::TrailerMissing => "2",
+            TransactionErrorCode::ControlNumberMismatch => "3",
+            TransactionErrorCode::SegmentCountMismatch => "4",
+            TransactionErrorCode::SegmentsInError => "5",
+            TransactionErrorCode::InvalidIdentifier => "6",
+            TransactionErrorCode::InvalidControlNumber => "7",
+            TransactionErrorCode::NotInGroup => "18",
+            TransactionErrorCode::InvalidImplementationReference => "19",
+            TransactionErrorCode::DuplicateControlNumber => "23",
+            TransactionErrorCode::ImplementationSegmentsInError => "I5",
+            TransactionErrorCode::ImplementationNotSupported => "I6",
+        }
+    }
+
+    /// Reads the code as written in IK502 or AK502, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    /// Returns the code as written in AK502 through AK506 of a 997, which has no
+    /// implementation codes: each is replaced by the closest syntax code.
+    pub fn as_997_str(&self) -> &'static str {
+        match self {
+            TransactionErrorCode::ImplementationSegmentsInError => "5",
+            TransactionErrorCode::ImplementationNotSupported => "1",
+            code => code.as_str(),
+        }
+    }
+}
+
+/// AK905 through AK909, why a functional group is in error.
+///
+/// The security-related codes are not modeled.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum GroupErrorCode {
+    /// `1`: the functional group is not supported.
+    NotSupported,
+    /// `2`: the version in GS08 is not supported.
+    VersionNotSupported,
+    /// `3`: the GE trailer is missing.
+    TrailerMissing,
+    /// `4`: GS06 and GE02 do not match.
+    ControlNumberMismatch,
+    /// `5`: GE01 does not match the number of transaction sets.
+    TransactionCountMismatch,
+    /// `6`: GS06 is not a valid control number.
+    InvalidControlNumber,
+    /// `19`: GS06 repeats within the interchange.
+    DuplicateControlNumber,
+}
+
+impl GroupErrorCode {
+    /// All codes, in code order.
+    pub const ALL: [GroupErrorCode; 7] = [
+        GroupErrorCode::NotSupported,
+        GroupErrorCode::VersionNotSupported,
+        GroupErrorCode::TrailerMissing,
+        GroupErrorCode::ControlNumberMismatch,
+        GroupErrorCode::TransactionCountMismatch,
+        GroupErrorCode::InvalidControlNumber,
+        GroupErrorCode::DuplicateControlNumber,
+    ];
+
+    /// Returns the code as written in AK905 through AK909.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupErrorCode::NotSupported => "1",
+            GroupErrorCode::VersionNotSupported => "2",
+            GroupErrorCode::TrailerMissing => "3",
+            GroupErrorCode::ControlNumberMismatch => "4",
+            GroupErrorCode::TransactionCountMismatch => "5",
+            GroupErrorCode::InvalidControlNumber => "6",
+            GroupErrorCode::DuplicateControlNumber => "19",
+        }
+    }
+
+    /// Reads the code as written in AK905, e.g. from a received acknowledgment.
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+}
+
+/// A data element error inside a [`SegmentFinding`], written as an IK4 segment.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ElementFinding {
+    position: usize,
+    component: Option<usize>,
+    repetition: Option<usize>,
+    reference: Option<String>,
+    code: ElementErrorCode,
+    value: Option<String>,
+}
+
+impl ElementFinding {
+    /// Creates a finding for the element at `position` in its segment, starting at 1.
+    pub fn new(position: usize, code: ElementErrorCode) -> Self {
+        ElementFinding {
+            position,
+            component: None,
+            repetition: None,
+            reference: None,
+            code,
+            value: None,
+        }
+    }
+
+    /// Sets the component position within a composite element, starting at 1.
+    pub fn with_component(mut self, component: usize) -> Self {
+        self.component = Some(component);
+        self
+    }
+
+    /// Sets the occurrence of a repeating element, starting at 1.
+    pub fn with_repetition(mut self, repetition: usize) -> Self {
+        self.repetition = Some(repetition);
+        self
+    }
+
+    /// Sets the data element reference number, e.g. `66` for NM108.
+    pub fn with_reference(mut self, reference: &str) -> Self {
+        self.reference = Some(reference.to_string());
+        self
+    }
+
+    /// Sets a copy of the value in error.
+    pub fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Returns the element position in its segment.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the component position, if the error is in a composite.
+    pub fn component(&self) -> Option<usize> {
+        self.component
+    }
+
+    /// Returns the occurrence, if the element repeats.
+    pub fn repetition(&self) -> Option<usize> {
+        self.repetition
+    }
+
+    /// Returns the data element reference number.
+    pub fn reference(&self) -> Option<&str> {
+        self.reference.as_deref()
+    }
+
+    /// Returns the error code.
+    pub fn code(&self) -> ElementErrorCode {
+        self.code
+    }
+
+    /// Returns the copy of the value in error.
+    pub fn value(&self) -> Option<&str> {
+        self.value.as_deref()
+    }
+}
+
+/// A segment error inside a [`TransactionFindings`], written as an IK3 segment followed
+/// by an IK4 per element error.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SegmentFinding {
+    segment_id: String,
+    position: usize,
+    loop_id: Option<String>,
+    code: Option<SegmentErrorCode>,
+    elements: Vec<ElementFinding>,
+}
+
+impl SegmentFinding {
+    /// Creates a finding for the segment with ID `segment_id` at `position` in its
+    /// transaction set, counting ST as 1.
+    pub fn new(segment_id: &str, position: usize) -> Self {
+        SegmentFinding {
+            segment_id: segment_id.to_string(),
+            position,
+            loop_id: None,
+            code: None,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Sets the ID of the loop the segment belongs to, e.g. `2010BA`.
+    pub fn with_loop_id(mut self, loop_id: &str) -> Self {
+        self.loop_id = Some(loop_id.to_string());
+        self
+    }
+
+    /// Sets the segment error code. Without one, IK304 is written as
+    /// [`SegmentErrorCode::ElementErrors`] if there are element errors and omitted
+    /// otherwise.
+    pub fn with_code(mut self, code: SegmentErrorCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Adds an element error.
+    pub fn with_element(mut self, element: ElementFinding) -> Self {
+        self.elements.push(element);
+        self
+    }
+
+    /// Returns the segment ID.
+    pub fn segment_id(&self) -> &str {
+        &self.segment_id
+    }
+
+    /// Returns the segment position in its transaction set.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the loop ID.
+    pub fn loop_id(&self) -> Option<&str> {
+        self.loop_id.as_deref()
+    }
+
+    /// Returns the segment error code as written in IK304.
+    pub fn code(&self) -> Option<SegmentErrorCode> {
+        self.code
+            .or_else(|| (!self.elements.is_empty()).then_some(SegmentErrorCode::ElementErrors))
+    }
+
+    /// Returns the element errors.
+    pub fn elements(&self) -> &[ElementFinding] {
+        &self.elements
+    }
+}
+
+/// Validation results for one received transaction set, acknowledged with AK2/IK5.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TransactionFindings {
+    transaction_set_id: String,
+    control_number: String,
+    implementation_reference: Option<String>,
+    code: Option<AckCode>,
+    segments: Vec<SegmentFinding>,
+    errors: Vec<TransactionErrorCode>,
+}
+
+impl TransactionFindings {
+    /// Creates results for the transaction set with ST01 `transaction_set_id` and ST02
+    /// `control_number`, as written.
+    pub fn new(transaction_set_id: &str, control_number: &str) -> Self {
+        TransactionFindings {
+            transaction_set_id: transaction_set_id.to_string(),
+            control_number: control_number.to_string(),
+            implementation_reference: None,
+            code: None,
+            segments: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Sets ST03, the implementation convention reference.
+    pub fn with_implementation_reference(mut self, reference: &str) -> Self {
+        self.implementation_reference = Some(reference.to_string());
+        self
+    }
+
+    /// Sets the acknowledgment code, e.g. to accept a transaction set with errors. Without
+    /// one, it is rejected if it has any segment or transaction errors.
+    pub fn with_code(mut self, code: AckCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Adds a segment error.
+    pub fn with_segment(mut self, segment: SegmentFinding) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// Adds a transaction set error. At most five are written.
+    pub fn with_error(mut self, error: TransactionErrorCode) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Returns ST01.
+    pub fn transaction_set_id(&self) -> &str {
+        &self.transaction_set_id
+    }
+
+    /// Returns ST02.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+
+    /// Returns ST03.
+    pub fn implementation_reference(&self) -> Option<&str> {
+        self.implementation_reference.as_deref()
+    }
+
+    /// Returns the acknowledgment code as written in IK501.
+    pub fn code(&self) -> AckCode {
+        self.code
+            .unwrap_or(if self.segments.is_empty() && self.errors.is_empty() {
+                AckCode::Accepted
+            } else {
+                AckCode::Rejected
+            })
+    }
+
+    /// Returns the segment errors.
+    pub fn segments(&self) -> &[SegmentFinding] {
+        &self.segments
+    }
+
+    /// Returns the transaction set errors.
+    pub fn errors(&self) -> &[TransactionErrorCode] {
+        &self.errors
+    }
+}
+
+/// Validation results for one received functional group, the input to
+/// [`implementation_ack`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GroupFindings {
+    functional_id: String,
+    control_number: String,
+    version: String,
+    included: Option<usize>,
+    code: Option<AckCode>,
+    transactions: Vec<TransactionFindings>,
+    errors: Vec<GroupErrorCode>,
+}
+
+impl GroupFindings {
+    /// Creates results for the group with GS01 `functional_id`, GS06 `control_number`,
+    /// and GS08 `version`, as written.
+    pub fn new(functional_id: &str, control_number: &str, version: &str) -> Self {
+        GroupFindings {
+            functional_id: functional_id.to_string(),
+            control_number: control_number.to_string(),
+            version: version.to_string(),
+            included: None,
+            code: None,
+            transactions: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Sets the number of transaction sets GE01 declares. Defaults to the number added.
+    pub fn with_included(mut self, included: usize) -> Self {
+        self.included = Some(included);
+        self
+    }
+
+    /// Sets the acknowledgment code for the group's own envelope. Without one, it is
+    /// rejected if there are group errors.
+    pub fn with_code(mut self, code: AckCode) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Adds the results of a transaction set.
+    pub fn with_transaction(mut self, transaction: TransactionFindings) -> Self {
+        self.transactions.push(transaction);
+        self
+    }
+
+    /// Adds a group error. At most five are written.
+    pub fn with_error(mut self, error: GroupErrorCode) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Returns GS01.
+    pub fn functional_id(&self) -> &str {
+        &self.functional_id
+    }
+
+    /// Returns GS06.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+
+    /// Returns GS08.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the transaction set results.
+    pub fn transactions(&self) -> &[TransactionFindings] {
+        &self.transactions
+    }
+
+    /// Returns the group errors.
+    pub fn errors(&self) -> &[GroupErrorCode] {
+        &self.errors
+    }
+
+    /// Returns the number of transaction sets accepted, with or without errors.
+    pub fn accepted(&self) -> usize {
+        self.transactions
+            .iter()
+            .filter(|t| t.code() != AckCode::Rejected)
+            .count()
+    }
+
+    /// Returns the acknowledgment code as written in AK901.
+    ///
+    /// A group whose own envelope is rejected, or none of whose transaction sets is
+    /// accepted, is rejected; one with only some accepted is partially accepted. A group
+    /// with every transaction set accepted, but some with errors, is accepted with
+    /// errors.
+    pub fn code(&self) -> AckCode {
+        let own = self.code.unwrap_or(if self.errors.is_empty() {
+            AckCode::Accepted
+        } else {
+            AckCode::Rejected
+        });
+        let received = self.transactions.len();
+        let accepted = self.accepted();
+        match own {
+            AckCode::Rejected => AckCode::Rejected,
+            _ if received > 0 && accepted == 0 => AckCode::Rejected,
+            _ if accepted < received => AckCode::PartiallyAccepted,
+            _ if self
+                .transactions
+                .iter()
+                .any(|t| t.code() == AckCode::AcceptedWithErrors) =>
+            {
+                AckCode::AcceptedWithErrors
+            }
+            code => code,
+        }
+    }
+}
+
+/// Serializes a 999 implementation acknowledgment for `group`, from ST through SE.
+///
+/// ST02 and SE02 are `control_number`, zero-padded to four digits. Each transaction set
+/// gets an AK2, an IK3 and IK4s for its segment errors, and an IK5; the AK9 closes the
+/// group with its code and counts. Every segment is followed by the terminator and its
+/// suffix. The result is meant to be placed in an `FA` functional group.
+///
+/// ```
+/// use x12_delimiters::functional_ack::*;
+/// use x12_delimiters::Delimiters;
+///
+/// let group = GroupFindings::new("HC", "17", "005010X222A1")
+///     .with_transaction(TransactionFindings::new("837", "0001"))
+///     .with_transaction(
+///         TransactionFindings::new("837", "0002").with_segment(
+///             SegmentFinding::new("NM1", 8).with_loop_id("2010BA").with_element(
+///                 ElementFinding::new(9, ElementErrorCode::TooShort)
+///                     .with_reference("67")
+///                     .with_value("1"),
+///             ),
+///         ),
+///     );
+/// let ack = implementation_ack(&group, 1, &Delimiters::default()).unwrap();
+/// assert_eq!(
+///     String::from_utf8(ack).unwrap(),
+///     "ST*999*0001*005010X231A1~AK1*HC*17*005010X222A1~AK2*837*0001~IK5*A~\
+/// AK2*837*0002~IK3*NM1*8*2010BA*8~IK4*9*67*4*1~IK5*R~AK9*P*2*2*1~SE*10*0001~"
+/// );
+/// ```
+///
+/// # Errors
+/// Returns `BuildError::DelimiterConflict` if a value, such as a copied bad element
+/// value, contains one of the delimiters.
+pub fn implementation_ack(
+    group: &GroupFindings,
+    control_number: u32,
+    delimiters: &Delimiters,
+) -> Result<Vec<u8>, BuildError> {
+    acknowledgment(
+        group,
+        AckFormat::Implementation999,
+        control_number,
+        delimiters,
+    )
+}
+
+/// Serializes a 997 functional acknowledgment for `group`, from ST through SE.
+///
+/// The 997 mirrors [`implementation_ack`] with the 004010 layout: AK3, AK4 and AK5 take
+/// the place of IK3, IK4 and IK5, and AK1 and AK2 carry no version or implementation
+/// reference. Implementation-specific error codes, which the 997 lacks, are written as
+/// the closest syntax codes.
+///
+/// ```
+/// use x12_delimiters::functional_ack::*;
+/// use x12_delimiters::Delimiters;
+///
+/// let group = GroupFindings::new("PO", "3", "004010").with_transaction(
+///     TransactionFindings::new("850", "0001")
+///         .with_segment(SegmentFinding::new("BEG", 2).with_code(SegmentErrorCode::ImplementationDependentMissing))
+///         .with_error(TransactionErrorCode::SegmentsInError),
+/// );
+/// let ack = functional_ack(&group, 12, &Delimiters::default()).unwrap();
+/// assert_eq!(
+///     String::from_utf8(ack).unwrap(),
+///     "ST*997*0012~AK1*PO*3~AK2*850*0001~AK3*BEG*2**3~AK5*R*5~AK9*R*1*1*0~SE*7*0012~"
+/// );
+/// ```
+///
+/// # Errors
+/// Same as [`implementation_ack`].
+pub fn functional_ack(
+    group: &GroupFindings,
+    control_number: u32,
+    delimiters: &Delimiters,
+) -> Result<Vec<u8>, BuildError> {
+    acknowledgment(group, AckFormat::Functional997, control_number, delimiters)
+}
+
+/// Serializes the acknowledgment for `group` in `format`; see [`implementation_ack`] and
+/// [`functional_ack`]. Use [`AckFormat::for_version`] to pick the format from the
+/// group's version.
+///
+/// # Errors
+/// Same as [`implementation_ack`].
+pub fn acknowledgment(
+    group: &GroupFindings,
+    format: AckFormat,
+    control_number: u32,
+    delimiters: &Delimiters,
+) -> Result<Vec<u8>, BuildError> {
+    let control = format!("{:04}", control_number);
+    let mut segments = ack_segments(group, format, &control, delimiters)?;
+    let count = (segments.len() + 1).to_string();
+    segments.push(raw_segment(
+        "SE",
+        [[count.as_str()], [control.as_str()]],
+        delimiters,
+        &ConflictAction::Error,
+    )?);
+    let mut out = Vec::new();
+    for segment in segments {
+        out.extend_from_slice(&segment);
+        out.push(delimiters.segment_terminator());
+        out.extend_from_slice(delimiters.suffix().as_bytes());
+    }
+    Ok(out)
+}
+
+/// Returns the raw segments of a 997 or 999 for `group`, from ST through AK9, without
+/// terminators.
+pub(crate) fn ack_segments(
+    group: &GroupFindings,
+    format: AckFormat,
+    control: &str,
+    delimiters: &Delimiters,
+) -> Result<Vec<Vec<u8>>, BuildError> {
+    let segment = |id: &str, elements: &[Vec<&str>]| {
+        // Trailing empty elements are omitted.
+        let end = elements
+            .iter()
+            .rposition(|e| e.iter().any(|c| !c.is_empty()))
+            .map_or(0, |i| i + 1);
+        raw_segment(id, &elements[..end], delimiters, &ConflictAction::Error)
+    };
+    let is_999 = format == AckFormat::Implementation999;
+    // Elements only the 999 has are left empty, and so omitted, in a 997.
+    let only_999 = |value: &'static str| if is_999 { value } else { "" };
+    let (segment_id, element_id, transaction_id) = if is_999 {
+        ("IK3", "IK4", "IK5")
+    } else {
+        ("AK3", "AK4", "AK5")
+    };
+
+    let mut segments = vec![
+        segment(
+            "ST",
+            &[
+                vec![format.transaction_set_id()],
+                vec![control],
+                vec![only_999(IMPLEMENTATION_ACK_VERSION)],
+            ],
+        )?,
+        segment(
+            "AK1",
+            &[
+                vec![group.functional_id.as_str()],
+                vec![group.control_number.as_str()],
+                vec![if is_999 { group.version.as_str() } else { "" }],
+            ],
+        )?,
+    ];
+    for transaction in &group.transactions {
+        let reference = transaction.implementation_reference().filter(|_| is_999);
+        segments.push(segment(
+            "AK2",
+            &[
+                vec![transaction.transaction_set_id.as_str()],
+                vec![transaction.control_number.as_str()],
+                vec![reference.unwrap_or_default()],
+            ],
+        )?);
+        for finding in &transaction.segments {
+            let position = finding.position.to_string();
+            let code = finding
+                .code()
+                .map_or("", |c| if is_999 { c.as_str() } else { c.as_997_str() });
+            segments.push(segment(
+                segment_id,
+                &[
+                    vec![finding.segment_id.as_str()],
+                    vec![position.as_str()],
+                    vec![finding.loop_id().unwrap_or_default()],
+                    vec![code],
+                ],
+            )?);
+            for element in &finding.elements {
+                let numbers = [
+                    Some(element.position),
+                    element.component,
+                    element.repetition,
+                ]
+                .map(|n| n.map(|n| n.to_string()).unwrap_or_default());
+                let components = numbers.iter().map(String::as_str).collect::<Vec<_>>();
+                let end = components
+                    .iter()
+                    .rposition(|c| !c.is_empty())
+                    .map_or(1, |i| i + 1);
+                let code = if is_999 {
+                    element.code.as_str()
+                } else {
+                    element.code.as_997_str()
+                };
+                segments.push(segment(
+                    element_id,
+                    &[
+                        components[..end].to_vec(),
+                        vec![element.reference().unwrap_or_default()],
+                        vec![code],
+                        vec![element.value().unwrap_or_default()],
+                    ],
+                )?);
+            }
+        }
+        let mut ik5 = vec![vec![transaction.code().as_str()]];
+        ik5.extend(
+            transaction
+                .errors
+                .iter()
+                .take(5)
+                .map(|e| vec![if is_999 { e.as_str() } else { e.as_997_str() }]),
+        );
+        segments.push(segment(transaction_id, &ik5)?);
+    }
+
+    let received = group.transactions.len();
+    let counts = [
+        group.included.unwrap_or(received),
+        received,
+        group.accepted(),
+    ]
+    .map(|n| n.to_string());
+    let mut ak9 = vec![vec![group.code().as_str()]];
+    ak9.extend(counts.iter().map(|n| vec![n.as_str()]));
+    ak9.extend(group.errors.iter().take(5).map(|e| vec![e.as_str()]));
+    segments.push(segment("AK9", &ak9)?);
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminatorSuffix;
+
+    #[test]
+    fn test_rejected_group_with_errors() {
+        let group = GroupFindings::new("HC", "17", "005010X222A1")
+            .with_included(3)
+            .with_error(GroupErrorCode::TransactionCountMismatch)
+            .with_transaction(
+                TransactionFindings::new("837", "0001")
+                    .with_implementation_reference("005010X222A1")
+                    .with_error(TransactionErrorCode::SegmentCountMismatch),
+            );
+        assert_eq!(group.code(), AckCode::Rejected);
+        let delimiters = Delimiters::new(b'\'', b'+', b'>').with_suffix(TerminatorSuffix::Lf);
+        let ack = implementation_ack(&group, 42, &delimiters).unwrap();
+        assert_eq!(
+            String::from_utf8(ack).unwrap(),
+            "ST+999+0042+005010X231A1'\nAK1+HC+17+005010X222A1'\n\
+AK2+837+0001+005010X222A1'\nIK5+R+4'\nAK9+R+3+1+0+5'\nSE+6+0042'\n"
+        );
+    }
+
+    #[test]
+    fn test_element_positions_and_overrides() {
+        let element = ElementFinding::new(3, ElementErrorCode::InvalidCodeValue)
+            .with_repetition(2)
+            .with_value("ZZ");
+        let transaction = TransactionFindings::new("837", "0001")
+            .with_code(AckCode::AcceptedWithErrors)
+            .with_segment(
+                SegmentFinding::new("CLM", 20)
+                    .with_code(SegmentErrorCode::ImplementationNotUsed)
+                    .with_element(element),
+            );
+        let group = GroupFindings::new("HC", "1", "005010").with_transaction(transaction);
+        assert_eq!(group.code(), AckCode::AcceptedWithErrors);
+        let segments = ack_segments(
+            &group,
+            AckFormat::Implementation999,
+            "0001",
+            &Delimiters::default(),
+        )
+        .unwrap();
+        assert_eq!(segments[3], b"IK3*CLM*20**I4");
+        assert_eq!(segments[4], b"IK4*3::2**7*ZZ");
+        assert_eq!(segments[5], b"IK5*E");
+    }
+
+    #[test]
+    fn test_conflicting_value_is_rejected() {
+        let group = GroupFindings::new("HC", "1", "005010").with_transaction(
+            TransactionFindings::new("837", "0001").with_segment(
+                SegmentFinding::new("NM1", 5).with_element(
+                    ElementFinding::new(3, ElementErrorCode::InvalidCharacter).with_value("A~B"),
+                ),
+            ),
+        );
+        assert!(matches!(
+            implementation_ack(&group, 1, &Delimiters::default()),
+            Err(BuildError::DelimiterConflict { byte: b'~', .. })
+        ));
+    }
+
+    #[test]
+    fn test_997_maps_implementation_codes() {
+        let group = GroupFindings::new("IN", "9", "004010").with_transaction(
+            TransactionFindings::new("810", "0001")
+                .with_implementation_reference("004010X001")
+                .with_segment(
+                    SegmentFinding::new("N1", 4).with_element(
+                        ElementFinding::new(2, ElementErrorCode::ImplementationPatternMismatch)
+                            .with_component(1),
+                    ),
+                )
+                .with_error(TransactionErrorCode::ImplementationSegmentsInError),
+        );
+        let ack = acknowledgment(
+            &group,
+            AckFormat::for_version(group.version()),
+            5,
+            &Delimiters::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(ack).unwrap(),
+            "ST*997*0005~AK1*IN*9~AK2*810*0001~AK3*N1*4**8~AK4*2:1**6~AK5*R*5~AK9*R*1*1*0~SE*8*0005~"
+        );
+        assert_eq!(AckFormat::for_version("X12"), AckFormat::Implementation999);
+        assert_eq!(AckFormat::for_version("003050"), AckFormat::Functional997);
+    }
+}
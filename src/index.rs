@@ -0,0 +1,1132 @@
+use std::io::{self, Read, Write};
+
+#[cfg(feature = "allocator-api2")]
+use allocator_api2::alloc::{Allocator, Global};
+#[cfg(feature = "allocator-api2")]
+use allocator_api2::vec::Vec as AllocVec;
+
+#[cfg(not(feature = "allocator-api2"))]
+use self::heap::{AllocVec, Allocator, Global};
+
+use crate::errors::IndexError;
+use crate::segment::{Segment, Span};
+use crate::{Delimiters, TerminatorSuffix};
+
+const SIDECAR_MAGIC: &[u8; 8] = b"X12IDX\0\x02";
+
+/// Offsets of every delimiter byte in a document, grouped by kind.
+///
+/// Each list is sorted ascending. Line endings after segment terminators are not
+/// delimiters and are not recorded.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DelimiterPositions {
+    segment_terminators: Vec<usize>,
+    element_separators: Vec<usize>,
+    sub_element_separators: Vec<usize>,
+}
+
+impl DelimiterPositions {
+    /// Returns the offsets of segment terminators.
+    pub fn segment_terminators(&self) -> &[usize] {
+        &self.segment_terminators
+    }
+
+    /// Returns the offsets of element separators.
+    pub fn element_separators(&self) -> &[usize] {
+        &self.element_separators
+    }
+
+    /// Returns the offsets of sub-element separators.
+    pub fn sub_element_separators(&self) -> &[usize] {
+        &self.sub_element_separators
+    }
+
+    /// Returns the total number of delimiters recorded.
+    pub fn len(&self) -> usize {
+        self.segment_terminators.len()
+            + self.element_separators.len()
+            + self.sub_element_separators.len()
+    }
+
+    /// Returns true if no delimiters were found.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Records the position of every delimiter in `data` in a single pass.
+///
+/// If two delimiters share a byte (see [`Delimiters::are_valid`]), occurrences are
+/// recorded as the segment terminator first, then the element separator.
+///
+/// ```
+/// use x12_delimiters::index::find_all_positions;
+/// use x12_delimiters::Delimiters;
+///
+/// let positions = find_all_positions(b"N1*PR*A:B~N3*X~", &Delimiters::default());
+/// assert_eq!(positions.segment_terminators(), &[9, 14]);
+/// assert_eq!(positions.element_separators(), &[2, 5, 12]);
+/// assert_eq!(positions.sub_element_separators(), &[7]);
+/// ```
+pub fn find_all_positions(data: &[u8], delimiters: &Delimiters) -> DelimiterPositions {
+    let terminator = delimiters.segment_terminator();
+    let element = delimiters.element_separator();
+    let sub_element = delimiters.sub_element_separator();
+
+    let mut positions = DelimiterPositions::default();
+    for (i, &byte) in data.iter().enumerate() {
+        if byte == terminator {
+            positions.segment_terminators.push(i);
+        } else if byte == element {
+            positions.element_separators.push(i);
+        } else if byte == sub_element {
+            positions.sub_element_separators.push(i);
+        }
+    }
+    positions
+}
+
+/// Segment boundaries and element offsets for a whole document, built in one scan.
+///
+/// The index stores offsets only, so it can outlive the buffer it was built from; pass
+/// the same bytes back to [`DocumentIndex::segment`] and [`DocumentIndex::element`] to
+/// read values. Segment spans exclude the terminator and any line ending, matching the
+/// segments yielded by [`Delimiters::segments`].
+///
+/// The offset tables are allocated with `A`, the global allocator by default. With the
+/// `allocator-api2` feature, use `DocumentIndex::build_in` to place them in an arena
+/// instead; with the `bumpalo` feature, `&bumpalo::Bump` is such an allocator. Only the
+/// index is allocator-generic: [`crate::interchange::Document`] and the other parsed
+/// models always allocate from the global heap.
+///
+/// To index a file, open it with [`crate::file::FileDocument::open`], which reads through
+/// io_uring with the `io_uring` feature, and call [`crate::file::FileDocument::index`].
+///
+/// The index also records the envelope tree (ISA/IEA, GS/GE, ST/SE) and can be saved to
+/// and loaded from a sidecar file with [`DocumentIndex::save`] and [`DocumentIndex::load`].
+///
+/// ```
+/// use x12_delimiters::index::DocumentIndex;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ST*835*0001~\nCLP*A1*1*100~\nSE*3*0001~";
+/// let index = DocumentIndex::build(data, &Delimiters::default());
+/// assert_eq!(index.len(), 3);
+/// assert_eq!(index.element(data, 1, 3), Some(&b"100"[..]));
+/// assert_eq!(index.segment(data, 2).unwrap().id(), b"SE");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentIndex<A: Allocator = Global> {
+    delimiters: Delimiters,
+    source_len: usize,
+    /// False if the last segment ran to the end of the data without a terminator.
+    tail_terminated: bool,
+    segments: AllocVec<SegmentEntry, A>,
+    separators: AllocVec<usize, A>,
+    envelopes: AllocVec<IndexedEnvelope, A>,
+    /// Open interchange, group, and transaction, as indices into `envelopes`.
+    open: [Option<usize>; 3],
+}
+
+/// The kind of an [`IndexedEnvelope`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum EnvelopeKind {
+    /// ISA/IEA.
+    Interchange,
+    /// GS/GE.
+    Group,
+    /// ST/SE.
+    Transaction,
+}
+
+impl EnvelopeKind {
+    fn level(self) -> usize {
+        match self {
+            EnvelopeKind::Interchange => 0,
+            EnvelopeKind::Group => 1,
+            EnvelopeKind::Transaction => 2,
+        }
+    }
+}
+
+/// An envelope in a [`DocumentIndex`], located by segment index.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IndexedEnvelope {
+    kind: EnvelopeKind,
+    header: usize,
+    trailer: Option<usize>,
+    parent: Option<usize>,
+}
+
+impl IndexedEnvelope {
+    /// Returns the kind of envelope.
+    pub fn kind(&self) -> EnvelopeKind {
+        self.kind
+    }
+
+    /// Returns the segment index of the header (ISA, GS, or ST).
+    pub fn header(&self) -> usize {
+        self.header
+    }
+
+    /// Returns the segment index of the trailer (IEA, GE, or SE), if it was found.
+    pub fn trailer(&self) -> Option<usize> {
+        self.trailer
+    }
+
+    /// Returns the index in [`DocumentIndex::envelopes`] of the enclosing envelope.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// A value yielded by [`Column`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ColumnValue<'a> {
+    segment: usize,
+    transaction: Option<usize>,
+    value: &'a [u8],
+}
+
+impl<'a> ColumnValue<'a> {
+    /// Returns the index of the segment the value was read from.
+    pub fn segment(&self) -> usize {
+        self.segment
+    }
+
+    /// Returns the index in [`DocumentIndex::envelopes`] of the enclosing ST/SE
+    /// transaction, or `None` if the segment is outside any transaction.
+    pub fn transaction(&self) -> Option<usize> {
+        self.transaction
+    }
+
+    /// Returns the element bytes.
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+}
+
+/// Iterator over one element of every segment with a given ID.
+///
+/// Created by [`DocumentIndex::column`].
+#[derive(Debug, Clone)]
+pub struct Column<'a, A: Allocator = Global> {
+    index: &'a DocumentIndex<A>,
+    data: &'a [u8],
+    id: &'a [u8],
+    n: usize,
+    segment: usize,
+    /// Next envelope whose header has not yet been passed.
+    next_envelope: usize,
+    /// Envelope index and last segment of the transaction containing `segment`.
+    transaction: Option<(usize, Option<usize>)>,
+}
+
+impl<'a, A: Allocator> Column<'a, A> {
+    /// Moves the transaction context forward to segment `i`.
+    ///
+    /// Envelopes are in header order, so a single forward walk over them tracks the
+    /// innermost open transaction as segments ascend.
+    fn advance_to(&mut self, i: usize) {
+        let envelopes = self.index.envelopes();
+        while let Some(envelope) = envelopes.get(self.next_envelope) {
+            if envelope.header > i {
+                break;
+            }
+            // A new header at any level ends a transaction whose SE was missing, as does
+            // the trailer of an enclosing envelope.
+            self.transaction = (envelope.kind == EnvelopeKind::Transaction).then(|| {
+                let mut end = *envelope;
+                while end.trailer.is_none() {
+                    match end.parent {
+                        Some(parent) => end = envelopes[parent],
+                        None => break,
+                    }
+                }
+                (self.next_envelope, end.trailer)
+            });
+            self.next_envelope += 1;
+        }
+        if let Some((_, Some(trailer))) = self.transaction {
+            if trailer < i {
+                self.transaction = None;
+            }
+        }
+    }
+}
+
+impl<'a, A: Allocator> Iterator for Column<'a, A> {
+    type Item = ColumnValue<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.segment < self.index.len() {
+            let i = self.segment;
+            self.segment += 1;
+            if self.index.element(self.data, i, 0) != Some(self.id) {
+                continue;
+            }
+            let Some(value) = self.index.element(self.data, i, self.n) else {
+                continue;
+            };
+            self.advance_to(i);
+            return Some(ColumnValue {
+                segment: i,
+                transaction: self.transaction.map(|(envelope, _)| envelope),
+                value,
+            });
+        }
+        None
+    }
+}
+
+impl<A: Allocator, B: Allocator> PartialEq<DocumentIndex<B>> for DocumentIndex<A> {
+    fn eq(&self, other: &DocumentIndex<B>) -> bool {
+        self.delimiters == other.delimiters
+            && self.source_len == other.source_len
+            && self.tail_terminated == other.tail_terminated
+            && self.segments[..] == other.segments[..]
+            && self.separators[..] == other.separators[..]
+            && self.envelopes[..] == other.envelopes[..]
+            && self.open == other.open
+    }
+}
+
+impl<A: Allocator> Eq for DocumentIndex<A> {}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct SegmentEntry {
+    span: Span,
+    /// Index into `separators` of this segment's first element separator.
+    first_separator: usize,
+}
+
+impl DocumentIndex {
+    /// Indexes every segment and element separator in `data`.
+    pub fn build(data: &[u8], delimiters: &Delimiters) -> Self {
+        let mut index = DocumentIndex::empty_in(delimiters, Global);
+        index.rebuild(data, delimiters);
+        index
+    }
+
+    /// Reads an index written by [`DocumentIndex::save`].
+    ///
+    /// # Errors
+    /// Returns `io::ErrorKind::InvalidData` if the input is not a sidecar written by this
+    /// version of the format, and any error from reading `input`.
+    pub fn load<R: Read>(mut input: R) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        input.read_to_end(&mut buf)?;
+        let mut reader = SidecarReader { buf: &buf, pos: 0 };
+
+        if reader.take(SIDECAR_MAGIC.len())? != SIDECAR_MAGIC {
+            return Err(invalid_sidecar("unrecognized sidecar header"));
+        }
+        let header = reader.take(6)?;
+        let suffix = match header[3] {
+            0 => TerminatorSuffix::None,
+            1 => TerminatorSuffix::Lf,
+            2 => TerminatorSuffix::CrLf,
+            3 => TerminatorSuffix::Cr,
+            _ => return Err(invalid_sidecar("unknown terminator suffix")),
+        };
+        let repetition_separator = match header[4] {
+            0 => None,
+            1 => Some(header[5]),
+            _ => return Err(invalid_sidecar("invalid repetition separator flag")),
+        };
+        let delimiters = Delimiters::new(header[0], header[1], header[2])
+            .with_repetition_separator(repetition_separator)
+            .with_suffix(suffix);
+        let source_len = reader.varint()?;
+        let tail_terminated = match reader.take(1)?[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid_sidecar("invalid tail flag")),
+        };
+
+        let segment_count = reader.varint()?;
+        let mut segments = AllocVec::with_capacity(segment_count.min(buf.len()));
+        let mut separator_counts = Vec::with_capacity(segment_count.min(buf.len()));
+        let mut previous = 0usize;
+        let mut first_separator = 0usize;
+        for _ in 0..segment_count {
+            let start = checked_add(previous, reader.varint()?)?;
+            let end = checked_add(start, reader.varint()?)?;
+            let separators = reader.varint()?;
+            segments.push(SegmentEntry {
+                span: Span::new(start, end),
+                first_separator,
+            });
+            separator_counts.push(separators);
+            first_separator = checked_add(first_separator, separators)?;
+            previous = end;
+        }
+        let mut separators = AllocVec::with_capacity(first_separator.min(buf.len()));
+        for (entry, &count) in segments.iter().zip(&separator_counts) {
+            let mut offset = entry.span.start;
+            for _ in 0..count {
+                offset = checked_add(offset, reader.varint()?)?;
+                if offset >= entry.span.end {
+                    return Err(invalid_sidecar("separator outside its segment"));
+                }
+                separators.push(offset);
+            }
+        }
+
+        let envelope_count = reader.varint()?;
+        let mut envelopes = AllocVec::with_capacity(envelope_count.min(buf.len()));
+        for _ in 0..envelope_count {
+            let kind = match reader.take(1)?[0] {
+                0 => EnvelopeKind::Interchange,
+                1 => EnvelopeKind::Group,
+                2 => EnvelopeKind::Transaction,
+                _ => return Err(invalid_sidecar("unknown envelope kind")),
+            };
+            let header = reader.varint()?;
+            let trailer = reader.varint()?.checked_sub(1);
+            let parent = reader.varint()?.checked_sub(1);
+            if header >= segments.len()
+                || trailer.is_some_and(|t| t >= segments.len())
+                || parent.is_some_and(|p| p >= envelopes.len())
+            {
+                return Err(invalid_sidecar("envelope refers to a missing segment"));
+            }
+            envelopes.push(IndexedEnvelope {
+                kind,
+                header,
+                trailer,
+                parent,
+            });
+        }
+        if reader.pos != buf.len() {
+            return Err(invalid_sidecar("trailing bytes after index"));
+        }
+
+        let mut index = DocumentIndex {
+            delimiters,
+            source_len,
+            tail_terminated,
+            segments,
+            separators,
+            envelopes,
+            open: [None; 3],
+        };
+        index.reopen();
+        Ok(index)
+    }
+
+}
+
+impl<A: Allocator + Clone> DocumentIndex<A> {
+    /// Like [`DocumentIndex::build`], allocating the index with `alloc`.
+    ///
+    /// Parsing millions of small interchanges allocates and frees three tables per
+    /// document. Building each index in an arena that is reset between batches replaces
+    /// that churn with pointer bumps.
+    #[cfg(feature = "allocator-api2")]
+    pub fn build_in(data: &[u8], delimiters: &Delimiters, alloc: A) -> Self {
+        let mut index = DocumentIndex::empty_in(delimiters, alloc);
+        index.rebuild(data, delimiters);
+        index
+    }
+
+    fn empty_in(delimiters: &Delimiters, alloc: A) -> Self {
+        DocumentIndex {
+            delimiters: *delimiters,
+            source_len: 0,
+            tail_terminated: true,
+            segments: AllocVec::new_in(alloc.clone()),
+            separators: AllocVec::new_in(alloc.clone()),
+            envelopes: AllocVec::new_in(alloc),
+            open: [None; 3],
+        }
+    }
+}
+
+impl<A: Allocator> DocumentIndex<A> {
+    /// Replaces the index with one for `data`, reusing the existing allocations.
+    ///
+    /// When indexing many small interchanges, keep one index and rebuild it for each
+    /// document: after the first few, no further allocation takes place.
+    ///
+    /// ```
+    /// use x12_delimiters::index::DocumentIndex;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let delimiters = Delimiters::default();
+    /// let mut index = DocumentIndex::build(b"", &delimiters);
+    /// for document in [&b"ST*1~SE*2~"[..], b"ST*2~BHT*0019~SE*3~"] {
+    ///     index.rebuild(document, &delimiters);
+    ///     assert_eq!(index.element(document, 0, 1).unwrap(), &document[3..4]);
+    /// }
+    /// ```
+    pub fn rebuild(&mut self, data: &[u8], delimiters: &Delimiters) {
+        self.delimiters = *delimiters;
+        self.source_len = 0;
+        self.tail_terminated = true;
+        self.segments.clear();
+        self.separators.clear();
+        self.envelopes.clear();
+        self.open = [None; 3];
+        self.scan(data, 0);
+    }
+
+    /// Returns the offset from which [`DocumentIndex::extend_from`] continues indexing.
+    ///
+    /// This is the length of the indexed data, unless the data ended in an unterminated
+    /// segment (e.g. a write in progress), in which case it is the start of that segment.
+    pub fn resume_offset(&self) -> usize {
+        match self.segments.last() {
+            Some(last) if !self.tail_terminated => last.span.start,
+            _ => self.source_len,
+        }
+    }
+
+    /// Extends the index with bytes appended to the indexed data.
+    ///
+    /// `new_bytes` must hold the data from `offset` onwards, and `offset` must equal
+    /// [`DocumentIndex::resume_offset`]. For a file that only grows, read from the resume
+    /// offset to the end of the file; an unterminated final segment is then indexed again
+    /// with its remaining bytes. The result is the same as rebuilding from byte zero.
+    ///
+    /// ```
+    /// use x12_delimiters::index::DocumentIndex;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let log = b"ST*835*0001~CLP*A1~SE*3*0001~";
+    /// let mut index = DocumentIndex::build(&log[..16], &Delimiters::default());
+    /// assert_eq!(index.resume_offset(), 12);
+    ///
+    /// let offset = index.resume_offset();
+    /// index.extend_from(offset, &log[offset..]).unwrap();
+    /// assert_eq!(index, DocumentIndex::build(log, &Delimiters::default()));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `IndexError::OffsetMismatch` if `offset` is not the resume offset; the
+    /// index is left unchanged.
+    pub fn extend_from(&mut self, offset: usize, new_bytes: &[u8]) -> Result<(), IndexError> {
+        let expected = self.resume_offset();
+        if offset != expected {
+            return Err(IndexError::OffsetMismatch {
+                expected,
+                actual: offset,
+            });
+        }
+        if !self.tail_terminated {
+            if let Some(tail) = self.segments.pop() {
+                let tail_index = self.segments.len();
+                self.separators.truncate(tail.first_separator);
+                if self
+                    .envelopes
+                    .last()
+                    .is_some_and(|e| e.header == tail_index)
+                {
+                    self.envelopes.pop();
+                }
+                for envelope in &mut self.envelopes {
+                    if envelope.trailer == Some(tail_index) {
+                        envelope.trailer = None;
+                    }
+                }
+                self.reopen();
+            }
+            self.tail_terminated = true;
+        }
+        self.scan(new_bytes, offset);
+        Ok(())
+    }
+
+    /// Indexes `data`, which starts at offset `base` of the document.
+    fn scan(&mut self, data: &[u8], base: usize) {
+        let terminator = self.delimiters.segment_terminator();
+        let element = self.delimiters.element_separator();
+        // Offset of the current segment and index of its first element separator.
+        let mut open: Option<(usize, usize)> = None;
+
+        for (i, &byte) in data.iter().enumerate() {
+            let offset = base + i;
+            let (start, first_separator) = match open {
+                Some(current) => current,
+                None if byte == b'\r' || byte == b'\n' => continue,
+                None => *open.insert((offset, self.separators.len())),
+            };
+            if byte == terminator {
+                self.push_segment(data, base, Span::new(start, offset), first_separator);
+                open = None;
+            } else if byte == element {
+                self.separators.push(offset);
+            }
+        }
+        if let Some((start, first_separator)) = open {
+            let end = data
+                .iter()
+                .rposition(|&b| b != b'\r' && b != b'\n')
+                .map_or(start, |i| base + i + 1);
+            self.push_segment(data, base, Span::new(start, end), first_separator);
+            self.tail_terminated = false;
+        }
+        self.source_len = base + data.len();
+    }
+
+    /// Recomputes the open envelopes by replaying headers and trailers in segment order.
+    fn reopen(&mut self) {
+        let mut events: Vec<(usize, usize)> = Vec::with_capacity(self.envelopes.len() * 2);
+        for (i, envelope) in self.envelopes.iter().enumerate() {
+            events.push((envelope.header, i));
+            events.extend(envelope.trailer.map(|trailer| (trailer, i)));
+        }
+        events.sort_unstable();
+
+        self.open = [None; 3];
+        for (segment, i) in events {
+            let envelope = self.envelopes[i];
+            let level = envelope.kind.level();
+            self.open[level..].fill(None);
+            if segment == envelope.header {
+                self.open[level] = Some(i);
+            }
+        }
+    }
+
+    /// Records a segment; `data` starts at offset `base` of the document.
+    fn push_segment(&mut self, data: &[u8], base: usize, span: Span, first_separator: usize) {
+        let segment = self.segments.len();
+        self.segments.push(SegmentEntry {
+            span,
+            first_separator,
+        });
+
+        let id_end = self
+            .separators
+            .get(first_separator)
+            .map_or(span.end, |&p| p.min(span.end));
+        let (kind, is_header) = match &data[span.start - base..id_end - base] {
+            b"ISA" => (EnvelopeKind::Interchange, true),
+            b"GS" => (EnvelopeKind::Group, true),
+            b"ST" => (EnvelopeKind::Transaction, true),
+            b"IEA" => (EnvelopeKind::Interchange, false),
+            b"GE" => (EnvelopeKind::Group, false),
+            b"SE" => (EnvelopeKind::Transaction, false),
+            _ => return,
+        };
+        let level = kind.level();
+        if is_header {
+            self.open[level..].fill(None);
+            let parent = self.open[..level].iter().rev().find_map(|&open| open);
+            self.open[level] = Some(self.envelopes.len());
+            self.envelopes.push(IndexedEnvelope {
+                kind,
+                header: segment,
+                trailer: None,
+                parent,
+            });
+        } else if let Some(open) = self.open[level] {
+            self.envelopes[open].trailer = Some(segment);
+            self.open[level..].fill(None);
+        }
+    }
+
+    /// Returns the delimiters the index was built with.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the length of the data the index was built from.
+    ///
+    /// Compare with the current file length to detect a stale sidecar.
+    pub fn source_len(&self) -> usize {
+        self.source_len
+    }
+
+    /// Returns every envelope in header order; children follow their parents.
+    pub fn envelopes(&self) -> &[IndexedEnvelope] {
+        &self.envelopes
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns true if the document has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Returns the span of segment `i`, excluding its terminator.
+    pub fn segment_span(&self, i: usize) -> Option<Span> {
+        self.segments.get(i).map(|entry| entry.span)
+    }
+
+    /// Returns the number of elements in segment `i`, counting the segment ID.
+    pub fn element_count(&self, i: usize) -> Option<usize> {
+        self.separators_of(i).map(|separators| separators.len() + 1)
+    }
+
+    /// Returns the span of element `n` of segment `i`, where element 0 is the segment ID.
+    pub fn element_span(&self, i: usize, n: usize) -> Option<Span> {
+        let span = self.segment_span(i)?;
+        let separators = self.separators_of(i)?;
+        if n > separators.len() {
+            return None;
+        }
+        let start = if n == 0 {
+            span.start
+        } else {
+            separators[n - 1] + 1
+        };
+        let end = separators.get(n).copied().unwrap_or(span.end);
+        Some(Span::new(start, end))
+    }
+
+    /// Returns segment `i`, reading its bytes from `data`.
+    ///
+    /// `data` must be the buffer the index was built from.
+    pub fn segment<'a>(&self, data: &'a [u8], i: usize) -> Option<Segment<'a>> {
+        let span = self.segment_span(i)?;
+        Some(Segment::new(
+            data.get(span.start..span.end)?,
+            self.delimiters,
+        ))
+    }
+
+    /// Returns element `n` of segment `i`, reading its bytes from `data`.
+    ///
+    /// `data` must be the buffer the index was built from.
+    pub fn element<'a>(&self, data: &'a [u8], i: usize, n: usize) -> Option<&'a [u8]> {
+        let span = self.element_span(i, n)?;
+        data.get(span.start..span.end)
+    }
+
+    /// Returns an iterator over element `n` of every segment with ID `id`, in document
+    /// order, with the transaction each value was found in.
+    ///
+    /// Segment IDs and elements are located through the index, so only the matching
+    /// elements are read from `data`. Segments with fewer than `n + 1` elements are
+    /// skipped. `data` must be the buffer the index was built from.
+    ///
+    /// ```
+    /// use x12_delimiters::index::DocumentIndex;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let data = b"ST*837*0001~CLM*A1*100~SE*3*0001~ST*837*0002~CLM*B2*250~SE*3*0002~";
+    /// let index = DocumentIndex::build(data, &Delimiters::default());
+    ///
+    /// let amounts: Vec<(&[u8], &[u8])> = index
+    ///     .column(data, b"CLM", 2)
+    ///     .map(|value| {
+    ///         let st = index.envelopes()[value.transaction().unwrap()].header();
+    ///         (index.element(data, st, 2).unwrap(), value.value())
+    ///     })
+    ///     .collect();
+    /// assert_eq!(amounts, [(&b"0001"[..], &b"100"[..]), (&b"0002"[..], &b"250"[..])]);
+    /// ```
+    pub fn column<'a>(&'a self, data: &'a [u8], id: &'a [u8], n: usize) -> Column<'a, A> {
+        Column {
+            index: self,
+            data,
+            id,
+            n,
+            segment: 0,
+            next_envelope: 0,
+            transaction: None,
+        }
+    }
+
+    /// Writes the index in the compact sidecar format.
+    ///
+    /// Offsets are delta-encoded as LEB128 varints, so a sidecar is typically a small
+    /// fraction of the size of the document it indexes.
+    ///
+    /// ```
+    /// use x12_delimiters::index::DocumentIndex;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let data = b"ST*835*0001~CLP*A1*1*100~SE*3*0001~";
+    /// let index = DocumentIndex::build(data, &Delimiters::default());
+    ///
+    /// let mut sidecar = Vec::new();
+    /// index.save(&mut sidecar).unwrap();
+    /// assert_eq!(DocumentIndex::load(&sidecar[..]).unwrap(), index);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns any error from writing to `out`.
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let mut buf = SIDECAR_MAGIC.to_vec();
+        buf.extend_from_slice(&[
+            self.delimiters.segment_terminator(),
+            self.delimiters.element_separator(),
+            self.delimiters.sub_element_separator(),
+            suffix_code(self.delimiters.suffix()),
+            self.delimiters.repetition_separator().is_some() as u8,
+            self.delimiters.repetition_separator().unwrap_or_default(),
+        ]);
+        put_varint(&mut buf, self.source_len);
+        buf.push(self.tail_terminated as u8);
+
+        put_varint(&mut buf, self.segments.len());
+        let mut previous = 0;
+        for (i, entry) in self.segments.iter().enumerate() {
+            put_varint(&mut buf, entry.span.start - previous);
+            put_varint(&mut buf, entry.span.len());
+            put_varint(&mut buf, self.separators_of(i).map_or(0, <[usize]>::len));
+            previous = entry.span.end;
+        }
+        for (i, entry) in self.segments.iter().enumerate() {
+            let mut offset = entry.span.start;
+            for &separator in self.separators_of(i).unwrap_or_default() {
+                put_varint(&mut buf, separator - offset);
+                offset = separator;
+            }
+        }
+
+        put_varint(&mut buf, self.envelopes.len());
+        for envelope in &self.envelopes {
+            buf.push(envelope.kind.level() as u8);
+            put_varint(&mut buf, envelope.header);
+            put_varint(&mut buf, envelope.trailer.map_or(0, |t| t + 1));
+            put_varint(&mut buf, envelope.parent.map_or(0, |p| p + 1));
+        }
+        out.write_all(&buf)
+    }
+
+    fn separators_of(&self, i: usize) -> Option<&[usize]> {
+        let first = self.segments.get(i)?.first_separator;
+        let end = self
+            .segments
+            .get(i + 1)
+            .map_or(self.separators.len(), |next| next.first_separator);
+        Some(&self.separators[first..end])
+    }
+}
+
+fn suffix_code(suffix: TerminatorSuffix) -> u8 {
+    match suffix {
+        TerminatorSuffix::None => 0,
+        TerminatorSuffix::Lf => 1,
+        TerminatorSuffix::CrLf => 2,
+        TerminatorSuffix::Cr => 3,
+    }
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn invalid_sidecar(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn checked_add(a: usize, b: usize) -> io::Result<usize> {
+    a.checked_add(b)
+        .ok_or_else(|| invalid_sidecar("offset overflow"))
+}
+
+struct SidecarReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SidecarReader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let bytes = self
+            .buf
+            .get(self.pos..self.pos + n)
+            .ok_or_else(|| invalid_sidecar("truncated sidecar"))?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> io::Result<usize> {
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7F) as usize)
+                .checked_shl(shift)
+                .ok_or_else(|| invalid_sidecar("varint overflow"))?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid_sidecar("varint overflow"))
+    }
+}
+
+/// Stand-ins for the `allocator-api2` items used by [`DocumentIndex`] when that feature
+/// is off, where the global heap is the only allocator.
+#[cfg(not(feature = "allocator-api2"))]
+mod heap {
+    use std::marker::PhantomData;
+    use std::ops::{Deref, DerefMut};
+
+    pub trait Allocator {}
+
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Global;
+
+    impl Allocator for Global {}
+
+    /// A `Vec` carrying the allocator type parameter.
+    #[derive(Debug, Clone)]
+    pub struct AllocVec<T, A>(Vec<T>, PhantomData<A>);
+
+    impl<T, A> AllocVec<T, A> {
+        pub fn new_in(_alloc: A) -> Self {
+            AllocVec(Vec::new(), PhantomData)
+        }
+
+        pub fn with_capacity(capacity: usize) -> Self {
+            AllocVec(Vec::with_capacity(capacity), PhantomData)
+        }
+    }
+
+    impl<T, A> Deref for AllocVec<T, A> {
+        type Target = Vec<T>;
+
+        fn deref(&self) -> &Vec<T> {
+            &self.0
+        }
+    }
+
+    impl<T, A> DerefMut for AllocVec<T, A> {
+        fn deref_mut(&mut self) -> &mut Vec<T> {
+            &mut self.0
+        }
+    }
+
+    impl<'a, T, A> IntoIterator for &'a AllocVec<T, A> {
+        type Item = &'a T;
+        type IntoIter = std::slice::Iter<'a, T>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.iter()
+        }
+    }
+
+    impl<'a, T, A> IntoIterator for &'a mut AllocVec<T, A> {
+        type Item = &'a mut T;
+        type IntoIter = std::slice::IterMut<'a, T>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.0.iter_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_skip_line_endings() {
+        let positions = find_all_positions(b"ST*837~\r\nSE*1~", &Delimiters::default());
+        assert_eq!(positions.segment_terminators(), &[6, 13]);
+        assert_eq!(positions.element_separators(), &[2, 11]);
+        assert!(positions.sub_element_separators().is_empty());
+        assert_eq!(positions.len(), 4);
+    }
+
+    #[test]
+    fn test_shared_byte_counts_as_terminator() {
+        let positions = find_all_positions(b"A*B~C", &Delimiters::new(b'*', b'*', b'~'));
+        assert_eq!(positions.segment_terminators(), &[1]);
+        assert!(positions.element_separators().is_empty());
+        assert_eq!(positions.sub_element_separators(), &[3]);
+        assert!(find_all_positions(b"", &Delimiters::default()).is_empty());
+    }
+
+    #[test]
+    fn test_index_matches_segment_iterator() {
+        let data = b"\r\nISA*00~GS*HC~\r\nN1*PR**X~\r\n~SE*3\r\n";
+        let delimiters = Delimiters::default();
+        let index = DocumentIndex::build(data, &delimiters);
+        let segments: Vec<Segment> = delimiters.segments(data).collect();
+
+        assert_eq!(index.len(), segments.len());
+        for (i, segment) in segments.iter().enumerate() {
+            assert_eq!(index.segment(data, i), Some(*segment));
+            let elements: Vec<&[u8]> = segment.as_bytes().split(|&b| b == b'*').collect();
+            assert_eq!(index.element_count(i), Some(elements.len()));
+            for (n, element) in elements.iter().enumerate() {
+                assert_eq!(index.element(data, i, n), Some(*element));
+            }
+            assert_eq!(index.element(data, i, elements.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_rebuild_reuses_allocations() {
+        let delimiters = Delimiters::default();
+        let large = b"ST*837*0001~NM1*IL*1*DOE*JOHN~SE*3*0001~";
+        let small = b"ST*1~";
+        let mut index = DocumentIndex::build(large, &delimiters);
+        let capacities = (index.segments.capacity(), index.separators.capacity());
+
+        index.rebuild(small, &delimiters);
+        assert_eq!(index, DocumentIndex::build(small, &delimiters));
+        assert_eq!(
+            (index.segments.capacity(), index.separators.capacity()),
+            capacities
+        );
+    }
+
+    const DOC: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\n\
+GS*HC*S*R*20250403*0856*1*X*005010X222A1~\r\nST*837*0001~\r\nBHT*0019~\r\nSE*3*0001~\r\n\
+ST*837*0002~\r\nSE*2*0002~\r\nGE*2*1~\r\nIEA*1*000000001~\r\n";
+
+    #[test]
+    fn test_envelope_tree() {
+        let index = DocumentIndex::build(DOC, &Delimiters::default());
+        let tree: Vec<_> = index
+            .envelopes()
+            .iter()
+            .map(|e| (e.kind(), e.header(), e.trailer(), e.parent()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (EnvelopeKind::Interchange, 0, Some(8), None),
+                (EnvelopeKind::Group, 1, Some(7), Some(0)),
+                (EnvelopeKind::Transaction, 2, Some(4), Some(1)),
+                (EnvelopeKind::Transaction, 5, Some(6), Some(1)),
+            ]
+        );
+
+        let unclosed = DocumentIndex::build(b"ISA*1~ST*1~SE*1~GE*1~", &Delimiters::default());
+        let tree: Vec<_> = unclosed
+            .envelopes()
+            .iter()
+            .map(|e| (e.kind(), e.trailer(), e.parent()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (EnvelopeKind::Interchange, None, None),
+                (EnvelopeKind::Transaction, Some(2), Some(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sidecar_roundtrip() {
+        let delimiters = Delimiters::default()
+            .with_repetition_separator(Some(b'^'))
+            .with_suffix(TerminatorSuffix::CrLf);
+        let index = DocumentIndex::build(DOC, &delimiters);
+        let mut sidecar = Vec::new();
+        index.save(&mut sidecar).unwrap();
+        assert!(sidecar.len() < DOC.len() / 2);
+
+        let loaded = DocumentIndex::load(&sidecar[..]).unwrap();
+        assert_eq!(loaded, index);
+        assert_eq!(loaded.source_len(), DOC.len());
+        assert_eq!(loaded.element(DOC, 5, 2), Some(&b"0002"[..]));
+    }
+
+    #[test]
+    fn test_sidecar_rejects_bad_input() {
+        let mut sidecar = Vec::new();
+        DocumentIndex::build(DOC, &Delimiters::default())
+            .save(&mut sidecar)
+            .unwrap();
+
+        for bad in [
+            &b"NOTANIDX"[..],
+            &sidecar[..sidecar.len() - 1],
+            &[&sidecar[..], b"\0"].concat(),
+        ] {
+            let error = DocumentIndex::load(bad).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_extend_from_matches_full_build() {
+        let delimiters = Delimiters::default();
+        let full = DocumentIndex::build(DOC, &delimiters);
+        for split in 0..=DOC.len() {
+            let mut index = DocumentIndex::build(&DOC[..split], &delimiters);
+            let offset = index.resume_offset();
+            index.extend_from(offset, &DOC[offset..]).unwrap();
+            assert_eq!(index, full, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn test_extend_after_load_and_offset_mismatch() {
+        let delimiters = Delimiters::default();
+        let mut sidecar = Vec::new();
+        DocumentIndex::build(&DOC[..140], &delimiters)
+            .save(&mut sidecar)
+            .unwrap();
+        let mut index = DocumentIndex::load(&sidecar[..]).unwrap();
+
+        let offset = index.resume_offset();
+        assert!(offset < 140);
+        assert_eq!(
+            index.extend_from(140, &DOC[140..]),
+            Err(IndexError::OffsetMismatch {
+                expected: offset,
+                actual: 140
+            })
+        );
+        index.extend_from(offset, &DOC[offset..]).unwrap();
+        assert_eq!(index, DocumentIndex::build(DOC, &delimiters));
+    }
+
+    #[cfg(feature = "bumpalo")]
+    #[test]
+    fn test_build_in_arena() {
+        let delimiters = Delimiters::default();
+        let data = b"ISA*00~GS*HC~ST*837*0001~CLM*A1*100~SE*3*0001~GE*1*1~IEA*1*1~";
+        let bump = bumpalo::Bump::new();
+        let index = DocumentIndex::build_in(data, &delimiters, &bump);
+        assert!(bump.allocated_bytes() > 0);
+        assert_eq!(index, DocumentIndex::build(data, &delimiters));
+        assert_eq!(index.column(data, b"CLM", 2).next().unwrap().value(), b"100");
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let index = DocumentIndex::build(b"\r\n", &Delimiters::default());
+        assert!(index.is_empty());
+        assert_eq!(index.segment_span(0), None);
+        assert_eq!(index.element_span(0, 0), None);
+    }
+
+    #[test]
+    fn test_column_tracks_transactions() {
+        let data = b"ISA*00~GS*HC~ST*837*0001~CLM*A1*100~CLM*A2~SE*4*0001~\
+ST*837*0002~CLM*B1*250~GE*2~CLM*C1*5~ST*837*0003~CLM*D1*75~";
+        let index = DocumentIndex::build(data, &Delimiters::default());
+        let column: Vec<(usize, Option<EnvelopeKind>, &[u8])> = index
+            .column(data, b"CLM", 2)
+            .map(|value| {
+                let kind = value.transaction().map(|t| index.envelopes()[t].kind());
+                (value.segment(), kind, value.value())
+            })
+            .collect();
+        let transaction = Some(EnvelopeKind::Transaction);
+        assert_eq!(
+            column,
+            vec![
+                (3, transaction, &b"100"[..]),
+                (7, transaction, &b"250"[..]),
+                (9, None, &b"5"[..]),
+                (11, transaction, &b"75"[..]),
+            ]
+        );
+        let transactions: Vec<Option<usize>> = index
+            .column(data, b"CLM", 1)
+            .map(|value| value.transaction())
+            .collect();
+        assert_eq!(transactions, vec![Some(2), Some(2), Some(3), None, Some(4)]);
+        assert_eq!(index.column(data, b"NM1", 0).count(), 0);
+    }
+}
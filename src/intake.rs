@@ -0,0 +1,342 @@
+use std::fmt;
+
+use crate::detect::{detect, Confidence};
+use crate::errors::DelimiterError;
+use crate::summary::interchange_spans;
+use crate::validation::{validate, Diagnostic, DiagnosticCode, Severity};
+
+const DEFAULT_MAX_BYTES: usize = 256 * 1024 * 1024;
+const DEFAULT_MAX_SEGMENTS: usize = 5_000_000;
+const DEFAULT_MAX_INTERCHANGES: usize = 10_000;
+
+/// What to do with an incoming file, from least to most severe.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum Verdict {
+    /// The file can be processed.
+    Accept,
+    /// The file can be processed, but has findings worth logging.
+    AcceptWithWarnings,
+    /// The file may be X12 but needs a human look before processing.
+    Quarantine,
+    /// The file is not usable X12 or exceeds hard limits.
+    Reject,
+}
+
+/// Why [`classify`] reached its verdict.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IntakeReason {
+    /// Delimiters could not be detected at all.
+    Undetectable(DelimiterError),
+    /// Delimiters were inferred from a truncated ISA.
+    LowConfidence,
+    /// A finding from envelope validation.
+    Validation(Diagnostic),
+    /// No complete interchange was found.
+    NoInterchanges,
+    /// The file is larger than [`IntakeLimits::max_bytes`].
+    TooLarge { limit: usize, actual: usize },
+    /// The file has more segments than [`IntakeLimits::max_segments`].
+    TooManySegments { limit: usize, actual: usize },
+    /// The file has more interchanges than [`IntakeLimits::max_interchanges`].
+    TooManyInterchanges { limit: usize, actual: usize },
+}
+
+impl IntakeReason {
+    /// Returns the verdict this reason alone would lead to.
+    pub fn verdict(&self) -> Verdict {
+        match self {
+            IntakeReason::Undetectable(_)
+            | IntakeReason::NoInterchanges
+            | IntakeReason::TooLarge { .. } => Verdict::Reject,
+            IntakeReason::Validation(diagnostic) => match diagnostic.code() {
+                DiagnosticCode::IsaTooShort | DiagnosticCode::DuplicateDelimiters => {
+                    Verdict::Reject
+                }
+                _ if diagnostic.severity() == Severity::Warning => Verdict::AcceptWithWarnings,
+                _ => Verdict::Quarantine,
+            },
+            IntakeReason::LowConfidence
+            | IntakeReason::TooManySegments { .. }
+            | IntakeReason::TooManyInterchanges { .. } => Verdict::Quarantine,
+        }
+    }
+}
+
+impl fmt::Display for IntakeReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntakeReason::Undetectable(e) => write!(f, "delimiters not detected: {}", e),
+            IntakeReason::LowConfidence => {
+                write!(f, "delimiters inferred from a truncated ISA segment")
+            }
+            IntakeReason::Validation(diagnostic) => write!(f, "{}", diagnostic),
+            IntakeReason::NoInterchanges => write!(f, "no interchange found"),
+            IntakeReason::TooLarge { limit, actual } => {
+                write!(f, "file is {} bytes, limit is {}", actual, limit)
+            }
+            IntakeReason::TooManySegments { limit, actual } => {
+                write!(f, "file has {} segments, limit is {}", actual, limit)
+            }
+            IntakeReason::TooManyInterchanges { limit, actual } => {
+                write!(f, "file has {} interchanges, limit is {}", actual, limit)
+            }
+        }
+    }
+}
+
+/// Resource limits applied by [`classify_with_limits`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct IntakeLimits {
+    max_bytes: usize,
+    max_segments: usize,
+    max_interchanges: usize,
+}
+
+impl IntakeLimits {
+    /// Creates the default limits: 256 MiB, five million segments and ten thousand
+    /// interchanges.
+    pub fn new() -> Self {
+        IntakeLimits {
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_segments: DEFAULT_MAX_SEGMENTS,
+            max_interchanges: DEFAULT_MAX_INTERCHANGES,
+        }
+    }
+
+    /// Sets the largest file size accepted; larger files are rejected unread.
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Sets the most segments a file may have before it is quarantined.
+    pub fn with_max_segments(mut self, max_segments: usize) -> Self {
+        self.max_segments = max_segments;
+        self
+    }
+
+    /// Sets the most interchanges a file may have before it is quarantined.
+    pub fn with_max_interchanges(mut self, max_interchanges: usize) -> Self {
+        self.max_interchanges = max_interchanges;
+        self
+    }
+
+    /// Returns the largest file size accepted.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Returns the most segments a file may have.
+    pub fn max_segments(&self) -> usize {
+        self.max_segments
+    }
+
+    /// Returns the most interchanges a file may have.
+    pub fn max_interchanges(&self) -> usize {
+        self.max_interchanges
+    }
+}
+
+impl Default for IntakeLimits {
+    fn default() -> Self {
+        IntakeLimits::new()
+    }
+}
+
+/// The outcome of [`classify`]: a verdict and every reason that contributed to it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IntakeDecision {
+    verdict: Verdict,
+    reasons: Vec<IntakeReason>,
+}
+
+impl IntakeDecision {
+    fn from_reasons(reasons: Vec<IntakeReason>) -> Self {
+        let verdict = reasons
+            .iter()
+            .map(IntakeReason::verdict)
+            .max()
+            .unwrap_or(Verdict::Accept);
+        IntakeDecision { verdict, reasons }
+    }
+
+    /// Returns the most severe verdict among the reasons, or `Accept` if there are none.
+    pub fn verdict(&self) -> Verdict {
+        self.verdict
+    }
+
+    /// Returns the reasons, in the order they were found.
+    pub fn reasons(&self) -> &[IntakeReason] {
+        &self.reasons
+    }
+
+    /// Returns true if the file can be processed, with or without warnings.
+    pub fn is_accepted(&self) -> bool {
+        self.verdict <= Verdict::AcceptWithWarnings
+    }
+}
+
+/// Classifies an incoming file with the default [`IntakeLimits`].
+///
+/// ```
+/// use x12_delimiters::intake::{classify, Verdict};
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~";
+/// assert_eq!(classify(data).verdict(), Verdict::Accept);
+/// assert_eq!(classify(b"not x12").verdict(), Verdict::Reject);
+/// ```
+pub fn classify(data: &[u8]) -> IntakeDecision {
+    classify_with_limits(data, &IntakeLimits::new())
+}
+
+/// Decides whether an incoming file is accepted, quarantined or rejected.
+///
+/// Combines the checks an intake gate needs into one pass:
+/// - files over the size limit are rejected without being scanned;
+/// - delimiters are detected with [`detect`]; undetectable delimiters are rejected and
+///   low-confidence ones quarantined without further checks;
+/// - the envelopes are checked with [`validate`]: errors quarantine the file, warnings
+///   accept it with warnings, and an unusable ISA rejects it;
+/// - a file without interchanges is rejected, and one over the segment or interchange
+///   limits is quarantined.
+///
+/// The verdict is the most severe one among all reasons.
+pub fn classify_with_limits(data: &[u8], limits: &IntakeLimits) -> IntakeDecision {
+    if data.len() > limits.max_bytes {
+        return IntakeDecision::from_reasons(vec![IntakeReason::TooLarge {
+            limit: limits.max_bytes,
+            actual: data.len(),
+        }]);
+    }
+
+    if !data.starts_with(b"ISA") {
+        let reason = IntakeReason::Undetectable(DelimiterError::MissingIsa);
+        return IntakeDecision::from_reasons(vec![reason]);
+    }
+    let delimiters = match detect(data) {
+        Ok(detection) if detection.confidence() == Confidence::Low => {
+            return IntakeDecision::from_reasons(vec![IntakeReason::LowConfidence]);
+        }
+        Ok(detection) => detection.delimiters(),
+        Err(error) => {
+            return IntakeDecision::from_reasons(vec![IntakeReason::Undetectable(error)]);
+        }
+    };
+
+    let mut reasons: Vec<IntakeReason> = validate(data)
+        .diagnostics()
+        .iter()
+        .cloned()
+        .map(IntakeReason::Validation)
+        .collect();
+
+    let interchanges = interchange_spans(data).len();
+    if interchanges == 0 {
+        reasons.push(IntakeReason::NoInterchanges);
+    } else if interchanges > limits.max_interchanges {
+        reasons.push(IntakeReason::TooManyInterchanges {
+            limit: limits.max_interchanges,
+            actual: interchanges,
+        });
+    }
+    if let Some(delimiters) = delimiters {
+        let segments = delimiters.segments(data).count();
+        if segments > limits.max_segments {
+            reasons.push(IntakeReason::TooManySegments {
+                limit: limits.max_segments,
+                actual: segments,
+            });
+        }
+    }
+    IntakeDecision::from_reasons(reasons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    fn document(body: &[u8]) -> Vec<u8> {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(body);
+        data
+    }
+
+    #[test]
+    fn test_clean_file_is_accepted() {
+        let data = document(
+            b"GS*HC*S*R*20250403*0856*1*X*005010~ST*837*0001~SE*2*0001~GE*1*1~IEA*1*000000001~",
+        );
+        let decision = classify(&data);
+        assert_eq!(decision.verdict(), Verdict::Accept);
+        assert!(decision.reasons().is_empty());
+        assert!(decision.is_accepted());
+    }
+
+    #[test]
+    fn test_warnings_and_errors() {
+        let unterminated = document(b"IEA*0*000000001");
+        let decision = classify(&unterminated);
+        assert_eq!(decision.verdict(), Verdict::AcceptWithWarnings);
+        assert!(decision.is_accepted());
+
+        let bad_count = document(b"IEA*2*000000001~");
+        let decision = classify(&bad_count);
+        assert_eq!(decision.verdict(), Verdict::Quarantine);
+        assert!(matches!(
+            &decision.reasons()[0],
+            IntakeReason::Validation(d) if d.code() == DiagnosticCode::CountMismatch
+        ));
+    }
+
+    #[test]
+    fn test_detection_failures() {
+        assert_eq!(
+            classify(&ISA[..60]).reasons(),
+            &[IntakeReason::LowConfidence]
+        );
+        assert_eq!(
+            classify(b"GS*HC~").reasons(),
+            &[IntakeReason::Undetectable(DelimiterError::MissingIsa)]
+        );
+        let mut duplicate = ISA.to_vec();
+        duplicate[104] = b'*';
+        assert_eq!(classify(&duplicate).verdict(), Verdict::Reject);
+    }
+
+    #[test]
+    fn test_limits() {
+        let data = document(b"IEA*0*000000001~");
+        let decision = classify_with_limits(&data, &IntakeLimits::new().with_max_bytes(100));
+        assert_eq!(
+            decision.reasons(),
+            &[IntakeReason::TooLarge {
+                limit: 100,
+                actual: data.len()
+            }]
+        );
+        assert_eq!(decision.verdict(), Verdict::Reject);
+
+        let mut twice = data.clone();
+        twice.extend_from_slice(&data);
+        let limits = IntakeLimits::new()
+            .with_max_interchanges(1)
+            .with_max_segments(3);
+        let decision = classify_with_limits(&twice, &limits);
+        assert_eq!(decision.verdict(), Verdict::Quarantine);
+        assert_eq!(
+            decision.reasons(),
+            &[
+                IntakeReason::TooManyInterchanges {
+                    limit: 1,
+                    actual: 2
+                },
+                IntakeReason::TooManySegments {
+                    limit: 3,
+                    actual: 4
+                },
+            ]
+        );
+    }
+}
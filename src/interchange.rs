@@ -0,0 +1,543 @@
+use crate::envelope::{GeSegment, GsSegment, IeaSegment, SeSegment, StSegment};
+use crate::errors::EnvelopeError;
+use crate::isa::IsaSegment;
+use crate::segment::{SegmentIter, Span};
+use crate::summary::{interchange_spans, scan_interchange, Scan};
+use crate::Delimiters;
+
+/// Iterator over the interchanges of a file, returned by [`interchanges`].
+#[derive(Debug, Clone)]
+pub struct Interchanges<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Interchanges<'a> {
+    /// Returns an iterator that also yields each interchange's span in the input.
+    pub fn spanned(self) -> impl Iterator<Item = (Span, Delimiters, &'a [u8])> {
+        let data = self.data;
+        let mut position = self.position;
+        std::iter::from_fn(move || match scan_interchange(data, position, None) {
+            Scan::Found(span, delimiters) => {
+                position = span.end;
+                Some((span, delimiters, &data[span.start..span.end]))
+            }
+            Scan::End | Scan::Expired => None,
+        })
+    }
+
+    /// Returns the input after the last interchange yielded so far, including any
+    /// whitespace before the next one. Once the iterator is exhausted, this is the
+    /// trailing data that does not start an interchange.
+    pub fn remainder(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+}
+
+impl<'a> Iterator for Interchanges<'a> {
+    type Item = (Delimiters, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match scan_interchange(self.data, self.position, None) {
+            Scan::Found(span, delimiters) => {
+                self.position = span.end;
+                Some((delimiters, &self.data[span.start..span.end]))
+            }
+            Scan::End | Scan::Expired => None,
+        }
+    }
+}
+
+impl std::iter::FusedIterator for Interchanges<'_> {}
+
+/// Splits a file of concatenated interchanges, yielding each with the delimiters declared
+/// in its own ISA.
+///
+/// Each interchange runs from its ISA through its IEA, or to the end of the data if the
+/// IEA is missing. Whitespace between interchanges is skipped, and iteration stops at
+/// the first position that does not start an ISA; see [`Interchanges::remainder`].
+/// Nothing beyond locating the IEA is validated; use [`Document::parse`] for that.
+///
+/// ```
+/// use x12_delimiters::interchange::interchanges;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~\r\n\
+/// ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>'IEA|0|000000002'";
+/// let found: Vec<_> = interchanges(data)
+///     .map(|(delimiters, bytes)| (delimiters.element_separator(), bytes.len()))
+///     .collect();
+/// assert_eq!(found, [(b'*', 122), (b'|', 122)]);
+/// ```
+pub fn interchanges(data: &[u8]) -> Interchanges<'_> {
+    Interchanges { data, position: 0 }
+}
+
+/// Every interchange in a file, parsed into functional groups and transaction sets.
+///
+/// Segment data is borrowed from the input; only the envelope headers and trailers are
+/// decoded.
+///
+/// ```
+/// use x12_delimiters::interchange::Document;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC*S*R*20250403*0856*17*X*005010X222A1~ST*837*0001~BHT*0019~SE*3*0001~GE*1*17~IEA*1*000000001~";
+/// let document = Document::parse(data).unwrap();
+/// let interchange = document.interchange(1).unwrap();
+/// let group = interchange.group(17).unwrap();
+/// assert_eq!(group.gs().functional_id(), "HC");
+/// let transaction = group.transaction(1).unwrap();
+/// assert_eq!(transaction.st().transaction_set_id(), "837");
+/// assert_eq!(transaction.as_bytes(), b"ST*837*0001~BHT*0019~SE*3*0001~");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Document<'a> {
+    interchanges: Vec<Interchange<'a>>,
+}
+
+impl<'a> Document<'a> {
+    /// Parses every interchange in `data`.
+    ///
+    /// Interchanges may be separated by whitespace, and each is read with the delimiters
+    /// from its own ISA. Empty or all-whitespace input gives an empty document.
+    ///
+    /// # Errors
+    /// Returns the errors of [`Interchange::parse`] for the first interchange that fails,
+    /// including non-whitespace data after the last interchange.
+    pub fn parse(data: &'a [u8]) -> Result<Self, EnvelopeError> {
+        let spans = interchange_spans(data);
+        let mut interchanges = Vec::with_capacity(spans.len());
+        for (span, _) in &spans {
+            interchanges.push(Interchange::parse_at(data, span.start)?);
+        }
+        let end = spans.last().map_or(0, |(span, _)| span.end);
+        if let Some(start) = data[end..].iter().position(|b| !b.is_ascii_whitespace()) {
+            interchanges.push(Interchange::parse_at(data, end + start)?);
+        }
+        Ok(Document { interchanges })
+    }
+
+    /// Returns the interchanges in document order.
+    pub fn interchanges(&self) -> &[Interchange<'a>] {
+        &self.interchanges
+    }
+
+    /// Returns an iterator over the interchanges.
+    pub fn iter(&self) -> std::slice::Iter<'_, Interchange<'a>> {
+        self.interchanges.iter()
+    }
+
+    /// Returns the first interchange whose ISA13 is `control_number`.
+    pub fn interchange(&self, control_number: u32) -> Option<&Interchange<'a>> {
+        self.interchanges
+            .iter()
+            .find(|interchange| interchange.isa.control_number() == control_number)
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s Document<'a> {
+    type Item = &'s Interchange<'a>;
+    type IntoIter = std::slice::Iter<'s, Interchange<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// One ISA/IEA interchange and the functional groups it encloses.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Interchange<'a> {
+    bytes: &'a [u8],
+    span: Span,
+    isa: IsaSegment,
+    delimiters: Delimiters,
+    groups: Vec<FunctionalGroup<'a>>,
+    iea: Option<IeaSegment>,
+}
+
+impl<'a> Interchange<'a> {
+    /// Parses the interchange at the start of `data`, through its IEA.
+    ///
+    /// A group or transaction set without its trailer ends after its last segment, and
+    /// its trailer is `None`. An interchange without an IEA runs to the end of `data`.
+    ///
+    /// # Errors
+    /// Returns `EnvelopeError::Isa` if the ISA cannot be parsed, the errors of the
+    /// envelope segment parsers for a malformed GS, GE, ST, SE, or IEA, and
+    /// `EnvelopeError::MissingSegment` for an ST or GE outside a functional group, or an
+    /// SE or other segment outside a transaction set.
+    pub fn parse(data: &'a [u8]) -> Result<Self, EnvelopeError> {
+        Interchange::parse_at(data, 0)
+    }
+
+    /// Parses the interchange starting at `start`, recording spans relative to `data`.
+    fn parse_at(data: &'a [u8], start: usize) -> Result<Self, EnvelopeError> {
+        let rest = &data[start..];
+        let (isa, delimiters) = IsaSegment::parse(rest)?;
+        let mut builder = Open {
+            delimiters,
+            groups: Vec::new(),
+            group: None,
+            transaction: None,
+        };
+        let mut end = rest.len();
+        let mut iea = None;
+        for (span, segment) in delimiters.segments(rest).spanned().skip(1) {
+            let span = Span::new(start + span.start, start + span.end);
+            match segment.id() {
+                b"GS" => {
+                    builder.close_group(data);
+                    builder.group = Some(FunctionalGroup {
+                        span,
+                        gs: GsSegment::parse(&segment)?,
+                        transactions: Vec::new(),
+                        ge: None,
+                    });
+                }
+                b"GE" => {
+                    builder.close_transaction(data);
+                    let mut group = builder
+                        .group
+                        .take()
+                        .ok_or(EnvelopeError::MissingSegment { expected: "GS" })?;
+                    group.ge = Some(GeSegment::parse(&segment)?);
+                    group.span.end = span.end;
+                    builder.groups.push(group);
+                }
+                b"ST" => {
+                    if builder.group.is_none() {
+                        return Err(EnvelopeError::MissingSegment { expected: "GS" });
+                    }
+                    builder.close_transaction(data);
+                    builder.transaction = Some((span, StSegment::parse(&segment)?, 1));
+                }
+                b"SE" => {
+                    let (open, st, count) = builder
+                        .transaction
+                        .take()
+                        .ok_or(EnvelopeError::MissingSegment { expected: "ST" })?;
+                    let se = SeSegment::parse(&segment)?;
+                    let span = Span::new(open.start, span.end);
+                    builder.push_transaction(data, span, st, Some(se), count + 1);
+                }
+                b"IEA" => {
+                    builder.close_group(data);
+                    iea = Some(IeaSegment::parse(&segment)?);
+                    end = span.end - start;
+                    break;
+                }
+                _ => match &mut builder.transaction {
+                    Some((open, _, count)) => {
+                        open.end = span.end;
+                        *count += 1;
+                    }
+                    None => return Err(EnvelopeError::MissingSegment { expected: "ST" }),
+                },
+            }
+        }
+        builder.close_group(data);
+        Ok(Interchange {
+            bytes: &rest[..end],
+            span: Span::new(start, start + end),
+            isa,
+            delimiters,
+            groups: builder.groups,
+            iea,
+        })
+    }
+
+    /// Returns the interchange from its ISA through its IEA terminator.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the position of the interchange in the parsed input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the ISA header.
+    pub fn isa(&self) -> &IsaSegment {
+        &self.isa
+    }
+
+    /// Returns the delimiters declared by the ISA.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the functional groups in document order.
+    pub fn groups(&self) -> &[FunctionalGroup<'a>] {
+        &self.groups
+    }
+
+    /// Returns the first functional group whose GS06 is `control_number`.
+    pub fn group(&self, control_number: u32) -> Option<&FunctionalGroup<'a>> {
+        self.groups
+            .iter()
+            .find(|group| group.gs.control_number() == control_number)
+    }
+
+    /// Returns every transaction set in every group, in document order.
+    pub fn transactions(&self) -> impl Iterator<Item = &TransactionSet<'a>> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.transactions.iter())
+    }
+
+    /// Returns the IEA trailer, or None if the interchange has none.
+    pub fn iea(&self) -> Option<&IeaSegment> {
+        self.iea.as_ref()
+    }
+}
+
+/// One GS/GE functional group and the transaction sets it encloses.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FunctionalGroup<'a> {
+    span: Span,
+    gs: GsSegment,
+    transactions: Vec<TransactionSet<'a>>,
+    ge: Option<GeSegment>,
+}
+
+impl<'a> FunctionalGroup<'a> {
+    /// Returns the position of the group in the parsed input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the GS header.
+    pub fn gs(&self) -> &GsSegment {
+        &self.gs
+    }
+
+    /// Returns the transaction sets in document order.
+    pub fn transactions(&self) -> &[TransactionSet<'a>] {
+        &self.transactions
+    }
+
+    /// Returns the first transaction set whose ST02 is `control_number`.
+    pub fn transaction(&self, control_number: u32) -> Option<&TransactionSet<'a>> {
+        self.transactions
+            .iter()
+            .find(|transaction| transaction.st.control_number() == control_number)
+    }
+
+    /// Returns the GE trailer, or None if the group has none.
+    pub fn ge(&self) -> Option<&GeSegment> {
+        self.ge.as_ref()
+    }
+}
+
+/// One ST/SE transaction set, borrowed from the input.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TransactionSet<'a> {
+    bytes: &'a [u8],
+    span: Span,
+    delimiters: Delimiters,
+    st: StSegment,
+    se: Option<SeSegment>,
+    segment_count: usize,
+}
+
+impl<'a> TransactionSet<'a> {
+    /// Returns the transaction from its ST through its SE terminator.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the position of the transaction in the parsed input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the ST header.
+    pub fn st(&self) -> &StSegment {
+        &self.st
+    }
+
+    /// Returns the SE trailer, or None if the transaction has none.
+    pub fn se(&self) -> Option<&SeSegment> {
+        self.se.as_ref()
+    }
+
+    /// Returns the number of segments from the ST through the SE, as SE01 should declare.
+    pub fn segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Returns the segments of the transaction, ST through SE.
+    pub fn segments(&self) -> SegmentIter<'a> {
+        self.delimiters.segments(self.bytes)
+    }
+}
+
+/// The envelopes still open while parsing an interchange.
+struct Open<'a> {
+    delimiters: Delimiters,
+    groups: Vec<FunctionalGroup<'a>>,
+    group: Option<FunctionalGroup<'a>>,
+    /// The span from the ST through the last segment so far, the header, and the number
+    /// of segments.
+    transaction: Option<(Span, StSegment, usize)>,
+}
+
+impl<'a> Open<'a> {
+    /// Ends the open transaction, which has no SE, after its last segment.
+    fn close_transaction(&mut self, data: &'a [u8]) {
+        if let Some((span, st, segment_count)) = self.transaction.take() {
+            self.push_transaction(data, span, st, None, segment_count);
+        }
+    }
+
+    /// Ends the open group, which has no GE, after its last transaction.
+    fn close_group(&mut self, data: &'a [u8]) {
+        self.close_transaction(data);
+        if let Some(group) = self.group.take() {
+            self.groups.push(group);
+        }
+    }
+
+    fn push_transaction(
+        &mut self,
+        data: &'a [u8],
+        span: Span,
+        st: StSegment,
+        se: Option<SeSegment>,
+        segment_count: usize,
+    ) {
+        if let Some(group) = &mut self.group {
+            group.span.end = span.end;
+            group.transactions.push(TransactionSet {
+                bytes: &data[span.start..span.end],
+                span,
+                delimiters: self.delimiters,
+                st,
+                se,
+                segment_count,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{interchange, isa};
+
+    #[test]
+    fn test_document_hierarchy() {
+        let first = interchange(
+            1,
+            "GS*HC*S*R*20250403*0856*7*X*005010X222A1~ST*837*0001~CLM*A~SE*3*0001~ST*837*0002~SE*2*0002~GE*2*7~",
+        );
+        let second = interchange(
+            2,
+            "GS*HS*S*R*20250403*0856*8*X*005010X279A1~ST*270*0001~SE*2*0001~GE*1*8~",
+        )
+        .replace('*', "|")
+        .replace('~', "}\r\n");
+        let data = format!("{}\n{}", first, second);
+        let document = Document::parse(data.as_bytes()).unwrap();
+        assert_eq!(document.interchanges().len(), 2);
+
+        let interchange = document.interchange(1).unwrap();
+        assert_eq!(interchange.as_bytes(), first.as_bytes());
+        assert_eq!(interchange.iea().unwrap().group_count(), 1);
+        let controls: Vec<_> = interchange
+            .transactions()
+            .map(|t| (t.st().control_number(), t.segment_count()))
+            .collect();
+        assert_eq!(controls, [(1, 3), (2, 2)]);
+        let group = interchange.group(7).unwrap();
+        assert_eq!(group.ge().unwrap().transaction_count(), 2);
+        assert_eq!(
+            &data.as_bytes()[group.span().start..group.span().end],
+            b"GS*HC*S*R*20250403*0856*7*X*005010X222A1~ST*837*0001~CLM*A~SE*3*0001~ST*837*0002~SE*2*0002~GE*2*7~"
+        );
+        assert!(interchange.group(8).is_none());
+
+        let other = document.interchange(2).unwrap();
+        assert_eq!(other.span().start, first.len() + 1);
+        assert_eq!(other.delimiters().element_separator(), b'|');
+        let transaction = other.group(8).unwrap().transaction(1).unwrap();
+        assert_eq!(transaction.as_bytes(), b"ST|270|0001}\r\nSE|2|0001}");
+        assert_eq!(transaction.segments().count(), 2);
+        assert_eq!(document.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_missing_trailers() {
+        let data = isa(3)
+            + "GS*HC*S*R*20250403*0856*1*X*005010~ST*837*0001~CLM*A~GS*HC*S*R*20250403*0856*2*X*005010~ST*837*0002~";
+        let interchange = Interchange::parse(data.as_bytes()).unwrap();
+        assert!(interchange.iea().is_none());
+        assert_eq!(interchange.as_bytes(), data.as_bytes());
+        let groups = interchange.groups();
+        assert_eq!(groups.len(), 2);
+        assert!(groups[0].ge().is_none());
+        let transaction = &groups[0].transactions()[0];
+        assert!(transaction.se().is_none());
+        assert_eq!(transaction.as_bytes(), b"ST*837*0001~CLM*A~");
+        assert_eq!(transaction.segment_count(), 2);
+        assert_eq!(groups[1].transactions()[0].as_bytes(), b"ST*837*0002~");
+    }
+
+    #[test]
+    fn test_structure_errors() {
+        let parse = |body: &str| Document::parse(interchange(1, body).as_bytes()).map(|_| ());
+        assert_eq!(
+            parse("ST*837*0001~SE*2*0001~"),
+            Err(EnvelopeError::MissingSegment { expected: "GS" })
+        );
+        assert_eq!(
+            parse("GS*HC*S*R*20250403*0856*1*X*005010~CLM*A~"),
+            Err(EnvelopeError::MissingSegment { expected: "ST" })
+        );
+        assert_eq!(
+            parse("GS*HC*S*R*20250403*0856*1*X*005010~GE*0*X~"),
+            Err(EnvelopeError::InvalidValue {
+                id: "GE",
+                element: 2
+            })
+        );
+        assert!(matches!(
+            Document::parse(format!("{}\ntrailing", interchange(1, "")).as_bytes()),
+            Err(EnvelopeError::Isa(_))
+        ));
+        assert_eq!(Document::parse(b" \r\n").unwrap().interchanges().len(), 0);
+    }
+
+    #[test]
+    fn test_interchanges_with_mixed_delimiters() {
+        let first = interchange(1, "GS*HC*S*R~");
+        let second = interchange(2, "").replace('*', "|").replace('~', "'\n");
+        let data = format!("\r\n{}\r\n{}\r\n", first, second);
+        let mut iter = interchanges(data.as_bytes());
+        let (delimiters, bytes) = iter.next().unwrap();
+        assert_eq!(delimiters.element_separator(), b'*');
+        assert_eq!(bytes, first.as_bytes());
+        let (delimiters, bytes) = iter.next().unwrap();
+        assert_eq!(delimiters.segment_terminator(), b'\'');
+        // The line ending after the IEA terminator is not part of the interchange.
+        assert_eq!(bytes, second.trim_end().as_bytes());
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.remainder(), b"\n\r\n");
+
+        let spans: Vec<_> = interchanges(data.as_bytes())
+            .spanned()
+            .map(|(span, _, _)| span)
+            .collect();
+        assert_eq!(spans[0], Span::new(2, 2 + first.len()));
+        assert_eq!(spans[1].end, data.len() - 3);
+    }
+
+    #[test]
+    fn test_interchanges_stops_at_trailing_data() {
+        let data = format!("{}GARBAGE", interchange(1, ""));
+        let mut iter = interchanges(data.as_bytes());
+        assert!(iter.next().is_some());
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.remainder(), b"GARBAGE");
+        assert_eq!(interchanges(b"").count(), 0);
+    }
+}
@@ -0,0 +1,756 @@
+use crate::control::ControlField;
+use crate::errors::{DelimiterError, IsaError};
+use crate::preview::escape_into;
+use crate::{Delimiters, TerminatorSuffix};
+
+const AUTHORIZATION_INFO_WIDTH: usize = 10;
+const SECURITY_INFO_WIDTH: usize = 10;
+const ID_WIDTH: usize = 15;
+const CONTROL_NUMBER_WIDTH: usize = 9;
+const ISA_LENGTH: usize = 106;
+const ISA_ELEMENT_SEPARATOR_INDEX: usize = 3;
+
+/// Required widths of ISA01 through ISA16.
+pub(crate) const ELEMENT_WIDTHS: [usize; 16] = [2, 10, 2, 10, 2, 15, 2, 15, 6, 4, 1, 5, 9, 1, 1, 1];
+
+/// First ISA12 version read with the fixed 4010-and-later layout.
+const FIRST_STANDARD_VERSION: u32 = 401;
+
+/// ISA elements masked by [`redact_isa_for_log`]: authorization and security information,
+/// sender and receiver IDs, and the control number.
+const REDACTED_ELEMENTS: [usize; 5] = [2, 4, 6, 8, 13];
+
+/// Which ISA layout rules [`IsaSegment::parse_with`] applies.
+///
+/// Interchanges before version 00401 (3050-era and older) were often written by
+/// translators that trimmed ISA fields instead of padding them to their fixed widths, so
+/// the 106-byte layout, and with it the byte offsets of ISA16 and the segment terminator,
+/// cannot be relied on. ISA11 is the standards identifier in these versions, never a
+/// repetition separator.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum IsaCompatibility {
+    /// Choose from ISA12: [`Self::Legacy`] for a five-digit version before 00401,
+    /// [`Self::Standard`] otherwise.
+    #[default]
+    Infer,
+    /// Every element must have its fixed width, and ISA13 must have nine digits.
+    Standard,
+    /// Element widths are not checked and ISA13 may have one to nine digits. ISA16 and the
+    /// segment terminator are the two bytes after the fifteenth element separator, and
+    /// there is no repetition separator.
+    Legacy,
+}
+
+impl IsaCompatibility {
+    /// Returns the mode [`Self::Infer`] picks for an ISA12 value.
+    pub fn for_version(isa12: &[u8]) -> Self {
+        let legacy = isa12.len() == 5
+            && isa12.iter().all(u8::is_ascii_digit)
+            && isa12.iter().fold(0, |n, &b| n * 10 + u32::from(b - b'0')) < FIRST_STANDARD_VERSION;
+        if legacy {
+            IsaCompatibility::Legacy
+        } else {
+            IsaCompatibility::Standard
+        }
+    }
+}
+
+/// Field values of an ISA interchange control header.
+///
+/// The delimiters are not stored here: ISA16 and the separators between elements come
+/// from the [`Delimiters`] the segment is written with.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IsaSegment {
+    authorization_qualifier: String,
+    authorization_info: String,
+    security_qualifier: String,
+    security_info: String,
+    sender_qualifier: String,
+    sender_id: String,
+    receiver_qualifier: String,
+    receiver_id: String,
+    date: String,
+    time: String,
+    repetition_separator: u8,
+    version: String,
+    control_number: u32,
+    acknowledgment_requested: bool,
+    usage_indicator: u8,
+}
+
+impl IsaSegment {
+    /// Creates an ISA header for a production 005010 interchange.
+    ///
+    /// Authorization and security information are left blank (qualifier `00`), no
+    /// acknowledgment is requested, and ISA11 is `^` unless the delimiters the segment is
+    /// written with set a repetition separator.
+    ///
+    /// # Arguments
+    /// * `sender_qualifier` / `sender_id` - ISA05 and ISA06
+    /// * `receiver_qualifier` / `receiver_id` - ISA07 and ISA08
+    /// * `date` - ISA09 as `YYMMDD`
+    /// * `time` - ISA10 as `HHMM`
+    /// * `control_number` - ISA13, written zero-padded to nine digits
+    pub fn new(
+        sender_qualifier: &str,
+        sender_id: &str,
+        receiver_qualifier: &str,
+        receiver_id: &str,
+        date: &str,
+        time: &str,
+        control_number: u32,
+    ) -> Self {
+        IsaSegment {
+            authorization_qualifier: "00".to_string(),
+            authorization_info: String::new(),
+            security_qualifier: "00".to_string(),
+            security_info: String::new(),
+            sender_qualifier: sender_qualifier.to_string(),
+            sender_id: sender_id.to_string(),
+            receiver_qualifier: receiver_qualifier.to_string(),
+            receiver_id: receiver_id.to_string(),
+            date: date.to_string(),
+            time: time.to_string(),
+            repetition_separator: b'^',
+            version: "00501".to_string(),
+            control_number,
+            acknowledgment_requested: false,
+            usage_indicator: b'P',
+        }
+    }
+
+    /// Sets ISA01 and ISA02.
+    pub fn with_authorization(mut self, qualifier: &str, info: &str) -> Self {
+        self.authorization_qualifier = qualifier.to_string();
+        self.authorization_info = info.to_string();
+        self
+    }
+
+    /// Sets ISA03 and ISA04.
+    pub fn with_security(mut self, qualifier: &str, info: &str) -> Self {
+        self.security_qualifier = qualifier.to_string();
+        self.security_info = info.to_string();
+        self
+    }
+
+    /// Sets ISA11, written when the delimiters have no repetition separator.
+    pub fn with_repetition_separator(mut self, repetition_separator: u8) -> Self {
+        self.repetition_separator = repetition_separator;
+        self
+    }
+
+    /// Sets ISA12, e.g. `00401` or `00501`.
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Sets ISA14.
+    pub fn with_acknowledgment_requested(mut self, requested: bool) -> Self {
+        self.acknowledgment_requested = requested;
+        self
+    }
+
+    /// Sets ISA15, `P` for production or `T` for test data.
+    pub fn with_usage_indicator(mut self, usage_indicator: u8) -> Self {
+        self.usage_indicator = usage_indicator;
+        self
+    }
+
+    /// Parses every element of the ISA segment at the start of `data`.
+    ///
+    /// Elements are split on the element separator at byte 3 and each must have its fixed
+    /// width, so a misaligned ISA is reported by element instead of yielding delimiters
+    /// read from the wrong positions. Padding is removed from ISA02, ISA04, ISA06 and
+    /// ISA08. ISA16 is returned as part of the delimiters.
+    ///
+    /// ```
+    /// use x12_delimiters::isa::IsaSegment;
+    ///
+    /// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+    /// let (isa, delimiters) = IsaSegment::parse(data).unwrap();
+    /// assert_eq!(isa.sender_id(), "SENDERID");
+    /// assert_eq!(isa.control_number(), 1);
+    /// assert_eq!(delimiters.sub_element_separator(), b':');
+    /// assert_eq!(isa.to_bytes(&delimiters), data);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `IsaError::Delimiters` if `data` does not start with `ISA`,
+    /// `IsaError::MissingElement` if it ends before ISA16 and the terminator,
+    /// `IsaError::FieldWidth` for the first element with the wrong width, and
+    /// `IsaError::InvalidValue` if ISA13 is not numeric or ISA14 is not `0` or `1`.
+    pub fn parse(data: &[u8]) -> Result<(Self, Delimiters), IsaError> {
+        IsaSegment::parse_with(data, IsaCompatibility::Standard)
+    }
+
+    /// Like [`IsaSegment::parse`], applying the layout rules of `compatibility`.
+    ///
+    /// With [`IsaCompatibility::Infer`], a pre-4010 interchange is read leniently while a
+    /// later one is held to the fixed layout.
+    ///
+    /// ```
+    /// use x12_delimiters::isa::{IsaCompatibility, IsaSegment};
+    ///
+    /// let data = b"ISA*00**00**ZZ*SENDERID*ZZ*RECEIVERID*950403*0856*U*00305*17*0*P*:~GS*";
+    /// assert!(IsaSegment::parse(data).is_err());
+    ///
+    /// let (isa, delimiters) = IsaSegment::parse_with(data, IsaCompatibility::Infer).unwrap();
+    /// assert_eq!((isa.sender_id(), isa.control_number()), ("SENDERID", 17));
+    /// assert_eq!(delimiters.segment_terminator(), b'~');
+    /// assert_eq!(delimiters.repetition_separator(), None);
+    /// ```
+    ///
+    /// # Errors
+    /// Returns the errors of [`IsaSegment::parse`]; in legacy mode `IsaError::FieldWidth`
+    /// is not returned, and any bytes are accepted as delimiters as long as they are
+    /// distinct, failing with `DelimiterError::DuplicateDelimiter` otherwise.
+    pub fn parse_with(
+        data: &[u8],
+        compatibility: IsaCompatibility,
+    ) -> Result<(Self, Delimiters), IsaError> {
+        if !data.starts_with(b"ISA") {
+            return Err(DelimiterError::NotIsaSegment.into());
+        }
+        let separator = *data
+            .get(ISA_ELEMENT_SEPARATOR_INDEX)
+            .ok_or(IsaError::MissingElement { element: 1 })?;
+        let mut elements: [&[u8]; 16] = [&[]; 16];
+        let mut rest = &data[ISA_ELEMENT_SEPARATOR_INDEX + 1..];
+        for (i, slot) in elements.iter_mut().enumerate().take(15) {
+            let end = rest
+                .iter()
+                .position(|&b| b == separator)
+                .ok_or(IsaError::MissingElement { element: i + 1 })?;
+            *slot = &rest[..end];
+            rest = &rest[end + 1..];
+        }
+        // ISA16 is a single byte and the segment terminator directly follows it.
+        if rest.len() < 2 {
+            return Err(IsaError::MissingElement { element: 16 });
+        }
+        elements[15] = &rest[..1];
+        let legacy = match compatibility {
+            IsaCompatibility::Infer => IsaCompatibility::for_version(elements[11]),
+            mode => mode,
+        } == IsaCompatibility::Legacy;
+        let delimiters = if legacy {
+            // Older senders often end segments with a bare CR or LF, so only distinctness
+            // is checked here.
+            let delimiters = Delimiters::new(rest[1], separator, rest[0]);
+            if !delimiters.are_valid() {
+                let duplicate = if separator == rest[0] {
+                    separator
+                } else {
+                    rest[1]
+                };
+                return Err(DelimiterError::DuplicateDelimiter(duplicate).into());
+            }
+            delimiters.with_suffix(TerminatorSuffix::detect(&rest[2..]))
+        } else {
+            for (i, (element, &expected)) in elements.iter().zip(&ELEMENT_WIDTHS).enumerate() {
+                if element.len() != expected {
+                    return Err(IsaError::FieldWidth {
+                        element: i + 1,
+                        expected,
+                        actual: element.len(),
+                    });
+                }
+            }
+            Delimiters::from_isa_strict(data)?
+        };
+
+        let text = |n: usize| String::from_utf8_lossy(elements[n - 1]).into_owned();
+        let padded = |n: usize| text(n).trim_end_matches(' ').to_string();
+        let control_field = if legacy {
+            ControlField::Group
+        } else {
+            ControlField::Interchange
+        };
+        let control_number = control_field
+            .parse(elements[12])
+            .map_err(|_| IsaError::InvalidValue { element: 13 })?;
+        let acknowledgment_requested = match elements[13] {
+            b"0" => false,
+            b"1" => true,
+            _ => return Err(IsaError::InvalidValue { element: 14 }),
+        };
+        let isa = IsaSegment {
+            authorization_qualifier: text(1),
+            authorization_info: padded(2),
+            security_qualifier: text(3),
+            security_info: padded(4),
+            sender_qualifier: text(5),
+            sender_id: padded(6),
+            receiver_qualifier: text(7),
+            receiver_id: padded(8),
+            date: text(9),
+            time: text(10),
+            repetition_separator: elements[10].first().copied().unwrap_or(b'U'),
+            version: text(12),
+            control_number,
+            acknowledgment_requested,
+            usage_indicator: elements[14].first().copied().unwrap_or(b'P'),
+        };
+        Ok((isa, delimiters))
+    }
+
+    /// Returns ISA01, the authorization information qualifier.
+    pub fn authorization_qualifier(&self) -> &str {
+        &self.authorization_qualifier
+    }
+
+    /// Returns ISA02, the authorization information, without padding.
+    pub fn authorization_info(&self) -> &str {
+        &self.authorization_info
+    }
+
+    /// Returns ISA03, the security information qualifier.
+    pub fn security_qualifier(&self) -> &str {
+        &self.security_qualifier
+    }
+
+    /// Returns ISA04, the security information, without padding.
+    pub fn security_info(&self) -> &str {
+        &self.security_info
+    }
+
+    /// Returns ISA05, the sender ID qualifier.
+    pub fn sender_qualifier(&self) -> &str {
+        &self.sender_qualifier
+    }
+
+    /// Returns ISA06, the interchange sender ID, without padding.
+    pub fn sender_id(&self) -> &str {
+        &self.sender_id
+    }
+
+    /// Returns ISA07, the receiver ID qualifier.
+    pub fn receiver_qualifier(&self) -> &str {
+        &self.receiver_qualifier
+    }
+
+    /// Returns ISA08, the interchange receiver ID, without padding.
+    pub fn receiver_id(&self) -> &str {
+        &self.receiver_id
+    }
+
+    /// Returns ISA09, the interchange date as `YYMMDD`.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Returns ISA10, the interchange time as `HHMM`.
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// Returns ISA11, the repetition separator or standards identifier as written.
+    ///
+    /// [`Delimiters::repetition_separator`] tells which of the two it is.
+    pub fn repetition_separator(&self) -> u8 {
+        self.repetition_separator
+    }
+
+    /// Returns ISA12, the interchange control version number.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns ISA13, the interchange control number.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Returns ISA14, whether an interchange acknowledgment is requested.
+    pub fn acknowledgment_requested(&self) -> bool {
+        self.acknowledgment_requested
+    }
+
+    /// Returns ISA15, `P` for production or `T` for test data.
+    pub fn usage_indicator(&self) -> u8 {
+        self.usage_indicator
+    }
+
+    /// Serializes the segment, including the segment terminator but not the suffix.
+    ///
+    /// Fixed-width fields are padded with spaces and the control number with zeros, so
+    /// fields within their maximum widths produce a 106-byte segment. ISA11 is the
+    /// repetition separator of `delimiters` if they have one.
+    pub fn to_bytes(&self, delimiters: &Delimiters) -> Vec<u8> {
+        let mut out = b"ISA".to_vec();
+        for field in self.fields(delimiters) {
+            out.push(delimiters.element_separator());
+            out.extend_from_slice(&field);
+        }
+        out.push(delimiters.element_separator());
+        out.push(delimiters.sub_element_separator());
+        out.push(delimiters.segment_terminator());
+        out
+    }
+
+    /// Like [`IsaSegment::to_bytes`], but fails instead of writing a segment that is not
+    /// exactly 106 bytes or that `delimiters` cannot split back into the same fields.
+    ///
+    /// Padded fields (ISA02, ISA04, ISA06 and ISA08) may be shorter than their width; every
+    /// other field must have its exact width.
+    ///
+    /// ```
+    /// use x12_delimiters::errors::IsaError;
+    /// use x12_delimiters::isa::IsaSegment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let delimiters = Delimiters::default();
+    /// let isa = IsaSegment::new("ZZ", "SENDERID", "ZZ", "RECEIVERID", "250403", "0856", 1);
+    /// assert_eq!(isa.try_to_bytes(&delimiters).unwrap().len(), 106);
+    ///
+    /// let long = IsaSegment::new("ZZ", "A-SENDER-ID-OVER-15", "ZZ", "R", "250403", "0856", 1);
+    /// assert_eq!(
+    ///     long.try_to_bytes(&delimiters),
+    ///     Err(IsaError::FieldWidth { element: 6, expected: 15, actual: 19 })
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `IsaError::FieldWidth` for the first field with the wrong width, and
+    /// `IsaError::InvalidValue` for the first field containing one of the delimiters.
+    pub fn try_to_bytes(&self, delimiters: &Delimiters) -> Result<Vec<u8>, IsaError> {
+        for (i, field) in self.fields(delimiters).iter().enumerate() {
+            let expected = ELEMENT_WIDTHS[i];
+            if field.len() != expected {
+                return Err(IsaError::FieldWidth {
+                    element: i + 1,
+                    expected,
+                    actual: field.len(),
+                });
+            }
+            // ISA11 declares the repetition separator, so only ISA11 may contain it.
+            let is_delimiter = |&b: &u8| {
+                b == delimiters.segment_terminator()
+                    || b == delimiters.element_separator()
+                    || b == delimiters.sub_element_separator()
+                    || (i != 10 && delimiters.repetition_separator() == Some(b))
+            };
+            if field.iter().any(is_delimiter) {
+                return Err(IsaError::InvalidValue { element: i + 1 });
+            }
+        }
+        Ok(self.to_bytes(delimiters))
+    }
+
+    /// Returns ISA01 through ISA15 as written with `delimiters`, with padding applied.
+    pub(crate) fn fields(&self, delimiters: &Delimiters) -> [Vec<u8>; 15] {
+        [
+            self.authorization_qualifier.clone().into_bytes(),
+            pad(&self.authorization_info, AUTHORIZATION_INFO_WIDTH),
+            self.security_qualifier.clone().into_bytes(),
+            pad(&self.security_info, SECURITY_INFO_WIDTH),
+            self.sender_qualifier.clone().into_bytes(),
+            pad(&self.sender_id, ID_WIDTH),
+            self.receiver_qualifier.clone().into_bytes(),
+            pad(&self.receiver_id, ID_WIDTH),
+            self.date.clone().into_bytes(),
+            self.time.clone().into_bytes(),
+            vec![delimiters
+                .repetition_separator()
+                .unwrap_or(self.repetition_separator)],
+            self.version.clone().into_bytes(),
+            format!(
+                "{:0width$}",
+                self.control_number,
+                width = CONTROL_NUMBER_WIDTH
+            )
+            .into_bytes(),
+            if self.acknowledgment_requested {
+                b"1".to_vec()
+            } else {
+                b"0".to_vec()
+            },
+            vec![self.usage_indicator],
+        ]
+    }
+}
+
+/// Renders a (possibly malformed) ISA segment for logs with identifying fields masked.
+///
+/// ISA02, ISA04, ISA06, ISA08 and ISA13 have every non-space byte replaced with `X` (or `#`
+/// if `X` is a delimiter), so padding, field widths, and all delimiters stay visible. Only
+/// the first 106 bytes are rendered; the number of omitted bytes is appended. Bytes outside
+/// printable ASCII are escaped (`\r`, `\n`, `\t`, `\xNN`) so the result is a single log line.
+///
+/// ```
+/// use x12_delimiters::isa::redact_isa_for_log;
+///
+/// let isa = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\nGS*";
+/// assert_eq!(
+///     redact_isa_for_log(isa),
+///     "ISA*00*          *00*          *ZZ*XXXXXXXX       *ZZ*XXXXXXXXXX     *250403*0856*^*00501*XXXXXXXXX*0*P*:~ [+5 bytes]"
+/// );
+/// ```
+pub fn redact_isa_for_log(isa: &[u8]) -> String {
+    let head = &isa[..isa.len().min(ISA_LENGTH)];
+    let mut redacted = head.to_vec();
+
+    if let Some(&separator) = head.get(ISA_ELEMENT_SEPARATOR_INDEX) {
+        let terminator = head.get(ISA_LENGTH - 1).copied();
+        let mask = if separator == b'X' || terminator == Some(b'X') {
+            b'#'
+        } else {
+            b'X'
+        };
+        let mut element = 0;
+        for byte in redacted.iter_mut().skip(ISA_ELEMENT_SEPARATOR_INDEX) {
+            if *byte == separator {
+                element += 1;
+            } else if REDACTED_ELEMENTS.contains(&element) && *byte != b' ' {
+                *byte = mask;
+            }
+        }
+    }
+
+    let mut out = String::with_capacity(redacted.len() + 16);
+    escape_into(&mut out, &redacted);
+    if isa.len() > head.len() {
+        out.push_str(&format!(" [+{} bytes]", isa.len() - head.len()));
+    }
+    out
+}
+
+fn pad(value: &str, width: usize) -> Vec<u8> {
+    format!("{:<width$}", value, width = width).into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_ISA_SEGMENT_STANDARD: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    #[test]
+    fn test_to_bytes_pads_fields() {
+        let isa = IsaSegment::new("ZZ", "SENDERID", "ZZ", "RECEIVERID", "250403", "0856", 1);
+        let bytes = isa.to_bytes(&Delimiters::default());
+        assert_eq!(bytes, SAMPLE_ISA_SEGMENT_STANDARD);
+        assert_eq!(bytes.len(), 106);
+
+        let delimiters = Delimiters::default().with_repetition_separator(Some(b'{'));
+        let bytes = isa.to_bytes(&delimiters);
+        assert_eq!(bytes[82], b'{');
+        assert_eq!(Delimiters::from_isa(&bytes).unwrap(), delimiters);
+    }
+
+    #[test]
+    fn test_parse_reads_every_element() {
+        let delimiters = Delimiters::new(b'}', b'|', b'>');
+        let written = IsaSegment::new("01", "123456789", "14", "987654321", "991231", "2359", 42)
+            .with_authorization("03", "AUTH")
+            .with_security("01", "SECRET")
+            .with_version("00401")
+            .with_repetition_separator(b'U')
+            .with_usage_indicator(b'T')
+            .with_acknowledgment_requested(true);
+        let mut bytes = written.to_bytes(&delimiters);
+        bytes.extend_from_slice(b"\r\nGS|HC");
+
+        let (isa, parsed) = IsaSegment::parse(&bytes).unwrap();
+        assert_eq!(isa, written);
+        assert_eq!(parsed, Delimiters::from_isa(&bytes).unwrap());
+        assert_eq!(
+            (isa.authorization_qualifier(), isa.authorization_info()),
+            ("03", "AUTH")
+        );
+        assert_eq!(
+            (isa.security_qualifier(), isa.security_info()),
+            ("01", "SECRET")
+        );
+        assert_eq!(
+            (isa.sender_qualifier(), isa.receiver_qualifier()),
+            ("01", "14")
+        );
+        assert_eq!(
+            (isa.date(), isa.time(), isa.version()),
+            ("991231", "2359", "00401")
+        );
+        assert_eq!(
+            (isa.repetition_separator(), isa.usage_indicator()),
+            (b'U', b'T')
+        );
+        assert!(isa.acknowledgment_requested());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_elements() {
+        let text = std::str::from_utf8(SAMPLE_ISA_SEGMENT_STANDARD).unwrap();
+        let parse = |isa: String| IsaSegment::parse(isa.as_bytes()).map(|_| ());
+        assert_eq!(
+            parse(text.replacen("SENDERID       ", "SENDERID        ", 1)),
+            Err(IsaError::FieldWidth {
+                element: 6,
+                expected: 15,
+                actual: 16
+            })
+        );
+        assert_eq!(
+            parse(text.replacen("000000001", "00000000X", 1)),
+            Err(IsaError::InvalidValue { element: 13 })
+        );
+        assert_eq!(
+            parse(text.replacen("*0*P*", "*2*P*", 1)),
+            Err(IsaError::InvalidValue { element: 14 })
+        );
+        assert_eq!(
+            parse(text[..60].to_string()),
+            Err(IsaError::MissingElement { element: 8 })
+        );
+        assert_eq!(
+            parse(text.replacen("ISA", "GS*", 1)),
+            Err(IsaError::Delimiters(DelimiterError::NotIsaSegment))
+        );
+    }
+
+    #[test]
+    fn test_compatibility_for_version() {
+        assert_eq!(
+            IsaCompatibility::for_version(b"00305"),
+            IsaCompatibility::Legacy
+        );
+        assert_eq!(
+            IsaCompatibility::for_version(b"00401"),
+            IsaCompatibility::Standard
+        );
+        assert_eq!(
+            IsaCompatibility::for_version(b"305"),
+            IsaCompatibility::Standard
+        );
+    }
+
+    #[test]
+    fn test_parse_with_legacy_layout() {
+        let data = b"ISA*00**00**ZZ*SENDER*ZZ*RECV*950403*0856*U*00305*17*0*T*>!\r\nGS*";
+        assert_eq!(
+            IsaSegment::parse(data).map(|_| ()),
+            Err(IsaError::FieldWidth {
+                element: 2,
+                expected: 10,
+                actual: 0
+            })
+        );
+        for mode in [IsaCompatibility::Infer, IsaCompatibility::Legacy] {
+            let (isa, delimiters) = IsaSegment::parse_with(data, mode).unwrap();
+            assert_eq!((isa.receiver_id(), isa.version()), ("RECV", "00305"));
+            assert_eq!((isa.control_number(), isa.usage_indicator()), (17, b'T'));
+            assert_eq!(
+                delimiters,
+                Delimiters::new(b'!', b'*', b'>').with_suffix(TerminatorSuffix::CrLf)
+            );
+        }
+
+        let trimmed = b"ISA*00**00**ZZ*SENDER*ZZ*RECV*250403*0856*^*00501*17*0*P*:~";
+        assert!(matches!(
+            IsaSegment::parse_with(trimmed, IsaCompatibility::Infer),
+            Err(IsaError::FieldWidth { element: 2, .. })
+        ));
+        assert!(IsaSegment::parse_with(trimmed, IsaCompatibility::Legacy).is_ok());
+    }
+
+    #[test]
+    fn test_parse_with_legacy_line_terminator() {
+        for terminator in [b'\n', b'\r'] {
+            let mut data = b"ISA*00**00**ZZ*SENDER*ZZ*RECV*950403*0856*U*00305*17*0*T*>".to_vec();
+            data.push(terminator);
+            data.extend_from_slice(b"GS*HC*SENDER*RECV");
+            let (isa, delimiters) = IsaSegment::parse_with(&data, IsaCompatibility::Infer).unwrap();
+            assert_eq!(isa.control_number(), 17);
+            assert_eq!(delimiters, Delimiters::new(terminator, b'*', b'>'));
+        }
+
+        let duplicate = b"ISA*00**00**ZZ*SENDER*ZZ*RECV*950403*0856*U*00305*17*0*T*>*GS";
+        assert_eq!(
+            IsaSegment::parse_with(duplicate, IsaCompatibility::Legacy).map(|_| ()),
+            Err(IsaError::Delimiters(DelimiterError::DuplicateDelimiter(
+                b'*'
+            )))
+        );
+    }
+
+    #[test]
+    fn test_try_to_bytes_validates_fields() {
+        let isa = IsaSegment::new("ZZ", "SENDERID", "ZZ", "RECEIVERID", "250403", "0856", 1);
+        let delimiters = Delimiters::default();
+        assert_eq!(
+            isa.try_to_bytes(&delimiters).unwrap(),
+            SAMPLE_ISA_SEGMENT_STANDARD
+        );
+        assert_eq!(
+            isa.clone().with_version("5010").try_to_bytes(&delimiters),
+            Err(IsaError::FieldWidth {
+                element: 12,
+                expected: 5,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            IsaSegment::new("ZZ", "S", "ZZ", "R", "250403", "0856", 1_000_000_000)
+                .try_to_bytes(&delimiters),
+            Err(IsaError::FieldWidth {
+                element: 13,
+                expected: 9,
+                actual: 10
+            })
+        );
+        assert_eq!(
+            isa.clone()
+                .with_repetition_separator(b':')
+                .try_to_bytes(&delimiters),
+            Err(IsaError::InvalidValue { element: 11 })
+        );
+        assert_eq!(
+            isa.with_authorization("00", "A*B")
+                .try_to_bytes(&delimiters),
+            Err(IsaError::InvalidValue { element: 2 })
+        );
+    }
+
+    #[test]
+    fn test_redact_preserves_structure() {
+        let redacted = redact_isa_for_log(SAMPLE_ISA_SEGMENT_STANDARD);
+        assert_eq!(redacted.len(), SAMPLE_ISA_SEGMENT_STANDARD.len());
+        assert!(!redacted.contains("SENDERID"));
+        assert!(!redacted.contains("RECEIVERID"));
+        assert!(!redacted.contains("000000001"));
+        assert_eq!(
+            Delimiters::from_isa(redacted.as_bytes()).unwrap(),
+            Delimiters::default().with_repetition_separator(Some(b'^'))
+        );
+    }
+
+    #[test]
+    fn test_redact_masks_credentials_and_escapes() {
+        let isa = IsaSegment::new("01", "SENDERID", "ZZ", "RCV", "250403", "0856", 7)
+            .with_security("01", "SECRET")
+            .to_bytes(&Delimiters::new(0x85, b'X', b'>'));
+        let redacted = redact_isa_for_log(&isa);
+        assert!(redacted.starts_with("ISAX00X          X01X######    X01X########"));
+        assert!(redacted.ends_with("X#########X0XPX>\\x85"));
+    }
+
+    #[test]
+    fn test_redact_truncated_input() {
+        assert_eq!(redact_isa_for_log(b"ISA*00*AUTH"), "ISA*00*XXXX");
+        assert_eq!(redact_isa_for_log(b"IS"), "IS");
+    }
+
+    #[test]
+    fn test_to_bytes_roundtrips_through_from_isa() {
+        let delimiters = Delimiters::new(b'}', b'|', b'>');
+        let isa = IsaSegment::new("01", "123456789", "14", "987654321", "991231", "2359", 42)
+            .with_version("00401")
+            .with_repetition_separator(b'U')
+            .with_usage_indicator(b'T')
+            .with_acknowledgment_requested(true);
+        let bytes = isa.to_bytes(&delimiters);
+        assert_eq!(bytes.len(), 106);
+        assert_eq!(Delimiters::from_isa(&bytes).unwrap(), delimiters);
+        assert!(bytes.ends_with(b"|U|00401|000000042|1|T|>}"));
+    }
+}
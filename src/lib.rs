@@ -1,4 +1,61 @@
+pub mod ack;
+pub mod archive;
+pub mod builder;
+pub mod canonical;
+pub mod charset;
+pub mod codepage;
+pub mod concat;
+pub mod conflict;
+pub mod control;
+pub mod counts;
+pub mod dedupe;
+pub mod detect;
+pub mod diff;
+pub mod element;
+pub mod envelope;
 pub mod errors;
+pub mod extract;
+#[cfg(feature = "json")]
+pub mod feed;
+pub mod file;
+pub mod fixed;
+pub mod functional_ack;
+pub mod index;
+pub mod intake;
+pub mod interchange;
+pub mod isa;
+pub mod manifest;
+pub mod names;
+pub mod outbound;
+pub mod pretty;
+pub mod preview;
+pub mod profile;
+pub mod push;
+pub mod reader;
+pub mod reconcile;
+pub mod redundancy;
+pub mod roundtrip;
+pub mod sample;
+pub mod search;
+pub mod segment;
+pub mod sequence;
+pub mod shared;
+pub mod split;
+pub mod suffix;
+pub mod summary;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod token;
+pub mod transcode;
+#[cfg(all(feature = "io_uring", target_os = "linux"))]
+pub mod uring;
+pub mod validation;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod watch;
+pub mod writer;
+
+use std::ops::RangeInclusive;
 
 use errors::DelimiterError;
 
@@ -6,10 +63,143 @@ const DEFAULT_SEGMENT_TERMINATOR: u8 = b'~';
 const DEFAULT_ELEMENT_SEPARATOR: u8 = b'*';
 const DEFAULT_SUB_ELEMENT_SEPARATOR: u8 = b':';
 
+const DEFAULT_ALLOWED_BYTES: RangeInclusive<u8> = 0x21..=0x7E;
+
 const ISA_MIN_LENGTH: usize = 106;
 const ISA_ELEMENT_SEPARATOR_INDEX: usize = 3;
 const ISA_SUB_ELEMENT_SEPARATOR_INDEX: usize = 104;
 const ISA_SEGMENT_TERMINATOR_INDEX: usize = 105;
+const ISA_REPETITION_SEPARATOR_INDEX: usize = 82;
+const ISA_VERSION_RANGE: std::ops::Range<usize> = 84..89;
+const FIRST_REPETITION_VERSION: u32 = 402;
+
+/// Positions of the element separators after ISA01 through ISA15, which are fixed width.
+const ISA_ELEMENT_BOUNDARIES: [usize; 16] =
+    [3, 6, 17, 20, 31, 34, 50, 53, 69, 76, 81, 83, 89, 99, 101, 103];
+
+/// Line-ending bytes that some senders append after every segment terminator.
+///
+/// Many X12 files are written one segment per line, so the terminator is followed by
+/// `\n` or `\r\n`. The suffix is cosmetic: it carries no data and is not part of the
+/// following segment.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum TerminatorSuffix {
+    /// The terminator is not followed by any line-ending bytes.
+    #[default]
+    None,
+    /// The terminator is followed by `\n`.
+    Lf,
+    /// The terminator is followed by `\r\n`.
+    CrLf,
+    /// The terminator is followed by `\r`.
+    Cr,
+}
+
+impl TerminatorSuffix {
+    /// Returns the bytes written after the segment terminator for this suffix.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            TerminatorSuffix::None => b"",
+            TerminatorSuffix::Lf => b"\n",
+            TerminatorSuffix::CrLf => b"\r\n",
+            TerminatorSuffix::Cr => b"\r",
+        }
+    }
+
+    /// Detects the suffix at the start of `bytes`, i.e. the bytes immediately following a
+    /// segment terminator.
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [b'\r', b'\n', ..] => TerminatorSuffix::CrLf,
+            [b'\r', ..] => TerminatorSuffix::Cr,
+            [b'\n', ..] => TerminatorSuffix::Lf,
+            _ => TerminatorSuffix::None,
+        }
+    }
+}
+
+/// How an interchange uses ISA11, decided by its ISA12 control version number.
+///
+/// Version 00402 repurposed ISA11 from the interchange control standards identifier
+/// (`U`) to the repetition separator, so the same byte means different things in 004010
+/// and 005010 interchanges.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum InterchangeVersion {
+    /// 00401 and earlier: ISA11 is the standards identifier.
+    Version4010,
+    /// 00402 and later, including 00501: ISA11 is the repetition separator.
+    Version5010,
+}
+
+impl InterchangeVersion {
+    /// Reads an ISA12 value such as `00501`.
+    ///
+    /// Returns `None` unless `isa12` is five digits.
+    pub fn from_isa12(isa12: &[u8]) -> Option<Self> {
+        if isa12.len() != 5 || !isa12.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        let number = isa12
+            .iter()
+            .fold(0, |n, &b| n * 10 + u32::from(b - b'0'));
+        Some(if number < FIRST_REPETITION_VERSION {
+            InterchangeVersion::Version4010
+        } else {
+            InterchangeVersion::Version5010
+        })
+    }
+
+    /// Returns true if ISA11 holds the repetition separator in this version.
+    pub fn has_repetition_separator(&self) -> bool {
+        *self == InterchangeVersion::Version5010
+    }
+}
+
+/// One of the three delimiters in a [`Delimiters`] set.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DelimiterKind {
+    /// Ends each segment; ISA byte 105.
+    SegmentTerminator,
+    /// Separates elements; ISA byte 3.
+    ElementSeparator,
+    /// Separates components of a composite element; ISA byte 104 (ISA16).
+    SubElementSeparator,
+}
+
+impl DelimiterKind {
+    /// All delimiter kinds, in the order [`Delimiters::new`] takes them.
+    pub const ALL: [DelimiterKind; 3] = [
+        DelimiterKind::SegmentTerminator,
+        DelimiterKind::ElementSeparator,
+        DelimiterKind::SubElementSeparator,
+    ];
+
+    /// Returns a human-readable name, e.g. `element separator`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DelimiterKind::SegmentTerminator => "segment terminator",
+            DelimiterKind::ElementSeparator => "element separator",
+            DelimiterKind::SubElementSeparator => "sub-element separator",
+        }
+    }
+
+    /// Returns the byte offset of this delimiter in an ISA segment.
+    pub fn isa_index(&self) -> usize {
+        match self {
+            DelimiterKind::SegmentTerminator => ISA_SEGMENT_TERMINATOR_INDEX,
+            DelimiterKind::ElementSeparator => ISA_ELEMENT_SEPARATOR_INDEX,
+            DelimiterKind::SubElementSeparator => ISA_SUB_ELEMENT_SEPARATOR_INDEX,
+        }
+    }
+}
+
+impl std::fmt::Display for DelimiterKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Represents the three delimiter types used in X12 EDI transactions.
 ///
@@ -18,11 +208,23 @@ const ISA_SEGMENT_TERMINATOR_INDEX: usize = 105;
 /// - Segment terminator: `~`
 /// - Element separator: `*`
 /// - Sub-element separator: `:`
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+///
+/// Interchanges from version 00402 on also declare a repetition separator in ISA11, which
+/// separates repeated occurrences of an element. It is optional because earlier versions
+/// use ISA11 for the standards identifier (`U`) instead.
+///
+/// A [`TerminatorSuffix`] records any line ending written after each segment terminator.
+/// It takes part in `==`; use [`Delimiters::eq_ignoring_suffix`] to compare only the
+/// delimiter bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Delimiters {
     segment_terminator: u8,
     element_separator: u8,
     sub_element_separator: u8,
+    #[cfg_attr(feature = "serde", serde(default))]
+    repetition_separator: Option<u8>,
+    suffix: TerminatorSuffix,
 }
 
 impl Delimiters {
@@ -37,6 +239,84 @@ impl Delimiters {
             segment_terminator,
             element_separator,
             sub_element_separator,
+            repetition_separator: None,
+            suffix: TerminatorSuffix::None,
+        }
+    }
+
+    /// Creates delimiters, rejecting combinations that cannot be parsed reliably.
+    ///
+    /// Each delimiter must be printable ASCII punctuation (`!` through `~`, excluding
+    /// letters and digits), and the three must be distinct. Use
+    /// [`Delimiters::try_new_in`] to allow other bytes, such as a newline terminator.
+    ///
+    /// ```
+    /// use x12_delimiters::errors::DelimiterError;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// assert_eq!(Delimiters::try_new(b'~', b'*', b':'), Ok(Delimiters::default()));
+    /// assert_eq!(
+    ///     Delimiters::try_new(b'~', b'*', b'*'),
+    ///     Err(DelimiterError::DuplicateDelimiter(b'*'))
+    /// );
+    /// assert!(Delimiters::try_new(b'\n', b'*', b':').is_err());
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `DelimiterError::DisallowedDelimiter` for the first delimiter outside the
+    /// allowed bytes, or `DelimiterError::DuplicateDelimiter` if two delimiters are equal.
+    pub fn try_new(
+        segment_terminator: u8,
+        element_separator: u8,
+        sub_element_separator: u8,
+    ) -> Result<Self, DelimiterError> {
+        Delimiters::try_new_in(
+            segment_terminator,
+            element_separator,
+            sub_element_separator,
+            DEFAULT_ALLOWED_BYTES,
+        )
+    }
+
+    /// Like [`Delimiters::try_new`], allowing any byte in `allowed` except letters and
+    /// digits.
+    ///
+    /// # Errors
+    /// Same as [`Delimiters::try_new`].
+    pub fn try_new_in(
+        segment_terminator: u8,
+        element_separator: u8,
+        sub_element_separator: u8,
+        allowed: RangeInclusive<u8>,
+    ) -> Result<Self, DelimiterError> {
+        let delimiters =
+            Delimiters::new(segment_terminator, element_separator, sub_element_separator);
+        for kind in DelimiterKind::ALL {
+            let byte = delimiters.get(kind);
+            if !allowed.contains(&byte) || byte.is_ascii_alphanumeric() {
+                return Err(DelimiterError::DisallowedDelimiter { kind, byte });
+            }
+        }
+        if element_separator == segment_terminator || element_separator == sub_element_separator {
+            return Err(DelimiterError::DuplicateDelimiter(element_separator));
+        }
+        if segment_terminator == sub_element_separator {
+            return Err(DelimiterError::DuplicateDelimiter(segment_terminator));
+        }
+        Ok(delimiters)
+    }
+
+    /// Returns a copy of these delimiters with the given terminator suffix.
+    pub fn with_suffix(self, suffix: TerminatorSuffix) -> Self {
+        Delimiters { suffix, ..self }
+    }
+
+    /// Returns a copy of these delimiters with the given repetition separator, or without
+    /// one if `repetition_separator` is `None`.
+    pub fn with_repetition_separator(self, repetition_separator: Option<u8>) -> Self {
+        Delimiters {
+            repetition_separator,
+            ..self
         }
     }
 
@@ -46,6 +326,15 @@ impl Delimiters {
     /// - Element separator is at position 3
     /// - Sub-element separator is at position 104
     /// - Segment terminator is at position 105
+    /// - Repetition separator, if any, is at position 82 (ISA11)
+    ///
+    /// ISA11 is read as a repetition separator only when ISA12 is 00402 or later (see
+    /// [`InterchangeVersion`]). If ISA12 is not a five-digit version, ISA11 is read as a
+    /// repetition separator unless it is a letter or digit, like the `U` standards
+    /// identifier. A letter or digit is never a repetition separator.
+    ///
+    /// Any line ending directly after the segment terminator is recorded as the
+    /// [`TerminatorSuffix`].
     ///
     /// # Arguments
     /// * `isa_segment` - Byte slice containing the ISA segment
@@ -63,14 +352,89 @@ impl Delimiters {
         let element_separator = isa_segment[ISA_ELEMENT_SEPARATOR_INDEX];
         let sub_element_separator = isa_segment[ISA_SUB_ELEMENT_SEPARATOR_INDEX];
         let segment_terminator = isa_segment[ISA_SEGMENT_TERMINATOR_INDEX];
+        let version = InterchangeVersion::from_isa12(&isa_segment[ISA_VERSION_RANGE]);
+        let repetition_separator = Some(isa_segment[ISA_REPETITION_SEPARATOR_INDEX])
+            .filter(|b| !b.is_ascii_alphanumeric())
+            .filter(|_| version != Some(InterchangeVersion::Version4010));
+        let suffix = TerminatorSuffix::detect(&isa_segment[ISA_MIN_LENGTH..]);
 
         Ok(Delimiters {
             element_separator,
             sub_element_separator,
             segment_terminator,
+            repetition_separator,
+            suffix,
         })
     }
 
+    /// Like [`Delimiters::from_isa`], but first verifies that `isa_segment` is shaped like an
+    /// ISA segment.
+    ///
+    /// `from_isa` only checks the length, so any 106 bytes yield delimiters. This checks that
+    /// the segment starts with `ISA` and that the element separator at byte 3 also appears
+    /// at every fixed element boundary through ISA16.
+    ///
+    /// ```
+    /// use x12_delimiters::errors::DelimiterError;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let isa = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+    /// assert!(Delimiters::from_isa_strict(isa).is_ok());
+    ///
+    /// let garbage = [b'x'; 106];
+    /// assert!(Delimiters::from_isa(&garbage).is_ok());
+    /// assert_eq!(Delimiters::from_isa_strict(&garbage), Err(DelimiterError::NotIsaSegment));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `DelimiterError::NotIsaSegment` if the data does not start with `ISA`,
+    /// `DelimiterError::InvalidIsaLength` if it is too short, and
+    /// `DelimiterError::InconsistentSeparators` with the first mismatching position if an
+    /// element boundary does not hold the element separator.
+    pub fn from_isa_strict(isa_segment: &[u8]) -> Result<Self, DelimiterError> {
+        if !isa_segment.starts_with(b"ISA") {
+            return Err(DelimiterError::NotIsaSegment);
+        }
+        if isa_segment.len() < ISA_MIN_LENGTH {
+            return Err(DelimiterError::InvalidIsaLength);
+        }
+        let element_separator = isa_segment[ISA_ELEMENT_SEPARATOR_INDEX];
+        if let Some(&index) = ISA_ELEMENT_BOUNDARIES
+            .iter()
+            .find(|&&index| isa_segment[index] != element_separator)
+        {
+            let byte = isa_segment[index];
+            return Err(DelimiterError::InconsistentSeparators { index, byte });
+        }
+        Delimiters::from_isa(isa_segment)
+    }
+
+    /// Like [`Delimiters::from_isa`], interpreting ISA11 as `version` regardless of ISA12.
+    ///
+    /// Use this for partners whose ISA12 does not match how they fill ISA11.
+    ///
+    /// ```
+    /// use x12_delimiters::{Delimiters, InterchangeVersion};
+    ///
+    /// let isa = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00401*000000001*0*P*:~";
+    /// assert_eq!(Delimiters::from_isa(isa).unwrap().repetition_separator(), None);
+    /// let version = InterchangeVersion::Version5010;
+    /// let delimiters = Delimiters::from_isa_with_version(isa, version).unwrap();
+    /// assert_eq!(delimiters.repetition_separator(), Some(b'^'));
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `DelimiterError::InvalidIsaLength` if the ISA segment is too short
+    pub fn from_isa_with_version(
+        isa_segment: &[u8],
+        version: InterchangeVersion,
+    ) -> Result<Self, DelimiterError> {
+        let delimiters = Delimiters::from_isa(isa_segment)?;
+        let repetition_separator = Some(isa_segment[ISA_REPETITION_SEPARATOR_INDEX])
+            .filter(|b| !b.is_ascii_alphanumeric() && version.has_repetition_separator());
+        Ok(delimiters.with_repetition_separator(repetition_separator))
+    }
+
     /// Returns the segment terminator character.
     pub fn segment_terminator(&self) -> u8 {
         self.segment_terminator
@@ -86,16 +450,84 @@ impl Delimiters {
         self.sub_element_separator
     }
 
-    /// Validates that all three delimiters are distinct.
+    /// Returns the repetition separator declared in ISA11, if any.
+    pub fn repetition_separator(&self) -> Option<u8> {
+        self.repetition_separator
+    }
+
+    /// Returns the line ending written after each segment terminator.
+    pub fn suffix(&self) -> TerminatorSuffix {
+        self.suffix
+    }
+
+    /// Returns the delimiter of the given kind.
+    pub fn get(&self, kind: DelimiterKind) -> u8 {
+        match kind {
+            DelimiterKind::SegmentTerminator => self.segment_terminator,
+            DelimiterKind::ElementSeparator => self.element_separator,
+            DelimiterKind::SubElementSeparator => self.sub_element_separator,
+        }
+    }
+
+    /// Replaces the delimiter of the given kind.
+    ///
+    /// The result is not checked; call [`Delimiters::are_valid`] if the new byte may
+    /// duplicate another delimiter.
+    pub fn set(&mut self, kind: DelimiterKind, byte: u8) {
+        match kind {
+            DelimiterKind::SegmentTerminator => self.segment_terminator = byte,
+            DelimiterKind::ElementSeparator => self.element_separator = byte,
+            DelimiterKind::SubElementSeparator => self.sub_element_separator = byte,
+        }
+    }
+
+    /// Compares the delimiter bytes only, ignoring the terminator suffix.
+    ///
+    /// Files from the same partner often differ only in line endings (`~` vs `~\r\n`);
+    /// this treats them as the same delimiter set.
+    pub fn eq_ignoring_suffix(&self, other: &Delimiters) -> bool {
+        self.segment_terminator == other.segment_terminator
+            && self.element_separator == other.element_separator
+            && self.sub_element_separator == other.sub_element_separator
+            && self.repetition_separator == other.repetition_separator
+    }
+
+    /// Validates that all three delimiters, and the repetition separator if any, are
+    /// distinct.
     ///
     /// In X12 EDI, all delimiters must be different characters to avoid ambiguity.
+    /// [`Delimiters::validate`] reports which delimiters collide.
     ///
     /// # Returns
     /// * `bool` - True if all delimiters are unique, false otherwise
     pub fn are_valid(&self) -> bool {
         self.segment_terminator != self.element_separator &&
         self.segment_terminator != self.sub_element_separator &&
-        self.element_separator != self.sub_element_separator
+        self.element_separator != self.sub_element_separator &&
+        !self.repetition_separator.is_some_and(|r| {
+            r == self.segment_terminator ||
+            r == self.element_separator ||
+            r == self.sub_element_separator
+        })
+    }
+
+    /// Picks a random valid delimiter set that avoids the given bytes.
+    ///
+    /// Delimiters are chosen from printable ASCII punctuation, so letters, digits and
+    /// spaces are never used, and all three are distinct. Pass every byte that generated
+    /// data may contain as `forbidden` to get a profile that cannot collide with it.
+    ///
+    /// # Returns
+    /// * `Option<Delimiters>` - None if fewer than three candidate bytes remain
+    #[cfg(feature = "rand")]
+    pub fn random_excluding<R: rand::Rng + ?Sized>(forbidden: &[u8], rng: &mut R) -> Option<Self> {
+        use rand::seq::IndexedRandom;
+
+        let candidates: Vec<u8> = (0x21..=0x7E)
+            .filter(|b: &u8| !b.is_ascii_alphanumeric() && !forbidden.contains(b))
+            .collect();
+        let mut chosen = candidates.choose_multiple(rng, 3).copied();
+        Some(Delimiters::new(chosen.next()?, chosen.next()?, chosen.next()?))
     }
 }
 
@@ -106,6 +538,8 @@ impl Default for Delimiters {
             segment_terminator: DEFAULT_SEGMENT_TERMINATOR,
             element_separator: DEFAULT_ELEMENT_SEPARATOR,
             sub_element_separator: DEFAULT_SUB_ELEMENT_SEPARATOR,
+            repetition_separator: None,
+            suffix: TerminatorSuffix::None,
         }
     }
 }
@@ -154,6 +588,87 @@ mod tests {
         assert_eq!(delimiters.sub_element_separator(), b'>');
     }
 
+    #[test]
+    fn test_from_isa_repetition_separator() {
+        let delimiters = Delimiters::from_isa(SAMPLE_ISA_SEGMENT_STANDARD).unwrap();
+        assert_eq!(delimiters.repetition_separator(), None);
+
+        let mut isa = SAMPLE_ISA_SEGMENT_STANDARD.to_vec();
+        isa[ISA_REPETITION_SEPARATOR_INDEX] = b'^';
+        let delimiters = Delimiters::from_isa(&isa).unwrap();
+        assert_eq!(delimiters.repetition_separator(), Some(b'^'));
+        assert_ne!(delimiters, Delimiters::default());
+        assert!(delimiters.are_valid());
+        assert!(!delimiters.with_repetition_separator(Some(b':')).are_valid());
+        assert!(!delimiters.eq_ignoring_suffix(&Delimiters::default()));
+    }
+
+    #[test]
+    fn test_from_isa_reads_version() {
+        let mut isa = SAMPLE_ISA_SEGMENT_ALT.to_vec();
+        isa[ISA_REPETITION_SEPARATOR_INDEX] = b'!';
+        assert_eq!(Delimiters::from_isa(&isa).unwrap().repetition_separator(), None);
+        isa[ISA_VERSION_RANGE].copy_from_slice(b"00402");
+        assert_eq!(Delimiters::from_isa(&isa).unwrap().repetition_separator(), Some(b'!'));
+        isa[ISA_VERSION_RANGE].copy_from_slice(b"5010 ");
+        assert_eq!(Delimiters::from_isa(&isa).unwrap().repetition_separator(), Some(b'!'));
+
+        let legacy = InterchangeVersion::Version4010;
+        let delimiters = Delimiters::from_isa_with_version(&isa, legacy).unwrap();
+        assert_eq!(delimiters.repetition_separator(), None);
+        assert_eq!(InterchangeVersion::from_isa12(b"00200"), Some(legacy));
+        assert_eq!(InterchangeVersion::from_isa12(b"0501"), None);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(
+            Delimiters::try_new(b'~', b' ', b':'),
+            Err(DelimiterError::DisallowedDelimiter {
+                kind: DelimiterKind::ElementSeparator,
+                byte: b' '
+            })
+        );
+        assert_eq!(
+            Delimiters::try_new(b'~', b'*', b'A'),
+            Err(DelimiterError::DisallowedDelimiter {
+                kind: DelimiterKind::SubElementSeparator,
+                byte: b'A'
+            })
+        );
+        assert_eq!(
+            Delimiters::try_new(b':', b'*', b':'),
+            Err(DelimiterError::DuplicateDelimiter(b':'))
+        );
+        assert_eq!(
+            Delimiters::try_new_in(b'\n', b'*', b':', 0x0A..=0x7E),
+            Ok(Delimiters::new(b'\n', b'*', b':'))
+        );
+        assert!(Delimiters::try_new_in(b'~', b'*', b':', 0x21..=0x2F).is_err());
+    }
+
+    #[test]
+    fn test_from_isa_strict() {
+        assert_eq!(
+            Delimiters::from_isa_strict(SAMPLE_ISA_SEGMENT_STANDARD),
+            Delimiters::from_isa(SAMPLE_ISA_SEGMENT_STANDARD)
+        );
+        assert_eq!(
+            Delimiters::from_isa_strict(TOO_SHORT_ISA),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+        assert_eq!(Delimiters::from_isa_strict(b"GS*HC"), Err(DelimiterError::NotIsaSegment));
+
+        // A sender ID one byte too long shifts every later separator.
+        let shifted = String::from_utf8(SAMPLE_ISA_SEGMENT_STANDARD.to_vec())
+            .unwrap()
+            .replacen("SENDERID       ", "SENDERID        ", 1);
+        assert_eq!(
+            Delimiters::from_isa_strict(shifted.as_bytes()),
+            Err(DelimiterError::InconsistentSeparators { index: 50, byte: b' ' })
+        );
+    }
+
     #[test]
     fn test_from_isa_too_short() {
         let result = Delimiters::from_isa(TOO_SHORT_ISA);
@@ -197,6 +712,79 @@ mod tests {
         assert!(!invalid_delimiters3.are_valid());
     }
 
+    #[test]
+    fn test_from_isa_detects_suffix() {
+        let mut isa = SAMPLE_ISA_SEGMENT_STANDARD.to_vec();
+        assert_eq!(Delimiters::from_isa(&isa).unwrap().suffix(), TerminatorSuffix::None);
+
+        isa.extend_from_slice(b"\r\nGS*");
+        let delimiters = Delimiters::from_isa(&isa).unwrap();
+        assert_eq!(delimiters.suffix(), TerminatorSuffix::CrLf);
+        assert_eq!(delimiters.segment_terminator(), b'~');
+    }
+
+    #[test]
+    fn test_terminator_suffix_detect() {
+        assert_eq!(TerminatorSuffix::detect(b""), TerminatorSuffix::None);
+        assert_eq!(TerminatorSuffix::detect(b"GS*"), TerminatorSuffix::None);
+        assert_eq!(TerminatorSuffix::detect(b"\nGS*"), TerminatorSuffix::Lf);
+        assert_eq!(TerminatorSuffix::detect(b"\r\nGS*"), TerminatorSuffix::CrLf);
+        assert_eq!(TerminatorSuffix::detect(b"\rGS*"), TerminatorSuffix::Cr);
+    }
+
+    #[test]
+    fn test_get_and_set_by_kind() {
+        let mut delimiters = Delimiters::default();
+        let bytes: Vec<u8> = DelimiterKind::ALL.iter().map(|&k| delimiters.get(k)).collect();
+        assert_eq!(bytes, b"~*:");
+
+        delimiters.set(DelimiterKind::ElementSeparator, b'|');
+        assert_eq!(delimiters, Delimiters::new(b'~', b'|', b':'));
+        delimiters.set(DelimiterKind::SubElementSeparator, b'|');
+        assert!(!delimiters.are_valid());
+
+        for kind in DelimiterKind::ALL {
+            assert_eq!(
+                SAMPLE_ISA_SEGMENT_ALT[kind.isa_index()],
+                Delimiters::from_isa(SAMPLE_ISA_SEGMENT_ALT).unwrap().get(kind)
+            );
+        }
+        assert_eq!(DelimiterKind::SubElementSeparator.to_string(), "sub-element separator");
+    }
+
+    #[test]
+    fn test_eq_ignoring_suffix() {
+        let plain = Delimiters::default();
+        let crlf = plain.with_suffix(TerminatorSuffix::CrLf);
+        assert_ne!(plain, crlf);
+        assert!(plain.eq_ignoring_suffix(&crlf));
+        assert!(!plain.eq_ignoring_suffix(&Delimiters::new(b'~', b'*', b'>')));
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_random_excluding() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let forbidden = b"~*:^-.";
+        for _ in 0..100 {
+            let delimiters = Delimiters::random_excluding(forbidden, &mut rng).unwrap();
+            assert!(delimiters.are_valid());
+            for b in [
+                delimiters.segment_terminator(),
+                delimiters.element_separator(),
+                delimiters.sub_element_separator(),
+            ] {
+                assert!(b.is_ascii_punctuation());
+                assert!(!forbidden.contains(&b));
+            }
+        }
+
+        let all_but_two: Vec<u8> = (0x21..=0x7E).filter(|&b| b != b'~' && b != b'*').collect();
+        assert_eq!(Delimiters::random_excluding(&all_but_two, &mut rng), None);
+    }
+
     use proptest::prelude::*;
 
     fn valid_delimiter() -> impl Strategy<Value = u8> {
@@ -235,11 +823,9 @@ mod tests {
 
     fn isa_segment_extended() -> impl Strategy<Value = (Vec<u8>, u8, u8, u8)> {
         isa_segment_with_delimiters().prop_flat_map(|(isa, elem_sep, sub_elem_sep, seg_term)| {
-            (0..=10).prop_map(move |n| {
+            (0..=10usize).prop_map(move |n| {
                 let mut extended_isa = isa.clone();
-                for _ in 0..n {
-                    extended_isa.push(b'X');
-                }
+                extended_isa.resize(isa.len() + n, b'X');
                 (extended_isa, elem_sep, sub_elem_sep, seg_term)
             })
         })
@@ -0,0 +1,195 @@
+use crate::errors::DelimiterError;
+use crate::segment::{element_at, Span};
+use crate::Delimiters;
+
+/// One transaction set listed by [`manifest`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ManifestEntry {
+    transaction_set_id: String,
+    control_number: String,
+    functional_id: Option<String>,
+    segment_count: usize,
+    span: Span,
+    claim_count: usize,
+    patient_count: usize,
+    closed: bool,
+}
+
+impl ManifestEntry {
+    fn open(
+        transaction_set_id: String,
+        control_number: String,
+        functional_id: Option<String>,
+        span: Span,
+    ) -> Self {
+        ManifestEntry {
+            transaction_set_id,
+            control_number,
+            functional_id,
+            segment_count: 1,
+            span,
+            claim_count: 0,
+            patient_count: 0,
+            closed: false,
+        }
+    }
+
+    /// Returns ST01, e.g. `837`.
+    pub fn transaction_set_id(&self) -> &str {
+        &self.transaction_set_id
+    }
+
+    /// Returns ST02, the transaction set control number.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+
+    /// Returns GS01 of the enclosing functional group, if there is one.
+    pub fn functional_id(&self) -> Option<&str> {
+        self.functional_id.as_deref()
+    }
+
+    /// Returns the number of segments from ST through SE inclusive, as SE01 should state.
+    pub fn segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Returns the bytes from the start of ST to the end of SE, terminator included.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the number of claims: `CLM` segments in an 837, `CLP` segments in an 835.
+    pub fn claim_count(&self) -> usize {
+        self.claim_count
+    }
+
+    /// Returns the number of subscriber and patient loops: `HL` segments with HL03 of
+    /// `22` or `23`.
+    ///
+    /// This is a heuristic: a subscriber who is also the patient is counted once, and
+    /// transaction sets without hierarchical levels report zero.
+    pub fn patient_count(&self) -> usize {
+        self.patient_count
+    }
+
+    /// Returns false if the transaction set has no SE trailer.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+/// Lists every transaction set in `data`, in document order.
+///
+/// Delimiters are read from the ISA at the start of `data`. The manifest is descriptive:
+/// envelope errors are not reported (see [`crate::validation::validate`]), and a
+/// transaction set missing its SE ends at the next ST, GE, or IEA, or at the end of the data.
+///
+/// ```
+/// use x12_delimiters::manifest::manifest;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\
+/// GS*HC*S*R*20250403*0856*1*X*005010X222A1~ST*837*0001~BHT*0019~HL*1**20*1~HL*2*1*22*0~CLM*A1*100~CLM*A2*50~SE*7*0001~GE*1*1~IEA*1*000000001~";
+///
+/// let entries = manifest(data).unwrap();
+/// assert_eq!(entries.len(), 1);
+/// assert_eq!(entries[0].transaction_set_id(), "837");
+/// assert_eq!(entries[0].segment_count(), 7);
+/// assert_eq!((entries[0].claim_count(), entries[0].patient_count()), (2, 1));
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::InvalidIsaLength` if `data` does not start with an ISA segment.
+pub fn manifest(data: &[u8]) -> Result<Vec<ManifestEntry>, DelimiterError> {
+    let delimiters = Delimiters::from_isa(data)?;
+    let mut entries = Vec::new();
+    let mut current: Option<ManifestEntry> = None;
+    let mut functional_id = None;
+
+    for (span, segment) in delimiters.segments(data).spanned() {
+        let element = |n| {
+            let value = element_at(segment.as_bytes(), &delimiters, n).unwrap_or_default();
+            String::from_utf8_lossy(value).trim().to_string()
+        };
+        match segment.id() {
+            b"ST" => {
+                entries.extend(current.take());
+                current = Some(ManifestEntry::open(
+                    element(1),
+                    element(2),
+                    functional_id.clone(),
+                    span,
+                ));
+                continue;
+            }
+            b"GS" => {
+                entries.extend(current.take());
+                functional_id = Some(element(1));
+                continue;
+            }
+            b"GE" | b"IEA" => {
+                entries.extend(current.take());
+                functional_id = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        entry.segment_count += 1;
+        entry.span.end = span.end;
+        match segment.id() {
+            b"SE" => {
+                entry.closed = true;
+                entries.extend(current.take());
+            }
+            b"CLM" | b"CLP" => entry.claim_count += 1,
+            b"HL" if matches!(element(3).as_str(), "22" | "23") => entry.patient_count += 1,
+            _ => {}
+        }
+    }
+    entries.extend(current);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    #[test]
+    fn test_manifest_entries() {
+        let data = format!(
+            "{}\nGS*HP*S*R*20250403*0856*1*X*005010X221A1~\nST*835*0001~\nCLP*A*1~\nCLP*B*1~\nSE*4*0001~\nST*835*0002~\nSE*2*0002~\nGE*2*1~\nIEA*1*000000001~\n",
+            ISA
+        );
+        let entries = manifest(data.as_bytes()).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let first = &entries[0];
+        assert_eq!(first.functional_id(), Some("HP"));
+        assert_eq!(first.control_number(), "0001");
+        assert_eq!((first.segment_count(), first.claim_count()), (4, 2));
+        assert!(first.is_closed());
+        let bytes = &data.as_bytes()[first.span().start..first.span().end];
+        assert!(bytes.starts_with(b"ST*835*0001~") && bytes.ends_with(b"SE*4*0001~"));
+
+        assert_eq!(entries[1].control_number(), "0002");
+        assert_eq!(entries[1].claim_count(), 0);
+    }
+
+    #[test]
+    fn test_unclosed_transaction_and_bad_isa() {
+        let data = format!("{}ST*837*0001~CLM*A~GE*1*1~", ISA);
+        let entries = manifest(data.as_bytes()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].is_closed());
+        assert_eq!(entries[0].functional_id(), None);
+        assert_eq!(entries[0].segment_count(), 2);
+
+        assert_eq!(manifest(b"ISA*00*"), Err(DelimiterError::InvalidIsaLength));
+    }
+}
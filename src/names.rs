@@ -0,0 +1,276 @@
+use crate::errors::NameError;
+use crate::{Delimiters, TerminatorSuffix};
+
+/// Delimiter names, in the order [`byte_name`] prefers them.
+const NAMES: &[(&str, u8)] = &[
+    ("tilde", b'~'),
+    ("asterisk", b'*'),
+    ("star", b'*'),
+    ("colon", b':'),
+    ("pipe", b'|'),
+    ("caret", b'^'),
+    ("greater-than", b'>'),
+    ("less-than", b'<'),
+    ("plus", b'+'),
+    ("apostrophe", b'\''),
+    ("quote", b'"'),
+    ("backslash", b'\\'),
+    ("slash", b'/'),
+    ("exclamation", b'!'),
+    ("at", b'@'),
+    ("hash", b'#'),
+    ("dollar", b'$'),
+    ("percent", b'%'),
+    ("ampersand", b'&'),
+    ("comma", b','),
+    ("period", b'.'),
+    ("semicolon", b';'),
+    ("question", b'?'),
+    ("equals", b'='),
+    ("underscore", b'_'),
+    ("hyphen", b'-'),
+    ("backtick", b'`'),
+    ("left-brace", b'{'),
+    ("right-brace", b'}'),
+    ("left-bracket", b'['),
+    ("right-bracket", b']'),
+    ("left-paren", b'('),
+    ("right-paren", b')'),
+    ("newline", b'\n'),
+    ("lf", b'\n'),
+    ("cr", b'\r'),
+    ("tab", b'\t'),
+    ("fs", 0x1C),
+    ("gs", 0x1D),
+    ("rs", 0x1E),
+    ("us", 0x1F),
+];
+
+const SUFFIXES: &[(&str, TerminatorSuffix)] = &[
+    ("none", TerminatorSuffix::None),
+    ("lf", TerminatorSuffix::Lf),
+    ("crlf", TerminatorSuffix::CrLf),
+    ("cr", TerminatorSuffix::Cr),
+];
+
+/// Returns the name [`Delimiters::to_names`] uses for `byte`, if it has one.
+pub fn byte_name(byte: u8) -> Option<&'static str> {
+    NAMES
+        .iter()
+        .find(|&&(_, b)| b == byte)
+        .map(|&(name, _)| name)
+}
+
+/// Parses a single delimiter name.
+///
+/// Names are case-insensitive and `_` may be used in place of `-`. A `0xNN` hex code or a
+/// single punctuation character is also accepted.
+pub fn byte_from_name(name: &str) -> Option<u8> {
+    let normalized = name.trim().to_ascii_lowercase().replace('_', "-");
+    if let Some(&(_, byte)) = NAMES.iter().find(|&&(n, _)| n == normalized) {
+        return Some(byte);
+    }
+    if let Some(hex) = normalized.strip_prefix("0x") {
+        return u8::from_str_radix(hex, 16).ok();
+    }
+    match normalized.as_bytes() {
+        [byte] if byte.is_ascii_punctuation() => Some(*byte),
+        _ => None,
+    }
+}
+
+impl Delimiters {
+    /// Parses a delimiter set from names, for configs where raw delimiter characters get
+    /// mangled by editors or quoting rules.
+    ///
+    /// Two forms are accepted:
+    /// - Whitespace-separated names for the segment terminator, element separator and
+    ///   sub-element separator, optionally followed by a suffix (`none`, `lf`, `crlf` or
+    ///   `cr`): `"tilde asterisk colon"`.
+    /// - An object with `segment`, `element`, `sub_element`, `repetition` and `suffix` keys,
+    ///   e.g. `{"segment":"tilde","element":"pipe"}`. Missing keys keep the default
+    ///   delimiters, with no repetition separator.
+    ///
+    /// See [`byte_from_name`] for the accepted names.
+    ///
+    /// ```
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let delimiters = Delimiters::from_names("tilde asterisk colon").unwrap();
+    /// assert_eq!(delimiters, Delimiters::default());
+    ///
+    /// let delimiters = Delimiters::from_names(r#"{"segment":"tilde","element":"pipe"}"#).unwrap();
+    /// assert_eq!(delimiters, Delimiters::new(b'~', b'|', b':'));
+    /// assert_eq!(delimiters.to_names(), "tilde pipe colon");
+    /// ```
+    ///
+    /// # Errors
+    /// Returns a `NameError` for unknown names or keys, a wrong number of names, or an
+    /// object that cannot be parsed.
+    pub fn from_names(names: &str) -> Result<Self, NameError> {
+        let names = names.trim();
+        match names.strip_prefix('{') {
+            Some(body) => {
+                let body = body.strip_suffix('}').ok_or(NameError::Malformed)?;
+                from_object(body)
+            }
+            None => from_words(names),
+        }
+    }
+
+    /// Formats the delimiters in the positional form accepted by [`Delimiters::from_names`].
+    ///
+    /// Bytes without a name are written as `0xNN`; the suffix is only written when set.
+    pub fn to_names(&self) -> String {
+        let mut names: Vec<String> = [
+            self.segment_terminator(),
+            self.element_separator(),
+            self.sub_element_separator(),
+        ]
+        .iter()
+        .map(|&b| byte_name(b).map_or_else(|| format!("0x{:02X}", b), str::to_string))
+        .collect();
+        if self.suffix() != TerminatorSuffix::None {
+            names.push(suffix_name(self.suffix()).to_string());
+        }
+        names.join(" ")
+    }
+}
+
+fn from_words(names: &str) -> Result<Delimiters, NameError> {
+    let words: Vec<&str> = names.split_whitespace().collect();
+    if words.len() != 3 && words.len() != 4 {
+        return Err(NameError::WrongCount(words.len()));
+    }
+    let mut bytes = [0; 3];
+    for (byte, word) in bytes.iter_mut().zip(&words) {
+        *byte = parse_byte(word)?;
+    }
+    let suffix = match words.get(3) {
+        Some(word) => parse_suffix(word)?,
+        None => TerminatorSuffix::None,
+    };
+    Ok(Delimiters::new(bytes[0], bytes[1], bytes[2]).with_suffix(suffix))
+}
+
+fn from_object(body: &str) -> Result<Delimiters, NameError> {
+    let defaults = Delimiters::default();
+    let mut values: [Option<u8>; 4] = [None; 4];
+    let mut suffix = None;
+    for pair in body.split(',').filter(|pair| !pair.trim().is_empty()) {
+        let (key, value) = pair.split_once(':').ok_or(NameError::Malformed)?;
+        let (key, value) = (unquote(key)?, unquote(value)?);
+        let slot = match key.to_ascii_lowercase().replace('-', "_").as_str() {
+            "segment" => &mut values[0],
+            "element" => &mut values[1],
+            "sub_element" | "subelement" | "component" => &mut values[2],
+            "repetition" => &mut values[3],
+            "suffix" => {
+                if suffix.replace(parse_suffix(value)?).is_some() {
+                    return Err(NameError::DuplicateKey(key.to_string()));
+                }
+                continue;
+            }
+            _ => return Err(NameError::UnknownKey(key.to_string())),
+        };
+        if slot.replace(parse_byte(value)?).is_some() {
+            return Err(NameError::DuplicateKey(key.to_string()));
+        }
+    }
+    Ok(Delimiters::new(
+        values[0].unwrap_or(defaults.segment_terminator()),
+        values[1].unwrap_or(defaults.element_separator()),
+        values[2].unwrap_or(defaults.sub_element_separator()),
+    )
+    .with_repetition_separator(values[3])
+    .with_suffix(suffix.unwrap_or_default()))
+}
+
+/// Strips surrounding whitespace and optional double quotes.
+fn unquote(token: &str) -> Result<&str, NameError> {
+    let token = token.trim();
+    match token.strip_prefix('"') {
+        Some(rest) => rest.strip_suffix('"').ok_or(NameError::Malformed),
+        None => Ok(token),
+    }
+}
+
+fn parse_byte(name: &str) -> Result<u8, NameError> {
+    byte_from_name(name).ok_or_else(|| NameError::UnknownName(name.to_string()))
+}
+
+fn parse_suffix(name: &str) -> Result<TerminatorSuffix, NameError> {
+    let normalized = name.trim().to_ascii_lowercase();
+    SUFFIXES
+        .iter()
+        .find(|&&(n, _)| n == normalized)
+        .map(|&(_, suffix)| suffix)
+        .ok_or_else(|| NameError::UnknownName(name.to_string()))
+}
+
+fn suffix_name(suffix: TerminatorSuffix) -> &'static str {
+    SUFFIXES
+        .iter()
+        .find(|&&(_, s)| s == suffix)
+        .map_or("none", |&(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_roundtrip() {
+        let delimiters = Delimiters::new(0x1D, b'*', 0x85).with_suffix(TerminatorSuffix::CrLf);
+        assert_eq!(delimiters.to_names(), "gs asterisk 0x85 crlf");
+        assert_eq!(
+            Delimiters::from_names(&delimiters.to_names()),
+            Ok(delimiters)
+        );
+        assert_eq!(
+            Delimiters::from_names("Tilde STAR greater_than"),
+            Ok(Delimiters::new(b'~', b'*', b'>'))
+        );
+    }
+
+    #[test]
+    fn test_object_form() {
+        let delimiters = Delimiters::from_names(
+            r#"{ "segment": "newline", "sub_element": "caret", "suffix": "none" }"#,
+        )
+        .unwrap();
+        assert_eq!(delimiters, Delimiters::new(b'\n', b'*', b'^'));
+        assert_eq!(
+            Delimiters::from_names("{segment: tilde, element: |}"),
+            Ok(Delimiters::new(b'~', b'|', b':'))
+        );
+        assert_eq!(
+            Delimiters::from_names("{repetition: caret}"),
+            Ok(Delimiters::default().with_repetition_separator(Some(b'^')))
+        );
+    }
+
+    #[test]
+    fn test_name_errors() {
+        assert_eq!(
+            Delimiters::from_names("tilde asterisk"),
+            Err(NameError::WrongCount(2))
+        );
+        assert_eq!(
+            Delimiters::from_names("tilde asterisk letter-a"),
+            Err(NameError::UnknownName("letter-a".to_string()))
+        );
+        assert_eq!(
+            Delimiters::from_names(r#"{"terminator":"tilde"}"#),
+            Err(NameError::UnknownKey("terminator".to_string()))
+        );
+        assert_eq!(
+            Delimiters::from_names(r#"{"segment":"tilde","segment":"pipe"}"#),
+            Err(NameError::DuplicateKey("segment".to_string()))
+        );
+        assert_eq!(
+            Delimiters::from_names(r#"{"segment":"tilde""#),
+            Err(NameError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::conflict::{ConflictAction, ConflictPolicy};
+use crate::{Delimiters, TerminatorSuffix};
+
+/// Everything a trading partner dictates about the bytes they receive, applied together.
+///
+/// Hand the policy to [`crate::builder::DocumentBuilder::with_outbound_policy`] or
+/// [`crate::builder::InterchangeBuilder::outbound_policy`] instead of setting
+/// delimiters, suffix, and conflict handling one by one, so a job cannot apply half of a
+/// partner's requirements. Length limits are checked as each segment is written:
+/// - the segment length counts the segment and its terminator, not the suffix
+/// - the line length counts the bytes between line breaks, not the breaks themselves;
+///   with [`TerminatorSuffix::None`] the whole document is one line
+///
+/// ```
+/// use x12_delimiters::builder::DocumentBuilder;
+/// use x12_delimiters::conflict::ConflictAction;
+/// use x12_delimiters::errors::BuildError;
+/// use x12_delimiters::outbound::OutboundPolicy;
+/// use x12_delimiters::{Delimiters, TerminatorSuffix};
+///
+/// let partner = OutboundPolicy::new(Delimiters::new(b'~', b'|', b'>'))
+///     .with_suffix(TerminatorSuffix::CrLf)
+///     .with_conflict_policy(ConflictAction::EscapeToSpace)
+///     .with_max_segment_length(20);
+///
+/// let mut builder = DocumentBuilder::with_outbound_policy(partner);
+/// builder.extend(Delimiters::default().segments(b"ST*837*0001~NTE*ADD*A|B~"));
+/// assert_eq!(builder.finish().unwrap(), b"ST|837|0001~\r\nNTE|ADD|A B~\r\n");
+///
+/// let mut builder = DocumentBuilder::with_outbound_policy(partner);
+/// builder.extend(Delimiters::default().segments(b"NTE*ADD*A VERY LONG NOTE~"));
+/// assert_eq!(builder.finish(), Err(BuildError::SegmentTooLong { length: 25, max: 20 }));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct OutboundPolicy<P = ConflictAction> {
+    delimiters: Delimiters,
+    conflict_policy: P,
+    max_segment_length: Option<usize>,
+    max_line_length: Option<usize>,
+}
+
+impl OutboundPolicy {
+    /// Creates a policy that writes with `delimiters`, including their suffix, rejects
+    /// conflicting data bytes, and sets no length limits.
+    pub fn new(delimiters: Delimiters) -> Self {
+        OutboundPolicy {
+            delimiters,
+            conflict_policy: ConflictAction::Error,
+            max_segment_length: None,
+            max_line_length: None,
+        }
+    }
+}
+
+impl<P: ConflictPolicy> OutboundPolicy<P> {
+    /// Sets the line ending written after every segment terminator.
+    pub fn with_suffix(mut self, suffix: TerminatorSuffix) -> Self {
+        self.delimiters = self.delimiters.with_suffix(suffix);
+        self
+    }
+
+    /// Replaces the policy for data bytes that collide with the output delimiters.
+    pub fn with_conflict_policy<Q: ConflictPolicy>(self, policy: Q) -> OutboundPolicy<Q> {
+        OutboundPolicy {
+            delimiters: self.delimiters,
+            conflict_policy: policy,
+            max_segment_length: self.max_segment_length,
+            max_line_length: self.max_line_length,
+        }
+    }
+
+    /// Sets the longest allowed segment, terminator included.
+    pub fn with_max_segment_length(mut self, max: usize) -> Self {
+        self.max_segment_length = Some(max);
+        self
+    }
+
+    /// Sets the longest allowed line, line break excluded.
+    pub fn with_max_line_length(mut self, max: usize) -> Self {
+        self.max_line_length = Some(max);
+        self
+    }
+
+    /// Returns the output delimiters, including the suffix.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the conflict policy.
+    pub fn conflict_policy(&self) -> &P {
+        &self.conflict_policy
+    }
+
+    /// Returns the segment length limit, if any.
+    pub fn max_segment_length(&self) -> Option<usize> {
+        self.max_segment_length
+    }
+
+    /// Returns the line length limit, if any.
+    pub fn max_line_length(&self) -> Option<usize> {
+        self.max_line_length
+    }
+
+    /// Splits the policy into its conflict policy and the rest, for the builders.
+    pub(crate) fn into_parts(self) -> (Delimiters, P, Option<usize>, Option<usize>) {
+        (
+            self.delimiters,
+            self.conflict_policy,
+            self.max_segment_length,
+            self.max_line_length,
+        )
+    }
+}
@@ -0,0 +1,186 @@
+use std::borrow::Cow;
+
+use crate::{Delimiters, TerminatorSuffix};
+
+/// Options for [`format_pretty`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PrettyOptions {
+    indent: usize,
+    line_ending: TerminatorSuffix,
+}
+
+impl PrettyOptions {
+    /// Creates options that write each segment on its own LF-terminated line, without
+    /// indentation.
+    pub fn new() -> Self {
+        PrettyOptions {
+            indent: 0,
+            line_ending: TerminatorSuffix::Lf,
+        }
+    }
+
+    /// Indents each segment by `indent` spaces per enclosing ISA, GS, or ST envelope.
+    ///
+    /// Indented output is for reading and diffing only: X12 parsers do not expect spaces
+    /// before a segment ID, so pass it through [`minify`] before processing it.
+    pub fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the line ending written after each segment terminator.
+    pub fn with_line_ending(mut self, line_ending: TerminatorSuffix) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Returns the number of spaces per envelope level.
+    pub fn indent(&self) -> usize {
+        self.indent
+    }
+
+    /// Returns the line ending written after each segment terminator.
+    pub fn line_ending(&self) -> TerminatorSuffix {
+        self.line_ending
+    }
+}
+
+impl Default for PrettyOptions {
+    fn default() -> Self {
+        PrettyOptions::new()
+    }
+}
+
+/// Writes each segment of `data` on its own line, for human review and line-based diffs.
+///
+/// Existing line endings and indentation between segments are replaced, so formatting
+/// is idempotent. With [`PrettyOptions::with_indent`], segments are indented by how many
+/// envelopes enclose them, and each trailer lines up with its header. When the segment
+/// terminator is itself CR or LF, no further line ending is added.
+///
+/// ```
+/// use x12_delimiters::pretty::{format_pretty, PrettyOptions};
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"GS*HC~ST*837*0001~BHT*0019~SE*3*0001~GE*1*1~";
+/// let pretty = format_pretty(data, &Delimiters::default(), &PrettyOptions::new().with_indent(2));
+/// assert_eq!(
+///     String::from_utf8(pretty).unwrap(),
+///     "GS*HC~\n  ST*837*0001~\n    BHT*0019~\n  SE*3*0001~\nGE*1*1~\n"
+/// );
+/// ```
+pub fn format_pretty(data: &[u8], delimiters: &Delimiters, options: &PrettyOptions) -> Vec<u8> {
+    let terminator = delimiters.segment_terminator();
+    let line_ending: &[u8] = if terminator == b'\r' || terminator == b'\n' {
+        b""
+    } else {
+        options.line_ending.as_bytes()
+    };
+    let mut out = Vec::with_capacity(data.len() + data.len() / 8);
+    let mut depth: usize = 0;
+    for (span, segment) in delimiters.segments(data).spanned() {
+        let raw = trim_indent(segment.as_bytes());
+        let id = raw
+            .split(|&b| b == delimiters.element_separator())
+            .next()
+            .unwrap_or_default();
+        let is_header = matches!(id, b"ISA" | b"GS" | b"ST");
+        if matches!(id, b"IEA" | b"GE" | b"SE") {
+            depth = depth.saturating_sub(1);
+        }
+        out.resize(out.len() + depth * options.indent, b' ');
+        out.extend_from_slice(raw);
+        if data[span.end - 1] == terminator {
+            out.push(terminator);
+        }
+        out.extend_from_slice(line_ending);
+        if is_header {
+            depth += 1;
+        }
+    }
+    out
+}
+
+/// Removes the whitespace between segments, the inverse of [`format_pretty`].
+///
+/// Spaces, tabs, CRs, and LFs before the first segment and after each segment
+/// terminator are dropped, unless that byte is the terminator itself. Whitespace inside
+/// segments is data and is kept. The input is returned unchanged when there is nothing
+/// to remove.
+///
+/// ```
+/// use x12_delimiters::pretty::minify;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"GS*HC~\n  ST*837*0001~\r\n    NM1*IL*1*DOE JOHN~\n";
+/// assert_eq!(&minify(data, &Delimiters::default())[..], b"GS*HC~ST*837*0001~NM1*IL*1*DOE JOHN~");
+/// ```
+pub fn minify<'a>(data: &'a [u8], delimiters: &Delimiters) -> Cow<'a, [u8]> {
+    let terminator = delimiters.segment_terminator();
+    let is_padding = |b: u8| matches!(b, b' ' | b'\t' | b'\r' | b'\n') && b != terminator;
+    let mut out: Option<Vec<u8>> = None;
+    let mut at_boundary = true;
+    for (i, &byte) in data.iter().enumerate() {
+        if at_boundary && is_padding(byte) {
+            out.get_or_insert_with(|| data[..i].to_vec());
+            continue;
+        }
+        at_boundary = byte == terminator;
+        if let Some(out) = &mut out {
+            out.push(byte);
+        }
+    }
+    match out {
+        Some(out) => Cow::Owned(out),
+        None => Cow::Borrowed(data),
+    }
+}
+
+/// Strips the indentation [`format_pretty`] may have written before a segment.
+fn trim_indent(raw: &[u8]) -> &[u8] {
+    let start = raw
+        .iter()
+        .position(|&b| b != b' ' && b != b'\t')
+        .unwrap_or(raw.len());
+    &raw[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC~ST*837*0001~BHT*0019~SE*3*0001~GE*1*1~IEA*1*000000001~";
+
+    #[test]
+    fn test_pretty_round_trips_through_minify() {
+        let delimiters = Delimiters::default();
+        let options = PrettyOptions::new()
+            .with_indent(4)
+            .with_line_ending(TerminatorSuffix::CrLf);
+        let pretty = format_pretty(DATA.as_bytes(), &delimiters, &options);
+        let text = String::from_utf8(pretty.clone()).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[3], "            BHT*0019~");
+        assert_eq!(lines[6], "IEA*1*000000001~");
+        assert!(text.ends_with("~\r\n"));
+        assert_eq!(format_pretty(&pretty, &delimiters, &options), pretty);
+        assert_eq!(&minify(&pretty, &delimiters)[..], DATA.as_bytes());
+        assert!(matches!(
+            minify(DATA.as_bytes(), &delimiters),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_newline_terminator_and_unterminated_tail() {
+        let delimiters = Delimiters::new(b'\n', b'*', b':');
+        let data = b"ST*837*0001\n  BHT*0019\nSE*3*0001";
+        let pretty = format_pretty(data, &delimiters, &PrettyOptions::new());
+        assert_eq!(pretty, b"ST*837*0001\nBHT*0019\nSE*3*0001");
+        assert_eq!(
+            &minify(data, &delimiters)[..],
+            b"ST*837*0001\nBHT*0019\nSE*3*0001"
+        );
+    }
+}
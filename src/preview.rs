@@ -0,0 +1,95 @@
+use crate::Delimiters;
+
+/// Elements longer than this many bytes are shortened by [`preview`].
+const MAX_ELEMENT_LEN: usize = 40;
+/// Bytes of a long element kept before the ellipsis.
+const ELIDED_ELEMENT_LEN: usize = 32;
+
+/// Renders the first `max_segments` segments for logs, emails and chat alerts.
+///
+/// Each segment is written on its own line with its terminator. Elements longer than 40
+/// bytes keep their first 32 bytes followed by `...[+N]`, where `N` is the number of bytes
+/// dropped. Bytes outside printable ASCII are escaped (`\r`, `\n`, `\t`, `\xNN`), so the
+/// result cannot carry terminal control sequences. If segments are left out, a final line
+/// gives the number of bytes not shown.
+///
+/// ```
+/// use x12_delimiters::preview::preview;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ST*837*0001~\r\nNM1*IL*1*DOE\x1b[31m~\r\nSE*3*0001~\r\n";
+/// assert_eq!(
+///     preview(data, &Delimiters::default(), 2),
+///     "ST*837*0001~\nNM1*IL*1*DOE\\x1B[31m~\n[+14 bytes]"
+/// );
+/// ```
+pub fn preview(data: &[u8], delimiters: &Delimiters, max_segments: usize) -> String {
+    let mut out = String::new();
+    let mut shown_end = 0;
+    let mut segments = delimiters.segments(data).spanned();
+    for (span, segment) in segments.by_ref().take(max_segments) {
+        for (i, element) in segment
+            .as_bytes()
+            .split(|&b| b == delimiters.element_separator())
+            .enumerate()
+        {
+            if i > 0 {
+                escape_into(&mut out, &[delimiters.element_separator()]);
+            }
+            if element.len() > MAX_ELEMENT_LEN {
+                escape_into(&mut out, &element[..ELIDED_ELEMENT_LEN]);
+                out.push_str(&format!("...[+{}]", element.len() - ELIDED_ELEMENT_LEN));
+            } else {
+                escape_into(&mut out, element);
+            }
+        }
+        if data[span.start + segment.as_bytes().len()..span.end].first()
+            == Some(&delimiters.segment_terminator())
+        {
+            escape_into(&mut out, &[delimiters.segment_terminator()]);
+        }
+        out.push('\n');
+        shown_end = span.end;
+    }
+    if segments.next().is_some() {
+        out.push_str(&format!("[+{} bytes]", data.len() - shown_end));
+    } else {
+        out.pop();
+    }
+    out
+}
+
+/// Appends `bytes` to `out`, escaping everything outside printable ASCII.
+pub(crate) fn escape_into(out: &mut String, bytes: &[u8]) {
+    for &byte in bytes {
+        match byte {
+            b'\r' => out.push_str("\\r"),
+            b'\n' => out.push_str("\\n"),
+            b'\t' => out.push_str("\\t"),
+            0x20..=0x7E => out.push(byte as char),
+            _ => out.push_str(&format!("\\x{:02X}", byte)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_elements_are_elided() {
+        let long = "A".repeat(100);
+        let data = format!("NTE*ADD*{}~", long);
+        let rendered = preview(data.as_bytes(), &Delimiters::default(), 10);
+        assert_eq!(rendered, format!("NTE*ADD*{}...[+68]~", &long[..32]));
+    }
+
+    #[test]
+    fn test_all_segments_and_control_delimiters() {
+        let delimiters = Delimiters::new(0x1C, 0x1D, b':');
+        let data = b"ST\x1D837\x1CSE\x1D2";
+        assert_eq!(preview(data, &delimiters, 5), "ST\\x1D837\\x1C\nSE\\x1D2");
+        assert_eq!(preview(b"", &delimiters, 5), "");
+        assert_eq!(preview(data, &delimiters, 0), "[+11 bytes]");
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crate::Delimiters;
+
+/// Compact handle for a delimiter profile interned in a [`ProfileRegistry`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct ProfileId(u16);
+
+impl ProfileId {
+    /// Creates an ID from its stored form.
+    pub fn from_u16(id: u16) -> Self {
+        ProfileId(id)
+    }
+
+    /// Returns the ID in its stored form, for writing into a metadata row.
+    pub fn as_u16(&self) -> u16 {
+        self.0
+    }
+}
+
+/// Interns delimiter profiles so each distinct set is stored once and referenced by a
+/// two-byte [`ProfileId`].
+///
+/// IDs are assigned in order of first use and never change, so they can be persisted
+/// alongside the registry. With the `serde` feature the registry serializes as the list
+/// of profiles in ID order.
+///
+/// ```
+/// use x12_delimiters::profile::ProfileRegistry;
+/// use x12_delimiters::Delimiters;
+///
+/// let mut registry = ProfileRegistry::new();
+/// let standard = registry.intern(Delimiters::default()).unwrap();
+/// let pipe = registry.intern(Delimiters::new(b'~', b'|', b'>')).unwrap();
+/// assert_eq!(registry.intern(Delimiters::default()), Some(standard));
+/// assert_ne!(standard, pipe);
+/// assert_eq!(registry.get(pipe), Some(Delimiters::new(b'~', b'|', b'>')));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "Vec<Delimiters>", into = "Vec<Delimiters>")
+)]
+pub struct ProfileRegistry {
+    profiles: Vec<Delimiters>,
+    ids: HashMap<Delimiters, ProfileId>,
+}
+
+impl ProfileRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        ProfileRegistry::default()
+    }
+
+    /// Returns the ID for `delimiters`, assigning the next free ID on first use.
+    ///
+    /// Profiles that differ only in their [`crate::TerminatorSuffix`] get different IDs.
+    /// Returns None if all 65,536 IDs are in use.
+    pub fn intern(&mut self, delimiters: Delimiters) -> Option<ProfileId> {
+        if let Some(&id) = self.ids.get(&delimiters) {
+            return Some(id);
+        }
+        let id = ProfileId(u16::try_from(self.profiles.len()).ok()?);
+        self.profiles.push(delimiters);
+        self.ids.insert(delimiters, id);
+        Some(id)
+    }
+
+    /// Returns the ID of an already interned profile.
+    pub fn id_of(&self, delimiters: &Delimiters) -> Option<ProfileId> {
+        self.ids.get(delimiters).copied()
+    }
+
+    /// Returns the profile for `id`, or None if the ID was not issued by this registry.
+    pub fn get(&self, id: ProfileId) -> Option<Delimiters> {
+        self.profiles.get(usize::from(id.0)).copied()
+    }
+
+    /// Returns the number of interned profiles.
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// Returns true if no profiles have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Returns each ID and its profile, in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (ProfileId, Delimiters)> + '_ {
+        self.profiles
+            .iter()
+            .enumerate()
+            .map(|(i, &delimiters)| (ProfileId(i as u16), delimiters))
+    }
+}
+
+impl PartialEq for ProfileRegistry {
+    fn eq(&self, other: &Self) -> bool {
+        self.profiles == other.profiles
+    }
+}
+
+impl Eq for ProfileRegistry {}
+
+/// Rebuilds a registry from profiles in ID order, keeping the first ID of any duplicate.
+impl From<Vec<Delimiters>> for ProfileRegistry {
+    fn from(profiles: Vec<Delimiters>) -> Self {
+        let mut ids = HashMap::with_capacity(profiles.len());
+        for (i, &delimiters) in profiles.iter().enumerate().take(usize::from(u16::MAX) + 1) {
+            ids.entry(delimiters).or_insert(ProfileId(i as u16));
+        }
+        ProfileRegistry { profiles, ids }
+    }
+}
+
+impl From<ProfileRegistry> for Vec<Delimiters> {
+    fn from(registry: ProfileRegistry) -> Self {
+        registry.profiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminatorSuffix;
+
+    #[test]
+    fn test_intern_assigns_stable_ids() {
+        let mut registry = ProfileRegistry::new();
+        let crlf = Delimiters::default().with_suffix(TerminatorSuffix::CrLf);
+        let a = registry.intern(Delimiters::default()).unwrap();
+        let b = registry.intern(crlf).unwrap();
+        assert_eq!((a.as_u16(), b.as_u16()), (0, 1));
+        assert_eq!(registry.id_of(&crlf), Some(b));
+        assert_eq!(registry.get(ProfileId::from_u16(2)), None);
+        assert_eq!(registry.len(), 2);
+        let ids: Vec<_> = registry.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [a, b]);
+    }
+
+    #[test]
+    fn test_registry_is_full_at_u16_max() {
+        let profiles: Vec<Delimiters> = (0..=u16::MAX)
+            .map(|i| Delimiters::new((i >> 8) as u8, i as u8, 0).with_suffix(TerminatorSuffix::Lf))
+            .collect();
+        let mut registry = ProfileRegistry::from(profiles);
+        assert_eq!(registry.len(), 65536);
+        assert_eq!(registry.intern(Delimiters::default()), None);
+        let last = Delimiters::new(0xFF, 0xFF, 0).with_suffix(TerminatorSuffix::Lf);
+        assert_eq!(registry.intern(last), Some(ProfileId::from_u16(u16::MAX)));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let mut registry = ProfileRegistry::new();
+        registry.intern(Delimiters::default());
+        let id = registry.intern(Delimiters::new(b'}', b'|', b'>')).unwrap();
+        let json = serde_json::to_string(&registry).unwrap();
+        let restored: ProfileRegistry = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, registry);
+        assert_eq!(restored.id_of(&Delimiters::new(b'}', b'|', b'>')), Some(id));
+        assert_eq!(serde_json::to_string(&id).unwrap(), "1");
+    }
+}
@@ -0,0 +1,650 @@
+#[cfg(feature = "tokio")]
+use std::pin::Pin;
+#[cfg(feature = "tokio")]
+use std::task::{Context, Poll};
+
+use crate::errors::DelimiterError;
+#[cfg(feature = "tokio")]
+use crate::errors::ReadError;
+use crate::reader::trim_line_ending;
+#[cfg(feature = "tokio")]
+use crate::reader::READ_CHUNK;
+use crate::segment::OwnedSegment;
+use crate::Delimiters;
+
+const ISA_LENGTH: usize = 106;
+
+/// Something [`PushParser`] recognized in the bytes fed to it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Event {
+    /// Delimiters were read from the ISA segment of a new interchange. Always precedes
+    /// the events of that ISA.
+    DelimitersDetected(Delimiters),
+    /// A segment begins at `offset`, counted from the first byte fed to the parser.
+    SegmentStart { id: Vec<u8>, offset: u64 },
+    /// An element of the current segment, with its position starting at 1. Empty
+    /// elements are reported too; sub-elements and repetitions are not split.
+    Element { index: usize, value: Vec<u8> },
+    /// The current segment ended.
+    SegmentEnd,
+    /// An IEA segment ended the interchange. The next interchange may use different
+    /// delimiters and is detected again from its ISA.
+    InterchangeEnd,
+}
+
+/// A sans-io X12 parser: feed it bytes as they arrive, get back the events they complete.
+///
+/// The parser owns no I/O, so it can be driven from sockets, message queues, or any
+/// other transport. Input may be split anywhere, even inside a delimiter-bearing ISA;
+/// bytes that do not yet complete a segment are buffered until a later
+/// [`PushParser::feed`] or [`PushParser::finish`]. Interchanges may be concatenated,
+/// with whitespace between them, and each is read with its own delimiters.
+///
+/// ```
+/// use x12_delimiters::push::{Event, PushParser};
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~";
+/// let mut parser = PushParser::new();
+/// let mut events = Vec::new();
+/// for chunk in data.chunks(10) {
+///     events.extend(parser.feed(chunk).unwrap());
+/// }
+/// events.extend(parser.finish().unwrap());
+///
+/// assert!(matches!(events[0], Event::DelimitersDetected(_)));
+/// assert_eq!(
+///     events[events.len() - 5..],
+///     [
+///         Event::SegmentStart { id: b"IEA".to_vec(), offset: 106 },
+///         Event::Element { index: 1, value: b"0".to_vec() },
+///         Event::Element { index: 2, value: b"000000001".to_vec() },
+///         Event::SegmentEnd,
+///         Event::InterchangeEnd,
+///     ]
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct PushParser {
+    buf: Vec<u8>,
+    offset: u64,
+    scanner: Scanner,
+}
+
+impl PushParser {
+    /// Creates a parser waiting for an ISA segment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the delimiters of the current interchange, or `None` between interchanges.
+    pub fn delimiters(&self) -> Option<Delimiters> {
+        self.scanner.delimiters
+    }
+
+    /// Returns the number of bytes buffered and not yet reported as events.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Appends `bytes` to the input and returns the events they complete.
+    ///
+    /// # Errors
+    /// Returns the error of [`Delimiters::from_isa`] if an interchange does not start with
+    /// an ISA segment. The failing bytes stay buffered, so feeding more returns the error
+    /// again.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Event>, DelimiterError> {
+        self.buf.extend_from_slice(bytes);
+        self.parse(false)
+    }
+
+    /// Ends the input and returns the events of the buffered bytes, reporting an
+    /// unterminated last segment as if it were terminated.
+    ///
+    /// The parser is reset afterwards, ready for a new stream.
+    ///
+    /// # Errors
+    /// Same as [`PushParser::feed`], including `DelimiterError::InvalidIsaLength` if the
+    /// input ends inside an ISA segment.
+    pub fn finish(&mut self) -> Result<Vec<Event>, DelimiterError> {
+        let events = self.parse(true)?;
+        *self = Self::default();
+        Ok(events)
+    }
+
+    fn parse(&mut self, eof: bool) -> Result<Vec<Event>, DelimiterError> {
+        let mut events = Vec::new();
+        let mut pos = 0;
+        let result = loop {
+            match self.scanner.step(&self.buf, &mut pos, eof) {
+                Ok(Step::Detected(delimiters)) => {
+                    events.push(Event::DelimitersDetected(delimiters))
+                }
+                Ok(Step::Segment {
+                    start,
+                    raw,
+                    delimiters,
+                }) => {
+                    let mut elements = raw.split(|&b| b == delimiters.element_separator());
+                    let id = elements.next().unwrap_or_default();
+                    events.push(Event::SegmentStart {
+                        id: id.to_vec(),
+                        offset: self.offset + start as u64,
+                    });
+                    events.extend(elements.enumerate().map(|(i, value)| Event::Element {
+                        index: i + 1,
+                        value: value.to_vec(),
+                    }));
+                    events.push(Event::SegmentEnd);
+                    if id == b"IEA" {
+                        events.push(Event::InterchangeEnd);
+                    }
+                }
+                Ok(Step::Pending) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+        self.buf.drain(..pos);
+        self.offset += pos as u64;
+        result.map(|()| events)
+    }
+}
+
+/// Splits segments off the front of a caller-owned buffer, one at a time.
+///
+/// This is the shape of a framing codec: append received bytes to the buffer, call
+/// [`SegmentDecoder::decode`] until it returns `None`, and call
+/// [`SegmentDecoder::decode_eof`] once the input ends. It fits async transports without
+/// tying the crate to a runtime; for example, a `tokio_util::codec::Decoder` impl only
+/// needs to forward to these methods. Delimiters are detected from the ISA of each
+/// interchange, as with [`PushParser`].
+///
+/// Decoded bytes are drained from the front of the buffer, so keep reads to a few
+/// kilobytes at a time rather than buffering a whole file.
+///
+/// ```
+/// use x12_delimiters::push::SegmentDecoder;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nIEA*0*000000001~\n";
+/// let mut decoder = SegmentDecoder::new();
+/// let mut buf = Vec::new();
+/// let mut ids = Vec::new();
+/// for chunk in data.chunks(64) {
+///     buf.extend_from_slice(chunk);
+///     while let Some(segment) = decoder.decode(&mut buf).unwrap() {
+///         ids.push(segment.id().to_vec());
+///     }
+/// }
+/// assert!(decoder.decode_eof(&mut buf).unwrap().is_none());
+/// assert_eq!(ids, [b"ISA".to_vec(), b"IEA".to_vec()]);
+/// ```
+#[derive(Debug, Default)]
+pub struct SegmentDecoder {
+    scanner: Scanner,
+    /// Bytes at the front of the buffer already decoded but not yet drained.
+    consumed: usize,
+}
+
+impl SegmentDecoder {
+    /// Creates a decoder waiting for an ISA segment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the delimiters of the current interchange, or `None` between interchanges.
+    pub fn delimiters(&self) -> Option<Delimiters> {
+        self.scanner.delimiters
+    }
+
+    /// Removes the next complete segment from the front of `buf` and returns it, or
+    /// returns `None` if `buf` does not hold one yet.
+    ///
+    /// # Errors
+    /// Same as [`PushParser::feed`].
+    pub fn decode(&mut self, buf: &mut Vec<u8>) -> Result<Option<OwnedSegment>, DelimiterError> {
+        self.next(buf, false)
+    }
+
+    /// Like [`SegmentDecoder::decode`], for the bytes left once the input has ended: an
+    /// unterminated last segment is returned as if it were terminated.
+    ///
+    /// # Errors
+    /// Same as [`PushParser::finish`].
+    pub fn decode_eof(
+        &mut self,
+        buf: &mut Vec<u8>,
+    ) -> Result<Option<OwnedSegment>, DelimiterError> {
+        self.next(buf, true)
+    }
+
+    fn next(
+        &mut self,
+        buf: &mut Vec<u8>,
+        eof: bool,
+    ) -> Result<Option<OwnedSegment>, DelimiterError> {
+        let result = self.next_in(buf, eof);
+        self.compact(buf);
+        result
+    }
+
+    /// Decodes the next segment after the consumed bytes of `buf`, leaving them in place
+    /// until [`SegmentDecoder::compact`] drops them.
+    fn next_in(&mut self, buf: &[u8], eof: bool) -> Result<Option<OwnedSegment>, DelimiterError> {
+        loop {
+            match self.scanner.step(buf, &mut self.consumed, eof)? {
+                Step::Detected(_) => {}
+                Step::Segment {
+                    raw, delimiters, ..
+                } => return Ok(Some(OwnedSegment::new(raw.to_vec(), delimiters))),
+                Step::Pending => return Ok(None),
+            }
+        }
+    }
+
+    /// Drops the consumed bytes from the front of `buf`.
+    fn compact(&mut self, buf: &mut Vec<u8>) {
+        buf.drain(..self.consumed);
+        self.consumed = 0;
+    }
+}
+
+/// Streams segments from a [`tokio::io::AsyncRead`] source without blocking the runtime.
+///
+/// The async counterpart of [`crate::reader::SegmentReader`], built on
+/// [`SegmentDecoder`]: bytes are read as they arrive and each complete segment is yielded
+/// from the [`futures_core::Stream`] impl. Delimiters are detected from the ISA of each
+/// interchange, so concatenated interchanges with different delimiters are read
+/// correctly. The stream ends after the first error.
+///
+/// ```
+/// use futures::StreamExt;
+/// use x12_delimiters::push::AsyncSegmentReader;
+///
+/// let data: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nIEA*0*000000001~\n";
+/// let ids: Vec<Vec<u8>> = futures::executor::block_on(
+///     AsyncSegmentReader::new(data)
+///         .map(|s| s.unwrap().id().to_vec())
+///         .collect(),
+/// );
+/// assert_eq!(ids, [b"ISA".to_vec(), b"IEA".to_vec()]);
+/// ```
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncSegmentReader<R> {
+    reader: R,
+    decoder: SegmentDecoder,
+    buf: Vec<u8>,
+    eof: bool,
+    done: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncSegmentReader<R> {
+    /// Prepares to stream segments from `reader`. Nothing is read until the stream is
+    /// polled.
+    pub fn new(reader: R) -> Self {
+        AsyncSegmentReader {
+            reader,
+            decoder: SegmentDecoder::new(),
+            buf: Vec::new(),
+            eof: false,
+            done: false,
+        }
+    }
+
+    /// Returns the delimiters of the current interchange, or `None` before its ISA has
+    /// been read.
+    pub fn delimiters(&self) -> Option<Delimiters> {
+        self.decoder.delimiters()
+    }
+
+    /// Returns the source, dropping any bytes read but not yet decoded.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> futures_core::Stream for AsyncSegmentReader<R> {
+    type Item = Result<OwnedSegment, ReadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            // Decoded segments stay in the buffer until the next read, so a chunk holding
+            // many segments is compacted once rather than once per segment.
+            let decoded = this.decoder.next_in(&this.buf, this.eof);
+            match decoded {
+                Ok(Some(segment)) => return Poll::Ready(Some(Ok(segment))),
+                Ok(None) if this.eof => this.done = true,
+                Ok(None) => {
+                    this.decoder.compact(&mut this.buf);
+                    this.buf.reserve(READ_CHUNK);
+                    let len = this.buf.len();
+                    let mut read = tokio::io::ReadBuf::uninit(this.buf.spare_capacity_mut());
+                    let filled = read.filled().as_ptr();
+                    let polled = Pin::new(&mut this.reader).poll_read(cx, &mut read);
+                    assert_eq!(filled, read.filled().as_ptr(), "reader swapped the ReadBuf");
+                    let n = read.filled().len();
+                    // SAFETY: `ReadBuf` guarantees its first `n` bytes were initialized, and
+                    // they are the start of the spare capacity, as checked above.
+                    unsafe { this.buf.set_len(len + n) };
+                    match polled {
+                        Poll::Ready(Ok(())) => this.eof = n == 0,
+                        Poll::Ready(Err(e)) => {
+                            this.done = true;
+                            return Poll::Ready(Some(Err(e.into())));
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+            }
+        }
+    }
+}
+
+/// What [`Scanner::step`] found.
+enum Step<'a> {
+    Detected(Delimiters),
+    Segment {
+        start: usize,
+        raw: &'a [u8],
+        delimiters: Delimiters,
+    },
+    Pending,
+}
+
+/// Segment boundary state shared by [`PushParser`] and [`SegmentDecoder`].
+#[derive(Debug, Default)]
+struct Scanner {
+    delimiters: Option<Delimiters>,
+    /// Bytes of the pending segment already searched for a terminator.
+    scanned: usize,
+}
+
+impl Scanner {
+    /// Reads the next step from `buf[*pos..]` and advances `pos` past the bytes it used.
+    /// After `Step::Pending` or an error, resume with the same `pos`, or with 0 once the
+    /// bytes before it have been dropped.
+    fn step<'a>(
+        &mut self,
+        buf: &'a [u8],
+        pos: &mut usize,
+        eof: bool,
+    ) -> Result<Step<'a>, DelimiterError> {
+        let rest = &buf[*pos..];
+        let Some(delimiters) = self.delimiters else {
+            let skip = rest.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            *pos += skip;
+            let rest = &rest[skip..];
+            if rest.len() >= 3 && !rest.starts_with(b"ISA") {
+                return Err(DelimiterError::NotIsaSegment);
+            }
+            // Two bytes past the ISA let `from_isa` see a CRLF terminator suffix.
+            if rest.is_empty() || (rest.len() <= ISA_LENGTH + 1 && !eof) {
+                return Ok(Step::Pending);
+            }
+            let delimiters = Delimiters::from_isa(rest)?;
+            self.delimiters = Some(delimiters);
+            return Ok(Step::Detected(delimiters));
+        };
+
+        let skip = rest
+            .iter()
+            .take_while(|&&b| matches!(b, b'\r' | b'\n'))
+            .count();
+        *pos += skip;
+        let rest = &rest[skip..];
+        let terminator = delimiters.segment_terminator();
+        let (raw, consumed) = match rest[self.scanned..].iter().position(|&b| b == terminator) {
+            Some(len) => (&rest[..self.scanned + len], self.scanned + len + 1),
+            None if eof && !rest.is_empty() => (trim_line_ending(rest), rest.len()),
+            None => {
+                self.scanned = rest.len();
+                return Ok(Step::Pending);
+            }
+        };
+        let start = *pos;
+        *pos += consumed;
+        self.scanned = 0;
+        if raw.split(|&b| b == delimiters.element_separator()).next() == Some(b"IEA") {
+            self.delimiters = None;
+        }
+        Ok(Step::Segment {
+            start,
+            raw,
+            delimiters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminatorSuffix;
+
+    const DOC: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\n\
+GS*HC*S*R*20250403*0856*1*X*005010X222A1~\r\n\
+ST*837*0001~\r\nCLM*A**1:2~\r\nSE*3*0001~\r\n\
+GE*1*1~\r\n\
+IEA*1*000000001~\r\n";
+
+    fn parse_chunks(data: &[u8], chunk: usize) -> Vec<Event> {
+        let mut parser = PushParser::new();
+        let mut events = Vec::new();
+        for bytes in data.chunks(chunk) {
+            events.extend(parser.feed(bytes).unwrap());
+        }
+        events.extend(parser.finish().unwrap());
+        events
+    }
+
+    fn ids(events: &[Event]) -> Vec<&[u8]> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                Event::SegmentStart { id, .. } => Some(&id[..]),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_events_do_not_depend_on_chunking() {
+        let expected = parse_chunks(DOC, DOC.len());
+        assert_eq!(
+            expected[0],
+            Event::DelimitersDetected(
+                Delimiters::default()
+                    .with_repetition_separator(Some(b'^'))
+                    .with_suffix(TerminatorSuffix::CrLf)
+            )
+        );
+        assert_eq!(
+            ids(&expected),
+            [&b"ISA"[..], b"GS", b"ST", b"CLM", b"SE", b"GE", b"IEA"]
+        );
+        let clm = expected
+            .iter()
+            .position(|e| matches!(e, Event::SegmentStart { id, .. } if id == b"CLM"))
+            .unwrap();
+        assert_eq!(
+            expected[clm..clm + 5],
+            [
+                Event::SegmentStart {
+                    id: b"CLM".to_vec(),
+                    offset: 165
+                },
+                Event::Element {
+                    index: 1,
+                    value: b"A".to_vec()
+                },
+                Event::Element {
+                    index: 2,
+                    value: Vec::new()
+                },
+                Event::Element {
+                    index: 3,
+                    value: b"1:2".to_vec()
+                },
+                Event::SegmentEnd,
+            ]
+        );
+        assert_eq!(expected.last(), Some(&Event::InterchangeEnd));
+        for chunk in [1, 3, 7, 107] {
+            assert_eq!(parse_chunks(DOC, chunk), expected);
+        }
+    }
+
+    #[test]
+    fn test_concatenated_interchanges_redetect_delimiters() {
+        let second = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}IEA|0|000000002}";
+        let mut data = DOC.to_vec();
+        data.extend_from_slice(b"\n\n");
+        data.extend_from_slice(second);
+        let events = parse_chunks(&data, 5);
+        let detected: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::DelimitersDetected(d) => Some(d.segment_terminator()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(detected, [b'~', b'}']);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| **e == Event::InterchangeEnd)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_buffers_partial_segments_until_finish() {
+        let mut parser = PushParser::new();
+        let events = parser.feed(&DOC[..120]).unwrap();
+        assert_eq!(events.last(), Some(&Event::SegmentEnd));
+        assert_eq!(parser.buffered(), 12);
+        assert!(parser.feed(b"").unwrap().is_empty());
+
+        let events = parser.finish().unwrap();
+        assert_eq!(ids(&events), [&b"GS"[..]]);
+        assert_eq!(parser.delimiters(), None);
+        assert_eq!(parser.buffered(), 0);
+    }
+
+    #[test]
+    fn test_decoder_matches_slice_splitting() {
+        let data = &DOC[..DOC.len() - 3];
+        let mut decoder = SegmentDecoder::new();
+        let mut buf = Vec::new();
+        let mut segments = Vec::new();
+        for chunk in data.chunks(4) {
+            buf.extend_from_slice(chunk);
+            while let Some(segment) = decoder.decode(&mut buf).unwrap() {
+                segments.push(segment);
+            }
+        }
+        assert!(decoder.delimiters().is_some());
+        assert_eq!(buf, b"IEA*1*000000001");
+        segments.extend(decoder.decode_eof(&mut buf).unwrap());
+        assert!(buf.is_empty());
+        assert_eq!(decoder.delimiters(), None);
+
+        let sliced: Vec<_> = segments[0]
+            .delimiters()
+            .segments(DOC)
+            .map(|s| s.to_owned_segment())
+            .collect();
+        assert_eq!(segments, sliced);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_reader_matches_slice_splitting() {
+        use futures::StreamExt;
+        use std::io;
+        use tokio::io::{AsyncRead, ReadBuf};
+
+        /// Returns `Pending` before every read, then at most `chunk` bytes.
+        struct Trickle<'a> {
+            data: &'a [u8],
+            chunk: usize,
+            ready: bool,
+        }
+
+        impl AsyncRead for Trickle<'_> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut ReadBuf<'_>,
+            ) -> Poll<io::Result<()>> {
+                if !self.ready {
+                    self.ready = true;
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                self.ready = false;
+                let n = self.chunk.min(buf.remaining()).min(self.data.len());
+                buf.put_slice(&self.data[..n]);
+                self.data = &self.data[n..];
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        let sliced: Vec<_> = Delimiters::from_isa(DOC)
+            .unwrap()
+            .segments(DOC)
+            .map(|s| s.to_owned_segment())
+            .collect();
+        for chunk in [1, 7, 4096] {
+            let reader = AsyncSegmentReader::new(Trickle {
+                data: DOC,
+                chunk,
+                ready: false,
+            });
+            let segments: Vec<_> = futures::executor::block_on(reader.collect::<Vec<_>>())
+                .into_iter()
+                .map(Result::unwrap)
+                .collect();
+            assert_eq!(segments, sliced);
+        }
+
+        // Many small segments per read are decoded without draining after each one.
+        let mut data = DOC[..DOC.len() - 18].to_vec();
+        for i in 0..10_000 {
+            data.extend_from_slice(format!("NTE*{}~", i).as_bytes());
+        }
+        let reader = AsyncSegmentReader::new(&data[..]);
+        let count = futures::executor::block_on(reader.map(Result::unwrap).count());
+        assert_eq!(count, 10_006);
+
+        let mut reader = AsyncSegmentReader::new(&b"GS*HC~"[..]);
+        futures::executor::block_on(async {
+            assert!(matches!(
+                reader.next().await,
+                Some(Err(ReadError::Delimiters(_)))
+            ));
+            assert!(reader.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut parser = PushParser::new();
+        assert_eq!(parser.feed(&DOC[..50]), Ok(Vec::new()));
+        assert_eq!(parser.finish(), Err(DelimiterError::InvalidIsaLength));
+
+        let mut parser = PushParser::new();
+        let junk = [b'X'; 200];
+        assert_eq!(parser.feed(&junk), Err(DelimiterError::NotIsaSegment));
+        assert_eq!(parser.feed(b""), Err(DelimiterError::NotIsaSegment));
+    }
+}
@@ -0,0 +1,717 @@
+use std::io::Read;
+
+use crate::errors::ReadError;
+use crate::segment::OwnedSegment;
+#[cfg(feature = "tracing")]
+use crate::segment::Segment;
+#[cfg(feature = "bytes")]
+use crate::shared::BytesSegment;
+use crate::Delimiters;
+
+const ISA_LENGTH: usize = 106;
+pub(crate) const READ_CHUNK: usize = 8 * 1024;
+
+/// Streams segments from any [`Read`] source without loading the whole document.
+///
+/// Delimiters are extracted from the ISA segment at the start of the stream, then segments
+/// are yielded one at a time from an internal buffer. Memory use is bounded by the largest
+/// segment plus the read chunk size; [`SegmentReader::with_max_segment_length`] caps it
+/// for input whose terminator may be wrong.
+///
+/// ```
+/// use x12_delimiters::reader::SegmentReader;
+///
+/// let data: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nIEA*0*000000001~\n";
+/// let reader = SegmentReader::new(data).unwrap();
+/// let ids: Vec<Vec<u8>> = reader.map(|s| s.unwrap().id().to_vec()).collect();
+/// assert_eq!(ids, vec![b"ISA".to_vec(), b"IEA".to_vec()]);
+/// ```
+#[derive(Debug)]
+pub struct SegmentReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    start: usize,
+    scanned: usize,
+    eof: bool,
+    chunk_size: usize,
+    max_segment_length: Option<usize>,
+    consumed: u64,
+    delimiters: Delimiters,
+    #[cfg(feature = "tracing")]
+    spans: Option<EnvelopeSpans>,
+}
+
+impl<R: Read> SegmentReader<R> {
+    /// Reads the ISA segment from `reader` and prepares to stream segments.
+    ///
+    /// # Errors
+    /// Returns `ReadError::Io` if reading fails, or `ReadError::Delimiters` if the stream
+    /// does not start with a complete ISA segment.
+    pub fn new(reader: R) -> Result<Self, ReadError> {
+        Self::with_chunk_size(reader, READ_CHUNK)
+    }
+
+    /// Like [`SegmentReader::new`], reading up to `chunk_size` bytes per call to
+    /// [`Read::read`] instead of the default 8 KiB.
+    ///
+    /// Larger chunks mean fewer system calls when reading unbuffered files from fast
+    /// storage. `chunk_size` is clamped to at least 1.
+    ///
+    /// # Errors
+    /// Same as [`SegmentReader::new`].
+    pub fn with_chunk_size(mut reader: R, chunk_size: usize) -> Result<Self, ReadError> {
+        let chunk_size = chunk_size.max(1);
+        let mut buf = Vec::with_capacity(chunk_size);
+        let mut eof = false;
+        // One byte past the ISA lets `from_isa` see the terminator suffix.
+        while buf.len() <= ISA_LENGTH + 1 && !eof {
+            eof = fill(&mut reader, &mut buf, chunk_size)? == 0;
+        }
+        let delimiters = Delimiters::from_isa(&buf)?;
+        Ok(SegmentReader {
+            reader,
+            buf,
+            start: 0,
+            scanned: 0,
+            eof,
+            chunk_size,
+            max_segment_length: None,
+            consumed: 0,
+            delimiters,
+            #[cfg(feature = "tracing")]
+            spans: None,
+        })
+    }
+
+    /// Returns the delimiters read from the ISA segment.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Fails with `ReadError::SegmentTooLong` instead of buffering a segment longer than
+    /// `max` bytes, terminator excluded.
+    ///
+    /// Without a limit, a stream whose segments never end in the expected terminator is
+    /// buffered whole. Iteration stops after the error.
+    ///
+    /// ```
+    /// use x12_delimiters::errors::ReadError;
+    /// use x12_delimiters::reader::SegmentReader;
+    ///
+    /// let mut data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\n".to_vec();
+    /// data.extend_from_slice(&[b'X'; 1 << 20]);
+    /// let mut reader = SegmentReader::new(&data[..]).unwrap().with_max_segment_length(4096);
+    /// assert!(reader.next().unwrap().is_ok());
+    /// assert!(matches!(
+    ///     reader.next(),
+    ///     Some(Err(ReadError::SegmentTooLong { offset: 107, max: 4096 }))
+    /// ));
+    /// assert!(reader.next().is_none());
+    /// ```
+    pub fn with_max_segment_length(mut self, max: usize) -> Self {
+        self.max_segment_length = Some(max);
+        self
+    }
+
+    /// Opens a `tracing` span per interchange, functional group, and transaction set.
+    ///
+    /// Spans are named `x12_interchange`, `x12_group`, and `x12_transaction`, nest in
+    /// envelope order, and record the envelope's IDs and control number. A span is entered
+    /// while segments inside it are read and closed when its trailer is read; transaction
+    /// spans also record their segment count on close.
+    #[cfg(feature = "tracing")]
+    pub fn trace_envelopes(mut self, enabled: bool) -> Self {
+        self.spans = if enabled {
+            Some(EnvelopeSpans::default())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Streams the remaining segments as [`bytes::Bytes`] views of the read buffer instead
+    /// of copying each one into a `Vec<u8>`.
+    ///
+    /// Segments read in the same chunk share one allocation, so they can be handed to
+    /// several consumers and kept alive independently without further copies. Bytes
+    /// already buffered are copied once; the delimiters and limits carry over.
+    ///
+    /// ```
+    /// use x12_delimiters::reader::SegmentReader;
+    ///
+    /// let data: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nIEA*0*000000001~\n";
+    /// let mut segments = SegmentReader::new(data).unwrap().into_bytes_segments();
+    /// segments.next().unwrap().unwrap();
+    /// let iea = segments.next().unwrap().unwrap();
+    /// assert_eq!(iea.id(), b"IEA");
+    /// assert_eq!(iea.into_bytes(), &b"IEA*0*000000001"[..]);
+    /// ```
+    #[cfg(feature = "bytes")]
+    pub fn into_bytes_segments(self) -> BytesSegments<R> {
+        BytesSegments {
+            reader: self.reader,
+            buf: bytes::BytesMut::from(&self.buf[self.start..]),
+            start: 0,
+            scanned: self.scanned - self.start,
+            eof: self.eof,
+            chunk_size: self.chunk_size,
+            max_segment_length: self.max_segment_length,
+            consumed: self.consumed + self.start as u64,
+            delimiters: self.delimiters,
+            #[cfg(feature = "tracing")]
+            spans: self.spans,
+        }
+    }
+
+    fn read_segment(&mut self) -> Result<Option<OwnedSegment>, ReadError> {
+        let terminator = self.delimiters.segment_terminator();
+        loop {
+            match scan_segment(
+                &self.buf,
+                &mut self.start,
+                &mut self.scanned,
+                terminator,
+                self.max_segment_length,
+            ) {
+                Scan::Complete(end) => {
+                    let raw = self.buf[self.start..end].to_vec();
+                    self.start = end + 1;
+                    self.scanned = self.start;
+                    return Ok(Some(OwnedSegment::new(raw, self.delimiters)));
+                }
+                Scan::TooLong(max) => {
+                    let offset = self.consumed + self.start as u64;
+                    self.buf.clear();
+                    (self.start, self.scanned, self.eof) = (0, 0, true);
+                    return Err(ReadError::SegmentTooLong { offset, max });
+                }
+                Scan::Partial => {}
+            }
+
+            if self.eof {
+                if self.start == self.buf.len() {
+                    return Ok(None);
+                }
+                let raw = trim_line_ending(&self.buf[self.start..]).to_vec();
+                self.start = self.buf.len();
+                return Ok(Some(OwnedSegment::new(raw, self.delimiters)));
+            }
+
+            if self.start > 0 {
+                self.consumed += self.start as u64;
+                self.buf.drain(..self.start);
+                self.scanned -= self.start;
+                self.start = 0;
+            }
+            self.eof = fill(&mut self.reader, &mut self.buf, self.chunk_size)? == 0;
+        }
+    }
+}
+
+impl<R: Read> Iterator for SegmentReader<R> {
+    type Item = Result<OwnedSegment, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.spans.as_ref().map(|spans| spans.current().entered());
+        let segment = self.read_segment().transpose()?;
+        #[cfg(feature = "tracing")]
+        if let (Ok(segment), Some(spans)) = (&segment, self.spans.as_mut()) {
+            spans.observe(&segment.as_segment());
+        }
+        Some(segment)
+    }
+}
+
+/// Iterator over the segments of a stream as shared [`bytes::Bytes`] views, created by
+/// [`SegmentReader::into_bytes_segments`].
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+pub struct BytesSegments<R> {
+    reader: R,
+    buf: bytes::BytesMut,
+    start: usize,
+    scanned: usize,
+    eof: bool,
+    chunk_size: usize,
+    max_segment_length: Option<usize>,
+    consumed: u64,
+    delimiters: Delimiters,
+    #[cfg(feature = "tracing")]
+    spans: Option<EnvelopeSpans>,
+}
+
+#[cfg(feature = "bytes")]
+impl<R: Read> BytesSegments<R> {
+    /// Returns the delimiters read from the ISA segment.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    fn read_segment(&mut self) -> Result<Option<BytesSegment>, ReadError> {
+        use bytes::Buf;
+
+        let terminator = self.delimiters.segment_terminator();
+        loop {
+            match scan_segment(
+                &self.buf,
+                &mut self.start,
+                &mut self.scanned,
+                terminator,
+                self.max_segment_length,
+            ) {
+                Scan::Complete(end) => {
+                    let mut raw = self.buf.split_to(end + 1);
+                    raw.advance(self.start);
+                    raw.truncate(end - self.start);
+                    self.consumed += end as u64 + 1;
+                    (self.start, self.scanned) = (0, 0);
+                    return Ok(Some(BytesSegment::new(raw.freeze(), self.delimiters)));
+                }
+                Scan::TooLong(max) => {
+                    let offset = self.consumed + self.start as u64;
+                    self.buf.clear();
+                    (self.start, self.scanned, self.eof) = (0, 0, true);
+                    return Err(ReadError::SegmentTooLong { offset, max });
+                }
+                Scan::Partial => {}
+            }
+
+            if self.eof {
+                if self.start == self.buf.len() {
+                    return Ok(None);
+                }
+                let mut raw = self.buf.split();
+                raw.advance(self.start);
+                raw.truncate(trim_line_ending(&raw).len());
+                (self.start, self.scanned) = (0, 0);
+                return Ok(Some(BytesSegment::new(raw.freeze(), self.delimiters)));
+            }
+
+            if self.start > 0 {
+                self.consumed += self.start as u64;
+                self.buf.advance(self.start);
+                self.scanned -= self.start;
+                self.start = 0;
+            }
+            self.eof = fill_bytes(&mut self.reader, &mut self.buf, self.chunk_size)? == 0;
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<R: Read> Iterator for BytesSegments<R> {
+    type Item = Result<BytesSegment, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.spans.as_ref().map(|spans| spans.current().entered());
+        let segment = self.read_segment().transpose()?;
+        #[cfg(feature = "tracing")]
+        if let (Ok(segment), Some(spans)) = (&segment, self.spans.as_mut()) {
+            spans.observe(&segment.as_segment());
+        }
+        Some(segment)
+    }
+}
+
+/// The end of the next segment in a read buffer, as found by [`scan_segment`].
+enum Scan {
+    /// The segment ends at the terminator at this index.
+    Complete(usize),
+    /// The segment is longer than the given limit.
+    TooLong(usize),
+    /// The buffer ends before the terminator.
+    Partial,
+}
+
+/// Skips line endings at `*start`, then looks for the terminator of the segment starting
+/// there. `*scanned` records how far the buffer has been searched, so a partial segment is
+/// not searched again after a refill.
+fn scan_segment(
+    buf: &[u8],
+    start: &mut usize,
+    scanned: &mut usize,
+    terminator: u8,
+    max_segment_length: Option<usize>,
+) -> Scan {
+    while *start < buf.len() && matches!(buf[*start], b'\r' | b'\n') {
+        *start += 1;
+    }
+    *scanned = (*scanned).max(*start);
+
+    let found = buf[*scanned..].iter().position(|&b| b == terminator);
+    let end = found.map_or(buf.len(), |len| *scanned + len);
+    if let Some(max) = max_segment_length.filter(|&max| end - *start > max) {
+        return Scan::TooLong(max);
+    }
+    match found {
+        Some(_) => Scan::Complete(end),
+        None => {
+            *scanned = buf.len();
+            Scan::Partial
+        }
+    }
+}
+
+pub(crate) fn trim_line_ending(raw: &[u8]) -> &[u8] {
+    let end = raw
+        .iter()
+        .rposition(|&b| b != b'\r' && b != b'\n')
+        .map_or(0, |i| i + 1);
+    &raw[..end]
+}
+
+pub(crate) fn fill<R: Read>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    chunk_size: usize,
+) -> Result<usize, ReadError> {
+    let len = buf.len();
+    buf.resize(len + chunk_size, 0);
+    loop {
+        match reader.read(&mut buf[len..]) {
+            Ok(n) => {
+                buf.truncate(len + n);
+                return Ok(n);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                buf.truncate(len);
+                return Err(e.into());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+fn fill_bytes<R: Read>(
+    reader: &mut R,
+    buf: &mut bytes::BytesMut,
+    chunk_size: usize,
+) -> Result<usize, ReadError> {
+    let len = buf.len();
+    buf.resize(len + chunk_size, 0);
+    loop {
+        match reader.read(&mut buf[len..]) {
+            Ok(n) => {
+                buf.truncate(len + n);
+                return Ok(n);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                buf.truncate(len);
+                return Err(e.into());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[derive(Debug, Default)]
+struct EnvelopeSpans {
+    interchange: Option<tracing::Span>,
+    group: Option<tracing::Span>,
+    transaction: Option<(tracing::Span, usize)>,
+}
+
+#[cfg(feature = "tracing")]
+impl EnvelopeSpans {
+    fn observe(&mut self, segment: &Segment<'_>) {
+        use crate::segment::element_at;
+
+        let delimiters = segment.delimiters();
+        let element = |n| {
+            let value = element_at(segment.as_bytes(), &delimiters, n).unwrap_or_default();
+            String::from_utf8_lossy(value).trim().to_string()
+        };
+
+        match segment.id() {
+            b"ISA" => {
+                self.transaction = None;
+                self.group = None;
+                self.interchange = Some(tracing::info_span!(
+                    "x12_interchange",
+                    sender = %element(6),
+                    receiver = %element(8),
+                    control_number = %element(13),
+                ));
+            }
+            b"GS" => {
+                self.transaction = None;
+                let parent = self.interchange.clone().unwrap_or_else(tracing::Span::none);
+                self.group = Some(tracing::info_span!(
+                    parent: &parent,
+                    "x12_group",
+                    functional_id = %element(1),
+                    control_number = %element(6),
+                ));
+            }
+            b"ST" => {
+                let parent = self
+                    .group
+                    .clone()
+                    .or_else(|| self.interchange.clone())
+                    .unwrap_or_else(tracing::Span::none);
+                let span = tracing::info_span!(
+                    parent: &parent,
+                    "x12_transaction",
+                    transaction_set = %element(1),
+                    control_number = %element(2),
+                    segments = tracing::field::Empty,
+                );
+                self.transaction = Some((span, 1));
+            }
+            b"SE" => {
+                if let Some((span, count)) = self.transaction.take() {
+                    span.record("segments", count + 1);
+                }
+            }
+            b"GE" => self.group = None,
+            b"IEA" => self.interchange = None,
+            _ => {
+                if let Some((_, count)) = self.transaction.as_mut() {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the innermost open envelope span.
+    fn current(&self) -> tracing::Span {
+        self.transaction
+            .as_ref()
+            .map(|(span, _)| span)
+            .or(self.group.as_ref())
+            .or(self.interchange.as_ref())
+            .cloned()
+            .unwrap_or_else(tracing::Span::none)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::DelimiterError;
+    use crate::TerminatorSuffix;
+
+    const DOC: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\n\
+GS*HC*S*R*20250403*0856*1*X*005010X222A1~\r\n\
+ST*837*0001~\r\nBHT*0019~\r\nSE*3*0001~\r\n\
+GE*1*1~\r\n\
+IEA*1*000000001~\r\n";
+
+    /// Returns at most `chunk` bytes per read, to exercise buffer refills.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn ids<R: Read>(reader: SegmentReader<R>) -> Vec<String> {
+        reader
+            .map(|s| String::from_utf8(s.unwrap().id().to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_reads_segments_and_delimiters() {
+        let reader = SegmentReader::new(DOC).unwrap();
+        assert_eq!(
+            reader.delimiters(),
+            Delimiters::default()
+                .with_repetition_separator(Some(b'^'))
+                .with_suffix(TerminatorSuffix::CrLf)
+        );
+        assert_eq!(
+            ids(reader),
+            vec!["ISA", "GS", "ST", "BHT", "SE", "GE", "IEA"]
+        );
+    }
+
+    #[test]
+    fn test_small_reads_match_slice_splitting() {
+        for chunk in [1, 3, 7, 64] {
+            let reader = SegmentReader::new(Trickle { data: DOC, chunk }).unwrap();
+            let streamed: Vec<OwnedSegment> = reader.map(|s| s.unwrap()).collect();
+            let sliced: Vec<OwnedSegment> = streamed[0]
+                .delimiters()
+                .segments(DOC)
+                .map(|s| s.to_owned_segment())
+                .collect();
+            assert_eq!(streamed, sliced);
+        }
+    }
+
+    #[test]
+    fn test_chunk_size_does_not_change_output() {
+        let expected: Vec<OwnedSegment> = SegmentReader::new(DOC)
+            .unwrap()
+            .map(|s| s.unwrap())
+            .collect();
+        for chunk_size in [0, 1, 5, 1 << 20] {
+            let reader = SegmentReader::with_chunk_size(DOC, chunk_size).unwrap();
+            assert_eq!(reader.map(|s| s.unwrap()).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_unterminated_tail_and_short_stream() {
+        let mut data = DOC[..DOC.len() - 3].to_vec();
+        data.extend_from_slice(b"\r\n");
+        let reader = SegmentReader::new(&data[..]).unwrap();
+        let last = reader.last().unwrap().unwrap();
+        assert_eq!(last.as_bytes(), b"IEA*1*000000001");
+
+        match SegmentReader::new(&b"ISA*00*"[..]) {
+            Err(ReadError::Delimiters(DelimiterError::InvalidIsaLength)) => {}
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_max_segment_length() {
+        let within: Vec<OwnedSegment> = SegmentReader::new(Trickle {
+            data: DOC,
+            chunk: 5,
+        })
+        .unwrap()
+        .with_max_segment_length(106)
+        .map(|s| s.unwrap())
+        .collect();
+        assert_eq!(within.len(), 7);
+
+        let mut reader = SegmentReader::new(Trickle {
+            data: DOC,
+            chunk: 5,
+        })
+        .unwrap()
+        .with_max_segment_length(20);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(ReadError::SegmentTooLong { offset: 0, max: 20 }))
+        ));
+        assert!(reader.next().is_none());
+
+        let mut data = DOC[..108].to_vec();
+        data.extend_from_slice(&[b'X'; 4096]);
+        let mut reader = SegmentReader::new(Trickle {
+            data: &data,
+            chunk: 64,
+        })
+        .unwrap()
+        .with_max_segment_length(110);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(
+            reader.next(),
+            Some(Err(ReadError::SegmentTooLong {
+                offset: 108,
+                max: 110
+            }))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes_segments_match_owned() {
+        let expected: Vec<OwnedSegment> = SegmentReader::new(DOC)
+            .unwrap()
+            .map(|s| s.unwrap())
+            .collect();
+        for chunk in [1, 3, 7, 64] {
+            let mut reader = SegmentReader::new(Trickle { data: DOC, chunk }).unwrap();
+            let isa = reader.next().unwrap().unwrap();
+            let mut segments = vec![BytesSegment::new(
+                isa.into_bytes().into(),
+                reader.delimiters(),
+            )];
+            segments.extend(reader.into_bytes_segments().map(|s| s.unwrap()));
+            let owned: Vec<OwnedSegment> = segments
+                .iter()
+                .map(|s| s.as_segment().to_owned_segment())
+                .collect();
+            assert_eq!(owned, expected);
+        }
+
+        // Segments read in one chunk share its allocation.
+        let segments: Vec<BytesSegment> = SegmentReader::new(DOC)
+            .unwrap()
+            .into_bytes_segments()
+            .map(|s| s.unwrap())
+            .collect();
+        let (st, se) = (segments[2].as_bytes(), segments[4].as_bytes());
+        assert_eq!(se, b"SE*3*0001");
+        assert_eq!(
+            se.as_ptr() as usize - st.as_ptr() as usize,
+            b"ST*837*0001~\r\nBHT*0019~\r\n".len()
+        );
+
+        let mut reader = SegmentReader::new(DOC)
+            .unwrap()
+            .with_max_segment_length(20)
+            .into_bytes_segments();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(ReadError::SegmentTooLong { offset: 0, max: 20 }))
+        ));
+        assert!(reader.next().is_none());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_envelope_spans() {
+        use std::sync::{Arc, Mutex};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Span names with their explicit parent IDs, in creation order.
+        type SpanLog = Arc<Mutex<Vec<(String, Option<u64>)>>>;
+
+        #[derive(Clone, Default)]
+        struct Recorder {
+            spans: SpanLog,
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+                let mut spans = self.spans.lock().unwrap();
+                let parent = attrs.parent().map(|id| id.into_u64());
+                spans.push((attrs.metadata().name().to_string(), parent));
+                Id::from_u64(spans.len() as u64)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let reader = SegmentReader::new(DOC).unwrap().trace_envelopes(true);
+            assert_eq!(reader.count(), 7);
+        });
+
+        let spans = recorder.spans.lock().unwrap().clone();
+        assert_eq!(
+            spans,
+            vec![
+                ("x12_interchange".to_string(), None),
+                ("x12_group".to_string(), Some(1)),
+                ("x12_transaction".to_string(), Some(2)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,706 @@
+use crate::ack::{AckCode, Ta1Code, Ta1Segment};
+use crate::control::ControlField;
+use crate::envelope::{numeric_element, required_element};
+use crate::errors::EnvelopeError;
+use crate::functional_ack::{
+    AckFormat, ElementErrorCode, ElementFinding, GroupErrorCode, SegmentErrorCode, SegmentFinding,
+    TransactionErrorCode, TransactionFindings,
+};
+use crate::interchange::{interchanges, Document};
+use crate::segment::Segment;
+
+/// One received 997 or 999, acknowledging a functional group that was sent.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GroupAck {
+    format: AckFormat,
+    control_number: String,
+    functional_id: String,
+    group_control_number: u32,
+    version: Option<String>,
+    code: AckCode,
+    included: usize,
+    received: usize,
+    accepted: usize,
+    transactions: Vec<TransactionFindings>,
+    errors: Vec<GroupErrorCode>,
+}
+
+impl GroupAck {
+    /// Returns whether this is a 997 or a 999.
+    pub fn format(&self) -> AckFormat {
+        self.format
+    }
+
+    /// Returns ST02 of the acknowledgment itself.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+
+    /// Returns AK101, the functional ID of the acknowledged group.
+    pub fn functional_id(&self) -> &str {
+        &self.functional_id
+    }
+
+    /// Returns AK102, the GS06 of the acknowledged group.
+    pub fn group_control_number(&self) -> u32 {
+        self.group_control_number
+    }
+
+    /// Returns AK103, the GS08 of the acknowledged group, if present.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Returns AK901, the acknowledgment code of the group.
+    pub fn code(&self) -> AckCode {
+        self.code
+    }
+
+    /// Returns AK902, the number of transaction sets the group declared.
+    pub fn included(&self) -> usize {
+        self.included
+    }
+
+    /// Returns AK903, the number of transaction sets the receiver read.
+    pub fn received(&self) -> usize {
+        self.received
+    }
+
+    /// Returns AK904, the number of transaction sets the receiver accepted.
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    /// Returns the acknowledged transaction sets, one per AK2 loop, with their IK5 or
+    /// AK5 code set explicitly.
+    pub fn transactions(&self) -> &[TransactionFindings] {
+        &self.transactions
+    }
+
+    /// Returns the group errors in AK905 through AK909.
+    pub fn errors(&self) -> &[GroupErrorCode] {
+        &self.errors
+    }
+}
+
+/// The TA1, 997, and 999 acknowledgments read from received interchanges.
+///
+/// ```
+/// use x12_delimiters::ack::{AckCode, Ta1Code};
+/// use x12_delimiters::reconcile::ReceivedAcks;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*PAYER          *ZZ*SENDERID       *250404*1200*^*00501*000000007*0*P*:~\
+/// TA1*000000001*250403*0856*A*000~\
+/// GS*FA*PAYER*SENDERID*20250404*1200*7*X*005010X231A1~\
+/// ST*999*0001*005010X231A1~AK1*HC*17*005010X222A1~AK2*837*0001~IK5*A~AK9*A*1*1*1~SE*6*0001~\
+/// GE*1*7~IEA*1*000000007~";
+/// let acks = ReceivedAcks::parse(data).unwrap();
+/// assert_eq!(acks.ta1s()[0].code(), Ta1Code::Accepted);
+/// let group = &acks.groups()[0];
+/// assert_eq!((group.functional_id(), group.group_control_number()), ("HC", 17));
+/// assert_eq!(group.transactions()[0].code(), AckCode::Accepted);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ReceivedAcks {
+    ta1s: Vec<Ta1Segment>,
+    groups: Vec<GroupAck>,
+}
+
+impl ReceivedAcks {
+    /// Reads every TA1 segment and every 997 and 999 transaction set in `data`, which
+    /// may hold several interchanges. Other transaction sets are skipped.
+    ///
+    /// # Errors
+    /// Returns the errors of [`Ta1Segment::parse`] for a malformed TA1,
+    /// `EnvelopeError::MissingElement` or `EnvelopeError::InvalidValue` for a malformed
+    /// AK1, AK2, IK3, IK4, IK5, or AK9 (or their 997 counterparts), and
+    /// `EnvelopeError::MissingSegment` for a segment out of order, such as an IK4 without
+    /// an IK3 or an AK2 loop without its IK5.
+    pub fn parse(data: &[u8]) -> Result<Self, EnvelopeError> {
+        let mut acks = ReceivedAcks::default();
+        for (delimiters, bytes) in interchanges(data) {
+            let mut open: Option<OpenAck> = None;
+            for segment in delimiters.segments(bytes) {
+                match (segment.id(), &mut open) {
+                    (b"TA1", _) => acks.ta1s.push(Ta1Segment::parse(&segment)?),
+                    (b"ST", _) => open = OpenAck::start(&segment)?,
+                    (b"SE", Some(_)) => {
+                        if let Some(group) = open.take().and_then(OpenAck::finish) {
+                            acks.groups.push(group?);
+                        }
+                    }
+                    (_, Some(ack)) => ack.read(&segment)?,
+                    _ => {}
+                }
+            }
+        }
+        Ok(acks)
+    }
+
+    /// Returns the TA1 segments, in the order received.
+    pub fn ta1s(&self) -> &[Ta1Segment] {
+        &self.ta1s
+    }
+
+    /// Returns the 997 and 999 acknowledgments, in the order received.
+    pub fn groups(&self) -> &[GroupAck] {
+        &self.groups
+    }
+}
+
+/// A 997 or 999 being read, up to its SE.
+struct OpenAck {
+    format: AckFormat,
+    control_number: String,
+    group: Option<GroupAck>,
+    transaction: Option<TransactionFindings>,
+    segment: Option<SegmentFinding>,
+}
+
+impl OpenAck {
+    /// Starts reading at an ST, or returns None if it is not a 997 or 999.
+    fn start(st: &Segment<'_>) -> Result<Option<Self>, EnvelopeError> {
+        let format = match st.element(1) {
+            Some(b"997") => AckFormat::Functional997,
+            Some(b"999") => AckFormat::Implementation999,
+            _ => return Ok(None),
+        };
+        Ok(Some(OpenAck {
+            format,
+            control_number: text(st, "ST", 2)?,
+            group: None,
+            transaction: None,
+            segment: None,
+        }))
+    }
+
+    fn read(&mut self, segment: &Segment<'_>) -> Result<(), EnvelopeError> {
+        let is_999 = self.format == AckFormat::Implementation999;
+        match segment.id() {
+            b"AK1" => {
+                self.group = Some(GroupAck {
+                    format: self.format,
+                    control_number: self.control_number.clone(),
+                    functional_id: text(segment, "AK1", 1)?,
+                    group_control_number: numeric_element(segment, "AK1", 2, ControlField::Group)?,
+                    version: text(segment, "AK1", 3).ok(),
+                    code: AckCode::Rejected,
+                    included: 0,
+                    received: 0,
+                    accepted: 0,
+                    transactions: Vec::new(),
+                    errors: Vec::new(),
+                });
+            }
+            b"AK2" => {
+                self.group_mut()?;
+                if self.transaction.is_some() {
+                    return Err(self.missing_ik5());
+                }
+                let mut transaction =
+                    TransactionFindings::new(&text(segment, "AK2", 1)?, &text(segment, "AK2", 2)?);
+                if let Ok(reference) = text(segment, "AK2", 3) {
+                    transaction = transaction.with_implementation_reference(&reference);
+                }
+                self.transaction = Some(transaction);
+            }
+            b"IK3" | b"AK3" if segment.id().starts_with(b"I") == is_999 => {
+                let id = if is_999 { "IK3" } else { "AK3" };
+                self.close_segment()?;
+                let mut finding =
+                    SegmentFinding::new(&text(segment, id, 1)?, number(segment, id, 2)?);
+                if let Ok(loop_id) = text(segment, id, 3) {
+                    finding = finding.with_loop_id(&loop_id);
+                }
+                if let Ok(code) = text(segment, id, 4) {
+                    let code = SegmentErrorCode::from_code(&code)
+                        .ok_or(EnvelopeError::InvalidValue { id, element: 4 })?;
+                    finding = finding.with_code(code);
+                }
+                self.segment = Some(finding);
+            }
+            b"IK4" | b"AK4" if segment.id().starts_with(b"I") == is_999 => {
+                let id = if is_999 { "IK4" } else { "AK4" };
+                let finding = self.segment.take().ok_or(EnvelopeError::MissingSegment {
+                    expected: if is_999 { "IK3" } else { "AK3" },
+                })?;
+                let code = ElementErrorCode::from_code(&text(segment, id, 3)?)
+                    .ok_or(EnvelopeError::InvalidValue { id, element: 3 })?;
+                let positions = segment
+                    .sub_elements(1)
+                    .ok_or(EnvelopeError::MissingElement { id, element: 1 })?
+                    .map(|n| parse_number(n).ok_or(EnvelopeError::InvalidValue { id, element: 1 }))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut element = ElementFinding::new(positions[0], code);
+                if let Some(&component) = positions.get(1).filter(|&&n| n > 0) {
+                    element = element.with_component(component);
+                }
+                if let Some(&repetition) = positions.get(2) {
+                    element = element.with_repetition(repetition);
+                }
+                if let Ok(reference) = text(segment, id, 2) {
+                    element = element.with_reference(&reference);
+                }
+                if let Ok(value) = text(segment, id, 4) {
+                    element = element.with_value(&value);
+                }
+                self.segment = Some(finding.with_element(element));
+            }
+            b"IK5" | b"AK5" if segment.id().starts_with(b"I") == is_999 => {
+                let id = if is_999 { "IK5" } else { "AK5" };
+                self.close_segment()?;
+                let mut transaction = self
+                    .transaction
+                    .take()
+                    .ok_or(EnvelopeError::MissingSegment { expected: "AK2" })?;
+                transaction = transaction.with_code(ack_code(segment, id, 1)?);
+                for element in 2..=6 {
+                    if let Ok(code) = text(segment, id, element) {
+                        let error = TransactionErrorCode::from_code(&code)
+                            .ok_or(EnvelopeError::InvalidValue { id, element })?;
+                        transaction = transaction.with_error(error);
+                    }
+                }
+                self.group_mut()?.transactions.push(transaction);
+            }
+            b"AK9" => {
+                if self.transaction.is_some() {
+                    return Err(self.missing_ik5());
+                }
+                let code = ack_code(segment, "AK9", 1)?;
+                let counts = [2, 3, 4]
+                    .map(|element| number(segment, "AK9", element))
+                    .into_iter()
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut errors = Vec::new();
+                for element in 5..=9 {
+                    if let Ok(code) = text(segment, "AK9", element) {
+                        errors.push(
+                            GroupErrorCode::from_code(&code)
+                                .ok_or(EnvelopeError::InvalidValue { id: "AK9", element })?,
+                        );
+                    }
+                }
+                let group = self.group_mut()?;
+                group.code = code;
+                group.included = counts[0];
+                group.received = counts[1];
+                group.accepted = counts[2];
+                group.errors = errors;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Ends reading at the SE, returning the group acknowledgment, if there was an AK1.
+    fn finish(self) -> Option<Result<GroupAck, EnvelopeError>> {
+        if self.transaction.is_some() {
+            return Some(Err(self.missing_ik5()));
+        }
+        self.group.map(Ok)
+    }
+
+    fn group_mut(&mut self) -> Result<&mut GroupAck, EnvelopeError> {
+        self.group
+            .as_mut()
+            .ok_or(EnvelopeError::MissingSegment { expected: "AK1" })
+    }
+
+    /// Adds the segment error being read to its transaction set.
+    fn close_segment(&mut self) -> Result<(), EnvelopeError> {
+        let Some(finding) = self.segment.take() else {
+            return Ok(());
+        };
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or(EnvelopeError::MissingSegment { expected: "AK2" })?;
+        self.transaction = Some(transaction.with_segment(finding));
+        Ok(())
+    }
+
+    fn missing_ik5(&self) -> EnvelopeError {
+        EnvelopeError::MissingSegment {
+            expected: match self.format {
+                AckFormat::Functional997 => "AK5",
+                AckFormat::Implementation999 => "IK5",
+            },
+        }
+    }
+}
+
+fn text(segment: &Segment<'_>, id: &'static str, element: usize) -> Result<String, EnvelopeError> {
+    required_element(segment, id, element).map(|value| String::from_utf8_lossy(value).into_owned())
+}
+
+fn parse_number(value: &[u8]) -> Option<usize> {
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+fn number(segment: &Segment<'_>, id: &'static str, element: usize) -> Result<usize, EnvelopeError> {
+    parse_number(required_element(segment, id, element)?)
+        .ok_or(EnvelopeError::InvalidValue { id, element })
+}
+
+fn ack_code(
+    segment: &Segment<'_>,
+    id: &'static str,
+    element: usize,
+) -> Result<AckCode, EnvelopeError> {
+    AckCode::from_code(&text(segment, id, element)?)
+        .ok_or(EnvelopeError::InvalidValue { id, element })
+}
+
+/// Where a sent transaction set stands, according to the acknowledgments received.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum AckStatus {
+    Accepted,
+    AcceptedWithErrors,
+    Rejected,
+    Pending,
+}
+
+impl AckStatus {
+    /// All statuses.
+    pub const ALL: [AckStatus; 4] = [
+        AckStatus::Accepted,
+        AckStatus::AcceptedWithErrors,
+        AckStatus::Rejected,
+        AckStatus::Pending,
+    ];
+
+    /// Returns a short lowercase name for reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckStatus::Accepted => "accepted",
+            AckStatus::AcceptedWithErrors => "accepted-with-errors",
+            AckStatus::Rejected => "rejected",
+            AckStatus::Pending => "pending",
+        }
+    }
+
+    fn from_ack(code: AckCode) -> Self {
+        match code {
+            AckCode::Accepted => AckStatus::Accepted,
+            AckCode::AcceptedWithErrors | AckCode::PartiallyAccepted => {
+                AckStatus::AcceptedWithErrors
+            }
+            AckCode::Rejected => AckStatus::Rejected,
+        }
+    }
+}
+
+/// The acknowledgment status of one sent transaction set.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TransactionStatus {
+    interchange_control_number: u32,
+    functional_id: String,
+    group_control_number: u32,
+    transaction_set_id: String,
+    control_number: u32,
+    status: AckStatus,
+}
+
+impl TransactionStatus {
+    /// Returns ISA13 of the interchange that carried the transaction set.
+    pub fn interchange_control_number(&self) -> u32 {
+        self.interchange_control_number
+    }
+
+    /// Returns GS01 of its functional group.
+    pub fn functional_id(&self) -> &str {
+        &self.functional_id
+    }
+
+    /// Returns GS06 of its functional group.
+    pub fn group_control_number(&self) -> u32 {
+        self.group_control_number
+    }
+
+    /// Returns ST01.
+    pub fn transaction_set_id(&self) -> &str {
+        &self.transaction_set_id
+    }
+
+    /// Returns ST02.
+    pub fn control_number(&self) -> u32 {
+        self.control_number
+    }
+
+    /// Returns the status.
+    pub fn status(&self) -> AckStatus {
+        self.status
+    }
+}
+
+/// The result of [`reconcile`]: a status for every sent transaction set, and the
+/// acknowledgments that matched nothing sent.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ReconciliationReport {
+    transactions: Vec<TransactionStatus>,
+    unmatched_ta1s: Vec<Ta1Segment>,
+    unmatched_groups: Vec<GroupAck>,
+}
+
+impl ReconciliationReport {
+    /// Returns the status of every sent transaction set, in document order.
+    pub fn transactions(&self) -> &[TransactionStatus] {
+        &self.transactions
+    }
+
+    /// Returns the number of transaction sets with `status`.
+    pub fn count(&self, status: AckStatus) -> usize {
+        self.transactions
+            .iter()
+            .filter(|t| t.status == status)
+            .count()
+    }
+
+    /// Returns whether every sent transaction set has been acknowledged.
+    pub fn is_complete(&self) -> bool {
+        self.count(AckStatus::Pending) == 0
+    }
+
+    /// Returns the TA1 segments whose control number matches no sent interchange.
+    pub fn unmatched_ta1s(&self) -> &[Ta1Segment] {
+        &self.unmatched_ta1s
+    }
+
+    /// Returns the 997 and 999 acknowledgments whose AK101 and AK102 match no sent
+    /// functional group.
+    pub fn unmatched_groups(&self) -> &[GroupAck] {
+        &self.unmatched_groups
+    }
+}
+
+/// Matches received acknowledgments to the interchanges in `sent` by control number
+/// and reports the status of each sent transaction set.
+///
+/// A rejecting TA1 rejects every transaction set in its interchange. Otherwise the
+/// transaction set takes the code of its AK2 loop, matched by ST01 and ST02, in the
+/// last 997 or 999 for its group, matched by GS01 and GS06. A transaction set without
+/// an AK2 loop takes the group's AK901, except that a partially accepted group accepts
+/// the transaction sets it does not list, since only errors need to be listed. With no
+/// acknowledgment for its group, a transaction set is pending. A and E codes map to
+/// [`AckStatus::Accepted`] and [`AckStatus::AcceptedWithErrors`], and P in IK5 or AK5
+/// maps to [`AckStatus::AcceptedWithErrors`].
+///
+/// ```
+/// use x12_delimiters::reconcile::{reconcile, AckStatus, ReceivedAcks};
+///
+/// let sent = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*PAYER          *250403*0856*^*00501*000000001*0*P*:~\
+/// GS*HC*SENDERID*PAYER*20250403*0856*17*X*005010X222A1~\
+/// ST*837*0001~BHT*0019~SE*3*0001~ST*837*0002~BHT*0019~SE*3*0002~GE*2*17~IEA*1*000000001~";
+/// let received = b"ISA*00*          *00*          *ZZ*PAYER          *ZZ*SENDERID       *250404*1200*^*00501*000000007*0*P*:~\
+/// GS*FA*PAYER*SENDERID*20250404*1200*7*X*005010X231A1~\
+/// ST*999*0001*005010X231A1~AK1*HC*17*005010X222A1~AK2*837*0002~IK5*R*5~AK9*P*2*2*1~SE*6*0001~\
+/// GE*1*7~IEA*1*000000007~";
+/// let report = reconcile(sent, &ReceivedAcks::parse(received).unwrap()).unwrap();
+/// let statuses: Vec<_> = report.transactions().iter().map(|t| t.status()).collect();
+/// assert_eq!(statuses, [AckStatus::Accepted, AckStatus::Rejected]);
+/// assert!(report.is_complete());
+/// ```
+///
+/// # Errors
+/// Returns the errors of [`Document::parse`] if `sent` cannot be parsed.
+pub fn reconcile(sent: &[u8], acks: &ReceivedAcks) -> Result<ReconciliationReport, EnvelopeError> {
+    let document = Document::parse(sent)?;
+    let mut report = ReconciliationReport::default();
+    let mut matched_ta1s = vec![false; acks.ta1s.len()];
+    let mut matched_groups = vec![false; acks.groups.len()];
+    for interchange in &document {
+        let control = interchange.isa().control_number();
+        let mut rejected = false;
+        for (i, ta1) in acks.ta1s.iter().enumerate() {
+            if ta1.control_number().parse() == Ok(control) {
+                matched_ta1s[i] = true;
+                // The latest TA1 wins, in case of a retransmission.
+                rejected = ta1.code() == Ta1Code::Rejected;
+            }
+        }
+        for group in interchange.groups() {
+            let gs = group.gs();
+            let matches = |ack: &GroupAck| {
+                ack.functional_id == gs.functional_id()
+                    && ack.group_control_number == gs.control_number()
+            };
+            for (i, ack) in acks.groups.iter().enumerate() {
+                if matches(ack) {
+                    matched_groups[i] = true;
+                }
+            }
+            let ack = acks.groups.iter().rev().find(|ack| matches(ack));
+            for transaction in group.transactions() {
+                let st = transaction.st();
+                let status = if rejected {
+                    AckStatus::Rejected
+                } else if let Some(ack) = ack {
+                    let listed = ack.transactions.iter().rev().find(|t| {
+                        t.transaction_set_id() == st.transaction_set_id()
+                            && t.control_number().parse() == Ok(st.control_number())
+                    });
+                    match (listed, ack.code) {
+                        (Some(listed), _) => AckStatus::from_ack(listed.code()),
+                        (None, AckCode::PartiallyAccepted) => AckStatus::Accepted,
+                        (None, code) => AckStatus::from_ack(code),
+                    }
+                } else {
+                    AckStatus::Pending
+                };
+                report.transactions.push(TransactionStatus {
+                    interchange_control_number: control,
+                    functional_id: gs.functional_id().to_string(),
+                    group_control_number: gs.control_number(),
+                    transaction_set_id: st.transaction_set_id().to_string(),
+                    control_number: st.control_number(),
+                    status,
+                });
+            }
+        }
+    }
+    report.unmatched_ta1s = unmatched(&acks.ta1s, &matched_ta1s);
+    report.unmatched_groups = unmatched(&acks.groups, &matched_groups);
+    Ok(report)
+}
+
+fn unmatched<T: Clone>(items: &[T], matched: &[bool]) -> Vec<T> {
+    items
+        .iter()
+        .zip(matched)
+        .filter(|(_, &matched)| !matched)
+        .map(|(item, _)| item.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ack::Ta1NoteCode;
+    use crate::test_support::isa_to;
+
+    fn sent(control: u32, transactions: &[u32]) -> String {
+        let mut data = isa_to("PAYER", control);
+        data.push_str("GS*HC*SENDERID*PAYER*20250403*0856*17*X*005010X222A1~");
+        for st in transactions {
+            data.push_str(&format!("ST*837*{:04}~BHT*0019~SE*3*{:04}~", st, st));
+        }
+        data.push_str(&format!(
+            "GE*{}*17~IEA*1*{:09}~",
+            transactions.len(),
+            control
+        ));
+        data
+    }
+
+    fn received(body: &str) -> ReceivedAcks {
+        let data = format!(
+            "{}GS*FA*PAYER*SENDERID*20250404*1200*7*X*005010X231A1~{}GE*1*7~IEA*1*000000007~",
+            isa_to("PAYER", 7),
+            body
+        );
+        ReceivedAcks::parse(data.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_999_errors() {
+        let acks = received(
+            "ST*999*0001*005010X231A1~AK1*HC*17*005010X222A1~AK2*837*0001*005010X222A1~\
+IK3*NM1*4*2010BA*8~IK4*3:0:2*1065*7*ZZ~IK4*9**1~IK5*R*I5~AK9*R*1*1*0*5~SE*8*0001~",
+        );
+        let group = &acks.groups()[0];
+        assert_eq!(group.format(), AckFormat::Implementation999);
+        assert_eq!(group.version(), Some("005010X222A1"));
+        assert_eq!(group.code(), AckCode::Rejected);
+        assert_eq!(
+            (group.included(), group.received(), group.accepted()),
+            (1, 1, 0)
+        );
+        assert_eq!(group.errors(), [GroupErrorCode::TransactionCountMismatch]);
+        let transaction = &group.transactions()[0];
+        assert_eq!(transaction.implementation_reference(), Some("005010X222A1"));
+        assert_eq!(
+            transaction.errors(),
+            [TransactionErrorCode::from_code("I5").unwrap()]
+        );
+        let segment = &transaction.segments()[0];
+        assert_eq!((segment.segment_id(), segment.position()), ("NM1", 4));
+        assert_eq!(segment.loop_id(), Some("2010BA"));
+        let elements = segment.elements();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(
+            elements[0],
+            ElementFinding::new(3, ElementErrorCode::from_code("7").unwrap())
+                .with_repetition(2)
+                .with_reference("1065")
+                .with_value("ZZ")
+        );
+        assert_eq!(elements[1].position(), 9);
+    }
+
+    #[test]
+    fn test_parse_997_and_ta1() {
+        let acks = received(
+            "TA1*000000001*250403*0856*R*022~\
+ST*997*0001~AK1*HC*17~AK2*837*0001~AK3*NM1*4**8~AK4*3**7~AK5*R*5~AK9*R*1*1*0~SE*8*0001~",
+        );
+        assert_eq!(
+            acks.ta1s()[0].note(),
+            Ta1NoteCode::from_code("022").unwrap()
+        );
+        let group = &acks.groups()[0];
+        assert_eq!(group.format(), AckFormat::Functional997);
+        assert_eq!(group.version(), None);
+        assert_eq!(
+            group.transactions()[0].segments()[0].elements()[0].position(),
+            3
+        );
+
+        let data = format!(
+            "{}ST*999*0001~AK1*HC*17~AK2*837*0001~AK9*R*1*1*0~SE*5*0001~",
+            isa_to("PAYER", 7)
+        );
+        assert_eq!(
+            ReceivedAcks::parse(data.as_bytes()),
+            Err(EnvelopeError::MissingSegment { expected: "IK5" })
+        );
+    }
+
+    #[test]
+    fn test_reconcile_statuses() {
+        let sent = format!("{}\n{}", sent(1, &[1, 2, 3]), sent(2, &[1]));
+        let acks = received(
+            "TA1*000000002*250403*0856*R*006~TA1*000000099*250403*0856*A*000~\
+ST*999*0001*005010X231A1~AK1*HC*17*005010X222A1~AK2*837*0001~IK5*E~AK2*837*0002~IK5*R*5~\
+AK9*P*3*3*2~SE*8*0001~ST*999*0002*005010X231A1~AK1*HC*18*005010X222A1~AK9*A*0*0*0~SE*4*0002~",
+        );
+        let report = reconcile(sent.as_bytes(), &acks).unwrap();
+        let statuses: Vec<_> = report
+            .transactions()
+            .iter()
+            .map(|t| {
+                (
+                    t.interchange_control_number(),
+                    t.control_number(),
+                    t.status(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (1, 1, AckStatus::AcceptedWithErrors),
+                (1, 2, AckStatus::Rejected),
+                (1, 3, AckStatus::Accepted),
+                (2, 1, AckStatus::Rejected),
+            ]
+        );
+        assert_eq!(report.count(AckStatus::Rejected), 2);
+        assert_eq!(report.unmatched_ta1s()[0].control_number(), "000000099");
+        assert_eq!(report.unmatched_groups()[0].group_control_number(), 18);
+
+        let pending = reconcile(sent.as_bytes(), &ReceivedAcks::default()).unwrap();
+        assert_eq!(pending.count(AckStatus::Pending), 4);
+        assert!(!pending.is_complete());
+    }
+}
@@ -0,0 +1,252 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::segment::Span;
+use crate::Delimiters;
+
+/// A segment that occurs more than once with identical bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RepeatedSegment<'a> {
+    bytes: &'a [u8],
+    first: Span,
+    count: usize,
+}
+
+impl<'a> RepeatedSegment<'a> {
+    /// Returns the segment content, without terminator.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the span of the first occurrence, terminator included.
+    pub fn first(&self) -> Span {
+        self.first
+    }
+
+    /// Returns the number of occurrences.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the bytes saved by storing the segment once and referencing it from the
+    /// other occurrences, ignoring the cost of the references.
+    pub fn savings(&self) -> usize {
+        (self.count - 1) * self.first.len()
+    }
+}
+
+/// A prefix shared by the values of one element across several segments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CommonPrefix<'a> {
+    segment_id: &'a [u8],
+    element: usize,
+    prefix: &'a [u8],
+    count: usize,
+}
+
+impl<'a> CommonPrefix<'a> {
+    /// Returns the ID of the segments the values come from.
+    pub fn segment_id(&self) -> &'a [u8] {
+        self.segment_id
+    }
+
+    /// Returns the element position, starting at 1.
+    pub fn element(&self) -> usize {
+        self.element
+    }
+
+    /// Returns the shared prefix.
+    pub fn prefix(&self) -> &'a [u8] {
+        self.prefix
+    }
+
+    /// Returns the number of values that start with the prefix.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the bytes saved by storing the prefix once, ignoring the cost of
+    /// referencing it.
+    pub fn savings(&self) -> usize {
+        (self.count - 1) * self.prefix.len()
+    }
+}
+
+/// Segment ID, element position, and the leading bytes values are grouped by.
+type ColumnKey<'a> = (&'a [u8], usize, &'a [u8]);
+
+/// Result of [`analyze_redundancy`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct RedundancyReport<'a> {
+    total_bytes: usize,
+    segment_count: usize,
+    repeated_segments: Vec<RepeatedSegment<'a>>,
+    common_prefixes: Vec<CommonPrefix<'a>>,
+}
+
+impl<'a> RedundancyReport<'a> {
+    /// Returns the size of the analyzed input.
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Returns the number of segments analyzed.
+    pub fn segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Returns the repeated segments, largest savings first.
+    pub fn repeated_segments(&self) -> &[RepeatedSegment<'a>] {
+        &self.repeated_segments
+    }
+
+    /// Returns the shared element-value prefixes, largest savings first.
+    pub fn common_prefixes(&self) -> &[CommonPrefix<'a>] {
+        &self.common_prefixes
+    }
+
+    /// Returns the total savings of deduplicating repeated segments.
+    pub fn segment_savings(&self) -> usize {
+        self.repeated_segments
+            .iter()
+            .map(RepeatedSegment::savings)
+            .sum()
+    }
+
+    /// Returns the total savings of storing shared prefixes once. Prefixes inside repeated
+    /// segments are counted here too, so this overlaps [`Self::segment_savings`].
+    pub fn prefix_savings(&self) -> usize {
+        self.common_prefixes.iter().map(CommonPrefix::savings).sum()
+    }
+}
+
+/// Reports repeated segments and shared element-value prefixes in `data`, to estimate how
+/// well dictionary or prefix compression would do on it.
+///
+/// Segments are compared byte for byte without their terminators. Element values are
+/// compared per segment ID and element position: values of at least `min_prefix` bytes
+/// are grouped by their first `min_prefix` bytes, and each group of two or more is
+/// reported with the longest prefix all its values share. Savings are rough estimates in
+/// bytes, before any general-purpose compression.
+///
+/// ```
+/// use x12_delimiters::redundancy::analyze_redundancy;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"NM1*IL*1*DOE~REF*SY*123450001~NM1*IL*1*DOE~REF*SY*123450002~";
+/// let report = analyze_redundancy(data, &Delimiters::default(), 4);
+/// assert_eq!(report.repeated_segments()[0].as_bytes(), b"NM1*IL*1*DOE");
+/// assert_eq!(report.segment_savings(), 13);
+///
+/// let prefix = &report.common_prefixes()[0];
+/// assert_eq!((prefix.segment_id(), prefix.element()), (&b"REF"[..], 2));
+/// assert_eq!(prefix.prefix(), b"12345000");
+/// assert_eq!(prefix.savings(), 8);
+/// ```
+pub fn analyze_redundancy<'a>(
+    data: &'a [u8],
+    delimiters: &Delimiters,
+    min_prefix: usize,
+) -> RedundancyReport<'a> {
+    let min_prefix = min_prefix.max(1);
+    let separator = delimiters.element_separator();
+    let mut segments: HashMap<&[u8], RepeatedSegment<'a>> = HashMap::new();
+    let mut values: HashMap<ColumnKey<'a>, (&'a [u8], usize)> = HashMap::new();
+    let mut segment_count = 0;
+
+    for (span, segment) in delimiters.segments(data).spanned() {
+        segment_count += 1;
+        let bytes = segment.as_bytes();
+        segments
+            .entry(bytes)
+            .and_modify(|repeated| repeated.count += 1)
+            .or_insert(RepeatedSegment {
+                bytes,
+                first: span,
+                count: 1,
+            });
+
+        let id = segment.id();
+        for (element, value) in bytes.split(|&b| b == separator).enumerate().skip(1) {
+            if value.len() < min_prefix {
+                continue;
+            }
+            values
+                .entry((id, element, &value[..min_prefix]))
+                .and_modify(|(prefix, count)| {
+                    *prefix = &prefix[..common_prefix_len(prefix, value)];
+                    *count += 1;
+                })
+                .or_insert((value, 1));
+        }
+    }
+
+    let mut repeated_segments: Vec<_> = segments
+        .into_values()
+        .filter(|repeated| repeated.count > 1)
+        .collect();
+    repeated_segments.sort_by_key(|repeated| (Reverse(repeated.savings()), repeated.first.start));
+
+    let mut common_prefixes: Vec<_> = values
+        .into_iter()
+        .filter(|(_, (_, count))| *count > 1)
+        .map(|((segment_id, element, _), (prefix, count))| CommonPrefix {
+            segment_id,
+            element,
+            prefix,
+            count,
+        })
+        .collect();
+    common_prefixes.sort_by_key(|p| (Reverse(p.savings()), p.segment_id, p.element, p.prefix));
+
+    RedundancyReport {
+        total_bytes: data.len(),
+        segment_count,
+        repeated_segments,
+        common_prefixes,
+    }
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_segments() {
+        let data = b"ST*837~\r\nHL*1~\r\nDTP*472*D8*20250403~\r\nHL*2~\r\nDTP*472*D8*20250403~\r\nDTP*472*D8*20250403~\r\nSE*7~\r\n";
+        let delimiters = Delimiters::default().with_suffix(crate::TerminatorSuffix::CrLf);
+        let report = analyze_redundancy(data, &delimiters, 4);
+        assert_eq!(report.total_bytes(), data.len());
+        assert_eq!(report.segment_count(), 7);
+        let repeated = report.repeated_segments();
+        assert_eq!(repeated.len(), 1);
+        assert_eq!(repeated[0].as_bytes(), b"DTP*472*D8*20250403");
+        assert_eq!(repeated[0].count(), 3);
+        assert_eq!(repeated[0].first(), Span::new(16, 36));
+        assert_eq!(report.segment_savings(), 40);
+    }
+
+    #[test]
+    fn test_common_prefixes() {
+        let data = b"CLM*ABC-0001*100~CLM*ABC-0002*250~CLM*XYZ*300~CLM*ABC-1000*75~REF*ABC-0003~";
+        let report = analyze_redundancy(data, &Delimiters::default(), 3);
+        let prefixes: Vec<_> = report
+            .common_prefixes()
+            .iter()
+            .map(|p| (p.segment_id(), p.element(), p.prefix(), p.count()))
+            .collect();
+        assert_eq!(prefixes, [(&b"CLM"[..], 1, &b"ABC-"[..], 3)]);
+        assert_eq!(report.prefix_savings(), 8);
+        assert!(report.repeated_segments().is_empty());
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let report = analyze_redundancy(b"", &Delimiters::default(), 0);
+        assert_eq!(report, RedundancyReport::default());
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::segment::Span;
+use crate::{Delimiters, TerminatorSuffix};
+
+/// Why re-serializing a document would change a byte range.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RoundtripReason {
+    /// The bytes between two segments, or before the first, are not the suffix that
+    /// would be written in their place.
+    LineEnding {
+        /// The suffix written after every terminator.
+        expected: TerminatorSuffix,
+    },
+    /// The final segment has no terminator, so one would be added; any line ending after
+    /// it would be replaced by the suffix.
+    MissingTerminator,
+}
+
+/// A byte range that would differ after re-serialization.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RoundtripChange {
+    span: Span,
+    reason: RoundtripReason,
+}
+
+impl RoundtripChange {
+    /// Returns the affected input bytes. Empty if bytes would only be inserted.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns why the range would change.
+    pub fn reason(&self) -> RoundtripReason {
+        self.reason
+    }
+
+    /// Returns true if the change only affects line endings, which carry no data.
+    pub fn is_cosmetic(&self) -> bool {
+        matches!(self.reason, RoundtripReason::LineEnding { .. })
+    }
+}
+
+impl fmt::Display for RoundtripChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bytes {}..{}: ", self.span.start, self.span.end)?;
+        match self.reason {
+            RoundtripReason::LineEnding { expected } => {
+                write!(f, "line ending would be rewritten as {:?}", expected)
+            }
+            RoundtripReason::MissingTerminator => {
+                write!(f, "final segment would gain a terminator")
+            }
+        }
+    }
+}
+
+/// Result of [`verify_roundtrip`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct RoundtripReport {
+    changes: Vec<RoundtripChange>,
+}
+
+impl RoundtripReport {
+    /// Returns every range that would change, in document order.
+    pub fn changes(&self) -> &[RoundtripChange] {
+        &self.changes
+    }
+
+    /// Returns true if re-serializing reproduces the input byte for byte.
+    pub fn is_exact(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns true if every change is cosmetic, so segment content survives even if the
+    /// bytes do not.
+    pub fn preserves_content(&self) -> bool {
+        self.changes.iter().all(RoundtripChange::is_cosmetic)
+    }
+}
+
+/// Reports which bytes of `data` would change if it were split into segments with
+/// `delimiters` and written back with the same delimiters.
+///
+/// Segment editing works on [`Delimiters::segments`], which drops line endings and writes
+/// the [`TerminatorSuffix`] of `delimiters` after every terminator instead. Run this as a
+/// pre-flight check: [`RoundtripReport::is_exact`] is the strict test that an in-place
+/// edit leaves every other byte alone, and [`RoundtripReport::preserves_content`] the
+/// lenient one that tolerates rewritten line endings.
+///
+/// ```
+/// use x12_delimiters::roundtrip::{verify_roundtrip, RoundtripReason};
+/// use x12_delimiters::{Delimiters, TerminatorSuffix};
+///
+/// let delimiters = Delimiters::default().with_suffix(TerminatorSuffix::CrLf);
+/// assert!(verify_roundtrip(b"ST*837~\r\nSE*2~\r\n", &delimiters).is_exact());
+///
+/// let report = verify_roundtrip(b"ST*837~\nSE*2", &delimiters);
+/// assert!(!report.preserves_content());
+/// assert_eq!(report.changes()[0].span().start, 7);
+/// assert_eq!(report.changes()[1].reason(), RoundtripReason::MissingTerminator);
+/// ```
+pub fn verify_roundtrip(data: &[u8], delimiters: &Delimiters) -> RoundtripReport {
+    let suffix = delimiters.suffix();
+    let mut changes = Vec::new();
+    let mut segments = delimiters.segments(data).spanned().peekable();
+    let leading = segments.peek().map_or(data.len(), |(span, _)| span.start);
+    check_line_ending(&mut changes, data, Span::new(0, leading), suffix, b"");
+    while let Some((span, segment)) = segments.next() {
+        let body_end = span.start + segment.as_bytes().len();
+        if data.get(body_end) != Some(&delimiters.segment_terminator()) {
+            changes.push(RoundtripChange {
+                span: Span::new(body_end, data.len()),
+                reason: RoundtripReason::MissingTerminator,
+            });
+            break;
+        }
+        let next = segments.peek().map_or(data.len(), |(next, _)| next.start);
+        let gap = Span::new(span.end, next);
+        check_line_ending(&mut changes, data, gap, suffix, suffix.as_bytes());
+    }
+    RoundtripReport { changes }
+}
+
+/// Records a change if the bytes in `span` are not `written`.
+fn check_line_ending(
+    changes: &mut Vec<RoundtripChange>,
+    data: &[u8],
+    span: Span,
+    suffix: TerminatorSuffix,
+    written: &[u8],
+) {
+    if &data[span.start..span.end] != written {
+        changes.push(RoundtripChange {
+            span,
+            reason: RoundtripReason::LineEnding { expected: suffix },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_roundtrip() {
+        let data = b"ISA*00~GS*HC~ST*837~SE*2~";
+        assert!(verify_roundtrip(data, &Delimiters::default()).is_exact());
+        assert!(verify_roundtrip(b"", &Delimiters::default()).is_exact());
+    }
+
+    #[test]
+    fn test_reports_line_endings_and_missing_terminator() {
+        let delimiters = Delimiters::default().with_suffix(TerminatorSuffix::Lf);
+        let data = b"\r\nST*837~\nBHT*0019~\r\n\nSE*2\r\n";
+        let report = verify_roundtrip(data, &delimiters);
+        let changes: Vec<_> = report
+            .changes()
+            .iter()
+            .map(|c| (c.span(), c.reason()))
+            .collect();
+        let line_ending = RoundtripReason::LineEnding {
+            expected: TerminatorSuffix::Lf,
+        };
+        assert_eq!(
+            changes,
+            [
+                (Span::new(0, 2), line_ending),
+                (Span::new(19, 22), line_ending),
+                (Span::new(26, 28), RoundtripReason::MissingTerminator),
+            ]
+        );
+        assert!(!report.preserves_content());
+        assert_eq!(
+            report.changes()[1].to_string(),
+            "bytes 19..22: line ending would be rewritten as Lf"
+        );
+    }
+
+    #[test]
+    fn test_cosmetic_changes_preserve_content() {
+        let report = verify_roundtrip(b"ST*837~\r\nSE*2~\r\n", &Delimiters::default());
+        assert_eq!(report.changes().len(), 2);
+        assert!(!report.is_exact());
+        assert!(report.preserves_content());
+    }
+}
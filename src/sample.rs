@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+
+use crate::builder::DocumentBuilder;
+use crate::errors::DelimiterError;
+use crate::segment::{Segment, Span};
+use crate::Delimiters;
+
+/// Which segments [`sample_segments`] returns.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SampleStrategy {
+    first: usize,
+    last: usize,
+    random: usize,
+    seed: u64,
+}
+
+impl SampleStrategy {
+    /// Creates a strategy that samples nothing; use the `with_*` methods to choose counts.
+    pub fn new() -> Self {
+        SampleStrategy::default()
+    }
+
+    /// Keeps the first `n` segments.
+    pub fn with_first(mut self, n: usize) -> Self {
+        self.first = n;
+        self
+    }
+
+    /// Keeps the last `n` segments.
+    pub fn with_last(mut self, n: usize) -> Self {
+        self.last = n;
+        self
+    }
+
+    /// Keeps `k` segments chosen uniformly at random.
+    pub fn with_random(mut self, k: usize) -> Self {
+        self.random = k;
+        self
+    }
+
+    /// Sets the seed for the random sample; the same seed and data give the same sample.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// Segments chosen by [`sample_segments`], each with its span in the input.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SegmentSample<'a> {
+    first: Vec<(Span, Segment<'a>)>,
+    last: Vec<(Span, Segment<'a>)>,
+    random: Vec<(Span, Segment<'a>)>,
+    total: usize,
+}
+
+impl<'a> SegmentSample<'a> {
+    /// Returns the first segments, in document order.
+    pub fn first(&self) -> &[(Span, Segment<'a>)] {
+        &self.first
+    }
+
+    /// Returns the last segments, in document order.
+    pub fn last(&self) -> &[(Span, Segment<'a>)] {
+        &self.last
+    }
+
+    /// Returns the random sample, in document order.
+    pub fn random(&self) -> &[(Span, Segment<'a>)] {
+        &self.random
+    }
+
+    /// Returns the number of segments in the input.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// Samples segments for spot checks in a single pass.
+///
+/// Memory use depends only on the requested counts, not on the size of the input: the
+/// last segments are kept in a ring buffer and the random sample is drawn by reservoir
+/// sampling over every segment, so it may overlap the first and last segments.
+///
+/// ```
+/// use x12_delimiters::sample::{sample_segments, SampleStrategy};
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ST*837~BHT*0019~NM1*41~NM1*40~HL*1~SE*6*0001~";
+/// let strategy = SampleStrategy::new().with_first(1).with_last(1).with_random(2);
+/// let sample = sample_segments(data, &Delimiters::default(), &strategy);
+/// assert_eq!(sample.first()[0].1.id(), b"ST");
+/// assert_eq!(sample.last()[0].1.id(), b"SE");
+/// assert_eq!(sample.random().len(), 2);
+/// assert_eq!(sample.total(), 6);
+/// ```
+pub fn sample_segments<'a>(
+    data: &'a [u8],
+    delimiters: &Delimiters,
+    strategy: &SampleStrategy,
+) -> SegmentSample<'a> {
+    let mut first = Vec::with_capacity(strategy.first);
+    let mut last = VecDeque::with_capacity(strategy.last);
+    let mut reservoir: Vec<(usize, (Span, Segment<'a>))> = Vec::with_capacity(strategy.random);
+    let mut rng = SplitMix64(strategy.seed);
+    let mut total = 0;
+
+    for item in delimiters.segments(data).spanned() {
+        if first.len() < strategy.first {
+            first.push(item);
+        }
+        if strategy.last > 0 {
+            if last.len() == strategy.last {
+                last.pop_front();
+            }
+            last.push_back(item);
+        }
+        if reservoir.len() < strategy.random {
+            reservoir.push((total, item));
+        } else if strategy.random > 0 {
+            let slot = rng.below(total as u64 + 1) as usize;
+            if slot < strategy.random {
+                reservoir[slot] = (total, item);
+            }
+        }
+        total += 1;
+    }
+
+    reservoir.sort_by_key(|&(index, _)| index);
+    SegmentSample {
+        first,
+        last: last.into(),
+        random: reservoir.into_iter().map(|(_, item)| item).collect(),
+        total,
+    }
+}
+
+/// Cuts a document after its first `n` segments and closes any envelopes left open.
+///
+/// Missing `SE`, `GE` and `IEA` trailers are appended with counts that match the kept
+/// segments and control numbers copied from their headers, so the result is a small but
+/// structurally valid interchange for reproducing issues. Existing trailer counts in the
+/// kept segments are corrected as well. The document's delimiters are kept; segments are
+/// written with the line ending that follows the ISA.
+///
+/// ```
+/// use x12_delimiters::sample::truncate_to_segments;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC*S*R*20250403*0856*7*X*005010~ST*837*0001~BHT*0019~CLM*A1*100~SE*4*0001~GE*1*7~IEA*1*000000001~";
+/// let sample = truncate_to_segments(data, 4).unwrap();
+/// assert!(sample.ends_with(b"ST*837*0001~BHT*0019~SE*3*0001~GE*1*7~IEA*1*000000001~"));
+/// ```
+///
+/// # Errors
+/// Returns a `DelimiterError` if `data` does not start with an ISA segment.
+pub fn truncate_to_segments(data: &[u8], n: usize) -> Result<Vec<u8>, DelimiterError> {
+    let delimiters = Delimiters::from_isa(data)?;
+    let mut builder = DocumentBuilder::new(delimiters);
+    let (mut interchange, mut group, mut transaction) = (false, false, false);
+    for segment in delimiters.segments(data).take(n) {
+        match segment.id() {
+            b"ISA" => (interchange, group, transaction) = (true, false, false),
+            b"GS" => (group, transaction) = (true, false),
+            b"ST" => transaction = true,
+            b"SE" => transaction = false,
+            b"GE" => (group, transaction) = (false, false),
+            b"IEA" => (interchange, group, transaction) = (false, false, false),
+            _ => {}
+        }
+        builder.push(segment).expect(SAME_DELIMITERS);
+    }
+    for (open, trailer) in [(transaction, "SE"), (group, "GE"), (interchange, "IEA")] {
+        if open {
+            builder
+                .push(Segment::new(trailer.as_bytes(), delimiters))
+                .expect(SAME_DELIMITERS);
+        }
+    }
+    Ok(builder.finish().expect(SAME_DELIMITERS))
+}
+
+const SAME_DELIMITERS: &str = "segments rewritten with their own delimiters never conflict";
+
+/// Small deterministic generator so sampling needs no random-number dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in `0..bound`; the modulo bias is negligible for segment counts.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(segments: usize) -> Vec<u8> {
+        (0..segments)
+            .flat_map(|i| format!("LX*{}~\n", i).into_bytes())
+            .collect()
+    }
+
+    fn numbers(items: &[(Span, Segment<'_>)]) -> Vec<usize> {
+        items
+            .iter()
+            .map(|(_, s)| {
+                std::str::from_utf8(&s.as_bytes()[3..])
+                    .unwrap()
+                    .parse()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_first_last_and_random() {
+        let data = document(1000);
+        let strategy = SampleStrategy::new()
+            .with_first(3)
+            .with_last(2)
+            .with_random(10)
+            .with_seed(42);
+        let sample = sample_segments(&data, &Delimiters::default(), &strategy);
+        assert_eq!(sample.total(), 1000);
+        assert_eq!(numbers(sample.first()), [0, 1, 2]);
+        assert_eq!(numbers(sample.last()), [998, 999]);
+
+        let random = numbers(sample.random());
+        assert_eq!(random.len(), 10);
+        assert!(random.windows(2).all(|w| w[0] < w[1]));
+        assert!(random.iter().any(|&n| n > 500));
+        let (span, segment) = sample.random()[0];
+        assert_eq!(&data[span.start..span.end - 1], segment.as_bytes());
+
+        let again = sample_segments(&data, &Delimiters::default(), &strategy);
+        assert_eq!(numbers(again.random()), random);
+    }
+
+    #[test]
+    fn test_truncate_closes_envelopes() {
+        let isa = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\n";
+        let body = "GS*HC*S*R*20250403*0856*7*X*005010~\nST*837*0001~\nBHT*0019~\nSE*3*0001~\nST*837*0002~\nBHT*0019~\nCLM*A1*100~\nSE*4*0002~\nGE*2*7~\nIEA*1*000000001~\n";
+        let data = format!("{}{}", isa, body);
+
+        let sample = truncate_to_segments(data.as_bytes(), 6).unwrap();
+        assert_eq!(
+            String::from_utf8(sample.clone()).unwrap(),
+            format!(
+                "{}GS*HC*S*R*20250403*0856*7*X*005010~\nST*837*0001~\nBHT*0019~\nSE*3*0001~\nST*837*0002~\nSE*2*0002~\nGE*2*7~\nIEA*1*000000001~\n",
+                isa
+            )
+        );
+        assert!(crate::validation::validate(&sample).is_valid());
+
+        let whole = truncate_to_segments(data.as_bytes(), 100).unwrap();
+        assert_eq!(whole, data.as_bytes());
+        let header_only = truncate_to_segments(data.as_bytes(), 1).unwrap();
+        assert!(header_only.ends_with(b"~\nIEA*0*000000001~\n"));
+        assert_eq!(
+            truncate_to_segments(b"ST*837~", 1),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_counts_larger_than_input() {
+        let data = document(4);
+        let strategy = SampleStrategy::new()
+            .with_first(10)
+            .with_last(10)
+            .with_random(10);
+        let sample = sample_segments(&data, &Delimiters::default(), &strategy);
+        assert_eq!(numbers(sample.first()), [0, 1, 2, 3]);
+        assert_eq!(numbers(sample.last()), [0, 1, 2, 3]);
+        assert_eq!(numbers(sample.random()), [0, 1, 2, 3]);
+
+        let empty = sample_segments(&data, &Delimiters::default(), &SampleStrategy::new());
+        assert!(empty.first().is_empty() && empty.last().is_empty() && empty.random().is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::segment::Span;
+use crate::Delimiters;
+
+/// Where [`find_element_value`] found a match.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct ElementMatch {
+    segment: usize,
+    element: usize,
+    component: usize,
+    span: Span,
+}
+
+impl ElementMatch {
+    /// Returns the index of the segment, counting from 0.
+    pub fn segment(&self) -> usize {
+        self.segment
+    }
+
+    /// Returns the element position, where element 0 is the segment ID.
+    pub fn element(&self) -> usize {
+        self.element
+    }
+
+    /// Returns the sub-element position within the element, counting from 0.
+    pub fn component(&self) -> usize {
+        self.component
+    }
+
+    /// Returns the span of the matched bytes in the input.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Searches element data for `needle`, never matching across a delimiter.
+///
+/// Each element and sub-element is searched on its own, so a needle that would only
+/// appear by joining two values (e.g. `12*34` when searching for `1234`) is not found.
+/// Overlapping occurrences within one value are all reported. An empty needle matches
+/// nothing.
+///
+/// ```
+/// use x12_delimiters::search::find_element_value;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"NM1*IL*1*DOE*JOHN~REF*SY*12:34~";
+/// let delimiters = Delimiters::default();
+/// assert!(find_element_value(data, &delimiters, b"1*DOE").is_empty());
+/// assert!(find_element_value(data, &delimiters, b"1234").is_empty());
+///
+/// let hits = find_element_value(data, &delimiters, b"DOE");
+/// assert_eq!((hits[0].segment(), hits[0].element()), (0, 3));
+/// ```
+pub fn find_element_value(
+    data: &[u8],
+    delimiters: &Delimiters,
+    needle: &[u8],
+) -> Vec<ElementMatch> {
+    let mut matches = Vec::new();
+    if needle.is_empty() {
+        return matches;
+    }
+    for (index, (span, segment)) in delimiters.segments(data).spanned().enumerate() {
+        let mut offset = span.start;
+        for (element, value) in segment
+            .as_bytes()
+            .split(|&b| b == delimiters.element_separator())
+            .enumerate()
+        {
+            let mut component_offset = offset;
+            for (component, part) in value
+                .split(|&b| b == delimiters.sub_element_separator())
+                .enumerate()
+            {
+                for (i, window) in part.windows(needle.len()).enumerate() {
+                    if window == needle {
+                        let start = component_offset + i;
+                        matches.push(ElementMatch {
+                            segment: index,
+                            element,
+                            component,
+                            span: Span::new(start, start + needle.len()),
+                        });
+                    }
+                }
+                component_offset += part.len() + 1;
+            }
+            offset += value.len() + 1;
+        }
+    }
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_in_components() {
+        let data = b"ISA*00~\r\nSV1*HC:99213:25*100~SV1*HC:99214*100~";
+        let delimiters = Delimiters::default();
+        let hits = find_element_value(data, &delimiters, b"9921");
+        let coordinates: Vec<_> = hits
+            .iter()
+            .map(|h| (h.segment(), h.element(), h.component()))
+            .collect();
+        assert_eq!(coordinates, [(1, 1, 1), (2, 1, 1)]);
+        for hit in &hits {
+            assert_eq!(&data[hit.span().start..hit.span().end], b"9921");
+        }
+
+        assert!(find_element_value(data, &delimiters, b"HC:9").is_empty());
+        assert!(find_element_value(data, &delimiters, b"100~").is_empty());
+        assert!(find_element_value(data, &delimiters, b"").is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_matches() {
+        let hits = find_element_value(b"REF*AAA~", &Delimiters::default(), b"AA");
+        let starts: Vec<_> = hits.iter().map(|h| h.span().start).collect();
+        assert_eq!(starts, [4, 5]);
+    }
+}
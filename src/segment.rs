@@ -0,0 +1,1073 @@
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::iter::FusedIterator;
+
+use crate::conflict::{is_delimiter, resolve_into, Conflict, ConflictAction, ConflictPolicy};
+use crate::element::ElementRef;
+use crate::errors::BuildError;
+use crate::Delimiters;
+
+/// A single segment borrowed from an X12 document.
+///
+/// The slice excludes the segment terminator and any [`crate::TerminatorSuffix`]. The
+/// segment keeps the delimiters it was read with, so it can be re-serialized with a
+/// different delimiter set.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Segment<'a> {
+    raw: &'a [u8],
+    delimiters: Delimiters,
+}
+
+impl<'a> Segment<'a> {
+    /// Creates a segment from its raw bytes (without terminator) and the delimiters it uses.
+    pub fn new(raw: &'a [u8], delimiters: Delimiters) -> Self {
+        Segment { raw, delimiters }
+    }
+
+    /// Returns the raw segment bytes, without the terminator.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns the delimiters the segment was read with.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the segment identifier, e.g. `ISA` or `NM1`.
+    pub fn id(&self) -> &'a [u8] {
+        segment_id(self.raw, &self.delimiters)
+    }
+
+    /// Returns the elements of the segment, starting with the segment ID as element 0.
+    ///
+    /// Every element separator starts a new element, so empty elements, including
+    /// trailing ones, are yielded as empty slices: `NM1*IL**` has the elements `NM1`,
+    /// `IL`, and two empty ones.
+    ///
+    /// ```
+    /// use x12_delimiters::segment::Segment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let segment = Segment::new(b"SV1*HC:99213*100**", Delimiters::default());
+    /// let elements: Vec<&[u8]> = segment.elements().collect();
+    /// assert_eq!(elements, [&b"SV1"[..], b"HC:99213", b"100", b"", b""]);
+    /// ```
+    pub fn elements(&self) -> Elements<'a> {
+        Elements {
+            rest: Some(self.raw),
+            separator: self.delimiters.element_separator(),
+        }
+    }
+
+    /// Returns element `n`, where element 0 is the segment ID.
+    ///
+    /// Returns `Some` with an empty slice for an element that is present but empty, and
+    /// `None` only if the segment has fewer than `n + 1` elements, i.e. fewer than `n`
+    /// element separators.
+    pub fn element(&self, n: usize) -> Option<&'a [u8]> {
+        self.elements().nth(n)
+    }
+
+    /// Returns element `n` as an [`ElementRef`] for typed conversions; see
+    /// [`Segment::element`].
+    pub fn value(&self, n: usize) -> Option<ElementRef<'a>> {
+        self.element(n).map(ElementRef::new)
+    }
+
+    /// Returns element `n` as text, or `None` if the element is absent; see
+    /// [`Segment::element`].
+    ///
+    /// The text borrows from the segment when the element is valid UTF-8, and is an owned
+    /// copy with invalid sequences replaced by U+FFFD otherwise.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use x12_delimiters::segment::Segment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let segment = Segment::new(b"NM1*IL*1*M\xdcLLER", Delimiters::default());
+    /// assert!(matches!(segment.element_text(1), Some(Cow::Borrowed("IL"))));
+    /// assert_eq!(segment.element_text(3).unwrap(), "M\u{fffd}LLER");
+    /// ```
+    pub fn element_text(&self, n: usize) -> Option<Cow<'a, str>> {
+        self.element(n).map(String::from_utf8_lossy)
+    }
+
+    /// Returns the components of element `n`, split on the sub-element separator, or
+    /// `None` if the element is absent. A simple element yields itself as its only
+    /// component.
+    ///
+    /// ```
+    /// use x12_delimiters::segment::Segment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let segment = Segment::new(b"SV1*HC:99213:25*100", Delimiters::default());
+    /// let codes: Vec<&[u8]> = segment.sub_elements(1).unwrap().collect();
+    /// assert_eq!(codes, [&b"HC"[..], b"99213", b"25"]);
+    /// assert_eq!(segment.sub_elements(2).unwrap().count(), 1);
+    /// assert!(segment.sub_elements(3).is_none());
+    /// ```
+    pub fn sub_elements(&self, n: usize) -> Option<SubElements<'a>> {
+        let element = self.element(n)?;
+        Some(SubElements {
+            rest: Some(element),
+            separator: self.delimiters.sub_element_separator(),
+        })
+    }
+
+    /// Returns the repetitions of element `n`, split on the repetition separator, or
+    /// `None` if the element is absent.
+    ///
+    /// Repetitions nest outside sub-elements: `HI*ABK:J449^ABF:E119` has two repetitions,
+    /// each a composite, so split each [`Repetition`] further with
+    /// [`Repetition::sub_elements`]. Without a repetition separator, as before version
+    /// 00402, and in the ISA, whose ISA11 declares the separator itself, the element is a
+    /// single repetition.
+    ///
+    /// ```
+    /// use x12_delimiters::segment::Segment;
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let delimiters = Delimiters::default().with_repetition_separator(Some(b'^'));
+    /// let segment = Segment::new(b"HI*ABK:J449^ABF:E119^ABF:I10", delimiters);
+    /// let codes: Vec<Vec<&[u8]>> = segment
+    ///     .repetitions(1)
+    ///     .unwrap()
+    ///     .map(|repetition| repetition.sub_elements().collect())
+    ///     .collect();
+    /// assert_eq!(codes[1], [&b"ABF"[..], b"E119"]);
+    /// assert_eq!(codes.len(), 3);
+    /// ```
+    pub fn repetitions(&self, n: usize) -> Option<Repetitions<'a>> {
+        let element = self.element(n)?;
+        let separator = self
+            .delimiters
+            .repetition_separator()
+            .filter(|_| self.id() != b"ISA");
+        Some(Repetitions {
+            rest: Some(element),
+            separator,
+            sub_element_separator: self.delimiters.sub_element_separator(),
+        })
+    }
+
+    /// Copies the segment into an [`OwnedSegment`].
+    pub fn to_owned_segment(&self) -> OwnedSegment {
+        OwnedSegment::new(self.raw.to_vec(), self.delimiters)
+    }
+
+    /// Appends the segment to `out` using `target` delimiters, followed by the target
+    /// terminator and suffix.
+    ///
+    /// Repetition separators are rewritten when both sets have one.
+    ///
+    /// # Errors
+    /// Returns `BuildError::DelimiterConflict` if a data byte equals one of the target
+    /// delimiters; `out` is left unchanged.
+    pub fn write_with(&self, out: &mut Vec<u8>, target: &Delimiters) -> Result<(), BuildError> {
+        self.write_with_policy(out, target, &ConflictAction::Error)
+    }
+
+    /// Like [`Segment::write_with`], consulting `policy` for data bytes that equal one of
+    /// the target delimiters.
+    ///
+    /// # Errors
+    /// Returns `BuildError::DelimiterConflict` if the policy rejects a conflict; `out` is
+    /// left unchanged.
+    pub fn write_with_policy<P: ConflictPolicy + ?Sized>(
+        &self,
+        out: &mut Vec<u8>,
+        target: &Delimiters,
+        policy: &P,
+    ) -> Result<(), BuildError> {
+        let start = out.len();
+        let result = write_raw(out, self.raw, &self.delimiters, target, policy);
+        if result.is_err() {
+            out.truncate(start);
+        }
+        result
+    }
+}
+
+/// A segment that owns its bytes, for use after the input buffer is gone.
+///
+/// Element access mirrors [`Segment`], borrowing from the owned bytes. Convert between the
+/// two with [`Segment::to_owned_segment`] or `From`, and [`OwnedSegment::as_segment`].
+///
+/// ```
+/// use x12_delimiters::segment::{OwnedSegment, Segment};
+/// use x12_delimiters::Delimiters;
+///
+/// let owned = {
+///     let data = b"NM1*IL*1*DOE".to_vec();
+///     OwnedSegment::from(Segment::new(&data, Delimiters::default()))
+/// };
+/// assert_eq!(owned.element(3), Some(&b"DOE"[..]));
+/// assert_eq!(owned.as_segment(), Segment::new(b"NM1*IL*1*DOE", Delimiters::default()));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OwnedSegment {
+    raw: Vec<u8>,
+    delimiters: Delimiters,
+}
+
+impl OwnedSegment {
+    /// Creates a segment from its raw bytes (without terminator) and the delimiters it uses.
+    pub fn new(raw: Vec<u8>, delimiters: Delimiters) -> Self {
+        OwnedSegment { raw, delimiters }
+    }
+
+    /// Returns the raw segment bytes, without the terminator.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Returns the delimiters the segment was read with.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the segment identifier, e.g. `ISA` or `NM1`.
+    pub fn id(&self) -> &[u8] {
+        segment_id(&self.raw, &self.delimiters)
+    }
+
+    /// Borrows the segment as a [`Segment`].
+    pub fn as_segment(&self) -> Segment<'_> {
+        Segment::new(&self.raw, self.delimiters)
+    }
+
+    /// Returns the elements of the segment; see [`Segment::elements`].
+    pub fn elements(&self) -> Elements<'_> {
+        self.as_segment().elements()
+    }
+
+    /// Returns element `n`, where element 0 is the segment ID; see [`Segment::element`].
+    pub fn element(&self, n: usize) -> Option<&[u8]> {
+        self.as_segment().element(n)
+    }
+
+    /// Returns element `n` as an [`ElementRef`]; see [`Segment::value`].
+    pub fn value(&self, n: usize) -> Option<ElementRef<'_>> {
+        self.as_segment().value(n)
+    }
+
+    /// Returns element `n` as text; see [`Segment::element_text`].
+    pub fn element_text(&self, n: usize) -> Option<Cow<'_, str>> {
+        self.as_segment().element_text(n)
+    }
+
+    /// Returns the components of element `n`; see [`Segment::sub_elements`].
+    pub fn sub_elements(&self, n: usize) -> Option<SubElements<'_>> {
+        self.as_segment().sub_elements(n)
+    }
+
+    /// Returns the repetitions of element `n`; see [`Segment::repetitions`].
+    pub fn repetitions(&self, n: usize) -> Option<Repetitions<'_>> {
+        self.as_segment().repetitions(n)
+    }
+
+    /// Returns the raw segment bytes, giving up the segment.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.raw
+    }
+
+    /// Appends the segment to `out` using `target` delimiters; see [`Segment::write_with`].
+    ///
+    /// # Errors
+    /// Returns `BuildError::DelimiterConflict` if a data byte equals one of the target
+    /// delimiters; `out` is left unchanged.
+    pub fn write_with(&self, out: &mut Vec<u8>, target: &Delimiters) -> Result<(), BuildError> {
+        self.as_segment().write_with(out, target)
+    }
+}
+
+impl From<Segment<'_>> for OwnedSegment {
+    fn from(segment: Segment<'_>) -> Self {
+        segment.to_owned_segment()
+    }
+}
+
+impl<'a> From<&'a OwnedSegment> for Segment<'a> {
+    fn from(segment: &'a OwnedSegment) -> Self {
+        segment.as_segment()
+    }
+}
+
+impl AsRef<[u8]> for Segment<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.raw
+    }
+}
+
+impl AsRef<[u8]> for OwnedSegment {
+    fn as_ref(&self) -> &[u8] {
+        &self.raw
+    }
+}
+
+impl PartialEq<Segment<'_>> for OwnedSegment {
+    fn eq(&self, other: &Segment<'_>) -> bool {
+        self.as_segment() == *other
+    }
+}
+
+impl PartialEq<OwnedSegment> for Segment<'_> {
+    fn eq(&self, other: &OwnedSegment) -> bool {
+        *self == other.as_segment()
+    }
+}
+
+/// A borrowed segment with random element access.
+///
+/// The element offset table is built on the first call to [`SegmentRef::element`] or
+/// [`SegmentRef::element_count`] and reused afterwards, so later lookups are O(1) and
+/// segments that are never inspected cost nothing beyond the slice.
+///
+/// ```
+/// use x12_delimiters::segment::{Segment, SegmentRef};
+/// use x12_delimiters::Delimiters;
+///
+/// let segment = SegmentRef::from(Segment::new(b"NM1*IL*1*DOE*JOHN", Delimiters::default()));
+/// assert_eq!(segment.element(3), Some(&b"DOE"[..]));
+/// assert_eq!(segment.element(9), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SegmentRef<'a> {
+    segment: Segment<'a>,
+    separators: OnceCell<Vec<usize>>,
+}
+
+impl<'a> SegmentRef<'a> {
+    /// Wraps `segment` without scanning it.
+    pub fn new(segment: Segment<'a>) -> Self {
+        SegmentRef {
+            segment,
+            separators: OnceCell::new(),
+        }
+    }
+
+    /// Returns the wrapped segment.
+    pub fn segment(&self) -> Segment<'a> {
+        self.segment
+    }
+
+    /// Returns the raw segment bytes, without the terminator.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.segment.as_bytes()
+    }
+
+    /// Returns the segment identifier, e.g. `ISA` or `NM1`.
+    pub fn id(&self) -> &'a [u8] {
+        self.segment.id()
+    }
+
+    /// Returns element `n`, where element 0 is the segment ID.
+    pub fn element(&self, n: usize) -> Option<&'a [u8]> {
+        let raw = self.segment.as_bytes();
+        let separators = self.separators();
+        if n > separators.len() {
+            return None;
+        }
+        let start = if n == 0 { 0 } else { separators[n - 1] + 1 };
+        let end = separators.get(n).copied().unwrap_or(raw.len());
+        Some(&raw[start..end])
+    }
+
+    /// Returns the number of elements, counting the segment ID.
+    pub fn element_count(&self) -> usize {
+        self.separators().len() + 1
+    }
+
+    fn separators(&self) -> &[usize] {
+        self.separators.get_or_init(|| {
+            let separator = self.segment.delimiters().element_separator();
+            self.segment
+                .as_bytes()
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| b == separator)
+                .map(|(i, _)| i)
+                .collect()
+        })
+    }
+}
+
+impl<'a> From<Segment<'a>> for SegmentRef<'a> {
+    fn from(segment: Segment<'a>) -> Self {
+        SegmentRef::new(segment)
+    }
+}
+
+impl PartialEq for SegmentRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.segment == other.segment
+    }
+}
+
+impl Eq for SegmentRef<'_> {}
+
+/// Iterator over the elements of a segment, created by [`Segment::elements`].
+#[derive(Debug, Clone)]
+pub struct Elements<'a> {
+    rest: Option<&'a [u8]>,
+    separator: u8,
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        split_next(&mut self.rest, self.separator)
+    }
+}
+
+impl FusedIterator for Elements<'_> {}
+
+/// Iterator over the components of an element, created by [`Segment::sub_elements`].
+#[derive(Debug, Clone)]
+pub struct SubElements<'a> {
+    rest: Option<&'a [u8]>,
+    separator: u8,
+}
+
+impl<'a> Iterator for SubElements<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        split_next(&mut self.rest, self.separator)
+    }
+}
+
+impl FusedIterator for SubElements<'_> {}
+
+/// Iterator over the repetitions of an element, created by [`Segment::repetitions`].
+#[derive(Debug, Clone)]
+pub struct Repetitions<'a> {
+    rest: Option<&'a [u8]>,
+    separator: Option<u8>,
+    sub_element_separator: u8,
+}
+
+impl<'a> Iterator for Repetitions<'a> {
+    type Item = Repetition<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw = match self.separator {
+            Some(separator) => split_next(&mut self.rest, separator)?,
+            None => self.rest.take()?,
+        };
+        Some(Repetition {
+            raw,
+            sub_element_separator: self.sub_element_separator,
+        })
+    }
+}
+
+impl FusedIterator for Repetitions<'_> {}
+
+/// One occurrence of a repeated element, yielded by [`Repetitions`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Repetition<'a> {
+    raw: &'a [u8],
+    sub_element_separator: u8,
+}
+
+impl<'a> Repetition<'a> {
+    /// Returns the bytes of the repetition.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns the repetition as an [`ElementRef`] for typed conversions.
+    pub fn value(&self) -> ElementRef<'a> {
+        ElementRef::new(self.raw)
+    }
+
+    /// Returns the components of the repetition, split on the sub-element separator.
+    pub fn sub_elements(&self) -> SubElements<'a> {
+        SubElements {
+            rest: Some(self.raw),
+            separator: self.sub_element_separator,
+        }
+    }
+}
+
+/// Returns the bytes of `rest` before the next `separator`, leaving the bytes after it.
+fn split_next<'a>(rest: &mut Option<&'a [u8]>, separator: u8) -> Option<&'a [u8]> {
+    let data = (*rest)?;
+    match data.iter().position(|&b| b == separator) {
+        Some(i) => {
+            *rest = Some(&data[i + 1..]);
+            Some(&data[..i])
+        }
+        None => {
+            *rest = None;
+            Some(data)
+        }
+    }
+}
+
+/// A byte range within a document.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// Offset of the first byte.
+    pub start: usize,
+    /// Offset one past the last byte.
+    pub end: usize,
+}
+
+impl Span {
+    /// Creates a span covering `start..end`.
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Returns the number of bytes covered.
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns true if the span covers no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Iterator over the segments of a document, created by [`Delimiters::segments`].
+#[derive(Debug, Clone)]
+pub struct SegmentIter<'a> {
+    data: &'a [u8],
+    position: usize,
+    delimiters: Delimiters,
+}
+
+impl<'a> SegmentIter<'a> {
+    /// Yields each segment together with its span in the input, terminator included.
+    pub fn spanned(self) -> SpannedSegments<'a> {
+        SpannedSegments { inner: self }
+    }
+
+    /// Yields the bytes of each segment, without terminator or line ending.
+    pub fn raw(self) -> RawSegments<'a> {
+        RawSegments { inner: self }
+    }
+
+    fn next_span(&mut self) -> Option<(Span, Segment<'a>)> {
+        let remaining = &self.data[self.position..];
+        let start = self.position + remaining.iter().position(|&b| b != b'\r' && b != b'\n')?;
+        let rest = &self.data[start..];
+        let terminator = self.delimiters.segment_terminator();
+        let (raw, end) = match rest.iter().position(|&b| b == terminator) {
+            Some(len) => (&rest[..len], start + len + 1),
+            None => {
+                let len = rest
+                    .iter()
+                    .rposition(|&b| b != b'\r' && b != b'\n')
+                    .map_or(0, |i| i + 1);
+                (&rest[..len], self.data.len())
+            }
+        };
+        self.position = end;
+        Some((Span::new(start, end), Segment::new(raw, self.delimiters)))
+    }
+}
+
+impl<'a> Iterator for SegmentIter<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_span().map(|(_, segment)| segment)
+    }
+}
+
+impl FusedIterator for SegmentIter<'_> {}
+
+/// Iterator over segments and their spans, created by [`SegmentIter::spanned`].
+#[derive(Debug, Clone)]
+pub struct SpannedSegments<'a> {
+    inner: SegmentIter<'a>,
+}
+
+impl<'a> Iterator for SpannedSegments<'a> {
+    type Item = (Span, Segment<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_span()
+    }
+}
+
+impl FusedIterator for SpannedSegments<'_> {}
+
+/// Iterator over the bytes of each segment, created by [`SegmentIter::raw`].
+#[derive(Debug, Clone)]
+pub struct RawSegments<'a> {
+    inner: SegmentIter<'a>,
+}
+
+impl<'a> Iterator for RawSegments<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_span()
+            .map(|(_, segment)| segment.as_bytes())
+    }
+}
+
+impl FusedIterator for RawSegments<'_> {}
+
+impl Delimiters {
+    /// Splits `data` into segments on the segment terminator.
+    ///
+    /// Line endings between segments are skipped and a trailing terminator does not
+    /// produce an empty segment. An unterminated final segment is still yielded, without
+    /// any trailing line ending. Nothing is allocated: segments borrow `data`, and
+    /// [`SegmentIter::raw`] yields plain byte slices.
+    ///
+    /// ```
+    /// use x12_delimiters::Delimiters;
+    ///
+    /// let data = b"ST*837*0001~\r\nBHT*0019~\r\nSE*3*0001~\r\n\r\n";
+    /// let segments: Vec<&[u8]> = Delimiters::default().segments(data).raw().collect();
+    /// assert_eq!(segments, [&b"ST*837*0001"[..], b"BHT*0019", b"SE*3*0001"]);
+    /// ```
+    pub fn segments<'a>(&self, data: &'a [u8]) -> SegmentIter<'a> {
+        SegmentIter {
+            data,
+            position: 0,
+            delimiters: *self,
+        }
+    }
+}
+
+/// Reassembles a document from segments using the delimiters of the first segment.
+///
+/// Fails with `BuildError::DelimiterConflict` if a later segment, read with different
+/// delimiters, carries a data byte that is a delimiter of the first.
+impl<'a> FromIterator<Segment<'a>> for Result<Vec<u8>, BuildError> {
+    fn from_iter<I: IntoIterator<Item = Segment<'a>>>(iter: I) -> Self {
+        collect_as_first(iter)
+    }
+}
+
+/// Reassembles a document from owned segments using the delimiters of the first segment.
+///
+/// Fails with `BuildError::DelimiterConflict` if a later segment, read with different
+/// delimiters, carries a data byte that is a delimiter of the first.
+impl FromIterator<OwnedSegment> for Result<Vec<u8>, BuildError> {
+    fn from_iter<I: IntoIterator<Item = OwnedSegment>>(iter: I) -> Self {
+        collect_as_first(iter)
+    }
+}
+
+fn collect_as_first<I>(iter: I) -> Result<Vec<u8>, BuildError>
+where
+    I: IntoIterator,
+    I::Item: AsSegment,
+{
+    let mut out = Vec::new();
+    let mut target = None;
+    for segment in iter {
+        let segment = segment.as_segment();
+        let target = *target.get_or_insert(segment.delimiters());
+        segment.write_with(&mut out, &target)?;
+    }
+    Ok(out)
+}
+
+/// Types that can be viewed as a [`Segment`], such as borrowed and owned segments.
+pub trait AsSegment {
+    /// Borrows the value as a [`Segment`].
+    fn as_segment(&self) -> Segment<'_>;
+}
+
+impl AsSegment for Segment<'_> {
+    fn as_segment(&self) -> Segment<'_> {
+        *self
+    }
+}
+
+impl AsSegment for OwnedSegment {
+    fn as_segment(&self) -> Segment<'_> {
+        OwnedSegment::as_segment(self)
+    }
+}
+
+impl<T: AsSegment + ?Sized> AsSegment for &T {
+    fn as_segment(&self) -> Segment<'_> {
+        (**self).as_segment()
+    }
+}
+
+/// Extension trait for collecting segments into a document with chosen delimiters.
+///
+/// ```
+/// use x12_delimiters::Delimiters;
+/// use x12_delimiters::segment::CollectSegments;
+///
+/// let source = Delimiters::default();
+/// let doc = b"ST*837*0001~NTE*ADD*internal~SE*3*0001~";
+/// let out = source
+///     .segments(doc)
+///     .filter(|s| s.id() != b"NTE")
+///     .collect_with(&Delimiters::new(b'\n', b'|', b'^'))
+///     .unwrap();
+/// assert_eq!(out, b"ST|837|0001\nSE|3|0001\n");
+/// ```
+pub trait CollectSegments: Iterator + Sized
+where
+    Self::Item: AsSegment,
+{
+    /// Writes every segment with `delimiters`, translating each segment's own separators.
+    ///
+    /// # Errors
+    /// Returns `BuildError::DelimiterConflict` if a data byte equals one of `delimiters`.
+    fn collect_with(self, delimiters: &Delimiters) -> Result<Vec<u8>, BuildError> {
+        self.collect_with_policy(delimiters, &ConflictAction::Error)
+    }
+
+    /// Like [`CollectSegments::collect_with`], consulting `policy` for data bytes that
+    /// equal one of `delimiters`.
+    ///
+    /// # Errors
+    /// Returns `BuildError::DelimiterConflict` if the policy rejects a conflict.
+    fn collect_with_policy<P: ConflictPolicy + ?Sized>(
+        self,
+        delimiters: &Delimiters,
+        policy: &P,
+    ) -> Result<Vec<u8>, BuildError> {
+        let mut out = Vec::new();
+        for segment in self {
+            segment
+                .as_segment()
+                .write_with_policy(&mut out, delimiters, policy)?;
+        }
+        Ok(out)
+    }
+}
+
+impl<I> CollectSegments for I
+where
+    I: Iterator,
+    I::Item: AsSegment,
+{
+}
+
+fn segment_id<'a>(raw: &'a [u8], delimiters: &Delimiters) -> &'a [u8] {
+    let separator = delimiters.element_separator();
+    match raw.iter().position(|&b| b == separator) {
+        Some(end) => &raw[..end],
+        None => raw,
+    }
+}
+
+/// Returns element `n` of a raw segment, where element 0 is the segment ID.
+pub(crate) fn element_at<'a>(raw: &'a [u8], delimiters: &Delimiters, n: usize) -> Option<&'a [u8]> {
+    raw.split(|&b| b == delimiters.element_separator()).nth(n)
+}
+
+fn write_raw<P: ConflictPolicy + ?Sized>(
+    out: &mut Vec<u8>,
+    raw: &[u8],
+    source: &Delimiters,
+    target: &Delimiters,
+    policy: &P,
+) -> Result<(), BuildError> {
+    out.reserve(raw.len() + 1 + target.suffix().as_bytes().len());
+    if source.eq_ignoring_suffix(target) {
+        out.extend_from_slice(raw);
+    } else {
+        let id = segment_id(raw, source);
+        let is_isa = id == b"ISA";
+        let repetition = source
+            .repetition_separator()
+            .zip(target.repetition_separator());
+        let mut element = 0;
+        for (offset, &b) in raw.iter().enumerate() {
+            if b == source.element_separator() {
+                element += 1;
+                out.push(target.element_separator());
+            } else if b == source.sub_element_separator() {
+                out.push(target.sub_element_separator());
+            } else if let Some((_, to)) = repetition.filter(|&(from, _)| b == from) {
+                out.push(to);
+            } else if is_isa && element == 11 && target.repetition_separator() == Some(b) {
+                // ISA11 declares the target repetition separator rather than colliding with it.
+                out.push(b);
+            } else if is_delimiter(target, b) {
+                resolve_into(out, policy, Conflict::new(id, element, offset, b), target)?;
+            } else {
+                out.push(b);
+            }
+        }
+    }
+    out.push(target.segment_terminator());
+    out.extend_from_slice(target.suffix().as_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TerminatorSuffix;
+
+    const DOC: &[u8] = b"ST*837*0001~NM1*IL*1*DOE*JOHN~SV1*HC:99213*100~SE*4*0001~";
+
+    #[test]
+    fn test_segment_ref_indexes_lazily() {
+        let delimiters = Delimiters::default();
+        let segments: Vec<SegmentRef> = delimiters.segments(DOC).map(SegmentRef::from).collect();
+        assert!(segments.iter().all(|s| s.separators.get().is_none()));
+
+        let sv1 = &segments[2];
+        assert_eq!(sv1.element(1), Some(&b"HC:99213"[..]));
+        assert_eq!(sv1.separators.get(), Some(&vec![3, 12]));
+        assert_eq!(sv1.element(0), Some(&b"SV1"[..]));
+        assert_eq!(sv1.element(2), Some(&b"100"[..]));
+        assert_eq!(sv1.element(3), None);
+        assert_eq!(sv1.element_count(), 3);
+        assert!(segments[1].separators.get().is_none());
+
+        let empty = SegmentRef::new(Segment::new(b"", delimiters));
+        assert_eq!(empty.element(0), Some(&b""[..]));
+        assert_eq!(empty.element_count(), 1);
+    }
+
+    #[test]
+    fn test_elements_and_sub_elements() {
+        let delimiters = Delimiters::new(b'~', b'|', b'>');
+        let segment = Segment::new(b"HI|ABK>J449|ABF>E119>>X||", delimiters);
+        assert_eq!(segment.elements().count(), 5);
+        assert_eq!(segment.element(0), Some(&b"HI"[..]));
+        assert_eq!(segment.element(3), Some(&b""[..]));
+        assert_eq!(segment.element(4), Some(&b""[..]));
+        assert_eq!(segment.element(5), None);
+        let parts: Vec<&[u8]> = segment.sub_elements(2).unwrap().collect();
+        assert_eq!(parts, [&b"ABF"[..], b"E119", b"", b"X"]);
+        assert_eq!(segment.sub_elements(3).unwrap().collect::<Vec<_>>(), [b""]);
+
+        let bare = Segment::new(b"", delimiters);
+        assert_eq!(bare.elements().collect::<Vec<_>>(), [b""]);
+        assert_eq!(bare.element(1), None);
+
+        let sv1 = SegmentRef::from(Segment::new(b"SV1|HC>99213|100|", delimiters));
+        for n in 0..5 {
+            assert_eq!(sv1.segment().element(n), sv1.element(n));
+        }
+    }
+
+    #[test]
+    fn test_repetitions() {
+        let delimiters = Delimiters::new(b'~', b'*', b':').with_repetition_separator(Some(b'^'));
+        let segment = Segment::new(b"EB*1**30^1^35*:^", delimiters);
+        let values: Vec<&[u8]> = segment
+            .repetitions(3)
+            .unwrap()
+            .map(|r| r.as_bytes())
+            .collect();
+        assert_eq!(values, [&b"30"[..], b"1", b"35"]);
+        assert_eq!(segment.repetitions(2).unwrap().count(), 1);
+        assert!(segment.repetitions(5).is_none());
+        let last: Vec<Vec<&[u8]>> = segment
+            .repetitions(4)
+            .unwrap()
+            .map(|r| r.sub_elements().collect())
+            .collect();
+        assert_eq!(last, [vec![&b""[..], b""], vec![b""]]);
+
+        let plain = Segment::new(b"EB*1**30^1", Delimiters::default());
+        assert_eq!(plain.repetitions(3).unwrap().count(), 1);
+
+        let isa = Segment::new(b"ISA*00*^*00501", delimiters);
+        let isa11: Vec<_> = isa.repetitions(2).unwrap().map(|r| r.as_bytes()).collect();
+        assert_eq!(isa11, [b"^"]);
+        assert_eq!(
+            segment
+                .repetitions(3)
+                .unwrap()
+                .nth(2)
+                .unwrap()
+                .value()
+                .as_u64(),
+            Ok(35)
+        );
+    }
+
+    #[test]
+    fn test_owned_segment_outlives_input() {
+        let delimiters = Delimiters::default().with_repetition_separator(Some(b'^'));
+        let owned: Vec<OwnedSegment> = {
+            let data = b"HI*ABK:J449^ABF:E119~NM1*IL*1*M\xfcLLER~".to_vec();
+            delimiters.segments(&data).map(OwnedSegment::from).collect()
+        };
+        let hi = &owned[0];
+        assert_eq!(hi.element(0), Some(&b"HI"[..]));
+        assert_eq!(hi.elements().count(), 2);
+        assert_eq!(hi.repetitions(1).unwrap().count(), 2);
+        assert_eq!(hi.sub_elements(1).unwrap().nth(1), Some(&b"J449^ABF"[..]));
+        assert!(matches!(
+            hi.element_text(1),
+            Some(Cow::Borrowed("ABK:J449^ABF:E119"))
+        ));
+        assert!(matches!(owned[1].element_text(3), Some(Cow::Owned(_))));
+        assert_eq!(owned[1].value(2).unwrap().as_u64(), Ok(1));
+
+        let borrowed = Segment::from(hi);
+        assert_eq!(borrowed, *hi);
+        assert_eq!(*hi, borrowed);
+        assert_eq!(borrowed.as_ref(), hi.as_ref());
+        assert_eq!(hi.clone().into_bytes(), b"HI*ABK:J449^ABF:E119");
+    }
+
+    #[test]
+    fn test_segments_split_on_terminator() {
+        let delimiters = Delimiters::default();
+        let ids: Vec<&[u8]> = delimiters.segments(DOC).map(|s| s.id()).collect();
+        assert_eq!(ids, vec![&b"ST"[..], b"NM1", b"SV1", b"SE"]);
+    }
+
+    #[test]
+    fn test_segments_skip_line_endings_and_keep_unterminated_tail() {
+        let delimiters = Delimiters::default();
+        let data = b"ST*837*0001~\r\nSE*2*0001~\n\nIEA*1\r\n";
+        let raw: Vec<&[u8]> = delimiters.segments(data).map(|s| s.as_bytes()).collect();
+        assert_eq!(raw, vec![&b"ST*837*0001"[..], b"SE*2*0001", b"IEA*1"]);
+        assert_eq!(delimiters.segments(b"\r\n").count(), 0);
+    }
+
+    #[test]
+    fn test_raw_segments_are_fused() {
+        let delimiters = Delimiters::default();
+        let mut raw = delimiters.segments(b"ST*837~~SE*2~\r\n").raw();
+        assert_eq!(raw.next(), Some(&b"ST*837"[..]));
+        assert_eq!(raw.next(), Some(&b""[..]));
+        assert_eq!(raw.next(), Some(&b"SE*2"[..]));
+        assert_eq!(raw.next(), None);
+        assert_eq!(raw.next(), None);
+    }
+
+    #[test]
+    fn test_spanned_segments() {
+        let delimiters = Delimiters::default();
+        let data = b"ST*837*0001~\r\nSE*2*0001~\nIEA";
+        let spans: Vec<Span> = delimiters
+            .segments(data)
+            .spanned()
+            .map(|(s, _)| s)
+            .collect();
+        assert_eq!(
+            spans,
+            vec![Span::new(0, 12), Span::new(14, 24), Span::new(25, 28)]
+        );
+        assert_eq!(&data[spans[1].start..spans[1].end], b"SE*2*0001~");
+    }
+
+    #[test]
+    fn test_from_iterator_uses_first_segment_delimiters() {
+        let delimiters = Delimiters::default();
+        let out: Result<Vec<u8>, BuildError> = delimiters.segments(DOC).collect();
+        assert_eq!(out.unwrap(), DOC);
+
+        let owned: Result<Vec<u8>, BuildError> = delimiters
+            .segments(DOC)
+            .map(|s| s.to_owned_segment())
+            .collect();
+        assert_eq!(owned.unwrap(), DOC);
+    }
+
+    #[test]
+    fn test_from_iterator_rejects_conflicts_between_sources() {
+        let pipes = Delimiters::new(b'~', b'|', b':');
+        let segments = [
+            Segment::new(b"ST*837*0001", Delimiters::default()),
+            Segment::new(b"NTE|A*B", pipes),
+        ];
+        let out: Result<Vec<u8>, BuildError> = segments.into_iter().collect();
+        assert_eq!(
+            out,
+            Err(BuildError::DelimiterConflict {
+                byte: b'*',
+                element: 1,
+                offset: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_collect_with_translates_delimiters() {
+        let source = Delimiters::default();
+        let target = Delimiters::new(b'!', b'^', b'&').with_suffix(TerminatorSuffix::CrLf);
+        let out = source
+            .segments(DOC)
+            .filter(|s| s.id() != b"NM1")
+            .collect_with(&target)
+            .unwrap();
+        assert_eq!(out, b"ST^837^0001!\r\nSV1^HC&99213^100!\r\nSE^4^0001!\r\n");
+    }
+
+    #[test]
+    fn test_collect_with_policy_resolves_conflicts() {
+        let source = Delimiters::default();
+        let target = Delimiters::new(b'~', b'*', b'^');
+        let data = b"NM1*IL*1*O^BRIEN~";
+
+        let result = source.segments(data).collect_with(&target);
+        assert_eq!(
+            result,
+            Err(BuildError::DelimiterConflict {
+                byte: b'^',
+                element: 3,
+                offset: 10
+            })
+        );
+
+        let policies = [
+            (ConflictAction::Strip, &b"NM1*IL*1*OBRIEN~"[..]),
+            (ConflictAction::EscapeToSpace, b"NM1*IL*1*O BRIEN~"),
+            (ConflictAction::ReplaceWith(b'\''), b"NM1*IL*1*O'BRIEN~"),
+        ];
+        for (policy, expected) in policies {
+            let out = source
+                .segments(data)
+                .collect_with_policy(&target, &policy)
+                .unwrap();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn test_write_with_leaves_output_unchanged_on_conflict() {
+        let mut out = b"ST*837*0001~".to_vec();
+        let segment = Segment::new(b"NTE*A|B", Delimiters::default());
+        let target = Delimiters::new(b'~', b'|', b':');
+        assert!(segment.write_with(&mut out, &target).is_err());
+        assert_eq!(out, b"ST*837*0001~");
+    }
+
+    #[test]
+    fn test_write_with_repetition_separator() {
+        let target = Delimiters::default().with_repetition_separator(Some(b'^'));
+        let mut out = Vec::new();
+        let note = Segment::new(b"NTE*ADD*A^B", Delimiters::default());
+        assert_eq!(
+            note.write_with(&mut out, &target),
+            Err(BuildError::DelimiterConflict { byte: b'^', element: 2, offset: 9 })
+        );
+
+        let source = Delimiters::new(b'~', b'|', b'>').with_repetition_separator(Some(b'{'));
+        let hi = Segment::new(b"HI|ABK>J449{ABF>E119", source);
+        hi.write_with(&mut out, &target).unwrap();
+        assert_eq!(out, b"HI*ABK:J449^ABF:E119~");
+    }
+
+    #[test]
+    fn test_collect_with_borrowed_owned_segments() {
+        let source = Delimiters::default();
+        let owned: Vec<OwnedSegment> = source.segments(DOC).map(|s| s.to_owned_segment()).collect();
+        assert_eq!(owned.iter().collect_with(&source).unwrap(), DOC);
+        assert_eq!(owned[1].as_segment().id(), b"NM1");
+    }
+}
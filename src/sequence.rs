@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use crate::summary::InterchangeSummary;
+
+/// Control-number findings for one sender.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SenderSequence {
+    sender_qualifier: String,
+    sender_id: String,
+    interchanges: usize,
+    range: Option<RangeInclusive<u32>>,
+    gaps: Vec<RangeInclusive<u32>>,
+    duplicates: Vec<u32>,
+    unparsable: Vec<String>,
+}
+
+impl SenderSequence {
+    /// Returns ISA05, the sender ID qualifier.
+    pub fn sender_qualifier(&self) -> &str {
+        &self.sender_qualifier
+    }
+
+    /// Returns ISA06, the sender ID.
+    pub fn sender_id(&self) -> &str {
+        &self.sender_id
+    }
+
+    /// Returns the number of interchanges seen from this sender.
+    pub fn interchanges(&self) -> usize {
+        self.interchanges
+    }
+
+    /// Returns the lowest and highest numeric control numbers seen.
+    pub fn range(&self) -> Option<RangeInclusive<u32>> {
+        self.range.clone()
+    }
+
+    /// Returns runs of control numbers missing between the lowest and highest seen.
+    pub fn gaps(&self) -> &[RangeInclusive<u32>] {
+        &self.gaps
+    }
+
+    /// Returns control numbers seen more than once, each listed once.
+    pub fn duplicates(&self) -> &[u32] {
+        &self.duplicates
+    }
+
+    /// Returns ISA13 values that are not numeric.
+    pub fn unparsable(&self) -> &[String] {
+        &self.unparsable
+    }
+
+    /// Returns true if the sequence has no gaps, duplicates, or unparsable values.
+    pub fn is_contiguous(&self) -> bool {
+        self.gaps.is_empty() && self.duplicates.is_empty() && self.unparsable.is_empty()
+    }
+}
+
+/// Finds gaps and duplicates in ISA13 control numbers, per sender (ISA05 + ISA06).
+///
+/// Interchanges may be given in any order, e.g. as collected from several batches. A
+/// missing run such as 5 through 7 usually means files were dropped in transit. Senders
+/// are returned sorted by qualifier and ID.
+///
+/// ```
+/// use x12_delimiters::sequence::analyze_control_numbers;
+/// use x12_delimiters::summary::InterchangeSummary;
+///
+/// let isa = |control: &str| {
+///     let data = format!("ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*{}*0*P*:~", control);
+///     InterchangeSummary::from_isa(data.as_bytes()).unwrap()
+/// };
+/// let summaries = [isa("000000004"), isa("000000001"), isa("000000002"), isa("000000002")];
+///
+/// let report = analyze_control_numbers(&summaries);
+/// assert_eq!(report[0].gaps(), &[3..=3]);
+/// assert_eq!(report[0].duplicates(), &[2]);
+/// ```
+pub fn analyze_control_numbers<'a, I>(summaries: I) -> Vec<SenderSequence>
+where
+    I: IntoIterator<Item = &'a InterchangeSummary>,
+{
+    let mut senders: BTreeMap<(&str, &str), Vec<&str>> = BTreeMap::new();
+    for summary in summaries {
+        let key = (summary.sender_qualifier(), summary.sender_id());
+        senders
+            .entry(key)
+            .or_default()
+            .push(summary.control_number());
+    }
+
+    senders
+        .into_iter()
+        .map(|((qualifier, id), controls)| {
+            let mut numbers = Vec::with_capacity(controls.len());
+            let mut unparsable = Vec::new();
+            for control in &controls {
+                match control.parse::<u32>() {
+                    Ok(number) => numbers.push(number),
+                    Err(_) => unparsable.push(control.to_string()),
+                }
+            }
+            numbers.sort_unstable();
+            let mut gaps = Vec::new();
+            let mut duplicates = Vec::new();
+            for pair in numbers.windows(2) {
+                let (previous, next) = (pair[0], pair[1]);
+                if next == previous {
+                    if duplicates.last() != Some(&next) {
+                        duplicates.push(next);
+                    }
+                } else if next > previous + 1 {
+                    gaps.push(previous + 1..=next - 1);
+                }
+            }
+            SenderSequence {
+                sender_qualifier: qualifier.to_string(),
+                sender_id: id.to_string(),
+                interchanges: controls.len(),
+                range: numbers.first().zip(numbers.last()).map(|(&a, &b)| a..=b),
+                gaps,
+                duplicates,
+                unparsable,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(sender: &str, control: &str) -> InterchangeSummary {
+        let data = format!(
+            "ISA*00*          *00*          *ZZ*{:<15}*ZZ*RECEIVERID     *250403*0856*^*00501*{:>9}*0*P*:~",
+            sender, control
+        );
+        InterchangeSummary::from_isa(data.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_per_sender_gaps_and_duplicates() {
+        let summaries = vec![
+            summary("B", "000000010"),
+            summary("A", "000000001"),
+            summary("A", "000000009"),
+            summary("A", "000000005"),
+            summary("A", "000000005"),
+            summary("A", "000000005"),
+            summary("B", "000000011"),
+            summary("A", "ABC"),
+        ];
+        let report = analyze_control_numbers(&summaries);
+        assert_eq!(report.len(), 2);
+
+        let a = &report[0];
+        assert_eq!(a.sender_id(), "A");
+        assert_eq!(a.interchanges(), 6);
+        assert_eq!(a.range(), Some(1..=9));
+        assert_eq!(a.gaps(), &[2..=4, 6..=8]);
+        assert_eq!(a.duplicates(), &[5]);
+        assert_eq!(a.unparsable(), &["ABC".to_string()]);
+        assert!(!a.is_contiguous());
+
+        let b = &report[1];
+        assert_eq!(b.range(), Some(10..=11));
+        assert!(b.is_contiguous());
+        assert!(analyze_control_numbers(&[]).is_empty());
+    }
+}
@@ -0,0 +1,250 @@
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::segment::{Segment, Span};
+use crate::summary::interchange_spans;
+use crate::Delimiters;
+
+/// A cheaply clonable view of part of a shared buffer.
+///
+/// Cloning bumps a reference count instead of copying bytes, so one buffer can be fanned
+/// out to many consumers, including across threads.
+#[derive(Debug, Clone)]
+pub struct SharedSlice {
+    buf: Arc<[u8]>,
+    span: Span,
+}
+
+impl SharedSlice {
+    /// Creates a view of `span` within `buf`.
+    ///
+    /// # Panics
+    /// Panics if `span` is out of bounds.
+    pub fn new(buf: Arc<[u8]>, span: Span) -> Self {
+        assert!(span.start <= span.end && span.end <= buf.len());
+        SharedSlice { buf, span }
+    }
+
+    /// Returns the viewed bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[self.span.start..self.span.end]
+    }
+
+    /// Returns the span of the view within the shared buffer.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the whole shared buffer.
+    pub fn buffer(&self) -> &Arc<[u8]> {
+        &self.buf
+    }
+}
+
+impl Deref for SharedSlice {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PartialEq for SharedSlice {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SharedSlice {}
+
+/// A segment that shares ownership of the buffer it was split from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SharedSegment {
+    raw: SharedSlice,
+    delimiters: Delimiters,
+}
+
+impl SharedSegment {
+    /// Returns the raw bytes of the segment, without terminator.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.raw.as_bytes()
+    }
+
+    /// Returns the raw bytes as a shared view.
+    pub fn raw(&self) -> &SharedSlice {
+        &self.raw
+    }
+
+    /// Returns the delimiters the segment was read with.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Borrows the segment as a [`Segment`].
+    pub fn as_segment(&self) -> Segment<'_> {
+        Segment::new(self.as_bytes(), self.delimiters)
+    }
+
+    /// Returns the segment ID, e.g. `ISA` or `GS`.
+    pub fn id(&self) -> &[u8] {
+        self.as_segment().id()
+    }
+}
+
+/// A segment held as a [`bytes::Bytes`] view, yielded by
+/// [`crate::reader::BytesSegments`].
+///
+/// Cloning and [`BytesSegment::into_bytes`] share the underlying buffer instead of copying,
+/// so segments can be fanned out to async consumers that take `Bytes`.
+#[cfg(feature = "bytes")]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BytesSegment {
+    raw: bytes::Bytes,
+    delimiters: Delimiters,
+}
+
+#[cfg(feature = "bytes")]
+impl BytesSegment {
+    /// Creates a segment from its raw bytes (without terminator) and the delimiters it uses.
+    pub fn new(raw: bytes::Bytes, delimiters: Delimiters) -> Self {
+        BytesSegment { raw, delimiters }
+    }
+
+    /// Returns the raw bytes of the segment, without terminator.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// Returns the raw bytes as a shared view.
+    pub fn raw(&self) -> &bytes::Bytes {
+        &self.raw
+    }
+
+    /// Returns the raw bytes as a shared view, giving up the segment.
+    pub fn into_bytes(self) -> bytes::Bytes {
+        self.raw
+    }
+
+    /// Returns the delimiters the segment was read with.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Borrows the segment as a [`Segment`].
+    pub fn as_segment(&self) -> Segment<'_> {
+        Segment::new(&self.raw, self.delimiters)
+    }
+
+    /// Returns the segment ID, e.g. `ISA` or `GS`.
+    pub fn id(&self) -> &[u8] {
+        self.as_segment().id()
+    }
+}
+
+/// Iterator over the segments of a shared buffer, created by [`shared_segments`].
+#[derive(Debug, Clone)]
+pub struct SharedSegments {
+    buf: Arc<[u8]>,
+    position: usize,
+    delimiters: Delimiters,
+}
+
+impl Iterator for SharedSegments {
+    type Item = SharedSegment;
+
+    fn next(&mut self) -> Option<SharedSegment> {
+        let base = self.position;
+        let (span, segment) = self
+            .delimiters
+            .segments(&self.buf[base..])
+            .spanned()
+            .next()?;
+        self.position = base + span.end;
+        let start = base + span.start;
+        Some(SharedSegment {
+            raw: SharedSlice::new(
+                Arc::clone(&self.buf),
+                Span::new(start, start + segment.as_bytes().len()),
+            ),
+            delimiters: self.delimiters,
+        })
+    }
+}
+
+/// Splits a shared buffer into segments that each keep the buffer alive, without copying.
+///
+/// Splitting follows [`Delimiters::segments`].
+///
+/// ```
+/// use std::sync::Arc;
+/// use x12_delimiters::shared::shared_segments;
+/// use x12_delimiters::Delimiters;
+///
+/// let buf: Arc<[u8]> = Arc::from(&b"ST*837*0001~\nSE*2*0001~"[..]);
+/// let segments: Vec<_> = shared_segments(Arc::clone(&buf), &Delimiters::default()).collect();
+/// assert_eq!(segments[1].as_bytes(), b"SE*2*0001");
+/// assert!(Arc::ptr_eq(segments[1].raw().buffer(), &buf));
+/// ```
+pub fn shared_segments(buf: Arc<[u8]>, delimiters: &Delimiters) -> SharedSegments {
+    SharedSegments {
+        buf,
+        position: 0,
+        delimiters: *delimiters,
+    }
+}
+
+/// Splits a shared buffer of concatenated interchanges, ISA through IEA, without copying.
+///
+/// Each interchange comes with the delimiters from its own ISA. Whitespace between
+/// interchanges is skipped and splitting stops at the first position that does not start
+/// an ISA.
+pub fn shared_interchanges(buf: Arc<[u8]>) -> Vec<(Delimiters, SharedSlice)> {
+    interchange_spans(&buf)
+        .into_iter()
+        .map(|(span, delimiters)| (delimiters, SharedSlice::new(Arc::clone(&buf), span)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    #[test]
+    fn test_shared_segments_match_borrowed() {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(b"\r\nGS*HC~\r\nIEA*1*000000001");
+        let buf: Arc<[u8]> = Arc::from(data.as_slice());
+        let delimiters = Delimiters::default();
+
+        let shared: Vec<_> = shared_segments(Arc::clone(&buf), &delimiters).collect();
+        let borrowed: Vec<_> = delimiters.segments(&data).collect();
+        assert_eq!(shared.len(), borrowed.len());
+        for (s, b) in shared.iter().zip(&borrowed) {
+            assert_eq!(s.as_segment(), *b);
+        }
+        assert_eq!(shared[2].id(), b"IEA");
+        assert_eq!(Arc::strong_count(&buf), 4);
+        drop(shared);
+        assert_eq!(Arc::strong_count(&buf), 1);
+    }
+
+    #[test]
+    fn test_shared_interchanges() {
+        let mut data = ISA.to_vec();
+        data.extend_from_slice(b"IEA*0*000000001~\n");
+        let first_len = data.len() - 1;
+        data.extend_from_slice(ISA);
+        let buf: Arc<[u8]> = Arc::from(data.as_slice());
+
+        let interchanges = shared_interchanges(buf);
+        assert_eq!(interchanges.len(), 2);
+        assert_eq!(interchanges[0].1.span(), Span::new(0, first_len));
+        assert_eq!(&interchanges[1].1[..], ISA);
+        assert_eq!(
+            interchanges[1].0,
+            Delimiters::default().with_repetition_separator(Some(b'^'))
+        );
+    }
+}
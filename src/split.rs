@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{DelimiterError, ReadError};
+use crate::interchange::interchanges;
+use crate::segment::Span;
+use crate::summary::InterchangeSummary;
+use crate::{Delimiters, TerminatorSuffix};
+
+/// One interchange of a batch, as returned by [`split_interchanges`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SplitInterchange<'a> {
+    bytes: &'a [u8],
+    span: Span,
+    summary: InterchangeSummary,
+}
+
+impl<'a> SplitInterchange<'a> {
+    /// Returns the interchange exactly as it appears in the batch, from the ISA tag
+    /// through the IEA terminator and its line ending, if any.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the position of [`Self::as_bytes`] in the batch.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the identifying fields of the ISA.
+    pub fn summary(&self) -> &InterchangeSummary {
+        &self.summary
+    }
+
+    /// Returns the delimiters declared by the ISA.
+    pub fn delimiters(&self) -> Delimiters {
+        self.summary.delimiters()
+    }
+
+    /// Returns a file name built from the sender ID, receiver ID, and control number,
+    /// e.g. `SENDERID_RECEIVERID_000000001.x12`.
+    ///
+    /// Bytes other than ASCII letters, digits, `-` and `.` are replaced with `_`, so the
+    /// name is safe on any file system.
+    pub fn file_name(&self) -> String {
+        let summary = &self.summary;
+        let name = format!(
+            "{}_{}_{}",
+            summary.sender_id(),
+            summary.receiver_id(),
+            summary.control_number()
+        );
+        let name: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{}.x12", name)
+    }
+}
+
+/// Splits a batch of concatenated interchanges, keeping each one byte for byte.
+///
+/// Each interchange keeps its own delimiters and runs from its ISA through its IEA, or
+/// to the end of the data if the IEA is missing. The line ending after the IEA belongs
+/// to its interchange; any other whitespace between interchanges is dropped. Nothing
+/// is re-serialized, so each part can be routed to its trading partner unchanged.
+///
+/// ```
+/// use x12_delimiters::split::split_interchanges;
+///
+/// let batch = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*PAYER1         *250403*0856*^*00501*000000001*0*P*:~\r\nIEA*0*000000001~\r\n\
+/// ISA|00|          |00|          |ZZ|SENDERID       |ZZ|PAYER2         |250403|0856|^|00501|000000002|0|P|>'IEA|0|000000002'";
+/// let parts = split_interchanges(batch).unwrap();
+/// assert_eq!(parts.len(), 2);
+/// assert!(parts[0].as_bytes().ends_with(b"IEA*0*000000001~\r\n"));
+/// assert_eq!(parts[1].summary().receiver_id(), "PAYER2");
+/// assert_eq!(parts[1].file_name(), "SENDERID_PAYER2_000000002.x12");
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError::InvalidIsaLength` or `DelimiterError::MissingIsa` if `data`
+/// does not start with an ISA segment, and `DelimiterError::NotIsaSegment` if data other
+/// than whitespace follows an interchange without starting another, since it would be
+/// lost.
+pub fn split_interchanges(data: &[u8]) -> Result<Vec<SplitInterchange<'_>>, DelimiterError> {
+    let mut parts = Vec::new();
+    let mut end = 0;
+    for (span, _, bytes) in interchanges(data).spanned() {
+        let suffix = TerminatorSuffix::detect(&data[span.end..]).as_bytes();
+        let span = Span::new(span.start, span.end + suffix.len());
+        end = span.end;
+        parts.push(SplitInterchange {
+            bytes: &data[span.start..span.end],
+            span,
+            summary: InterchangeSummary::from_isa(bytes)?,
+        });
+    }
+    if parts.is_empty() {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa);
+    }
+    if !data[end..].iter().all(u8::is_ascii_whitespace) {
+        return Err(DelimiterError::NotIsaSegment);
+    }
+    Ok(parts)
+}
+
+/// Splits a batch like [`split_interchanges`], returning each interchange as a
+/// [`bytes::Bytes`] view of `data` instead of a borrowed slice.
+///
+/// The views share `data`'s buffer, so they can outlive the borrow and be sent to other
+/// tasks or threads without copying.
+///
+/// ```
+/// use bytes::Bytes;
+/// use x12_delimiters::split::split_bytes;
+///
+/// let batch = Bytes::from_static(b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*PAYER1         *250403*0856*^*00501*000000001*0*P*:~\nIEA*0*000000001~\n");
+/// let parts = split_bytes(&batch).unwrap();
+/// assert_eq!(parts[0].0.receiver_id(), "PAYER1");
+/// assert_eq!(parts[0].1, batch);
+/// ```
+///
+/// # Errors
+/// Same as [`split_interchanges`].
+#[cfg(feature = "bytes")]
+pub fn split_bytes(
+    data: &bytes::Bytes,
+) -> Result<Vec<(InterchangeSummary, bytes::Bytes)>, DelimiterError> {
+    Ok(split_interchanges(data)?
+        .into_iter()
+        .map(|part| {
+            let span = part.span();
+            (part.summary, data.slice(span.start..span.end))
+        })
+        .collect())
+}
+
+/// Splits a batch like [`split_interchanges`] and writes each interchange to the writer
+/// `open` returns for it, such as a file or an in-memory buffer.
+///
+/// Returns the number of interchanges written.
+///
+/// ```
+/// use x12_delimiters::split::split_into;
+///
+/// let batch = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~";
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("RECEIVERID");
+/// std::fs::create_dir(&path).unwrap();
+/// let count = split_into(batch, |part| {
+///     let partner = dir.path().join(part.summary().receiver_id());
+///     std::fs::File::create(partner.join(part.file_name()))
+/// })
+/// .unwrap();
+/// assert_eq!(count, 1);
+/// let written = std::fs::read(path.join("SENDERID_RECEIVERID_000000001.x12")).unwrap();
+/// assert_eq!(written, batch);
+/// ```
+///
+/// # Errors
+/// Returns `ReadError::Delimiters` with the errors of [`split_interchanges`], before
+/// anything is written, and `ReadError::Io` if opening or writing an output fails.
+pub fn split_into<F, W>(data: &[u8], mut open: F) -> Result<usize, ReadError>
+where
+    F: FnMut(&SplitInterchange<'_>) -> io::Result<W>,
+    W: Write,
+{
+    let parts = split_interchanges(data)?;
+    for part in &parts {
+        let mut out = open(part)?;
+        out.write_all(part.as_bytes())?;
+        out.flush()?;
+    }
+    Ok(parts.len())
+}
+
+/// Writes each interchange of a batch to its own file in `dir`, named by
+/// [`SplitInterchange::file_name`].
+///
+/// Interchanges that would get the same name, such as retransmissions, get `-2`, `-3`,
+/// and so on appended to the stem. Existing files with the same names are overwritten.
+/// Returns the paths written, in batch order.
+///
+/// # Errors
+/// Same as [`split_into`].
+pub fn split_to_dir<P: AsRef<Path>>(data: &[u8], dir: P) -> Result<Vec<PathBuf>, ReadError> {
+    let dir = dir.as_ref();
+    let mut used = HashSet::new();
+    let mut paths = Vec::new();
+    split_into(data, |part| {
+        let name = part.file_name();
+        let stem = name.trim_end_matches(".x12");
+        let mut unique = name.clone();
+        let mut n = 1;
+        while !used.insert(unique.clone()) {
+            n += 1;
+            unique = format!("{}-{}.x12", stem, n);
+        }
+        let path = dir.join(unique);
+        let file = File::create(&path)?;
+        paths.push(path);
+        Ok(BufWriter::new(file))
+    })?;
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::interchange_to;
+
+    #[test]
+    fn test_parts_reassemble_batch() {
+        let first = format!("{}\n", interchange_to("PAYER/1", 1, ""));
+        let second = interchange_to("PAYER2", 2, "").replace('*', "|");
+        let batch = format!("{}{}\n\n", first, second);
+        let parts = split_interchanges(batch.as_bytes()).unwrap();
+        assert_eq!(parts[0].as_bytes(), first.as_bytes());
+        assert_eq!(parts[0].span(), Span::new(0, first.len()));
+        assert_eq!(parts[0].file_name(), "SENDERID_PAYER_1_000000001.x12");
+        assert_eq!(parts[1].as_bytes(), format!("{}\n", second).as_bytes());
+        assert_eq!(parts[1].delimiters().element_separator(), b'|');
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            split_interchanges(b"GS*HC~"),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+        let trailing = format!("{}\nGS*HC~", interchange_to("R", 1, ""));
+        assert_eq!(
+            split_interchanges(trailing.as_bytes()),
+            Err(DelimiterError::NotIsaSegment)
+        );
+        let mut opened = 0;
+        let result = split_into(trailing.as_bytes(), |_| {
+            opened += 1;
+            Ok(Vec::new())
+        });
+        assert!(matches!(
+            result,
+            Err(ReadError::Delimiters(DelimiterError::NotIsaSegment))
+        ));
+        assert_eq!(opened, 0);
+    }
+
+    #[test]
+    fn test_split_to_dir_names_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let batch = format!(
+            "{}{}{}",
+            interchange_to("R", 1, ""),
+            interchange_to("R", 2, ""),
+            interchange_to("R", 1, "")
+        );
+        let paths = split_to_dir(batch.as_bytes(), dir.path()).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "SENDERID_R_000000001.x12",
+                "SENDERID_R_000000002.x12",
+                "SENDERID_R_000000001-2.x12"
+            ]
+        );
+        assert_eq!(
+            std::fs::read(&paths[2]).unwrap(),
+            interchange_to("R", 1, "").as_bytes()
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_split_bytes_shares_buffer() {
+        let batch = bytes::Bytes::from(format!(
+            "{}\r\n{}",
+            interchange_to("PAYER1", 1, ""),
+            interchange_to("PAYER2", 2, "")
+        ));
+        let parts = split_bytes(&batch).unwrap();
+        let borrowed = split_interchanges(&batch).unwrap();
+        assert_eq!(parts.len(), 2);
+        for ((summary, bytes), part) in parts.iter().zip(&borrowed) {
+            assert_eq!(summary, part.summary());
+            assert_eq!(bytes.as_ptr(), part.as_bytes().as_ptr());
+            assert_eq!(&bytes[..], part.as_bytes());
+        }
+    }
+}
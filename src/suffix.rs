@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+
+use crate::{Delimiters, TerminatorSuffix};
+
+/// Counts of the line endings that follow segment terminators, built by [`suffix_report`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuffixReport {
+    /// Terminators followed directly by the next segment or the end of the data.
+    pub none: usize,
+    /// Terminators followed by `\n`.
+    pub lf: usize,
+    /// Terminators followed by `\r\n`.
+    pub crlf: usize,
+    /// Terminators followed by `\r` alone.
+    pub cr: usize,
+}
+
+impl SuffixReport {
+    /// Returns the count for one suffix style.
+    pub fn count(&self, suffix: TerminatorSuffix) -> usize {
+        match suffix {
+            TerminatorSuffix::None => self.none,
+            TerminatorSuffix::Lf => self.lf,
+            TerminatorSuffix::CrLf => self.crlf,
+            TerminatorSuffix::Cr => self.cr,
+        }
+    }
+
+    /// Returns the total number of terminators seen.
+    pub fn total(&self) -> usize {
+        self.none + self.lf + self.crlf + self.cr
+    }
+
+    /// Returns true if more than one suffix style is present.
+    pub fn is_mixed(&self) -> bool {
+        [self.none, self.lf, self.crlf, self.cr]
+            .iter()
+            .filter(|&&count| count > 0)
+            .count()
+            > 1
+    }
+
+    /// Returns the most common suffix style, or None if there are no terminators.
+    ///
+    /// Ties are broken in the order `None`, `Lf`, `CrLf`, `Cr`.
+    pub fn dominant(&self) -> Option<TerminatorSuffix> {
+        let styles = [
+            TerminatorSuffix::None,
+            TerminatorSuffix::Lf,
+            TerminatorSuffix::CrLf,
+            TerminatorSuffix::Cr,
+        ];
+        let mut best: Option<TerminatorSuffix> = None;
+        for style in styles {
+            if self.count(style) > best.map_or(0, |b| self.count(b)) {
+                best = Some(style);
+            }
+        }
+        best
+    }
+}
+
+/// Reports which line-ending styles follow the segment terminators in `data`.
+///
+/// Every terminator is counted, including the last one. Extra blank lines after a
+/// terminator are counted under the style of the first line ending.
+///
+/// ```
+/// use x12_delimiters::suffix::suffix_report;
+/// use x12_delimiters::{Delimiters, TerminatorSuffix};
+///
+/// let report = suffix_report(b"ST*837~\r\nBHT*0019~\nSE*3*0001~\r\n", &Delimiters::default());
+/// assert!(report.is_mixed());
+/// assert_eq!(report.crlf, 2);
+/// assert_eq!(report.dominant(), Some(TerminatorSuffix::CrLf));
+/// ```
+pub fn suffix_report(data: &[u8], delimiters: &Delimiters) -> SuffixReport {
+    let mut report = SuffixReport::default();
+    let terminator = delimiters.segment_terminator();
+    for (i, _) in data.iter().enumerate().filter(|(_, &b)| b == terminator) {
+        match TerminatorSuffix::detect(&data[i + 1..]) {
+            TerminatorSuffix::None => report.none += 1,
+            TerminatorSuffix::Lf => report.lf += 1,
+            TerminatorSuffix::CrLf => report.crlf += 1,
+            TerminatorSuffix::Cr => report.cr += 1,
+        }
+    }
+    report
+}
+
+/// Rewrites the line endings after every segment terminator to `target`.
+///
+/// The whole run of CR and LF bytes after each terminator is replaced, so blank lines
+/// between segments are removed as well. Line breaks elsewhere are left alone, and the
+/// input is returned unchanged when every terminator already uses `target`.
+///
+/// ```
+/// use x12_delimiters::suffix::normalize_suffixes;
+/// use x12_delimiters::{Delimiters, TerminatorSuffix};
+///
+/// let data = b"ST*837~\r\nBHT*0019~\nSE*3*0001~";
+/// let normalized = normalize_suffixes(data, &Delimiters::default(), TerminatorSuffix::Lf);
+/// assert_eq!(&normalized[..], b"ST*837~\nBHT*0019~\nSE*3*0001~\n");
+/// ```
+pub fn normalize_suffixes<'a>(
+    data: &'a [u8],
+    delimiters: &Delimiters,
+    target: TerminatorSuffix,
+) -> Cow<'a, [u8]> {
+    let terminator = delimiters.segment_terminator();
+    let is_line_break = |b: u8| (b == b'\r' || b == b'\n') && b != terminator;
+    let ending = target.as_bytes();
+
+    let already_normalized = data
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b == terminator)
+        .all(|(i, _)| {
+            let rest = &data[i + 1..];
+            let run = rest.iter().take_while(|&&b| is_line_break(b)).count();
+            &rest[..run] == ending
+        });
+    if already_normalized {
+        return Cow::Borrowed(data);
+    }
+
+    let mut out = Vec::with_capacity(data.len() + data.len() / 16);
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        out.push(byte);
+        i += 1;
+        if byte == terminator {
+            while data.get(i).is_some_and(|&b| is_line_break(b)) {
+                i += 1;
+            }
+            out.extend_from_slice(ending);
+        }
+    }
+    Cow::Owned(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_each_style() {
+        let data = b"A~B~\nC~\r\nD~\rE~\r\n\r\nF~";
+        let report = suffix_report(data, &Delimiters::default());
+        assert_eq!(
+            report,
+            SuffixReport {
+                none: 2,
+                lf: 1,
+                crlf: 2,
+                cr: 1
+            }
+        );
+        assert_eq!(report.total(), 6);
+        assert!(report.is_mixed());
+        assert_eq!(report.dominant(), Some(TerminatorSuffix::None));
+        assert_eq!(SuffixReport::default().dominant(), None);
+        assert!(!suffix_report(b"A~\nB~\n", &Delimiters::default()).is_mixed());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let delimiters = Delimiters::default();
+        let data = b"A~B~\nC~\r\nD~\rE~\r\n\r\nF~";
+        let crlf = normalize_suffixes(data, &delimiters, TerminatorSuffix::CrLf);
+        assert_eq!(&crlf[..], b"A~\r\nB~\r\nC~\r\nD~\r\nE~\r\nF~\r\n");
+        let report = suffix_report(&crlf, &delimiters);
+        assert_eq!(report.crlf, 6);
+        assert!(!report.is_mixed());
+
+        assert!(matches!(
+            normalize_suffixes(&crlf, &delimiters, TerminatorSuffix::CrLf),
+            Cow::Borrowed(_)
+        ));
+        let none = normalize_suffixes(&crlf, &delimiters, TerminatorSuffix::None);
+        assert_eq!(&none[..], b"A~B~C~D~E~F~");
+    }
+
+    #[test]
+    fn test_newline_terminator_is_untouched() {
+        let delimiters = Delimiters::new(b'\n', b'*', b':');
+        let data = b"A*1\nB*2\n";
+        assert_eq!(suffix_report(data, &delimiters).none, 2);
+        assert!(matches!(
+            normalize_suffixes(data, &delimiters, TerminatorSuffix::None),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -0,0 +1,411 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::time::Instant;
+
+use crate::control::compare_control_numbers;
+use crate::errors::DelimiterError;
+use crate::segment::{element_at, Span};
+use crate::Delimiters;
+
+const ISA_LENGTH: usize = 106;
+/// Segments scanned between clock reads in [`interchange_spans_until`].
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+/// Identifying fields of an interchange, read from its ISA segment.
+///
+/// Values are stored as written, with fixed-width padding removed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct InterchangeSummary {
+    delimiters: Delimiters,
+    sender_qualifier: String,
+    sender_id: String,
+    receiver_qualifier: String,
+    receiver_id: String,
+    date: String,
+    time: String,
+    version: String,
+    control_number: String,
+    usage_indicator: String,
+}
+
+impl InterchangeSummary {
+    /// Reads the summary from the ISA segment at the start of `data`.
+    ///
+    /// Only the first 106 bytes (plus any line ending) are examined. Missing elements in a
+    /// malformed ISA are returned as empty strings.
+    ///
+    /// # Errors
+    /// Returns `DelimiterError::InvalidIsaLength` if `data` is shorter than an ISA segment.
+    pub fn from_isa(data: &[u8]) -> Result<Self, DelimiterError> {
+        let delimiters = Delimiters::from_isa(data)?;
+        let raw = &data[..ISA_LENGTH - 1];
+        let element = |n| {
+            let value = element_at(raw, &delimiters, n).unwrap_or_default();
+            String::from_utf8_lossy(value).trim().to_string()
+        };
+        Ok(InterchangeSummary {
+            delimiters,
+            sender_qualifier: element(5),
+            sender_id: element(6),
+            receiver_qualifier: element(7),
+            receiver_id: element(8),
+            date: element(9),
+            time: element(10),
+            version: element(12),
+            control_number: element(13),
+            usage_indicator: element(15),
+        })
+    }
+
+    /// Returns the delimiters declared by the ISA.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns ISA05, the sender ID qualifier.
+    pub fn sender_qualifier(&self) -> &str {
+        &self.sender_qualifier
+    }
+
+    /// Returns ISA06, the interchange sender ID.
+    pub fn sender_id(&self) -> &str {
+        &self.sender_id
+    }
+
+    /// Returns ISA07, the receiver ID qualifier.
+    pub fn receiver_qualifier(&self) -> &str {
+        &self.receiver_qualifier
+    }
+
+    /// Returns ISA08, the interchange receiver ID.
+    pub fn receiver_id(&self) -> &str {
+        &self.receiver_id
+    }
+
+    /// Returns ISA09, the interchange date as `YYMMDD`.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    /// Returns ISA10, the interchange time as `HHMM`.
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    /// Returns ISA12, the interchange control version number.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns ISA13, the interchange control number, as written.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+
+    /// Returns ISA15, `P` for production or `T` for test data.
+    pub fn usage_indicator(&self) -> &str {
+        &self.usage_indicator
+    }
+
+    /// Returns the key identifying this interchange for deduplication.
+    pub fn dedupe_key(&self) -> DedupeKey {
+        DedupeKey {
+            sender_qualifier: self.sender_qualifier.clone(),
+            sender_id: self.sender_id.clone(),
+            receiver_qualifier: self.receiver_qualifier.clone(),
+            receiver_id: self.receiver_id.clone(),
+            control_number: self.control_number.clone(),
+        }
+    }
+
+    /// Returns the key that orders interchanges deterministically for re-enveloping and
+    /// replay.
+    ///
+    /// ```
+    /// use x12_delimiters::summary::InterchangeSummary;
+    ///
+    /// let isa = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000010*0*P*:~";
+    /// let later = InterchangeSummary::from_isa(isa.as_bytes()).unwrap();
+    /// let earlier = InterchangeSummary::from_isa(isa.replace("000000010", "000000009").as_bytes()).unwrap();
+    /// assert!(earlier.sort_key() < later.sort_key());
+    /// ```
+    pub fn sort_key(&self) -> SortKey {
+        SortKey {
+            sender_qualifier: self.sender_qualifier.clone(),
+            sender_id: self.sender_id.clone(),
+            date: self.date.clone(),
+            time: self.time.clone(),
+            control_number: self.control_number.clone(),
+        }
+    }
+}
+
+/// Sender, date, time, and control number, compared in that order.
+///
+/// Dates compare by full year: a `YYMMDD` year below 50 is read as 20YY and any other as
+/// 19YY, so `991231` sorts before `000101`. Times compare as written. Control numbers compare numerically (see
+/// [`crate::control::compare_control_numbers`]), so `000000042` and `42` sort together,
+/// with ties broken by the bytes as written.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct SortKey {
+    sender_qualifier: String,
+    sender_id: String,
+    date: String,
+    time: String,
+    control_number: String,
+}
+
+impl SortKey {
+    /// Returns ISA05 and ISA06.
+    pub fn sender(&self) -> (&str, &str) {
+        (&self.sender_qualifier, &self.sender_id)
+    }
+
+    /// Returns ISA09 and ISA10.
+    pub fn timestamp(&self) -> (&str, &str) {
+        (&self.date, &self.time)
+    }
+
+    /// Returns ISA13.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.sender(), full_date(&self.date), &self.time)
+            .cmp(&(other.sender(), full_date(&other.date), &other.time))
+            .then_with(|| self.date.cmp(&other.date))
+            .then_with(|| {
+                compare_control_numbers(
+                    self.control_number.as_bytes(),
+                    other.control_number.as_bytes(),
+                )
+            })
+            .then_with(|| self.control_number.cmp(&other.control_number))
+    }
+}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Expands a six-digit `YYMMDD` date to `YYYYMMDD`, reading years below 50 as 20YY and the
+/// rest as 19YY. Other values are returned as written.
+fn full_date(date: &str) -> Cow<'_, str> {
+    if date.len() != 6 || !date.bytes().all(|b| b.is_ascii_digit()) {
+        return Cow::Borrowed(date);
+    }
+    let century = if date.as_bytes()[0] < b'5' {
+        "20"
+    } else {
+        "19"
+    };
+    Cow::Owned(format!("{}{}", century, date))
+}
+
+/// Sender, receiver, and control number: the fields a resent interchange repeats.
+///
+/// Control numbers compare as written, so `000000042` and `42` are different keys.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
+pub struct DedupeKey {
+    sender_qualifier: String,
+    sender_id: String,
+    receiver_qualifier: String,
+    receiver_id: String,
+    control_number: String,
+}
+
+impl DedupeKey {
+    /// Returns ISA05 and ISA06.
+    pub fn sender(&self) -> (&str, &str) {
+        (&self.sender_qualifier, &self.sender_id)
+    }
+
+    /// Returns ISA07 and ISA08.
+    pub fn receiver(&self) -> (&str, &str) {
+        (&self.receiver_qualifier, &self.receiver_id)
+    }
+
+    /// Returns ISA13.
+    pub fn control_number(&self) -> &str {
+        &self.control_number
+    }
+}
+
+/// Returns the span and delimiters of each interchange in a file of concatenated
+/// interchanges.
+///
+/// Each interchange runs from its ISA through its IEA, or to the end of the data if the
+/// IEA is missing, and is split with the delimiters declared in its own ISA. Whitespace
+/// between interchanges is skipped, and scanning stops at the first position that does
+/// not start an ISA.
+pub(crate) fn interchange_spans(data: &[u8]) -> Vec<(Span, Delimiters)> {
+    interchange_spans_until(data, None).0
+}
+
+/// Like [`interchange_spans`], stopping once `deadline` has passed.
+///
+/// Returns the spans of the interchanges completely scanned, and true if scanning stopped
+/// early because of the deadline.
+pub(crate) fn interchange_spans_until(
+    data: &[u8],
+    deadline: Option<Instant>,
+) -> (Vec<(Span, Delimiters)>, bool) {
+    let mut spans = Vec::new();
+    let mut position = 0;
+    loop {
+        match scan_interchange(data, position, deadline) {
+            Scan::Found(span, delimiters) => {
+                position = span.end;
+                spans.push((span, delimiters));
+            }
+            Scan::End => return (spans, false),
+            Scan::Expired => return (spans, true),
+        }
+    }
+}
+
+/// Outcome of [`scan_interchange`].
+pub(crate) enum Scan {
+    /// An interchange with its span in the data and its delimiters.
+    Found(Span, Delimiters),
+    /// No ISA starts at the position, after skipping whitespace.
+    End,
+    /// The deadline passed before the interchange was completely scanned.
+    Expired,
+}
+
+/// Scans the interchange that starts at `position`, after any whitespace; see
+/// [`interchange_spans`].
+pub(crate) fn scan_interchange(data: &[u8], position: usize, deadline: Option<Instant>) -> Scan {
+    let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    let mut position = position;
+    while data.get(position).is_some_and(|b| b.is_ascii_whitespace()) {
+        position += 1;
+    }
+    let rest = data.get(position..).unwrap_or_default();
+    if !rest.starts_with(b"ISA") {
+        return Scan::End;
+    }
+    let Ok(delimiters) = Delimiters::from_isa(rest) else {
+        return Scan::End;
+    };
+    if expired() {
+        return Scan::Expired;
+    }
+    let mut end = rest.len();
+    for (n, (span, segment)) in delimiters.segments(rest).spanned().enumerate() {
+        if n % DEADLINE_CHECK_INTERVAL == DEADLINE_CHECK_INTERVAL - 1 && expired() {
+            return Scan::Expired;
+        }
+        if segment.id() == b"IEA" {
+            end = span.end;
+            break;
+        }
+    }
+    Scan::Found(Span::new(position, position + end), delimiters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_isa() {
+        let data = b"ISA^00^          ^00^          ^01^123456789      ^ZZ^RECEIVERID     ^250403^0856^U^00401^000000042^1^T^>}\r\nGS";
+        let summary = InterchangeSummary::from_isa(data).unwrap();
+        assert_eq!(summary.delimiters().element_separator(), b'^');
+        assert_eq!(summary.sender_qualifier(), "01");
+        assert_eq!(summary.sender_id(), "123456789");
+        assert_eq!(summary.receiver_id(), "RECEIVERID");
+        assert_eq!((summary.date(), summary.time()), ("250403", "0856"));
+        assert_eq!(summary.version(), "00401");
+        assert_eq!(summary.control_number(), "000000042");
+        assert_eq!(summary.usage_indicator(), "T");
+        assert_eq!(
+            InterchangeSummary::from_isa(b"ISA*00*"),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_sort_key_orders_batches() {
+        let summary = |sender: &str, date: &str, control: &str| {
+            let isa = format!(
+                "ISA*00*          *00*          *ZZ*{:<15}*ZZ*RECEIVERID     *{}*0856*^*00501*{}*0*P*:~",
+                sender, date, control
+            );
+            InterchangeSummary::from_isa(isa.as_bytes()).unwrap()
+        };
+        let mut batch = [
+            summary("SENDERB", "250401", "000000001"),
+            summary("SENDERA", "250403", "000000100"),
+            summary("SENDERA", "250403", "000000020"),
+            summary("SENDERA", "250402", "000000900"),
+        ];
+        batch.sort_by_key(InterchangeSummary::sort_key);
+        let order: Vec<_> = batch
+            .iter()
+            .map(|s| (s.sender_id(), s.control_number()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("SENDERA", "000000900"),
+                ("SENDERA", "000000020"),
+                ("SENDERA", "000000100"),
+                ("SENDERB", "000000001"),
+            ]
+        );
+
+        let padded = summary("SENDERA", "250403", "000000042").sort_key();
+        let short = summary("SENDERA", "250403", "42       ").sort_key();
+        assert_eq!(short.control_number(), "42");
+        assert_eq!(short.cmp(&padded), Ordering::Greater);
+        assert_ne!(short, padded);
+    }
+
+    #[test]
+    fn test_sort_key_century_pivot() {
+        let key = |date: &str| {
+            let isa = format!(
+                "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *{}*0856*^*00501*000000001*0*P*:~",
+                date
+            );
+            InterchangeSummary::from_isa(isa.as_bytes())
+                .unwrap()
+                .sort_key()
+        };
+        assert!(key("991231") < key("000101"));
+        assert!(key("500101") < key("491231"));
+        assert!(key("250403") < key("490101"));
+        assert!(key("990101") < key("991231"));
+        assert_eq!(key("000101").cmp(&key("000101")), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_interchange_spans() {
+        let first = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~GS*HC~IEA*1*000000001~";
+        let second = "ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000002|0|P|>}IEA|1|000000002}";
+        let data = format!("{}\r\n{}\r\nJUNK", first, second);
+        let spans = interchange_spans(data.as_bytes());
+        assert_eq!(
+            spans,
+            vec![
+                (
+                    Span::new(0, first.len()),
+                    Delimiters::default().with_repetition_separator(Some(b'^'))
+                ),
+                (
+                    Span::new(first.len() + 2, first.len() + 2 + second.len()),
+                    Delimiters::new(b'}', b'|', b'>').with_repetition_separator(Some(b'^'))
+                ),
+            ]
+        );
+        assert!(interchange_spans(b"ISA*00*").is_empty());
+    }
+}
@@ -0,0 +1,23 @@
+/// A 00501 ISA segment whose control number, `00000000N`, is filled in by [`isa_to`].
+const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*00000000N*0*P*:~";
+
+/// Returns the ISA segment from `SENDERID` to `RECEIVERID` with control number `control`.
+pub(crate) fn isa(control: u32) -> String {
+    isa_to("RECEIVERID", control)
+}
+
+/// Returns the ISA segment from `SENDERID` to `receiver` with control number `control`.
+pub(crate) fn isa_to(receiver: &str, control: u32) -> String {
+    ISA.replace("00000000N", &format!("{:09}", control))
+        .replace("RECEIVERID     ", &format!("{:15}", receiver))
+}
+
+/// Returns an interchange to `RECEIVERID`: its ISA, `body`, and the matching IEA.
+pub(crate) fn interchange(control: u32, body: &str) -> String {
+    interchange_to("RECEIVERID", control, body)
+}
+
+/// Returns an interchange to `receiver`: its ISA, `body`, and the matching IEA.
+pub(crate) fn interchange_to(receiver: &str, control: u32, body: &str) -> String {
+    format!("{}{}IEA*1*{:09}~", isa_to(receiver, control), body, control)
+}
@@ -0,0 +1,241 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use crate::Delimiters;
+
+/// One lexical unit of an X12 document.
+///
+/// Delimiter tokens carry no bytes: they are written with the delimiters of the
+/// [`TokenDocument`] that holds them.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Token<'a> {
+    /// Element or sub-element content, including any padding. Never empty.
+    Data(Cow<'a, [u8]>),
+    /// The element separator.
+    ElementSeparator,
+    /// The sub-element separator.
+    SubElementSeparator,
+    /// The segment terminator.
+    SegmentTerminator,
+    /// A run of `\r` and `\n` bytes before a segment, such as a terminator suffix.
+    LineEnding(Cow<'a, [u8]>),
+}
+
+/// A document as a flat token sequence that preserves every input byte.
+///
+/// Unlike [`Delimiters::segments`], which drops line endings, the token model keeps
+/// them, so [`TokenDocument::to_bytes`] reproduces the input exactly. Edit tokens in
+/// place through [`TokenDocument::tokens_mut`]; bytes outside the edited tokens are
+/// unchanged on output.
+///
+/// ```
+/// use std::borrow::Cow;
+/// use x12_delimiters::token::{Token, TokenDocument};
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"NM1*IL*1*DOE~\r\nREF*SY*123~\r\n";
+/// let mut document = TokenDocument::parse(data, &Delimiters::default());
+/// assert_eq!(document.to_bytes(), data);
+///
+/// document.tokens_mut()[6] = Token::Data(Cow::Borrowed(b"ROE"));
+/// assert_eq!(document.to_bytes(), b"NM1*IL*1*ROE~\r\nREF*SY*123~\r\n");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TokenDocument<'a> {
+    delimiters: Delimiters,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> TokenDocument<'a> {
+    /// Tokenizes `data` with `delimiters`.
+    ///
+    /// Any byte sequence is accepted. A byte that matches more than one delimiter is read
+    /// as the segment terminator first, then the element separator. Line endings are
+    /// recognized only at the start of the data and directly after a terminator; elsewhere
+    /// `\r` and `\n` are data.
+    pub fn parse(data: &'a [u8], delimiters: &Delimiters) -> Self {
+        let mut tokens = Vec::new();
+        let mut position = 0;
+        let mut segment_start = true;
+        while position < data.len() {
+            if segment_start {
+                let run = data[position..]
+                    .iter()
+                    .take_while(|&&b| b == b'\r' || b == b'\n')
+                    .count();
+                if run > 0 {
+                    tokens.push(Token::LineEnding(Cow::Borrowed(
+                        &data[position..position + run],
+                    )));
+                    position += run;
+                    continue;
+                }
+                segment_start = false;
+            }
+
+            let byte = data[position];
+            let delimiter = if byte == delimiters.segment_terminator() {
+                segment_start = true;
+                Some(Token::SegmentTerminator)
+            } else if byte == delimiters.element_separator() {
+                Some(Token::ElementSeparator)
+            } else if byte == delimiters.sub_element_separator() {
+                Some(Token::SubElementSeparator)
+            } else {
+                None
+            };
+            if let Some(token) = delimiter {
+                tokens.push(token);
+                position += 1;
+                continue;
+            }
+
+            let len = data[position..]
+                .iter()
+                .position(|&b| {
+                    b == delimiters.segment_terminator()
+                        || b == delimiters.element_separator()
+                        || b == delimiters.sub_element_separator()
+                })
+                .unwrap_or(data.len() - position);
+            tokens.push(Token::Data(Cow::Borrowed(&data[position..position + len])));
+            position += len;
+        }
+        TokenDocument {
+            delimiters: *delimiters,
+            tokens,
+        }
+    }
+
+    /// Returns the delimiters used to write delimiter tokens.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the tokens in document order.
+    pub fn tokens(&self) -> &[Token<'a>] {
+        &self.tokens
+    }
+
+    /// Returns the tokens for editing.
+    pub fn tokens_mut(&mut self) -> &mut Vec<Token<'a>> {
+        &mut self.tokens
+    }
+
+    /// Serializes the tokens. For an unedited document this equals the parsed input.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.write_to(&mut out)
+            .expect("writing to a Vec cannot fail");
+        out
+    }
+
+    /// Writes the serialized tokens to `out`.
+    ///
+    /// # Errors
+    /// Returns any error from `out`.
+    pub fn write_to<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        for token in &self.tokens {
+            match token {
+                Token::Data(bytes) | Token::LineEnding(bytes) => out.write_all(bytes)?,
+                Token::ElementSeparator => out.write_all(&[self.delimiters.element_separator()])?,
+                Token::SubElementSeparator => {
+                    out.write_all(&[self.delimiters.sub_element_separator()])?
+                }
+                Token::SegmentTerminator => {
+                    out.write_all(&[self.delimiters.segment_terminator()])?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies every borrowed token so the document no longer refers to the input.
+    pub fn into_owned(self) -> TokenDocument<'static> {
+        let tokens = self
+            .tokens
+            .into_iter()
+            .map(|token| match token {
+                Token::Data(bytes) => Token::Data(Cow::Owned(bytes.into_owned())),
+                Token::LineEnding(bytes) => Token::LineEnding(Cow::Owned(bytes.into_owned())),
+                Token::ElementSeparator => Token::ElementSeparator,
+                Token::SubElementSeparator => Token::SubElementSeparator,
+                Token::SegmentTerminator => Token::SegmentTerminator,
+            })
+            .collect();
+        TokenDocument {
+            delimiters: self.delimiters,
+            tokens,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn data(bytes: &[u8]) -> Token<'_> {
+        Token::Data(Cow::Borrowed(bytes))
+    }
+
+    fn line_ending(bytes: &[u8]) -> Token<'_> {
+        Token::LineEnding(Cow::Borrowed(bytes))
+    }
+
+    #[test]
+    fn test_tokens() {
+        let document = TokenDocument::parse(b"\nSV1*HC:1**~\r\n\r\nSE*1\r", &Delimiters::default());
+        assert_eq!(
+            document.tokens(),
+            &[
+                line_ending(b"\n"),
+                data(b"SV1"),
+                Token::ElementSeparator,
+                data(b"HC"),
+                Token::SubElementSeparator,
+                data(b"1"),
+                Token::ElementSeparator,
+                Token::ElementSeparator,
+                Token::SegmentTerminator,
+                line_ending(b"\r\n\r\n"),
+                data(b"SE"),
+                Token::ElementSeparator,
+                data(b"1\r"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_owned_outlives_input() {
+        let document = {
+            let input = b"ST*837~\r\n".to_vec();
+            TokenDocument::parse(&input, &Delimiters::default()).into_owned()
+        };
+        assert_eq!(document.to_bytes(), b"ST*837~\r\n");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_roundtrip_is_byte_exact(
+            input in proptest::collection::vec(any::<u8>(), 0..512),
+            (terminator, element, sub_element) in (any::<u8>(), any::<u8>(), any::<u8>())
+        ) {
+            let delimiters = Delimiters::new(terminator, element, sub_element);
+            let document = TokenDocument::parse(&input, &delimiters);
+            prop_assert_eq!(document.to_bytes(), input);
+        }
+
+        #[test]
+        fn prop_roundtrip_x12_like(
+            input in proptest::collection::vec(
+                prop_oneof![Just(b'~'), Just(b'*'), Just(b':'), Just(b'\r'), Just(b'\n'), Just(b'A')],
+                0..256
+            )
+        ) {
+            let document = TokenDocument::parse(&input, &Delimiters::default());
+            prop_assert!(document.tokens().iter().all(|t| !matches!(t, Token::Data(d) if d.is_empty())));
+            prop_assert_eq!(document.to_bytes(), input);
+        }
+    }
+}
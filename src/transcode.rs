@@ -0,0 +1,942 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::codepage::ByteTranscoder;
+use crate::conflict::{is_delimiter, resolve_into, Conflict, ConflictAction, ConflictPolicy};
+use crate::errors::{BuildError, DelimiterError, TranscodeError, WriteError};
+use crate::segment::{Segment, Span};
+use crate::summary::interchange_spans;
+use crate::Delimiters;
+
+/// Rewrites every interchange in `data` with the `target` delimiters and suffix.
+///
+/// Each interchange is read with the delimiters declared in its own ISA, so files that
+/// concatenate interchanges from different senders are handled. ISA16 becomes the target
+/// sub-element separator. If both the interchange and `target` have a repetition
+/// separator, ISA11 and every repetition are rewritten as in [`redelimit`]. Line endings
+/// between segments are replaced by the target suffix.
+///
+/// ```
+/// use x12_delimiters::transcode::transcode;
+/// use x12_delimiters::{Delimiters, TerminatorSuffix};
+///
+/// let data = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}\r\nIEA|0|000000001}\r\n";
+/// let target = Delimiters::default().with_suffix(TerminatorSuffix::Lf);
+/// assert_eq!(
+///     transcode(data, &target).unwrap(),
+///     b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\nIEA*0*000000001~\n"
+/// );
+/// ```
+///
+/// # Errors
+/// Returns `TranscodeError::Delimiters` if `data` does not start with an ISA segment,
+/// with `DelimiterError::TrailingData` if it has data other than whitespace after its
+/// last interchange, and `TranscodeError::Build` if a data byte equals one of the target delimiters.
+pub fn transcode(data: &[u8], target: &Delimiters) -> Result<Vec<u8>, TranscodeError> {
+    transcode_with_policy(data, target, &ConflictAction::Error)
+}
+
+/// Like [`transcode`], consulting `policy` for data bytes that equal a target delimiter.
+///
+/// # Errors
+/// Same as [`transcode`], with `TranscodeError::Build` only when the policy rejects a
+/// conflict.
+pub fn transcode_with_policy<P: ConflictPolicy + ?Sized>(
+    data: &[u8],
+    target: &Delimiters,
+    policy: &P,
+) -> Result<Vec<u8>, TranscodeError> {
+    let spans = interchange_spans(data);
+    if spans.is_empty() {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa.into());
+    }
+
+    check_remainder(data, &spans)?;
+
+    let mut out = Vec::with_capacity(data.len());
+    for &(span, source) in &spans {
+        let to = target_for(&source, target);
+        for segment in source.segments(&data[span.start..span.end]).raw() {
+            write_redelimited(&mut out, segment, &source, &to, policy)?;
+        }
+    }
+    Ok(out)
+}
+
+/// Fails unless everything after the last interchange in `spans` is whitespace.
+fn check_remainder(data: &[u8], spans: &[(Span, Delimiters)]) -> Result<(), DelimiterError> {
+    let end = spans.last().map_or(0, |(span, _)| span.end);
+    if data[end..].iter().all(u8::is_ascii_whitespace) {
+        Ok(())
+    } else {
+        Err(DelimiterError::TrailingData { offset: end })
+    }
+}
+
+/// Returns `target` as applied to an interchange read with `source`: the repetition
+/// separator is dropped when the interchange declares none, e.g. before version 00402.
+fn target_for(source: &Delimiters, target: &Delimiters) -> Delimiters {
+    match source.repetition_separator() {
+        Some(_) => *target,
+        None => target.with_repetition_separator(None),
+    }
+}
+
+/// Rewrites `input`, read with `from`, using the `to` delimiters and suffix.
+///
+/// Unlike [`transcode`], the source delimiters are given rather than read from each ISA,
+/// so fragments without an envelope can be converted too. Every separator is rewritten:
+/// element and sub-element separators everywhere, including ISA16, and the repetition
+/// separator when both sets have one, including ISA11. Line endings between segments are
+/// replaced by the target suffix. Data bytes that equal a target delimiter are rejected.
+///
+/// ```
+/// use x12_delimiters::transcode::redelimit;
+/// use x12_delimiters::Delimiters;
+///
+/// let from = Delimiters::default().with_repetition_separator(Some(b'^'));
+/// let to = Delimiters::new(b'\'', b'+', b'>').with_repetition_separator(Some(b'{'));
+/// let output = redelimit(b"HI*ABK:J449^ABF:E119~\nNM1*IL*1~", &from, &to).unwrap();
+/// assert_eq!(output, b"HI+ABK>J449{ABF>E119'NM1+IL+1'");
+/// ```
+///
+/// # Errors
+/// Returns `BuildError::DelimiterConflict` for the first data byte that equals a target
+/// delimiter.
+pub fn redelimit(input: &[u8], from: &Delimiters, to: &Delimiters) -> Result<Vec<u8>, BuildError> {
+    redelimit_with_policy(input, from, to, &ConflictAction::Error)
+}
+
+/// Like [`redelimit`], consulting `policy` for data bytes that equal a target delimiter,
+/// so they can be escaped, replaced, or stripped instead of rejected.
+///
+/// # Errors
+/// Returns `BuildError::DelimiterConflict` if the policy rejects a conflict.
+pub fn redelimit_with_policy<P: ConflictPolicy + ?Sized>(
+    input: &[u8],
+    from: &Delimiters,
+    to: &Delimiters,
+    policy: &P,
+) -> Result<Vec<u8>, BuildError> {
+    let mut out = Vec::with_capacity(input.len());
+    for segment in from.segments(input).raw() {
+        write_redelimited(&mut out, segment, from, to, policy)?;
+    }
+    Ok(out)
+}
+
+/// Like [`redelimit_with_policy`], reading from `reader` and writing to `writer` one
+/// segment at a time, so memory use is bounded by the longest segment.
+///
+/// Returns the number of segments written. Segments before a rejected one have already
+/// been written.
+///
+/// ```
+/// use x12_delimiters::conflict::ConflictAction;
+/// use x12_delimiters::transcode::redelimit_stream;
+/// use x12_delimiters::Delimiters;
+///
+/// let input: &[u8] = b"NTE|ADD|A*B}\r\nSE|2|0001}\r\n";
+/// let from = Delimiters::new(b'}', b'|', b'>');
+/// let mut output = Vec::new();
+/// let policy = ConflictAction::EscapeToSpace;
+/// let count = redelimit_stream(input, &mut output, &from, &Delimiters::default(), &policy).unwrap();
+/// assert_eq!((count, &output[..]), (2, &b"NTE*ADD*A B~SE*2*0001~"[..]));
+/// ```
+///
+/// # Errors
+/// Returns `WriteError::Io` if reading or writing fails, and `WriteError::Build` if the
+/// policy rejects a conflict.
+pub fn redelimit_stream<R, W, P>(
+    reader: R,
+    mut writer: W,
+    from: &Delimiters,
+    to: &Delimiters,
+    policy: &P,
+) -> Result<usize, WriteError>
+where
+    R: Read,
+    W: Write,
+    P: ConflictPolicy + ?Sized,
+{
+    let terminator = from.segment_terminator();
+    let mut reader = BufReader::new(reader);
+    let mut segment = Vec::new();
+    let mut out = Vec::new();
+    let mut count = 0;
+    loop {
+        segment.clear();
+        if reader.read_until(terminator, &mut segment)? == 0 {
+            break;
+        }
+        // Split the same way as `Delimiters::segments`, so both paths agree.
+        for raw in from.segments(&segment).raw() {
+            out.clear();
+            write_redelimited(&mut out, raw, from, to, policy)?;
+            writer.write_all(&out)?;
+            count += 1;
+        }
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Appends `raw`, read with `from`, to `out` using the `to` delimiters, followed by the
+/// target terminator and suffix.
+fn write_redelimited<P: ConflictPolicy + ?Sized>(
+    out: &mut Vec<u8>,
+    raw: &[u8],
+    from: &Delimiters,
+    to: &Delimiters,
+    policy: &P,
+) -> Result<(), BuildError> {
+    let id = Segment::new(raw, *from).id();
+    let repetition = from.repetition_separator().zip(to.repetition_separator());
+    let is_isa = id == b"ISA";
+    let mut element = 0;
+    for (offset, &b) in raw.iter().enumerate() {
+        match repetition {
+            // ISA11 declares the repetition separator, so it is rewritten as a delimiter.
+            Some((source, target)) if is_isa && element == 11 && b == source => {
+                out.push(target);
+                continue;
+            }
+            Some((source, target)) if !is_isa && b == source => {
+                out.push(target);
+                continue;
+            }
+            _ => {}
+        }
+        if b == from.element_separator() {
+            element += 1;
+            out.push(to.element_separator());
+        } else if b == from.sub_element_separator() {
+            out.push(to.sub_element_separator());
+        } else if is_isa && element == 11 && to.repetition_separator() == Some(b) {
+            out.push(b);
+        } else if is_delimiter(to, b) {
+            resolve_into(out, policy, Conflict::new(id, element, offset, b), to)?;
+        } else {
+            out.push(b);
+        }
+    }
+    out.push(to.segment_terminator());
+    out.extend_from_slice(to.suffix().as_bytes());
+    Ok(())
+}
+
+/// Like [`transcode`], for data in the code page handled by `source`.
+///
+/// The data is decoded to Latin-1 before its delimiters are read, so the output is
+/// Latin-1 with the `target` delimiters. Encode it with a [`ByteTranscoder`] to send it
+/// back to a partner in another code page.
+///
+/// ```
+/// use x12_delimiters::codepage::{ByteTranscoder, CP037};
+/// use x12_delimiters::transcode::transcode_encoded;
+/// use x12_delimiters::Delimiters;
+///
+/// let isa = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}IEA|0|000000001}";
+/// let ascii = transcode_encoded(&CP037.encode(isa), &CP037, &Delimiters::default()).unwrap();
+/// assert!(ascii.starts_with(b"ISA*00*"));
+/// assert!(ascii.ends_with(b"*P*:~IEA*0*000000001~"));
+/// ```
+///
+/// # Errors
+/// Same as [`transcode`].
+pub fn transcode_encoded<T: ByteTranscoder + ?Sized>(
+    data: &[u8],
+    source: &T,
+    target: &Delimiters,
+) -> Result<Vec<u8>, TranscodeError> {
+    transcode(&source.decode(data), target)
+}
+
+/// Output of [`transcode_if_needed`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Transcoded<'a> {
+    data: Cow<'a, [u8]>,
+}
+
+impl<'a> Transcoded<'a> {
+    /// Returns the transcoded bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns true if the output differs from the input, i.e. the bytes were rewritten.
+    pub fn is_changed(&self) -> bool {
+        matches!(self.data, Cow::Owned(_))
+    }
+
+    /// Returns the output, borrowed from the input when nothing changed.
+    pub fn into_cow(self) -> Cow<'a, [u8]> {
+        self.data
+    }
+}
+
+/// Like [`transcode`], but returns the input itself when it already uses the target
+/// delimiters and line endings.
+///
+/// Checking is a read-only pass over the input, so re-running a normalization job over
+/// already-normalized files costs no allocation and leaves them byte-for-byte identical.
+///
+/// ```
+/// use x12_delimiters::transcode::transcode_if_needed;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~";
+/// let result = transcode_if_needed(data, &Delimiters::default()).unwrap();
+/// assert!(!result.is_changed());
+/// assert_eq!(result.as_bytes(), data);
+/// ```
+///
+/// # Errors
+/// Same as [`transcode`].
+pub fn transcode_if_needed<'a>(
+    data: &'a [u8],
+    target: &Delimiters,
+) -> Result<Transcoded<'a>, TranscodeError> {
+    let data = if plan(data, target)?.is_noop() {
+        Cow::Borrowed(data)
+    } else {
+        Cow::Owned(transcode(data, target)?)
+    };
+    Ok(Transcoded { data })
+}
+
+/// What [`transcode`] would change, computed by [`plan`] without writing any output.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TranscodePlan<'a> {
+    interchanges: Vec<(Span, Delimiters)>,
+    segment_terminators: usize,
+    element_separators: usize,
+    sub_element_separators: usize,
+    repetition_separators: usize,
+    line_endings_changed: usize,
+    terminators_added: usize,
+    conflicts: Vec<(Span, Conflict<'a>)>,
+}
+
+impl<'a> TranscodePlan<'a> {
+    /// Returns each interchange's span and the delimiters declared by its ISA.
+    pub fn interchanges(&self) -> &[(Span, Delimiters)] {
+        &self.interchanges
+    }
+
+    /// Returns the number of segment terminators that would be replaced.
+    pub fn segment_terminators(&self) -> usize {
+        self.segment_terminators
+    }
+
+    /// Returns the number of element separators that would be replaced.
+    pub fn element_separators(&self) -> usize {
+        self.element_separators
+    }
+
+    /// Returns the number of sub-element separators that would be replaced.
+    pub fn sub_element_separators(&self) -> usize {
+        self.sub_element_separators
+    }
+
+    /// Returns the number of repetition separators, including ISA11, that would be
+    /// replaced.
+    pub fn repetition_separators(&self) -> usize {
+        self.repetition_separators
+    }
+
+    /// Returns the number of segments whose following line ending or whitespace would be
+    /// replaced by the target suffix.
+    pub fn line_endings_changed(&self) -> usize {
+        self.line_endings_changed
+    }
+
+    /// Returns the number of unterminated segments that would gain a terminator.
+    pub fn terminators_added(&self) -> usize {
+        self.terminators_added
+    }
+
+    /// Returns every data byte that equals a target delimiter, with the span of its
+    /// segment in the input. [`transcode`] stops at the first of these; a policy decides
+    /// what [`transcode_with_policy`] does with each.
+    pub fn conflicts(&self) -> &[(Span, Conflict<'a>)] {
+        &self.conflicts
+    }
+
+    /// Returns true if transcoding would return the input unchanged.
+    pub fn is_noop(&self) -> bool {
+        self.segment_terminators == 0
+            && self.element_separators == 0
+            && self.sub_element_separators == 0
+            && self.repetition_separators == 0
+            && self.line_endings_changed == 0
+            && self.terminators_added == 0
+            && self.conflicts.is_empty()
+    }
+}
+
+/// Reports what [`transcode`] would change in `data` without producing output.
+///
+/// Use it to preview a migration: the plan counts replaced delimiters and line endings
+/// and lists every conflict, where `transcode` would stop at the first.
+///
+/// ```
+/// use x12_delimiters::transcode::plan;
+/// use x12_delimiters::Delimiters;
+///
+/// let data = b"ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}\r\nNTE|A*B}\r\n";
+/// let plan = plan(data, &Delimiters::default()).unwrap();
+/// assert_eq!(plan.element_separators(), 17);
+/// assert_eq!(plan.line_endings_changed(), 2);
+/// assert_eq!(plan.conflicts()[0].1.byte(), b'*');
+/// ```
+///
+/// # Errors
+/// Returns `DelimiterError` if `data` does not start with an ISA segment or has data
+/// other than whitespace after its last interchange.
+pub fn plan<'a>(data: &'a [u8], target: &Delimiters) -> Result<TranscodePlan<'a>, DelimiterError> {
+    let spans = interchange_spans(data);
+    if spans.is_empty() {
+        Delimiters::from_isa(data)?;
+        return Err(DelimiterError::MissingIsa);
+    }
+    check_remainder(data, &spans)?;
+
+    let mut plan = TranscodePlan::default();
+    if spans[0].0.start > 0 {
+        // Leading whitespace is dropped.
+        plan.line_endings_changed += 1;
+    }
+    plan.interchanges = spans;
+
+    let mut previous_end: Option<usize> = None;
+    for &(region, source) in &plan.interchanges {
+        let target = &target_for(&source, target);
+        let bytes = &data[region.start..region.end];
+        for (span, segment) in source.segments(bytes).spanned() {
+            let span = Span::new(region.start + span.start, region.start + span.end);
+            if let Some(end) = previous_end {
+                if &data[end..span.start] != target.suffix().as_bytes() {
+                    plan.line_endings_changed += 1;
+                }
+            }
+            previous_end = Some(span.end);
+
+            let raw = segment.as_bytes();
+            let after = &data[span.start + raw.len()..span.end];
+            if after.first() == Some(&source.segment_terminator()) {
+                if source.segment_terminator() != target.segment_terminator() {
+                    plan.segment_terminators += 1;
+                }
+            } else {
+                // An unterminated final segment keeps any trailing line ending in its span.
+                plan.terminators_added += 1;
+                if after != target.suffix().as_bytes() {
+                    plan.line_endings_changed += 1;
+                }
+                previous_end = None;
+            }
+            if source.eq_ignoring_suffix(target) {
+                continue;
+            }
+            // Mirrors `write_redelimited`: ISA11 is the only repetition separator in an ISA.
+            let repetition = source
+                .repetition_separator()
+                .zip(target.repetition_separator());
+            let is_isa = segment.id() == b"ISA";
+            let mut element = 0;
+            for (offset, &b) in raw.iter().enumerate() {
+                let repeats = |&(from, _): &(u8, u8)| b == from && (!is_isa || element == 11);
+                if let Some((from, to)) = repetition.filter(repeats) {
+                    if from != to {
+                        plan.repetition_separators += 1;
+                    }
+                } else if b == source.element_separator() {
+                    element += 1;
+                    if b != target.element_separator() {
+                        plan.element_separators += 1;
+                    }
+                } else if b == source.sub_element_separator() {
+                    if b != target.sub_element_separator() {
+                        plan.sub_element_separators += 1;
+                    }
+                } else if is_isa && element == 11 && target.repetition_separator() == Some(b) {
+                    // ISA11 declares the target repetition separator.
+                } else if is_delimiter(target, b) {
+                    let conflict = Conflict::new(segment.id(), element, offset, b);
+                    plan.conflicts.push((span, conflict));
+                }
+            }
+        }
+    }
+    if let Some(end) = previous_end {
+        if &data[end..] != target.suffix().as_bytes() {
+            plan.line_endings_changed += 1;
+        }
+    }
+    Ok(plan)
+}
+
+/// Options for [`convert_tree`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConvertOptions {
+    overwrite: bool,
+    conflict_action: ConflictAction,
+    #[cfg(all(feature = "io_uring", target_os = "linux"))]
+    io_uring: bool,
+}
+
+impl ConvertOptions {
+    /// Creates options that skip existing outputs and fail files with delimiter conflicts.
+    pub fn new() -> Self {
+        ConvertOptions::default()
+    }
+
+    /// Re-converts files whose output already exists instead of skipping them.
+    pub fn with_overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Sets how data bytes that equal a target delimiter are handled.
+    pub fn with_conflict_action(mut self, action: ConflictAction) -> Self {
+        self.conflict_action = action;
+        self
+    }
+
+    /// Reads source files through [`crate::uring::UringReader`], keeping several block
+    /// reads in flight, instead of one buffered read at a time. Falls back to buffered
+    /// reads if the ring cannot be set up.
+    #[cfg(all(feature = "io_uring", target_os = "linux"))]
+    pub fn with_io_uring(mut self, io_uring: bool) -> Self {
+        self.io_uring = io_uring;
+        self
+    }
+
+    fn read_source(&self, path: &Path) -> io::Result<Vec<u8>> {
+        #[cfg(all(feature = "io_uring", target_os = "linux"))]
+        if self.io_uring {
+            return crate::uring::read_file(path);
+        }
+        fs::read(path)
+    }
+}
+
+/// Why [`convert_tree`] could not convert a file.
+#[derive(Debug)]
+pub enum ConvertFailure {
+    /// The file could not be read or its output written.
+    Io(io::Error),
+    /// The file could not be transcoded.
+    Transcode(TranscodeError),
+}
+
+/// Totals from [`convert_tree`].
+#[derive(Debug, Default)]
+pub struct ConvertReport {
+    /// Files written during this run.
+    pub converted: usize,
+    /// Files skipped because their output already existed.
+    pub skipped: usize,
+    /// Bytes read from converted files.
+    pub bytes_in: u64,
+    /// Bytes written for converted files.
+    pub bytes_out: u64,
+    /// Files that failed, relative to the source directory, in path order.
+    pub failed: Vec<(PathBuf, ConvertFailure)>,
+}
+
+/// Transcodes every file under `src_dir` into the same relative path under `dst_dir`.
+///
+/// Outputs are written to a `.part` file and renamed into place, so an interrupted run
+/// never leaves a truncated output behind; re-running skips files already converted
+/// unless [`ConvertOptions::with_overwrite`] is set. A failing file is recorded in the
+/// report and the run continues. With the `rayon` feature, files are converted in
+/// parallel.
+///
+/// # Errors
+/// Returns an error only if the source tree cannot be listed; per-file problems are
+/// reported in [`ConvertReport::failed`].
+pub fn convert_tree(
+    src_dir: &Path,
+    dst_dir: &Path,
+    target: &Delimiters,
+    options: &ConvertOptions,
+) -> io::Result<ConvertReport> {
+    let mut files = Vec::new();
+    list_files(src_dir, Path::new(""), &mut files)?;
+    files.sort();
+
+    let convert = |relative: &PathBuf| convert_file(src_dir, dst_dir, relative, target, options);
+    #[cfg(feature = "rayon")]
+    let outcomes: Vec<FileOutcome> = {
+        use rayon::prelude::*;
+        files.par_iter().map(convert).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let outcomes: Vec<FileOutcome> = files.iter().map(convert).collect();
+
+    let mut report = ConvertReport::default();
+    for (relative, outcome) in files.into_iter().zip(outcomes) {
+        match outcome {
+            FileOutcome::Converted {
+                bytes_in,
+                bytes_out,
+            } => {
+                report.converted += 1;
+                report.bytes_in += bytes_in;
+                report.bytes_out += bytes_out;
+            }
+            FileOutcome::Skipped => report.skipped += 1,
+            FileOutcome::Failed(failure) => report.failed.push((relative, failure)),
+        }
+    }
+    Ok(report)
+}
+
+enum FileOutcome {
+    Converted { bytes_in: u64, bytes_out: u64 },
+    Skipped,
+    Failed(ConvertFailure),
+}
+
+fn convert_file(
+    src_dir: &Path,
+    dst_dir: &Path,
+    relative: &Path,
+    target: &Delimiters,
+    options: &ConvertOptions,
+) -> FileOutcome {
+    let destination = dst_dir.join(relative);
+    if !options.overwrite && destination.exists() {
+        return FileOutcome::Skipped;
+    }
+    let result = options
+        .read_source(&src_dir.join(relative))
+        .map_err(ConvertFailure::Io)
+        .and_then(|data| {
+            let output = transcode_with_policy(&data, target, &options.conflict_action)
+                .map_err(ConvertFailure::Transcode)?;
+            write_atomically(&destination, &output).map_err(ConvertFailure::Io)?;
+            Ok((data.len() as u64, output.len() as u64))
+        });
+    match result {
+        Ok((bytes_in, bytes_out)) => FileOutcome::Converted {
+            bytes_in,
+            bytes_out,
+        },
+        Err(failure) => FileOutcome::Failed(failure),
+    }
+}
+
+fn write_atomically(destination: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut partial = destination.as_os_str().to_owned();
+    partial.push(".part");
+    fs::write(&partial, contents)?;
+    fs::rename(&partial, destination)
+}
+
+fn list_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            list_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIPE_ISA: &str = "ISA|00|          |00|          |ZZ|SENDERID       |ZZ|RECEIVERID     |250403|0856|^|00501|000000001|0|P|>}";
+    const STAR_ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000002*0*P*:~";
+
+    #[test]
+    fn test_redelimit_rewrites_every_separator() {
+        let from = Delimiters::new(b'}', b'|', b'>').with_repetition_separator(Some(b'^'));
+        let to = Delimiters::default()
+            .with_repetition_separator(Some(b'{'))
+            .with_suffix(crate::TerminatorSuffix::Lf);
+        let input = format!("{}\r\nHI|ABK>J449^ABF>E119}}\r\nIEA|1|000000001", PIPE_ISA);
+        let output = redelimit(input.as_bytes(), &from, &to).unwrap();
+        let expected = format!(
+            "{}\nHI*ABK:J449{{ABF:E119~\nIEA*1*000000001~\n",
+            STAR_ISA
+                .replace("*^*", "*{*")
+                .replace("000000002", "000000001")
+        );
+        assert_eq!(String::from_utf8(output.clone()).unwrap(), expected);
+        assert_eq!(Delimiters::from_isa(&output).unwrap(), to);
+
+        let mut streamed = Vec::new();
+        let reader = std::io::BufReader::with_capacity(7, input.as_bytes());
+        let count = redelimit_stream(reader, &mut streamed, &from, &to, &ConflictAction::Error);
+        assert_eq!(count.unwrap(), 3);
+        assert_eq!(streamed, output);
+    }
+
+    #[test]
+    fn test_redelimit_conflicts() {
+        let from = Delimiters::new(b'}', b'|', b'>').with_repetition_separator(Some(b'^'));
+        let to = Delimiters::default().with_repetition_separator(Some(b'{'));
+        assert_eq!(
+            redelimit(b"NTE|ADD|A{B}", &from, &to),
+            Err(BuildError::DelimiterConflict {
+                byte: b'{',
+                element: 2,
+                offset: 9
+            })
+        );
+        assert_eq!(
+            redelimit_with_policy(b"NTE|ADD|A*B:C}", &from, &to, &ConflictAction::Strip),
+            Ok(b"NTE*ADD*ABC~".to_vec())
+        );
+
+        let mut out = Vec::new();
+        let input: &[u8] = b"NTE|ADD|OK}NTE|ADD|A~B}";
+        let result = redelimit_stream(input, &mut out, &from, &to, &ConflictAction::Error);
+        assert!(matches!(
+            result,
+            Err(WriteError::Build(BuildError::DelimiterConflict {
+                byte: b'~',
+                ..
+            }))
+        ));
+        assert_eq!(out, b"NTE*ADD*OK~");
+    }
+
+    #[test]
+    fn test_transcode_mixed_interchanges() {
+        let data = format!(
+            "{}SV1|HC>1}}IEA|0|1}}\n{}SV1*HC:2~IEA*0*2~",
+            PIPE_ISA, STAR_ISA
+        );
+        let out = transcode(data.as_bytes(), &Delimiters::default()).unwrap();
+        let expected = format!(
+            "{}SV1*HC:1~IEA*0*1~{}SV1*HC:2~IEA*0*2~",
+            PIPE_ISA.replace('|', "*").replace(">}", ":~"),
+            STAR_ISA
+        );
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_transcode_repetition_separator() {
+        let data = format!("{}HI|ABK>J449^ABF>E119}}NTE|A{{B}}IEA|0|1}}", PIPE_ISA);
+        let target = Delimiters::default().with_repetition_separator(Some(b'{'));
+        assert!(matches!(
+            transcode(data.as_bytes(), &target),
+            Err(TranscodeError::Build(BuildError::DelimiterConflict {
+                byte: b'{',
+                element: 1,
+                ..
+            }))
+        ));
+
+        let out = transcode_with_policy(data.as_bytes(), &target, &ConflictAction::Strip).unwrap();
+        let expected = format!(
+            "{}HI*ABK:J449{{ABF:E119~NTE*AB~IEA*0*1~",
+            PIPE_ISA.replace('|', "*").replace('^', "{").replace(">}", ":~")
+        );
+        assert_eq!(String::from_utf8(out.clone()).unwrap(), expected);
+        assert_eq!(Delimiters::from_isa(&out).unwrap(), target);
+
+        let plan = plan(data.as_bytes(), &target).unwrap();
+        assert_eq!(plan.repetition_separators(), 2);
+        assert_eq!(plan.conflicts().len(), 1);
+        assert!(super::plan(&out, &target).unwrap().is_noop());
+
+        // A 4010 interchange declares no repetition separator, so `{` is data there.
+        let legacy = format!("{}NTE|A{{B}}", PIPE_ISA.replace("^|00501", "U|00401"));
+        let out = transcode(legacy.as_bytes(), &target).unwrap();
+        assert!(out.ends_with(b"*U*00401*000000001*0*P*:~NTE*A{B~"));
+    }
+
+    #[test]
+    fn test_transcode_rejects_trailing_data() {
+        let data = format!("{}IEA|0|1}}\r\n\r\n", PIPE_ISA);
+        assert!(transcode(data.as_bytes(), &Delimiters::default()).is_ok());
+
+        let junk = format!("{}IEA|0|1}}\r\nGS|HC}}", PIPE_ISA);
+        let offset = junk.find("\r\nGS").unwrap();
+        assert_eq!(
+            transcode(junk.as_bytes(), &Delimiters::default()),
+            Err(TranscodeError::Delimiters(DelimiterError::TrailingData { offset }))
+        );
+        assert_eq!(
+            plan(junk.as_bytes(), &Delimiters::default()),
+            Err(DelimiterError::TrailingData { offset })
+        );
+    }
+
+    #[test]
+    fn test_transcode_errors() {
+        assert_eq!(
+            transcode(b"ISA*00*", &Delimiters::default()),
+            Err(TranscodeError::Delimiters(DelimiterError::InvalidIsaLength))
+        );
+        assert_eq!(
+            transcode(&[b'X'; 200], &Delimiters::default()),
+            Err(TranscodeError::Delimiters(DelimiterError::MissingIsa))
+        );
+        let data = format!("{}NTE|A*B}}", PIPE_ISA);
+        assert!(matches!(
+            transcode(data.as_bytes(), &Delimiters::default()),
+            Err(TranscodeError::Build(BuildError::DelimiterConflict {
+                byte: b'*',
+                ..
+            }))
+        ));
+        let stripped = transcode_with_policy(
+            data.as_bytes(),
+            &Delimiters::default(),
+            &ConflictAction::Strip,
+        )
+        .unwrap();
+        assert!(stripped.ends_with(b"NTE*AB~"));
+    }
+
+    #[test]
+    fn test_plan_matches_transcode() {
+        let data = format!(
+            "{}SV1|HC>1}}\r\nIEA|0|1}}\r\n{}SV1*HC:2~IEA*0*2",
+            PIPE_ISA, STAR_ISA
+        );
+        let target = Delimiters::default();
+        let plan = plan(data.as_bytes(), &target).unwrap();
+
+        assert_eq!(plan.interchanges().len(), 2);
+        assert_eq!(plan.interchanges()[0].1.element_separator(), b'|');
+        assert_eq!(plan.segment_terminators(), 3);
+        assert_eq!(plan.element_separators(), 16 + 1 + 2);
+        assert_eq!(plan.sub_element_separators(), 2);
+        assert_eq!(plan.line_endings_changed(), 2);
+        assert_eq!(plan.terminators_added(), 1);
+        assert!(plan.conflicts().is_empty());
+        assert!(!plan.is_noop());
+
+        let out = transcode(data.as_bytes(), &target).unwrap();
+        assert!(super::plan(&out, &target).unwrap().is_noop());
+        let leading = [b"\r\n", &out[..]].concat();
+        assert_eq!(
+            super::plan(&leading, &target)
+                .unwrap()
+                .line_endings_changed(),
+            1
+        );
+
+        let crlf = target.with_suffix(crate::TerminatorSuffix::CrLf);
+        let tail = super::plan(data.as_bytes(), &crlf).unwrap();
+        assert_eq!(tail.line_endings_changed(), 4);
+        let out = transcode(data.as_bytes(), &crlf).unwrap();
+        assert!(super::plan(&out, &crlf).unwrap().is_noop());
+    }
+
+    #[test]
+    fn test_plan_lists_every_conflict() {
+        let data = format!("{}NTE|A*B}}NTE|C|D~}}", PIPE_ISA);
+        let plan = plan(data.as_bytes(), &Delimiters::default()).unwrap();
+        let conflicts: Vec<(usize, u8, usize)> = plan
+            .conflicts()
+            .iter()
+            .map(|(span, c)| (span.start, c.byte(), c.element()))
+            .collect();
+        assert_eq!(conflicts, vec![(106, b'*', 1), (114, b'~', 2)]);
+        assert_eq!(
+            super::plan(b"ISA*00*", &Delimiters::default()),
+            Err(DelimiterError::InvalidIsaLength)
+        );
+    }
+
+    #[test]
+    fn test_transcode_if_needed_is_idempotent() {
+        let data = format!("{}SV1|HC>1}}\r\nIEA|0|1}}\r\n", PIPE_ISA);
+        let target = Delimiters::default();
+        let first = transcode_if_needed(data.as_bytes(), &target).unwrap();
+        assert!(first.is_changed());
+        assert_eq!(
+            first.as_bytes(),
+            transcode(data.as_bytes(), &target).unwrap()
+        );
+
+        let second = transcode_if_needed(first.as_bytes(), &target).unwrap();
+        assert!(!second.is_changed());
+        assert!(matches!(second.into_cow(), Cow::Borrowed(b) if b == first.as_bytes()));
+
+        let crlf = target.with_suffix(crate::TerminatorSuffix::CrLf);
+        assert!(transcode_if_needed(first.as_bytes(), &crlf)
+            .unwrap()
+            .is_changed());
+    }
+
+    #[test]
+    fn test_convert_tree_is_resumable() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        fs::create_dir(src.path().join("2024")).unwrap();
+        fs::write(
+            src.path().join("2024/a.x12"),
+            format!("{}IEA|0|1}}", PIPE_ISA),
+        )
+        .unwrap();
+        fs::write(src.path().join("b.x12"), STAR_ISA).unwrap();
+        fs::write(src.path().join("bad.x12"), "garbage").unwrap();
+
+        let target = Delimiters::default();
+        let report = convert_tree(src.path(), dst.path(), &target, &ConvertOptions::new()).unwrap();
+        assert_eq!((report.converted, report.skipped), (2, 0));
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, PathBuf::from("bad.x12"));
+        assert!(matches!(report.failed[0].1, ConvertFailure::Transcode(_)));
+        assert_eq!(
+            fs::read(dst.path().join("2024/a.x12")).unwrap(),
+            format!("{}IEA*0*1~", PIPE_ISA.replace('|', "*").replace(">}", ":~")).into_bytes()
+        );
+        assert!(!dst.path().join("b.x12.part").exists());
+
+        let rerun = convert_tree(src.path(), dst.path(), &target, &ConvertOptions::new()).unwrap();
+        assert_eq!(
+            (rerun.converted, rerun.skipped, rerun.failed.len()),
+            (0, 2, 1)
+        );
+
+        let options = ConvertOptions::new().with_overwrite(true);
+        let forced = convert_tree(src.path(), dst.path(), &target, &options).unwrap();
+        assert_eq!(forced.converted, 2);
+    }
+
+    #[cfg(all(feature = "io_uring", target_os = "linux"))]
+    #[test]
+    fn test_convert_tree_with_io_uring() {
+        let src = tempfile::tempdir().unwrap();
+        let (buffered, uring) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let mut batch = String::new();
+        for _ in 0..2_000 {
+            batch.push_str(&format!("{}NTE|ADD|NOTE}}IEA|0|1}}\n", PIPE_ISA));
+        }
+        fs::write(src.path().join("batch.x12"), &batch).unwrap();
+
+        let target = Delimiters::default();
+        let options = ConvertOptions::new();
+        let expected = convert_tree(src.path(), buffered.path(), &target, &options).unwrap();
+        let options = options.with_io_uring(true);
+        let report = convert_tree(src.path(), uring.path(), &target, &options).unwrap();
+        assert_eq!((report.converted, report.bytes_in), (1, batch.len() as u64));
+        assert_eq!(report.bytes_out, expected.bytes_out);
+        assert_eq!(
+            fs::read(uring.path().join("batch.x12")).unwrap(),
+            fs::read(buffered.path().join("batch.x12")).unwrap()
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+use io_uring::{opcode, types, IoUring};
+
+const DEFAULT_BLOCK_SIZE: usize = 256 * 1024;
+const DEFAULT_QUEUE_DEPTH: usize = 8;
+
+/// Reads a file sequentially through io_uring, keeping several block reads in flight.
+///
+/// The buffered path issues one `read` system call at a time, so the device is idle while
+/// each chunk is scanned. This reader submits `depth` reads of `block_size` bytes at
+/// consecutive offsets and resubmits each buffer as soon as it has been consumed, keeping
+/// the device queue full. It implements [`Read`], so it plugs into
+/// [`crate::reader::SegmentReader`], [`crate::transcode::redelimit_stream`], and
+/// [`crate::index::DocumentIndex::extend_from`] loops unchanged.
+///
+/// With the `io_uring` feature, [`crate::file::FileDocument::open`] and
+/// [`crate::transcode::convert_tree`] (see `ConvertOptions::with_io_uring`) read whole
+/// files through this reader, falling back to buffered reads where io_uring is
+/// unavailable.
+///
+/// ```no_run
+/// use x12_delimiters::reader::SegmentReader;
+/// use x12_delimiters::uring::UringReader;
+///
+/// let file = UringReader::open("/archive/batch-0001.x12").unwrap();
+/// let reader = SegmentReader::with_chunk_size(file, 256 * 1024).unwrap();
+/// for segment in reader {
+///     let segment = segment.unwrap();
+///     // ...
+/// }
+/// ```
+pub struct UringReader {
+    file: File,
+    ring: IoUring,
+    blocks: Vec<Block>,
+    /// Indices into `blocks` in file order; the front is read from next.
+    order: VecDeque<usize>,
+    next_offset: u64,
+    in_flight: usize,
+}
+
+#[derive(Debug)]
+struct Block {
+    buf: Box<[u8]>,
+    offset: u64,
+    filled: usize,
+    pos: usize,
+    pending: bool,
+    /// Set when a read of this block returned no data.
+    at_end: bool,
+}
+
+impl UringReader {
+    /// Opens `path` with 256 KiB blocks and eight reads in flight.
+    ///
+    /// # Errors
+    /// Returns any error from opening the file or setting up the ring, e.g.
+    /// `io::ErrorKind::Unsupported` or `PermissionDenied` where io_uring is disabled.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        UringReader::new(File::open(path)?, DEFAULT_BLOCK_SIZE, DEFAULT_QUEUE_DEPTH)
+    }
+
+    /// Reads `file` from its start in blocks of `block_size` bytes, with up to `depth`
+    /// reads in flight. Both are clamped to at least 1.
+    ///
+    /// # Errors
+    /// Returns any error from setting up the ring or submitting the first reads.
+    pub fn new(file: File, block_size: usize, depth: usize) -> io::Result<Self> {
+        let depth = depth.max(1);
+        let entries = u32::try_from(depth.next_power_of_two()).unwrap_or(u32::MAX);
+        let blocks = (0..depth)
+            .map(|_| Block {
+                buf: vec![0; block_size.max(1)].into_boxed_slice(),
+                offset: 0,
+                filled: 0,
+                pos: 0,
+                pending: false,
+                at_end: false,
+            })
+            .collect();
+        let mut reader = UringReader {
+            file,
+            ring: IoUring::new(entries)?,
+            blocks,
+            order: VecDeque::with_capacity(depth),
+            next_offset: 0,
+            in_flight: 0,
+        };
+        for i in 0..depth {
+            reader.start_block(i);
+            reader.submit(i)?;
+        }
+        reader.ring.submit()?;
+        Ok(reader)
+    }
+
+    /// Assigns block `i` the next unread offset and queues it at the back.
+    fn start_block(&mut self, i: usize) {
+        let block = &mut self.blocks[i];
+        block.offset = self.next_offset;
+        block.filled = 0;
+        block.pos = 0;
+        block.at_end = false;
+        self.next_offset += block.buf.len() as u64;
+        self.order.push_back(i);
+    }
+
+    /// Queues a read of the unfilled part of block `i`.
+    fn submit(&mut self, i: usize) -> io::Result<()> {
+        let block = &mut self.blocks[i];
+        let rest = &mut block.buf[block.filled..];
+        let len = u32::try_from(rest.len()).unwrap_or(u32::MAX);
+        let entry = opcode::Read::new(types::Fd(self.file.as_raw_fd()), rest.as_mut_ptr(), len)
+            .offset(block.offset + block.filled as u64)
+            .build()
+            .user_data(i as u64);
+        // SAFETY: the buffer is a boxed slice owned by `self`, so it does not move, and it
+        // is not touched again until the completion for `i` has been reaped; `Drop` waits
+        // for every outstanding read before the buffers are freed.
+        unsafe {
+            self.ring
+                .submission()
+                .push(&entry)
+                .map_err(|_| io::Error::other("io_uring submission queue full"))?;
+        }
+        block.pending = true;
+        self.in_flight += 1;
+        Ok(())
+    }
+
+    /// Waits for at least one read to complete and records the results.
+    fn reap(&mut self) -> io::Result<()> {
+        self.ring.submit_and_wait(1)?;
+        let completions: Vec<(u64, i32)> = self
+            .ring
+            .completion()
+            .map(|cqe| (cqe.user_data(), cqe.result()))
+            .collect();
+        let mut error = None;
+        for (user_data, result) in completions {
+            let block = &mut self.blocks[user_data as usize];
+            block.pending = false;
+            self.in_flight -= 1;
+            match result {
+                n if n < 0 => {
+                    let e = io::Error::from_raw_os_error(-n);
+                    if e.kind() == io::ErrorKind::Interrupted {
+                        // Retried when the block reaches the front.
+                        continue;
+                    }
+                    error.get_or_insert(e);
+                }
+                0 => block.at_end = true,
+                n => block.filled += n as usize,
+            }
+        }
+        error.map_or(Ok(()), Err)
+    }
+}
+
+/// Reads the whole file at `path` through a [`UringReader`], falling back to [`fs::read`]
+/// if the ring cannot be set up, e.g. where io_uring is disabled.
+pub(crate) fn read_file(path: &Path) -> io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let len = file
+        .metadata()
+        .map_or(0, |metadata| metadata.len() as usize);
+    let Ok(mut reader) = UringReader::new(file, DEFAULT_BLOCK_SIZE, DEFAULT_QUEUE_DEPTH) else {
+        return fs::read(path);
+    };
+    let mut data = Vec::with_capacity(len);
+    reader.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+impl Read for UringReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let Some(&front) = self.order.front() else {
+                return Ok(0);
+            };
+            let block = &mut self.blocks[front];
+            if block.pos < block.filled {
+                let n = out.len().min(block.filled - block.pos);
+                out[..n].copy_from_slice(&block.buf[block.pos..block.pos + n]);
+                block.pos += n;
+                return Ok(n);
+            }
+            if block.pending {
+                self.reap()?;
+                continue;
+            }
+            if block.at_end {
+                // Everything after the end of the file reads as empty too.
+                self.order.clear();
+                return Ok(0);
+            }
+            if block.filled < block.buf.len() {
+                // A short read, or one that was interrupted: fetch the rest of the block.
+                self.submit(front)?;
+                continue;
+            }
+            self.order.pop_front();
+            self.start_block(front);
+            self.submit(front)?;
+            self.ring.submit()?;
+        }
+    }
+}
+
+impl fmt::Debug for UringReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UringReader")
+            .field("file", &self.file)
+            .field("blocks", &self.blocks.len())
+            .field("next_offset", &self.next_offset)
+            .field("in_flight", &self.in_flight)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for UringReader {
+    fn drop(&mut self) {
+        // The kernel may still write into the buffers until each read completes.
+        while self.in_flight > 0 {
+            if self.ring.submit_and_wait(1).is_err() {
+                // Leaking is the only safe option if the outstanding reads cannot be reaped.
+                std::mem::forget(std::mem::take(&mut self.blocks));
+                return;
+            }
+            self.in_flight -= self.ring.completion().count();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::SegmentReader;
+    use std::io::Write;
+
+    #[test]
+    fn test_reads_whole_file_in_order() {
+        let data: Vec<u8> = (0..100_000u32).flat_map(|i| i.to_le_bytes()).collect();
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(&data).unwrap();
+
+        let mut reader = match UringReader::new(file, 4096, 4) {
+            Ok(reader) => reader,
+            Err(e) => {
+                // Sandboxes and some containers disable io_uring.
+                eprintln!("skipping: io_uring is unavailable: {}", e);
+                return;
+            }
+        };
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, data);
+        assert_eq!(reader.read(&mut [0; 16]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_segment_reader_over_uring() {
+        let mut data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\n".to_vec();
+        for i in 0..500 {
+            data.extend_from_slice(format!("NTE*ADD*NOTE {}~\n", i).as_bytes());
+        }
+        data.extend_from_slice(b"IEA*0*000000001~\n");
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+
+        let uring = match UringReader::open(file.path()) {
+            Ok(uring) => uring,
+            Err(e) => {
+                eprintln!("skipping: io_uring is unavailable: {}", e);
+                return;
+            }
+        };
+        let reader = SegmentReader::with_chunk_size(uring, 512).unwrap();
+        let ids: Vec<Vec<u8>> = reader.map(|s| s.unwrap().id().to_vec()).collect();
+        assert_eq!(ids.len(), 502);
+        assert_eq!(ids.last().unwrap(), b"IEA");
+    }
+
+    #[test]
+    fn test_read_file_matches_fs_read() {
+        let data: Vec<u8> = (0..300_000u32).map(|i| i as u8).collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        assert_eq!(read_file(file.path()).unwrap(), data);
+        assert_eq!(
+            read_file(&file.path().with_extension("missing"))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
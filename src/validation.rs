@@ -0,0 +1,1205 @@
+use std::fmt;
+
+use crate::charset::{check_delimiter, CharacterSet};
+use crate::segment::{element_at, Segment, Span};
+use crate::{DelimiterKind, Delimiters};
+
+/// How serious a diagnostic is.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Severity {
+    /// The document is usable but deviates from common practice.
+    Warning,
+    /// The document violates the X12 envelope rules.
+    Error,
+}
+
+/// Stable identifier of a validation rule.
+///
+/// Codes are part of the serialized report schema: new codes may be added, but existing
+/// ones keep their names.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DiagnosticCode {
+    /// The document is too short to contain an ISA segment.
+    IsaTooShort,
+    /// Two or more delimiters declared in the ISA are the same byte.
+    DuplicateDelimiters,
+    /// A segment appears outside the envelope that must contain it.
+    SegmentOutsideEnvelope,
+    /// A trailer (SE, GE, IEA) appears without its header.
+    UnexpectedTrailer,
+    /// A header (ISA, GS, ST) is never closed by its trailer.
+    UnclosedEnvelope,
+    /// A trailer count (SE01, GE01, IEA01) does not match the enclosed contents.
+    CountMismatch,
+    /// A trailer control number does not match its header.
+    ControlNumberMismatch,
+    /// The last segment is not followed by a segment terminator.
+    MissingTerminator,
+    /// A delimiter is not a special character of the agreed character set.
+    IllegalDelimiter,
+    /// ISA06 or ISA08 is empty, contains illegal bytes, or does not fit its qualifier.
+    InvalidIdentifier,
+}
+
+impl DiagnosticCode {
+    /// Returns the code as it appears in serialized reports, e.g. `count_mismatch`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagnosticCode::IsaTooShort => "isa_too_short",
+            DiagnosticCode::DuplicateDelimiters => "duplicate_delimiters",
+            DiagnosticCode::SegmentOutsideEnvelope => "segment_outside_envelope",
+            DiagnosticCode::UnexpectedTrailer => "unexpected_trailer",
+            DiagnosticCode::UnclosedEnvelope => "unclosed_envelope",
+            DiagnosticCode::CountMismatch => "count_mismatch",
+            DiagnosticCode::ControlNumberMismatch => "control_number_mismatch",
+            DiagnosticCode::MissingTerminator => "missing_terminator",
+            DiagnosticCode::IllegalDelimiter => "illegal_delimiter",
+            DiagnosticCode::InvalidIdentifier => "invalid_identifier",
+        }
+    }
+
+    /// Returns a one-line description of the rule.
+    pub fn description(&self) -> &'static str {
+        match self {
+            DiagnosticCode::IsaTooShort => "The document is too short to contain an ISA segment.",
+            DiagnosticCode::DuplicateDelimiters => {
+                "The delimiters declared in the ISA segment must be distinct."
+            }
+            DiagnosticCode::SegmentOutsideEnvelope => {
+                "Every segment must appear inside the envelope that contains it."
+            }
+            DiagnosticCode::UnexpectedTrailer => "A trailer segment must close an open header.",
+            DiagnosticCode::UnclosedEnvelope => {
+                "Every header segment must be closed by its trailer."
+            }
+            DiagnosticCode::CountMismatch => {
+                "Trailer counts (SE01, GE01, IEA01) must match the enclosed contents."
+            }
+            DiagnosticCode::ControlNumberMismatch => {
+                "Trailer control numbers must match their header control numbers."
+            }
+            DiagnosticCode::MissingTerminator => "The last segment should be terminated.",
+            DiagnosticCode::IllegalDelimiter => {
+                "Delimiters must be special characters of the agreed X12 character set."
+            }
+            DiagnosticCode::InvalidIdentifier => {
+                "Interchange sender and receiver IDs must be valid for their qualifiers."
+            }
+        }
+    }
+
+    /// Returns the severity diagnostics with this code are reported at.
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticCode::MissingTerminator => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single finding from validation.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    code: DiagnosticCode,
+    severity: Severity,
+    message: String,
+    span: Option<Span>,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic at the code's default severity.
+    pub fn new(code: DiagnosticCode, message: impl Into<String>, span: Option<Span>) -> Self {
+        Diagnostic {
+            code,
+            severity: code.severity(),
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Returns the rule that produced the diagnostic.
+    pub fn code(&self) -> DiagnosticCode {
+        self.code
+    }
+
+    /// Returns how serious the diagnostic is.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns a human-readable description.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the bytes the diagnostic refers to, if it refers to a location.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}[{}]: {}", severity, self.code, self.message)?;
+        if let Some(span) = self.span {
+            write!(f, " at bytes {}..{}", span.start, span.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// The findings from validating a document.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        ValidationReport::default()
+    }
+
+    /// Adds a diagnostic.
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Returns all diagnostics in the order they were found.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns an iterator over error-severity diagnostics.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+    }
+
+    /// Returns an iterator over warning-severity diagnostics.
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+    }
+
+    /// Returns true if the report contains no errors. Warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Serializes the report as JSON.
+    ///
+    /// The schema is stable across releases:
+    ///
+    /// ```json
+    /// {
+    ///   "schema_version": 1,
+    ///   "valid": false,
+    ///   "diagnostics": [
+    ///     {
+    ///       "code": "count_mismatch",
+    ///       "severity": "error",
+    ///       "message": "SE01 is 5 but the envelope has 4 segments",
+    ///       "span": { "start": 147, "end": 157 }
+    ///     }
+    ///   ]
+    /// }
+    /// ```
+    ///
+    /// `span` is `null` for diagnostics that do not refer to a location.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        #[derive(serde::Serialize)]
+        struct JsonReport<'a> {
+            schema_version: u32,
+            valid: bool,
+            diagnostics: &'a [Diagnostic],
+        }
+
+        let report = JsonReport {
+            schema_version: 1,
+            valid: self.is_valid(),
+            diagnostics: &self.diagnostics,
+        };
+        serde_json::to_string(&report).expect("validation reports always serialize")
+    }
+
+    /// Serializes the report as a SARIF 2.1.0 log with a single run.
+    ///
+    /// Each diagnostic becomes a result whose `ruleId` is the diagnostic code and whose
+    /// region is given as `byteOffset`/`byteLength` within `artifact_uri`. The rules used
+    /// in the report are listed in the tool driver.
+    #[cfg(feature = "sarif")]
+    pub fn to_sarif(&self, artifact_uri: &str) -> String {
+        use serde_json::{json, Value};
+
+        let mut rules: Vec<DiagnosticCode> = Vec::new();
+        for diagnostic in &self.diagnostics {
+            if !rules.contains(&diagnostic.code) {
+                rules.push(diagnostic.code);
+            }
+        }
+        let rules: Vec<Value> = rules
+            .iter()
+            .map(|code| {
+                json!({
+                    "id": code.as_str(),
+                    "shortDescription": { "text": code.description() },
+                })
+            })
+            .collect();
+
+        let results: Vec<Value> = self
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let mut location = json!({ "artifactLocation": { "uri": artifact_uri } });
+                if let Some(span) = diagnostic.span {
+                    location["region"] = json!({
+                        "byteOffset": span.start,
+                        "byteLength": span.len(),
+                    });
+                }
+                let level = match diagnostic.severity {
+                    Severity::Warning => "warning",
+                    Severity::Error => "error",
+                };
+                json!({
+                    "ruleId": diagnostic.code.as_str(),
+                    "level": level,
+                    "message": { "text": diagnostic.message },
+                    "locations": [{ "physicalLocation": location }],
+                })
+            })
+            .collect();
+
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }],
+        });
+        log.to_string()
+    }
+}
+
+/// A problem with a delimiter set, found by [`Delimiters::validate`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum DelimiterIssue {
+    /// The segment terminator and element separator are the same byte.
+    SegmentEqualsElement,
+    /// The segment terminator and sub-element separator are the same byte.
+    SegmentEqualsSubElement,
+    /// The element separator and sub-element separator are the same byte.
+    ElementEqualsSubElement,
+    /// The repetition separator is the same byte as the given delimiter.
+    RepetitionEquals(DelimiterKind),
+    /// The delimiter is a whitespace byte, which editors and transports may alter.
+    Whitespace(DelimiterKind),
+    /// The delimiter is outside printable ASCII (`0x20` through `0x7E`).
+    NotPrintable(DelimiterKind),
+}
+
+impl fmt::Display for DelimiterIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DelimiterIssue::SegmentEqualsElement => {
+                f.write_str("segment terminator equals element separator")
+            }
+            DelimiterIssue::SegmentEqualsSubElement => {
+                f.write_str("segment terminator equals sub-element separator")
+            }
+            DelimiterIssue::ElementEqualsSubElement => {
+                f.write_str("element separator equals sub-element separator")
+            }
+            DelimiterIssue::RepetitionEquals(kind) => {
+                write!(f, "repetition separator equals {}", kind)
+            }
+            DelimiterIssue::Whitespace(kind) => write!(f, "{} is whitespace", kind),
+            DelimiterIssue::NotPrintable(kind) => {
+                write!(f, "{} is not printable ASCII", kind)
+            }
+        }
+    }
+}
+
+/// The issues found by [`Delimiters::validate`], in a fixed order: collisions first, then
+/// per-delimiter issues in [`DelimiterKind::ALL`] order.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DelimiterReport {
+    issues: Vec<DelimiterIssue>,
+}
+
+impl DelimiterReport {
+    /// Returns every issue found.
+    pub fn issues(&self) -> &[DelimiterIssue] {
+        &self.issues
+    }
+
+    /// Returns true if the delimiters collide.
+    ///
+    /// Colliding delimiters make the document ambiguous. Whitespace and unprintable
+    /// delimiters are legal but fragile, and do not count.
+    pub fn has_collisions(&self) -> bool {
+        self.issues.iter().any(|issue| {
+            !matches!(
+                issue,
+                DelimiterIssue::Whitespace(_) | DelimiterIssue::NotPrintable(_)
+            )
+        })
+    }
+
+    /// Returns true if no issues were found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for DelimiterReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return f.write_str("no delimiter issues");
+        }
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl Delimiters {
+    /// Checks the delimiters and names every problem found.
+    ///
+    /// Unlike [`Delimiters::are_valid`], which only answers whether the delimiters are
+    /// distinct, the report says which delimiters collide and flags whitespace and
+    /// unprintable delimiters.
+    ///
+    /// ```
+    /// use x12_delimiters::validation::DelimiterIssue;
+    /// use x12_delimiters::{DelimiterKind, Delimiters};
+    ///
+    /// assert!(Delimiters::default().validate().is_clean());
+    ///
+    /// let report = Delimiters::new(b'\n', b'*', b'*').validate();
+    /// assert!(report.has_collisions());
+    /// assert_eq!(
+    ///     report.issues(),
+    ///     &[
+    ///         DelimiterIssue::ElementEqualsSubElement,
+    ///         DelimiterIssue::Whitespace(DelimiterKind::SegmentTerminator),
+    ///         DelimiterIssue::NotPrintable(DelimiterKind::SegmentTerminator),
+    ///     ]
+    /// );
+    /// ```
+    pub fn validate(&self) -> DelimiterReport {
+        let mut issues = Vec::new();
+        let (segment, element, sub_element) = (
+            self.segment_terminator(),
+            self.element_separator(),
+            self.sub_element_separator(),
+        );
+        if segment == element {
+            issues.push(DelimiterIssue::SegmentEqualsElement);
+        }
+        if segment == sub_element {
+            issues.push(DelimiterIssue::SegmentEqualsSubElement);
+        }
+        if element == sub_element {
+            issues.push(DelimiterIssue::ElementEqualsSubElement);
+        }
+        if let Some(repetition) = self.repetition_separator() {
+            for kind in DelimiterKind::ALL {
+                if self.get(kind) == repetition {
+                    issues.push(DelimiterIssue::RepetitionEquals(kind));
+                }
+            }
+        }
+        for kind in DelimiterKind::ALL {
+            let byte = self.get(kind);
+            if byte.is_ascii_whitespace() {
+                issues.push(DelimiterIssue::Whitespace(kind));
+            }
+            if !(0x20..=0x7E).contains(&byte) {
+                issues.push(DelimiterIssue::NotPrintable(kind));
+            }
+        }
+        DelimiterReport { issues }
+    }
+}
+
+/// Aggregate counts from validating a batch of ISA segments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IsaBatchStats {
+    /// Number of ISA segments checked.
+    pub total: usize,
+    /// Segments with a complete ISA and distinct delimiters.
+    pub valid: usize,
+    /// Segments shorter than 106 bytes.
+    pub too_short: usize,
+    /// Segments declaring the same byte for two or more delimiters.
+    pub duplicate_delimiters: usize,
+}
+
+impl IsaBatchStats {
+    fn record(&mut self, result: &Result<Delimiters, DiagnosticCode>) {
+        self.total += 1;
+        match result {
+            Ok(_) => self.valid += 1,
+            Err(DiagnosticCode::IsaTooShort) => self.too_short += 1,
+            Err(_) => self.duplicate_delimiters += 1,
+        }
+    }
+}
+
+/// Per-segment results and aggregate counts from [`validate_isa_batch`].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct IsaBatchReport {
+    results: Vec<Result<Delimiters, DiagnosticCode>>,
+    stats: IsaBatchStats,
+}
+
+impl IsaBatchReport {
+    fn from_results(results: Vec<Result<Delimiters, DiagnosticCode>>) -> Self {
+        let mut stats = IsaBatchStats::default();
+        for result in &results {
+            stats.record(result);
+        }
+        IsaBatchReport { results, stats }
+    }
+
+    /// Returns one result per input, in input order: the delimiters on success, or
+    /// `IsaTooShort` / `DuplicateDelimiters`.
+    pub fn results(&self) -> &[Result<Delimiters, DiagnosticCode>] {
+        &self.results
+    }
+
+    /// Returns the aggregate counts.
+    pub fn stats(&self) -> IsaBatchStats {
+        self.stats
+    }
+
+    /// Returns true if every segment in the batch is valid.
+    pub fn is_valid(&self) -> bool {
+        self.stats.valid == self.stats.total
+    }
+
+    /// Returns the input index and code of each invalid segment.
+    pub fn failures(&self) -> impl Iterator<Item = (usize, DiagnosticCode)> + '_ {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, result)| result.err().map(|code| (i, code)))
+    }
+}
+
+/// Validates many ISA segments at once, e.g. every interchange received by a gateway.
+///
+/// Each segment is checked for length and distinct delimiters only; use [`validate`] for
+/// full envelope checks. The results are written into a single allocation sized to the
+/// batch.
+///
+/// ```
+/// use x12_delimiters::validation::{validate_isa_batch, DiagnosticCode};
+///
+/// let isa: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+/// let report = validate_isa_batch(&[isa, b"ISA*00*"]);
+/// assert_eq!(report.stats().valid, 1);
+/// assert_eq!(report.failures().collect::<Vec<_>>(), vec![(1, DiagnosticCode::IsaTooShort)]);
+/// ```
+pub fn validate_isa_batch(isas: &[&[u8]]) -> IsaBatchReport {
+    IsaBatchReport::from_results(isas.iter().map(|isa| check_isa(isa)).collect())
+}
+
+/// Parallel version of [`validate_isa_batch`] using the global rayon thread pool.
+///
+/// Results are identical to the sequential version and stay in input order. Worth it only
+/// for large batches; the per-segment check is a handful of byte comparisons.
+#[cfg(feature = "rayon")]
+pub fn par_validate_isa_batch(isas: &[&[u8]]) -> IsaBatchReport {
+    use rayon::prelude::*;
+
+    let mut results = Vec::with_capacity(isas.len());
+    isas.par_iter()
+        .map(|isa| check_isa(isa))
+        .collect_into_vec(&mut results);
+    IsaBatchReport::from_results(results)
+}
+
+fn check_isa(isa: &[u8]) -> Result<Delimiters, DiagnosticCode> {
+    let delimiters = Delimiters::from_isa(isa).map_err(|_| DiagnosticCode::IsaTooShort)?;
+    if !delimiters.are_valid() {
+        return Err(DiagnosticCode::DuplicateDelimiters);
+    }
+    Ok(delimiters)
+}
+
+/// An open envelope. The control number borrows from the document, so validating a
+/// well-formed document allocates nothing.
+struct Envelope<'a> {
+    span: Span,
+    control: &'a [u8],
+    count: usize,
+}
+
+/// Validates the envelope structure of an X12 document.
+///
+/// Delimiters are read from the ISA at the start of `data`. The document is then checked
+/// for balanced ISA/IEA, GS/GE and ST/SE envelopes, matching control numbers, and correct
+/// trailer counts. Validation continues past errors so the report lists every finding.
+pub fn validate(data: &[u8]) -> ValidationReport {
+    let mut report = ValidationReport::new();
+    let delimiters = match Delimiters::from_isa(data) {
+        Ok(delimiters) => delimiters,
+        Err(error) => {
+            let span = Span::new(0, data.len());
+            report.push(Diagnostic::new(
+                DiagnosticCode::IsaTooShort,
+                error.to_string(),
+                Some(span),
+            ));
+            return report;
+        }
+    };
+    if !delimiters.are_valid() {
+        report.push(Diagnostic::new(
+            DiagnosticCode::DuplicateDelimiters,
+            "segment terminator, element separator and sub-element separator must differ",
+            Some(Span::new(0, 106)),
+        ));
+        return report;
+    }
+
+    let mut interchange: Option<Envelope> = None;
+    let mut group: Option<Envelope> = None;
+    let mut transaction: Option<Envelope> = None;
+    let mut last_end = 0;
+
+    for (span, segment) in delimiters.segments(data).spanned() {
+        last_end = span.end;
+        let element = |n| element_at(segment.as_bytes(), &delimiters, n).unwrap_or_default();
+        match segment.id() {
+            b"ISA" => {
+                close_unclosed(&mut report, "ISA", interchange.take());
+                interchange = Some(Envelope::open(span, element(13)));
+            }
+            b"GS" => {
+                if interchange.is_none() {
+                    report_outside(&mut report, &segment, span, "an interchange");
+                }
+                close_unclosed(&mut report, "GS", group.take());
+                group = Some(Envelope::open(span, element(6)));
+            }
+            b"ST" => {
+                if group.is_none() {
+                    report_outside(&mut report, &segment, span, "a functional group");
+                }
+                close_unclosed(&mut report, "ST", transaction.take());
+                transaction = Some(Envelope {
+                    span,
+                    control: element(2),
+                    count: 1,
+                });
+            }
+            b"SE" => match transaction.take() {
+                Some(mut st) => {
+                    st.count += 1;
+                    st.check(&mut report, span, "SE", element(1), element(2), "segments");
+                    if let Some(gs) = group.as_mut() {
+                        gs.count += 1;
+                    }
+                }
+                None => report_trailer(&mut report, span, "SE", "ST"),
+            },
+            b"GE" => match group.take() {
+                Some(gs) => {
+                    close_unclosed(&mut report, "ST", transaction.take());
+                    gs.check(
+                        &mut report,
+                        span,
+                        "GE",
+                        element(1),
+                        element(2),
+                        "transaction sets",
+                    );
+                    if let Some(isa) = interchange.as_mut() {
+                        isa.count += 1;
+                    }
+                }
+                None => report_trailer(&mut report, span, "GE", "GS"),
+            },
+            b"IEA" => match interchange.take() {
+                Some(isa) => {
+                    close_unclosed(&mut report, "ST", transaction.take());
+                    close_unclosed(&mut report, "GS", group.take());
+                    isa.check(
+                        &mut report,
+                        span,
+                        "IEA",
+                        element(1),
+                        element(2),
+                        "functional groups",
+                    );
+                }
+                None => report_trailer(&mut report, span, "IEA", "ISA"),
+            },
+            _ => match transaction.as_mut() {
+                Some(st) => st.count += 1,
+                None => report_outside(&mut report, &segment, span, "a transaction set"),
+            },
+        }
+    }
+
+    close_unclosed(&mut report, "ST", transaction);
+    close_unclosed(&mut report, "GS", group);
+    close_unclosed(&mut report, "ISA", interchange);
+
+    if data[..last_end].last() != Some(&delimiters.segment_terminator()) {
+        report.push(Diagnostic::new(
+            DiagnosticCode::MissingTerminator,
+            "the last segment is not terminated",
+            Some(Span::new(last_end, last_end)),
+        ));
+    }
+    report
+}
+
+/// Like [`validate`], but also requires each delimiter to be a special character of `set`.
+///
+/// Each delimiter that breaks a rule gets an `IllegalDelimiter` diagnostic whose message
+/// names the delimiter and the rule, pointing at the delimiter's position in the ISA.
+///
+/// ```
+/// use x12_delimiters::charset::CharacterSet;
+/// use x12_delimiters::validation::{validate_strict, DiagnosticCode};
+///
+/// let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~";
+/// assert!(validate_strict(data, CharacterSet::Extended).is_valid());
+///
+/// let report = validate_strict(data, CharacterSet::Basic);
+/// assert_eq!(report.diagnostics()[0].code(), DiagnosticCode::IllegalDelimiter);
+/// ```
+pub fn validate_strict(data: &[u8], set: CharacterSet) -> ValidationReport {
+    let mut report = validate(data);
+    let Ok(delimiters) = Delimiters::from_isa(data) else {
+        return report;
+    };
+    let mut illegal = Vec::new();
+    for kind in [
+        DelimiterKind::ElementSeparator,
+        DelimiterKind::SubElementSeparator,
+        DelimiterKind::SegmentTerminator,
+    ] {
+        let byte = delimiters.get(kind);
+        if let Err(rule) = check_delimiter(byte, set) {
+            let position = kind.isa_index();
+            illegal.push(Diagnostic::new(
+                DiagnosticCode::IllegalDelimiter,
+                format!("{} 0x{:02X}: {}", kind, byte, rule),
+                Some(Span::new(position, position + 1)),
+            ));
+        }
+    }
+    if !illegal.is_empty() {
+        illegal.append(&mut report.diagnostics);
+        report.diagnostics = illegal;
+    }
+    report
+}
+
+/// Checks the interchange sender and receiver IDs (ISA06 and ISA08).
+///
+/// Each ID must be left-justified and non-blank, use only characters of the X12 extended
+/// character set, contain none of the interchange's delimiters or its repetition
+/// separator, and match the format of its qualifier (ISA05 or ISA07) where the qualifier
+/// has one, e.g. nine digits for a DUNS number (`01`). Fields are read at their fixed ISA
+/// positions, so an ID containing a delimiter is still checked as a whole. Each problem
+/// gets an `InvalidIdentifier` diagnostic spanning the field.
+///
+/// ```
+/// use x12_delimiters::validation::validate_isa_identifiers;
+///
+/// let isa = b"ISA*00*          *00*          *01*12345678       *ZZ*RECEIVER:ID    *250403*0856*^*00501*000000001*0*P*:~";
+/// let report = validate_isa_identifiers(isa);
+/// let messages: Vec<_> = report.diagnostics().iter().map(|d| d.message()).collect();
+/// assert_eq!(messages, [
+///     "ISA06 must be a 9-digit DUNS number for qualifier 01",
+///     "ISA08 contains delimiter byte 0x3A",
+/// ]);
+/// ```
+pub fn validate_isa_identifiers(isa: &[u8]) -> ValidationReport {
+    let mut report = ValidationReport::new();
+    let delimiters = match Delimiters::from_isa(isa) {
+        Ok(delimiters) => delimiters,
+        Err(error) => {
+            report.push(Diagnostic::new(
+                DiagnosticCode::IsaTooShort,
+                error.to_string(),
+                Some(Span::new(0, isa.len())),
+            ));
+            return report;
+        }
+    };
+    let forbidden = [
+        Some(delimiters.element_separator()),
+        Some(delimiters.sub_element_separator()),
+        Some(delimiters.segment_terminator()),
+        delimiters.repetition_separator(),
+    ];
+    for (name, qualifier, field) in [("ISA06", ISA05, ISA06), ("ISA08", ISA07, ISA08)] {
+        let span = Span::new(field.0, field.1);
+        let qualifier = trim(&isa[qualifier.0..qualifier.1]);
+        let id = &isa[field.0..field.1];
+        let mut invalid = |message: String| {
+            report.push(Diagnostic::new(
+                DiagnosticCode::InvalidIdentifier,
+                message,
+                Some(span),
+            ))
+        };
+
+        if let Some(&byte) = id.iter().find(|&&b| forbidden.contains(&Some(b))) {
+            invalid(format!("{} contains delimiter byte 0x{:02X}", name, byte));
+            continue;
+        }
+        if let Some(&byte) = id.iter().find(|&&b| !CharacterSet::Extended.contains(b)) {
+            invalid(format!(
+                "{} contains byte 0x{:02X} outside the X12 character sets",
+                name, byte
+            ));
+            continue;
+        }
+        let value = trim(id);
+        if value.is_empty() {
+            invalid(format!("{} is blank", name));
+            continue;
+        }
+        if id[0] == b' ' {
+            invalid(format!("{} must be left-justified", name));
+            continue;
+        }
+        if let Some((format, matches)) = qualifier_format(qualifier) {
+            if !matches(value) {
+                invalid(format!(
+                    "{} must be {} for qualifier {}",
+                    name,
+                    format,
+                    String::from_utf8_lossy(qualifier)
+                ));
+            }
+        }
+    }
+    report
+}
+
+/// Byte ranges of fixed-width ISA fields.
+const ISA05: (usize, usize) = (32, 34);
+const ISA06: (usize, usize) = (35, 50);
+const ISA07: (usize, usize) = (51, 53);
+const ISA08: (usize, usize) = (54, 69);
+
+type IdCheck = fn(&[u8]) -> bool;
+
+/// Returns the required ID format for an interchange ID qualifier, if it has one.
+fn qualifier_format(qualifier: &[u8]) -> Option<(&'static str, IdCheck)> {
+    fn digits(value: &[u8], len: usize) -> bool {
+        value.len() == len && value.iter().all(u8::is_ascii_digit)
+    }
+    match qualifier {
+        b"01" => Some(("a 9-digit DUNS number", |v| digits(v, 9))),
+        b"08" => Some(("a numeric UCC EDI communications ID", |v| {
+            v.iter().all(u8::is_ascii_digit)
+        })),
+        b"12" => Some(("a 10-digit phone number", |v| digits(v, 10))),
+        b"14" => Some(("a 9-digit DUNS number with a 4-character suffix", |v| {
+            v.len() == 13 && digits(&v[..9], 9) && v[9..].iter().all(u8::is_ascii_alphanumeric)
+        })),
+        b"30" => Some(("a 9-digit federal tax ID", |v| digits(v, 9))),
+        _ => None,
+    }
+}
+
+impl<'a> Envelope<'a> {
+    fn open(span: Span, control: &'a [u8]) -> Self {
+        Envelope {
+            span,
+            control,
+            count: 0,
+        }
+    }
+
+    fn check(
+        &self,
+        report: &mut ValidationReport,
+        span: Span,
+        trailer: &str,
+        count: &[u8],
+        control: &[u8],
+        contents: &str,
+    ) {
+        if parse_number(count) != Some(self.count as u64) {
+            report.push(Diagnostic::new(
+                DiagnosticCode::CountMismatch,
+                format!(
+                    "{}01 is {} but the envelope has {} {}",
+                    trailer,
+                    String::from_utf8_lossy(count),
+                    self.count,
+                    contents
+                ),
+                Some(span),
+            ));
+        }
+        let matches = match (parse_number(control), parse_number(self.control)) {
+            (Some(a), Some(b)) => a == b,
+            _ => trim(control) == trim(self.control),
+        };
+        if !matches {
+            report.push(Diagnostic::new(
+                DiagnosticCode::ControlNumberMismatch,
+                format!(
+                    "{}02 is {} but the header control number is {}",
+                    trailer,
+                    String::from_utf8_lossy(control),
+                    String::from_utf8_lossy(self.control)
+                ),
+                Some(span),
+            ));
+        }
+    }
+}
+
+fn trim(value: &[u8]) -> &[u8] {
+    let start = value.iter().position(|b| !b.is_ascii_whitespace());
+    let end = value.iter().rposition(|b| !b.is_ascii_whitespace());
+    match (start, end) {
+        (Some(start), Some(end)) => &value[start..=end],
+        _ => &[],
+    }
+}
+
+fn parse_number(value: &[u8]) -> Option<u64> {
+    std::str::from_utf8(value).ok()?.trim().parse().ok()
+}
+
+fn close_unclosed(report: &mut ValidationReport, header: &str, envelope: Option<Envelope>) {
+    if let Some(envelope) = envelope {
+        report.push(Diagnostic::new(
+            DiagnosticCode::UnclosedEnvelope,
+            format!("{} is never closed by its trailer", header),
+            Some(envelope.span),
+        ));
+    }
+}
+
+fn report_trailer(report: &mut ValidationReport, span: Span, trailer: &str, header: &str) {
+    report.push(Diagnostic::new(
+        DiagnosticCode::UnexpectedTrailer,
+        format!("{} appears without a matching {}", trailer, header),
+        Some(span),
+    ));
+}
+
+fn report_outside(report: &mut ValidationReport, segment: &Segment<'_>, span: Span, what: &str) {
+    report.push(Diagnostic::new(
+        DiagnosticCode::SegmentOutsideEnvelope,
+        format!(
+            "{} appears outside {}",
+            String::from_utf8_lossy(segment.id()),
+            what
+        ),
+        Some(span),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\
+GS*HC*S*R*20250403*0856*1*X*005010X222A1~\
+ST*837*0001~BHT*0019~SE*3*0001~\
+GE*1*1~\
+IEA*1*000000001~";
+
+    fn codes(report: &ValidationReport) -> Vec<DiagnosticCode> {
+        report.diagnostics().iter().map(|d| d.code()).collect()
+    }
+
+    #[test]
+    fn test_validate_isa_identifiers() {
+        let isa = |sender_qualifier: &str, sender: &str, receiver: &str| {
+            crate::isa::IsaSegment::new(
+                sender_qualifier,
+                sender,
+                "ZZ",
+                receiver,
+                "250403",
+                "0856",
+                1,
+            )
+            .to_bytes(&Delimiters::default())
+        };
+        assert!(validate_isa_identifiers(&isa("01", "123456789", "RECEIVER")).is_valid());
+        assert!(validate_isa_identifiers(&isa("14", "123456789ABCD", "RECEIVER")).is_valid());
+
+        let messages = |data: &[u8]| -> Vec<String> {
+            validate_isa_identifiers(data)
+                .diagnostics()
+                .iter()
+                .map(|d| d.message().to_string())
+                .collect()
+        };
+        assert_eq!(
+            messages(&isa("12", "555-0100", "  RECEIVER")),
+            [
+                "ISA06 must be a 10-digit phone number for qualifier 12",
+                "ISA08 must be left-justified",
+            ]
+        );
+        assert_eq!(
+            messages(&isa("ZZ", "", "RECEIVER^1")),
+            ["ISA06 is blank", "ISA08 contains delimiter byte 0x5E",]
+        );
+        assert_eq!(
+            messages(&isa("ZZ", "SENDER\u{7}", "RECEIVER")),
+            ["ISA06 contains byte 0x07 outside the X12 character sets",]
+        );
+
+        // ISA11 only holds a repetition separator from 00402 on, and never a letter.
+        let text = |data: Vec<u8>| String::from_utf8(data).unwrap();
+        let legacy = text(isa("ZZ", "SENDER^1", "USER")).replace("^*00501", "^*00401");
+        assert!(validate_isa_identifiers(legacy.as_bytes()).is_valid());
+        let standards = text(isa("ZZ", "USER", "RECEIVER")).replace("^*00501", "U*00501");
+        assert!(validate_isa_identifiers(standards.as_bytes()).is_valid());
+
+        let report = validate_isa_identifiers(&isa("ZZ", "S~", "R"));
+        assert_eq!(
+            report.diagnostics()[0].code(),
+            DiagnosticCode::InvalidIdentifier
+        );
+        assert_eq!(report.diagnostics()[0].span(), Some(Span::new(35, 50)));
+        assert_eq!(
+            validate_isa_identifiers(b"ISA*00*").diagnostics()[0].code(),
+            DiagnosticCode::IsaTooShort
+        );
+    }
+
+    #[test]
+    fn test_validate_strict_names_rule() {
+        let mut data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~IEA*0*000000001~".to_vec();
+        data[104] = b'A';
+        let report = validate_strict(&data, CharacterSet::Extended);
+        assert!(!report.is_valid());
+        let diagnostic = &report.diagnostics()[0];
+        assert_eq!(diagnostic.code(), DiagnosticCode::IllegalDelimiter);
+        assert_eq!(diagnostic.span(), Some(Span::new(104, 105)));
+        assert_eq!(
+            diagnostic.message(),
+            "sub-element separator 0x41: delimiters must not be letters or digits"
+        );
+        assert!(validate(&data).is_valid());
+    }
+
+    #[test]
+    fn test_delimiter_report() {
+        let report = Delimiters::new(b'~', b'~', b'~')
+            .with_repetition_separator(Some(b'~'))
+            .validate();
+        assert_eq!(
+            &report.issues()[..4],
+            &[
+                DelimiterIssue::SegmentEqualsElement,
+                DelimiterIssue::SegmentEqualsSubElement,
+                DelimiterIssue::ElementEqualsSubElement,
+                DelimiterIssue::RepetitionEquals(DelimiterKind::SegmentTerminator),
+            ]
+        );
+        assert_eq!(report.issues().len(), 6);
+
+        let report = Delimiters::new(0x85, b' ', b':').validate();
+        assert!(!report.has_collisions());
+        assert_eq!(
+            report.to_string(),
+            "segment terminator is not printable ASCII; element separator is whitespace"
+        );
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let report = validate(VALID);
+        assert!(report.is_valid());
+        assert!(report.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn test_too_short_and_duplicate_delimiters() {
+        assert_eq!(
+            codes(&validate(b"ISA*00*")),
+            vec![DiagnosticCode::IsaTooShort]
+        );
+
+        let mut duplicate = VALID.to_vec();
+        duplicate[104] = b'*';
+        assert_eq!(
+            codes(&validate(&duplicate)),
+            vec![DiagnosticCode::DuplicateDelimiters]
+        );
+    }
+
+    #[test]
+    fn test_count_and_control_mismatches() {
+        let data = String::from_utf8(VALID.to_vec())
+            .unwrap()
+            .replace("SE*3*0001", "SE*4*0002")
+            .replace("IEA*1*000000001", "IEA*2*1");
+        let report = validate(data.as_bytes());
+        assert_eq!(
+            codes(&report),
+            vec![
+                DiagnosticCode::CountMismatch,
+                DiagnosticCode::ControlNumberMismatch,
+                DiagnosticCode::CountMismatch,
+            ]
+        );
+        let se = report.diagnostics()[0].span().unwrap();
+        assert_eq!(&data.as_bytes()[se.start..se.end], b"SE*4*0002~");
+    }
+
+    #[test]
+    fn test_structure_errors() {
+        let data = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\
+BHT*0019~SE*1*0001~GS*HC*S*R*20250403*0856*1*X*005010~ST*837*0001";
+        let report = validate(data);
+        assert_eq!(
+            codes(&report),
+            vec![
+                DiagnosticCode::SegmentOutsideEnvelope,
+                DiagnosticCode::UnexpectedTrailer,
+                DiagnosticCode::UnclosedEnvelope,
+                DiagnosticCode::UnclosedEnvelope,
+                DiagnosticCode::UnclosedEnvelope,
+                DiagnosticCode::MissingTerminator,
+            ]
+        );
+        assert_eq!(report.warnings().count(), 1);
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_validate_isa_batch() {
+        let mut duplicate = VALID[..106].to_vec();
+        duplicate[104] = b'*';
+        let isas: [&[u8]; 4] = [&VALID[..106], b"ISA*00*", &duplicate, VALID];
+        let report = validate_isa_batch(&isas);
+
+        assert_eq!(
+            report.stats(),
+            IsaBatchStats {
+                total: 4,
+                valid: 2,
+                too_short: 1,
+                duplicate_delimiters: 1,
+            }
+        );
+        assert_eq!(
+            report.results()[0],
+            Ok(Delimiters::default().with_repetition_separator(Some(b'^')))
+        );
+        assert_eq!(
+            report.failures().collect::<Vec<_>>(),
+            vec![
+                (1, DiagnosticCode::IsaTooShort),
+                (2, DiagnosticCode::DuplicateDelimiters),
+            ]
+        );
+        assert!(!report.is_valid());
+        assert!(validate_isa_batch(&[]).is_valid());
+
+        #[cfg(feature = "rayon")]
+        assert_eq!(par_validate_isa_batch(&isas), report);
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Diagnostic::new(
+            DiagnosticCode::CountMismatch,
+            "SE01 is 4",
+            Some(Span::new(10, 20)),
+        );
+        assert_eq!(
+            diagnostic.to_string(),
+            "error[count_mismatch]: SE01 is 4 at bytes 10..20"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json_schema() {
+        let report = validate(b"ISA*00*");
+        assert_eq!(
+            report.to_json(),
+            r#"{"schema_version":1,"valid":false,"diagnostics":[{"code":"isa_too_short","severity":"error","message":"ISA segment must be at least 106 bytes long to extract delimiters","span":{"start":0,"end":7}}]}"#
+        );
+        let roundtrip: ValidationReport = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(roundtrip, report);
+    }
+
+    #[cfg(feature = "sarif")]
+    #[test]
+    fn test_to_sarif() {
+        let data = String::from_utf8(VALID.to_vec())
+            .unwrap()
+            .replace("SE*3*0001", "SE*4*0001")
+            .replace("GE*1*1", "GE*1*9");
+        let report = validate(data.as_bytes());
+        let sarif: serde_json::Value =
+            serde_json::from_str(&report.to_sarif("file:///inbound/claims.x12")).unwrap();
+
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "x12-delimiters");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], "count_mismatch");
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(
+            location["artifactLocation"]["uri"],
+            "file:///inbound/claims.x12"
+        );
+        assert_eq!(location["region"]["byteLength"], 10);
+        assert_eq!(run["results"][1]["ruleId"], "control_number_mismatch");
+    }
+}
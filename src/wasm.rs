@@ -0,0 +1,164 @@
+//! JavaScript bindings for browser-based tooling, built with the `wasm` feature.
+//!
+//! Build with `wasm-pack build --target web -- --features wasm`. Byte inputs are
+//! `Uint8Array`s, and delimiters are exposed to JavaScript as one-character strings.
+
+use wasm_bindgen::prelude::*;
+
+use crate::detect::sniff;
+use crate::pretty::{format_pretty, PrettyOptions};
+use crate::{Delimiters, TerminatorSuffix};
+
+/// A delimiter set, as seen from JavaScript.
+#[wasm_bindgen(js_name = Delimiters)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct JsDelimiters {
+    inner: Delimiters,
+}
+
+#[wasm_bindgen(js_class = Delimiters)]
+impl JsDelimiters {
+    /// Returns the segment terminator.
+    #[wasm_bindgen(getter, js_name = segmentTerminator)]
+    pub fn segment_terminator(&self) -> String {
+        text(self.inner.segment_terminator())
+    }
+
+    /// Returns the element separator.
+    #[wasm_bindgen(getter, js_name = elementSeparator)]
+    pub fn element_separator(&self) -> String {
+        text(self.inner.element_separator())
+    }
+
+    /// Returns the sub-element separator.
+    #[wasm_bindgen(getter, js_name = subElementSeparator)]
+    pub fn sub_element_separator(&self) -> String {
+        text(self.inner.sub_element_separator())
+    }
+
+    /// Returns the repetition separator, or `undefined` before version 00402.
+    #[wasm_bindgen(getter, js_name = repetitionSeparator)]
+    pub fn repetition_separator(&self) -> Option<String> {
+        self.inner.repetition_separator().map(text)
+    }
+
+    /// Returns the line ending after each terminator: `""`, `"\n"`, `"\r\n"`, or `"\r"`.
+    #[wasm_bindgen(getter, js_name = lineEnding)]
+    pub fn line_ending(&self) -> String {
+        String::from_utf8_lossy(self.inner.suffix().as_bytes()).into_owned()
+    }
+}
+
+impl From<Delimiters> for JsDelimiters {
+    fn from(inner: Delimiters) -> Self {
+        JsDelimiters { inner }
+    }
+}
+
+impl From<JsDelimiters> for Delimiters {
+    fn from(delimiters: JsDelimiters) -> Self {
+        delimiters.inner
+    }
+}
+
+/// Delimiters inferred by [`detect`], with a confidence from 0 to 1.
+#[wasm_bindgen]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Detection {
+    delimiters: JsDelimiters,
+    confidence: f64,
+}
+
+#[wasm_bindgen]
+impl Detection {
+    /// Returns the inferred delimiters.
+    #[wasm_bindgen(getter)]
+    pub fn delimiters(&self) -> JsDelimiters {
+        self.delimiters
+    }
+
+    /// Returns 1 for delimiters read from a complete ISA, less for guesses.
+    #[wasm_bindgen(getter)]
+    pub fn confidence(&self) -> f64 {
+        self.confidence
+    }
+}
+
+/// Reads the delimiters from the ISA segment at the start of `data`.
+///
+/// # Errors
+/// Throws the [`Delimiters::from_isa`] error message if `data` does not start with a
+/// valid ISA segment.
+#[wasm_bindgen(js_name = fromIsa)]
+pub fn from_isa(data: &[u8]) -> Result<JsDelimiters, JsError> {
+    Delimiters::from_isa(data)
+        .map(JsDelimiters::from)
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Infers the delimiters of `data`, from its ISA if it has one and from the segment
+/// structure otherwise; see [`crate::detect::sniff`].
+///
+/// # Errors
+/// Throws if `data` has no recognizable segment structure.
+#[wasm_bindgen]
+pub fn detect(data: &[u8]) -> Result<Detection, JsError> {
+    let sniffed = sniff(data).map_err(|e| JsError::new(&e.to_string()))?;
+    Ok(Detection {
+        delimiters: sniffed.delimiters().into(),
+        confidence: sniffed.confidence(),
+    })
+}
+
+/// Splits `data` into segments, without terminators or line endings.
+///
+/// Bytes that are not valid UTF-8 are replaced with U+FFFD.
+#[wasm_bindgen(js_name = splitSegments)]
+pub fn split_segments(data: &[u8], delimiters: &JsDelimiters) -> Vec<String> {
+    delimiters
+        .inner
+        .segments(data)
+        .raw()
+        .map(|raw| String::from_utf8_lossy(raw).into_owned())
+        .collect()
+}
+
+/// Writes each segment on its own LF-terminated line, indented by `indent` spaces per
+/// enclosing envelope; see [`crate::pretty::format_pretty`].
+#[wasm_bindgen(js_name = prettyPrint)]
+pub fn pretty_print(data: &[u8], delimiters: &JsDelimiters, indent: usize) -> String {
+    let options = PrettyOptions::new()
+        .with_indent(indent)
+        .with_line_ending(TerminatorSuffix::Lf);
+    String::from_utf8_lossy(&format_pretty(data, &delimiters.inner, &options)).into_owned()
+}
+
+fn text(byte: u8) -> String {
+    (byte as char).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only success paths run natively: creating a `JsError` needs a JavaScript host.
+    const DATA: &[u8] = b"ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~\r\nGS*HC~\r\nGE*0*1~\r\nIEA*1*000000001~\r\n";
+
+    #[test]
+    fn test_bindings() {
+        let delimiters = from_isa(DATA).unwrap();
+        assert_eq!(delimiters.segment_terminator(), "~");
+        assert_eq!(delimiters.repetition_separator().as_deref(), Some("^"));
+        assert_eq!(delimiters.line_ending(), "\r\n");
+
+        let detection = detect(DATA).unwrap();
+        assert_eq!(detection.confidence(), 1.0);
+        assert_eq!(detection.delimiters(), delimiters);
+
+        let segments = split_segments(DATA, &delimiters);
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[1], "GS*HC");
+        assert!(pretty_print(DATA, &delimiters, 2)
+            .ends_with("~\n  GS*HC~\n  GE*0*1~\nIEA*1*000000001~\n"));
+    }
+}
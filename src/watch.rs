@@ -0,0 +1,419 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(feature = "notify")]
+use std::sync::mpsc;
+#[cfg(feature = "notify")]
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::summary::{interchange_spans, InterchangeSummary};
+
+/// What one call to [`DropFolder::poll`] did.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct PollStats {
+    /// Files read and passed to the callback during this poll.
+    pub files_ingested: usize,
+    /// Interchanges passed to the callback during this poll.
+    pub interchanges: usize,
+    /// Files still being written, to be checked again on the next poll.
+    pub pending: usize,
+    /// Ingested files that did not start with an ISA segment.
+    pub rejected: Vec<PathBuf>,
+    /// Files that could not be inspected or read, with the kind of error. They are retried
+    /// on the next poll; files removed while being polled are skipped instead.
+    pub failed: Vec<(PathBuf, io::ErrorKind)>,
+}
+
+impl PollStats {
+    /// Records a per-file error, ignoring files that were removed mid-poll.
+    fn record_failure(&mut self, path: PathBuf, error: io::Error) {
+        if error.kind() != io::ErrorKind::NotFound {
+            self.failed.push((path, error.kind()));
+        }
+    }
+}
+
+/// Ingests X12 files dropped into a directory.
+///
+/// Call [`DropFolder::poll`] periodically. A new file is ingested once its size and
+/// modification time are unchanged between two polls, so files still being written (e.g.
+/// by a slow SFTP upload) are not read half-way. Each ingested file is split into its
+/// interchanges and the callback is invoked once per interchange. Files are ingested at
+/// most once per `DropFolder`; moving them out of the directory is left to the callback's
+/// owner.
+///
+/// Hidden files and files ending in `.tmp` or `.part` are ignored, since uploaders
+/// commonly write to such names and rename on completion.
+///
+/// With the `notify` feature, [`DropFolder::watch`] polls only when the directory
+/// changes instead of on a timer.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use x12_delimiters::watch::DropFolder;
+///
+/// let mut inbox = DropFolder::new("/srv/edi/inbound");
+/// loop {
+///     inbox.poll(|path, summary, bytes| {
+///         println!("{}: {} ({} bytes)", path.display(), summary.sender_id(), bytes.len());
+///     })?;
+///     std::thread::sleep(Duration::from_secs(5));
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct DropFolder {
+    dir: PathBuf,
+    observed: HashMap<PathBuf, (u64, Option<SystemTime>)>,
+    ingested: HashSet<PathBuf>,
+}
+
+impl DropFolder {
+    /// Watches `dir`. Nothing is read until the first poll.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DropFolder {
+            dir: dir.into(),
+            observed: HashMap::new(),
+            ingested: HashSet::new(),
+        }
+    }
+
+    /// Returns the watched directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Scans the directory once and ingests every file whose writes have completed.
+    ///
+    /// `on_interchange` receives the file path, the interchange summary, and the bytes of
+    /// the interchange from its ISA through its IEA. Files are visited in name order.
+    ///
+    /// A file that cannot be inspected or read is recorded in [`PollStats::failed`] and
+    /// the poll moves on to the next file, so one unreadable file does not hold up the
+    /// rest of the directory.
+    ///
+    /// # Errors
+    /// Returns any error from listing the directory.
+    pub fn poll<F>(&mut self, mut on_interchange: F) -> io::Result<PollStats>
+    where
+        F: FnMut(&Path, &InterchangeSummary, &[u8]),
+    {
+        let mut stats = PollStats::default();
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if self.ingested.contains(&path) || is_ignored(&path) {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_file() => paths.push(path),
+                Ok(_) => {}
+                Err(e) => stats.record_failure(path, e),
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    self.observed.remove(&path);
+                    stats.record_failure(path, e);
+                    continue;
+                }
+            };
+            let observation = (metadata.len(), metadata.modified().ok());
+            if self.observed.insert(path.clone(), observation) != Some(observation) {
+                stats.pending += 1;
+                continue;
+            }
+
+            let data = match fs::read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    stats.record_failure(path, e);
+                    continue;
+                }
+            };
+            self.observed.remove(&path);
+            self.ingested.insert(path.clone());
+            stats.files_ingested += 1;
+
+            let spans = interchange_spans(&data);
+            if spans.is_empty() {
+                stats.rejected.push(path);
+                continue;
+            }
+            for (span, _) in spans {
+                let bytes = &data[span.start..span.end];
+                if let Ok(summary) = InterchangeSummary::from_isa(bytes) {
+                    on_interchange(&path, &summary, bytes);
+                    stats.interchanges += 1;
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// A [`DropFolder`] woken by filesystem notifications instead of a fixed poll interval.
+///
+/// Created by [`DropFolder::watch`]. Each call to [`FolderWatcher::wait`] blocks until a
+/// file in the directory is created, written, or renamed, waits for the directory to be
+/// quiet, then polls. Files still being written are polled again after the quiet period,
+/// so completion is detected the same way as with [`DropFolder::poll`].
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use x12_delimiters::watch::DropFolder;
+///
+/// let mut inbox = DropFolder::new("/srv/edi/inbound").watch()?;
+/// loop {
+///     inbox.wait(Duration::from_secs(2), |path, summary, _| {
+///         println!("{}: {}", path.display(), summary.sender_id());
+///     })?;
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "notify")]
+#[derive(Debug)]
+pub struct FolderWatcher {
+    folder: DropFolder,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    pending: bool,
+    _watcher: notify::RecommendedWatcher,
+}
+
+#[cfg(feature = "notify")]
+impl DropFolder {
+    /// Subscribes to change notifications for the directory.
+    ///
+    /// Files already in the directory are picked up by the first [`FolderWatcher::wait`].
+    ///
+    /// # Errors
+    /// Returns an error if the platform watcher cannot be created or the directory cannot
+    /// be watched.
+    pub fn watch(self) -> io::Result<FolderWatcher> {
+        use notify::Watcher;
+
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(notify_error)?;
+        watcher
+            .watch(&self.dir, notify::RecursiveMode::NonRecursive)
+            .map_err(notify_error)?;
+        Ok(FolderWatcher {
+            folder: self,
+            events,
+            // Poll once straight away for files that arrived before the watch started.
+            pending: true,
+            _watcher: watcher,
+        })
+    }
+}
+
+#[cfg(feature = "notify")]
+impl FolderWatcher {
+    /// Returns the watched folder.
+    pub fn folder(&self) -> &DropFolder {
+        &self.folder
+    }
+
+    /// Waits for the directory to change and be quiet for `settle`, then polls it.
+    ///
+    /// If the previous poll left files pending, this waits at most `settle` before polling
+    /// again, so a file is ingested about `settle` after its last write even if no further
+    /// notifications arrive.
+    ///
+    /// # Errors
+    /// Returns any error from [`DropFolder::poll`] or reported by the watcher.
+    pub fn wait<F>(&mut self, settle: Duration, on_interchange: F) -> io::Result<PollStats>
+    where
+        F: FnMut(&Path, &InterchangeSummary, &[u8]),
+    {
+        if !self.pending {
+            loop {
+                let event = self
+                    .events
+                    .recv()
+                    .map_err(|_| io::Error::other("directory watcher stopped"))?;
+                if is_change(&event.map_err(notify_error)?) {
+                    break;
+                }
+            }
+        }
+        loop {
+            match self.events.recv_timeout(settle) {
+                Ok(event) => {
+                    event.map_err(notify_error)?;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::other("directory watcher stopped"));
+                }
+            }
+        }
+        let stats = self.folder.poll(on_interchange)?;
+        self.pending = stats.pending > 0;
+        Ok(stats)
+    }
+}
+
+/// Returns true for events that may mean a new or grown file, ignoring the reads made
+/// while ingesting.
+#[cfg(feature = "notify")]
+fn is_change(event: &notify::Event) -> bool {
+    use notify::event::{AccessKind, AccessMode, EventKind};
+
+    match event.kind {
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any | EventKind::Other => true,
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) | EventKind::Remove(_) => false,
+    }
+}
+
+#[cfg(feature = "notify")]
+fn notify_error(e: notify::Error) -> io::Error {
+    match e.kind {
+        notify::ErrorKind::Io(e) => e,
+        _ => io::Error::other(e),
+    }
+}
+
+fn is_ignored(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    name.starts_with('.') || name.ends_with(".tmp") || name.ends_with(".part")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SENDERID       *ZZ*RECEIVERID     *250403*0856*^*00501*000000001*0*P*:~";
+
+    #[test]
+    fn test_ingests_settled_files_once() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("a.x12"),
+            format!("{0}IEA*0*1~\n{0}IEA*0*1~", ISA),
+        )
+        .unwrap();
+        fs::write(dir.path().join("b.x12"), "not x12").unwrap();
+        fs::write(dir.path().join("c.x12.part"), ISA).unwrap();
+
+        let mut inbox = DropFolder::new(dir.path());
+        let mut seen = Vec::new();
+        let mut callback = |path: &Path, summary: &InterchangeSummary, bytes: &[u8]| {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            seen.push((name, summary.sender_id().to_string(), bytes.len()));
+        };
+
+        let first = inbox.poll(&mut callback).unwrap();
+        assert_eq!((first.pending, first.files_ingested), (2, 0));
+
+        let second = inbox.poll(&mut callback).unwrap();
+        assert_eq!((second.files_ingested, second.interchanges), (2, 2));
+        assert_eq!(second.rejected, vec![dir.path().join("b.x12")]);
+
+        fs::write(dir.path().join("d.x12"), ISA).unwrap();
+        let third = inbox.poll(&mut callback).unwrap();
+        assert_eq!((third.pending, third.files_ingested), (1, 0));
+
+        assert_eq!(
+            seen,
+            vec![
+                ("a.x12".to_string(), "SENDERID".to_string(), ISA.len() + 8),
+                ("a.x12".to_string(), "SENDERID".to_string(), ISA.len() + 8),
+            ]
+        );
+    }
+
+    #[cfg(feature = "notify")]
+    #[test]
+    fn test_watch_ingests_on_notification() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("early.x12"), ISA).unwrap();
+        let mut inbox = DropFolder::new(dir.path()).watch().unwrap();
+        let settle = Duration::from_millis(50);
+
+        let mut names = Vec::new();
+        let mut callback = |path: &Path, _: &InterchangeSummary, _: &[u8]| {
+            names.push(path.file_name().unwrap().to_string_lossy().to_string());
+        };
+        while inbox.wait(settle, &mut callback).unwrap().files_ingested == 0 {}
+
+        let path = dir.path().join("late.x12");
+        let writer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            fs::write(path, ISA).unwrap();
+        });
+        while inbox.wait(settle, &mut callback).unwrap().files_ingested == 0 {}
+        writer.join().unwrap();
+        assert_eq!(names, ["early.x12", "late.x12"]);
+        assert_eq!(inbox.folder().dir(), dir.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_file_does_not_block_later_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let locked = dir.path().join("a.x12");
+        fs::write(&locked, ISA).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        fs::write(dir.path().join("b.x12"), ISA).unwrap();
+        if fs::read(&locked).is_ok() {
+            eprintln!("skipping: file permissions are not enforced for this user");
+            return;
+        }
+
+        let mut inbox = DropFolder::new(dir.path());
+        let mut names = Vec::new();
+        let poll = |inbox: &mut DropFolder, names: &mut Vec<String>| {
+            inbox
+                .poll(|path, _, _| {
+                    names.push(path.file_name().unwrap().to_string_lossy().to_string())
+                })
+                .unwrap()
+        };
+        assert_eq!(poll(&mut inbox, &mut names).pending, 2);
+        let stats = poll(&mut inbox, &mut names);
+        assert_eq!(stats.files_ingested, 1);
+        assert_eq!(
+            stats.failed,
+            vec![(locked.clone(), io::ErrorKind::PermissionDenied)]
+        );
+        assert_eq!(names, ["b.x12"]);
+
+        // The unreadable file is retried once its permissions are fixed.
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o644)).unwrap();
+        let stats = poll(&mut inbox, &mut names);
+        assert_eq!((stats.files_ingested, stats.failed.len()), (1, 0));
+        assert_eq!(names, ["b.x12", "a.x12"]);
+
+        fs::remove_file(&locked).unwrap();
+        assert_eq!(poll(&mut inbox, &mut names), PollStats::default());
+    }
+
+    #[test]
+    fn test_growing_file_stays_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("upload.x12");
+        fs::write(&path, &ISA[..50]).unwrap();
+
+        let mut inbox = DropFolder::new(dir.path());
+        let mut count = 0;
+        inbox.poll(|_, _, _| count += 1).unwrap();
+        fs::write(&path, ISA).unwrap();
+        assert_eq!(inbox.poll(|_, _, _| count += 1).unwrap().pending, 1);
+        assert_eq!(inbox.poll(|_, _, _| count += 1).unwrap().interchanges, 1);
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,218 @@
+use std::io::Write;
+
+use crate::builder::raw_segment;
+use crate::conflict::{ConflictAction, ConflictPolicy};
+use crate::errors::WriteError;
+use crate::Delimiters;
+
+/// Serializes segments from their elements straight to a [`Write`] sink.
+///
+/// Each element is given as its list of sub-elements; a simple element is a list of one.
+/// Components are joined with the sub-element separator, elements with the element
+/// separator, and each segment is followed by the terminator and its suffix. Data bytes
+/// that equal one of the delimiters are handled by the writer's [`ConflictPolicy`], which
+/// rejects them by default.
+///
+/// Unlike [`crate::builder::DocumentBuilder`], the writer does not track envelopes or fill
+/// in trailer counts; every segment is written as given.
+///
+/// ```
+/// use x12_delimiters::writer::SegmentWriter;
+/// use x12_delimiters::{Delimiters, TerminatorSuffix};
+///
+/// let delimiters = Delimiters::default().with_suffix(TerminatorSuffix::Lf);
+/// let mut writer = SegmentWriter::new(Vec::new(), delimiters).with_trim_trailing(true);
+/// writer.write_segment("NM1", [["IL"], ["1"], ["DOE"], [""], [""]]).unwrap();
+/// writer.write_segment("SV1", vec![vec!["HC", "99213"], vec!["100"]]).unwrap();
+/// assert_eq!(writer.segment_count(), 2);
+/// assert_eq!(writer.into_inner(), b"NM1*IL*1*DOE~\nSV1*HC:99213*100~\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SegmentWriter<W, P = ConflictAction> {
+    inner: W,
+    delimiters: Delimiters,
+    policy: P,
+    trim_trailing: bool,
+    segment_count: usize,
+}
+
+impl<W: Write> SegmentWriter<W> {
+    /// Creates a writer that writes to `inner` with `delimiters` and rejects conflicts.
+    pub fn new(inner: W, delimiters: Delimiters) -> Self {
+        SegmentWriter {
+            inner,
+            delimiters,
+            policy: ConflictAction::Error,
+            trim_trailing: false,
+            segment_count: 0,
+        }
+    }
+}
+
+impl<W: Write, P: ConflictPolicy> SegmentWriter<W, P> {
+    /// Replaces the policy for data bytes that equal one of the delimiters.
+    pub fn with_conflict_policy<Q: ConflictPolicy>(self, policy: Q) -> SegmentWriter<W, Q> {
+        SegmentWriter {
+            inner: self.inner,
+            delimiters: self.delimiters,
+            policy,
+            trim_trailing: self.trim_trailing,
+            segment_count: self.segment_count,
+        }
+    }
+
+    /// Omits trailing empty elements, and trailing empty sub-elements of the last
+    /// element, as X12 syntax requires. Off by default, so segments are written with
+    /// exactly the elements given.
+    pub fn with_trim_trailing(mut self, enabled: bool) -> Self {
+        self.trim_trailing = enabled;
+        self
+    }
+
+    /// Returns the delimiters segments are written with.
+    pub fn delimiters(&self) -> Delimiters {
+        self.delimiters
+    }
+
+    /// Returns the number of segments written so far.
+    pub fn segment_count(&self) -> usize {
+        self.segment_count
+    }
+
+    /// Writes one segment.
+    ///
+    /// The segment is serialized completely before anything is written, so a rejected
+    /// segment leaves the sink unchanged.
+    ///
+    /// # Errors
+    /// Returns `WriteError::Build` if the conflict policy rejects a data byte, or
+    /// `WriteError::Io` if writing fails.
+    pub fn write_segment<E, C>(&mut self, id: &str, elements: E) -> Result<(), WriteError>
+    where
+        E: IntoIterator,
+        E::Item: IntoIterator<Item = C>,
+        C: AsRef<str>,
+    {
+        let mut raw = raw_segment(id, elements, &self.delimiters, &self.policy)?;
+        if self.trim_trailing {
+            let separators = [
+                self.delimiters.element_separator(),
+                self.delimiters.sub_element_separator(),
+            ];
+            let end = raw
+                .iter()
+                .rposition(|b| !separators.contains(b))
+                .map_or(0, |i| i + 1)
+                .max(id.len());
+            raw.truncate(end);
+        }
+        raw.push(self.delimiters.segment_terminator());
+        raw.extend_from_slice(self.delimiters.suffix().as_bytes());
+        self.inner.write_all(&raw)?;
+        self.segment_count += 1;
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    ///
+    /// # Errors
+    /// Returns the error of [`Write::flush`].
+    pub fn flush(&mut self) -> Result<(), WriteError> {
+        Ok(self.inner.flush()?)
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Writes one segment to `out` with `delimiters`, rejecting data bytes that equal a
+/// delimiter; see [`SegmentWriter`].
+///
+/// ```
+/// use x12_delimiters::writer::write_segment;
+/// use x12_delimiters::Delimiters;
+///
+/// let mut out = Vec::new();
+/// write_segment(&mut out, "REF", [["SY"], ["123456789"]], &Delimiters::default()).unwrap();
+/// assert_eq!(out, b"REF*SY*123456789~");
+/// ```
+///
+/// # Errors
+/// Same as [`SegmentWriter::write_segment`].
+pub fn write_segment<W, E, C>(
+    out: &mut W,
+    id: &str,
+    elements: E,
+    delimiters: &Delimiters,
+) -> Result<(), WriteError>
+where
+    W: Write + ?Sized,
+    E: IntoIterator,
+    E::Item: IntoIterator<Item = C>,
+    C: AsRef<str>,
+{
+    SegmentWriter::new(out, *delimiters).write_segment(id, elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::BuildError;
+    use crate::TerminatorSuffix;
+
+    #[test]
+    fn test_writes_composites_with_target_delimiters() {
+        let delimiters = Delimiters::new(b'\'', b'+', b'>').with_suffix(TerminatorSuffix::CrLf);
+        let mut writer = SegmentWriter::new(Vec::new(), delimiters);
+        writer
+            .write_segment("HI", [vec!["ABK", "J449"], vec!["ABF", "E119"]])
+            .unwrap();
+        writer
+            .write_segment("NM1", [["IL"], [""], ["DOE"], [""]])
+            .unwrap();
+        assert_eq!(
+            writer.get_ref(),
+            b"HI+ABK>J449+ABF>E119'\r\nNM1+IL++DOE+'\r\n"
+        );
+    }
+
+    #[test]
+    fn test_trim_trailing() {
+        let mut writer =
+            SegmentWriter::new(Vec::new(), Delimiters::default()).with_trim_trailing(true);
+        writer
+            .write_segment("SV1", [vec!["HC", "99213", "", ""], vec![""], vec![""]])
+            .unwrap();
+        writer.write_segment("N3", [[""], [""]]).unwrap();
+        writer
+            .write_segment("CLM", [vec!["A"], vec![""], vec!["1", ""]])
+            .unwrap();
+        assert_eq!(writer.into_inner(), b"SV1*HC:99213~N3~CLM*A**1~");
+    }
+
+    #[test]
+    fn test_conflicts_leave_output_unchanged() {
+        let mut out = b"ST*837~".to_vec();
+        assert!(matches!(
+            write_segment(&mut out, "NTE", [["A*B"]], &Delimiters::default()),
+            Err(WriteError::Build(BuildError::DelimiterConflict {
+                byte: b'*',
+                element: 1,
+                offset: 1
+            }))
+        ));
+        assert_eq!(out, b"ST*837~");
+
+        let mut writer = SegmentWriter::new(Vec::new(), Delimiters::default())
+            .with_conflict_policy(ConflictAction::EscapeToSpace);
+        writer.write_segment("NTE", [["A*B"]]).unwrap();
+        assert_eq!(writer.into_inner(), b"NTE*A B~");
+    }
+}